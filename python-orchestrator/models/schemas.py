@@ -11,12 +11,17 @@ from pydantic import BaseModel, Field
 class TranscriptChunk(BaseModel):
     """Message received from Rust WebSocket server."""
 
-    type: str  # "transcript" | "status" | "error"
+    type: str  # "transcript" | "status" | "error" | "level" | "speech_start" | "speech_end"
     text: str
     language: str = ""
     timestamp: int = 0
     is_final: bool = False
     rtf: float | None = None
+    speaker: str | None = None
+    rms: float | None = None
+    peak: float | None = None
+    state: str | None = None  # status/error: "loading_model" | "ready" | "capturing" | ...
+    code: str | None = None  # error code when state is "error"
 
 
 # --- Internal models ---
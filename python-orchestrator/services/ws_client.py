@@ -114,6 +114,8 @@ class RustBridgeClient:
         elif chunk.type == "error":
             logger.error(f"Rust error: {chunk.text}")
             await self.broadcast(chunk.model_dump())
+        elif chunk.type in ("level", "speech_start", "speech_end"):
+            await self.broadcast(chunk.model_dump(exclude_none=True))
 
     async def start(self) -> None:
         """Start connection and listening in a background task (non-blocking)."""
@@ -0,0 +1,56 @@
+//! Launch at login, through the autostart plugin (a launch agent on
+//! macOS). The tray menu's "Launch at Login" item shows and toggles it.
+
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+pub const MENU_ID: &str = "autostart";
+
+/// The tray menu's item, kept in step with the launch agent.
+pub struct AutostartItem(pub CheckMenuItem<tauri::Wry>);
+
+/// The tray menu's item, checked if the app launches at login.
+pub fn menu_item(app: &AppHandle) -> tauri::Result<CheckMenuItem<tauri::Wry>> {
+    let enabled = app.autolaunch().is_enabled().unwrap_or(false);
+    CheckMenuItem::with_id(app, MENU_ID, "Launch at Login", true, enabled, None::<&str>)
+}
+
+/// Launch the app at login, or stop, and update the tray menu.
+fn set(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    let changed = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    changed.map_err(|e| format!("Failed to update launch at login: {e}"))?;
+    if let Some(item) = app.try_state::<AutostartItem>() {
+        let _ = item.0.set_checked(enabled);
+    }
+    Ok(())
+}
+
+/// Toggle launch at login from the tray menu.
+pub fn toggle(app: &AppHandle) {
+    let enabled = app.autolaunch().is_enabled().unwrap_or(false);
+    if let Err(e) = set(app, !enabled) {
+        eprintln!("[VoxVault] {e}");
+        // The item checked itself when clicked
+        if let Some(item) = app.try_state::<AutostartItem>() {
+            let _ = item.0.set_checked(enabled);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set(&app, enabled)
+}
+
+#[tauri::command]
+pub async fn get_autostart(app: AppHandle) -> Result<bool, String> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("Failed to read launch at login: {e}"))
+}
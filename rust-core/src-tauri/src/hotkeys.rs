@@ -0,0 +1,109 @@
+//! Global shortcuts for the core actions, working while another app has
+//! focus or the window is hidden.
+//!
+//! Bindings are part of the app [settings](crate::settings) and use the
+//! global-shortcut plugin's syntax ("CommandOrControl+Alt+R").
+//! [`crate::on_hotkey`] carries the actions out.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+/// What a global shortcut does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Start or stop the session.
+    ToggleCapture,
+    /// Turn stealth mode (content protection) on or off.
+    ToggleStealth,
+    /// Hide the window, or bring it back.
+    ToggleWindow,
+    /// Capture only while the shortcut is held.
+    PushToTalk,
+    /// Copy the transcript to the clipboard.
+    CopyTranscript,
+}
+
+/// Shortcut of each action (None = unbound).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    pub toggle_capture: Option<String>,
+    pub toggle_stealth: Option<String>,
+    pub toggle_window: Option<String>,
+    pub push_to_talk: Option<String>,
+    pub copy_transcript: Option<String>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            toggle_capture: Some("CommandOrControl+Alt+R".to_string()),
+            toggle_stealth: Some("CommandOrControl+Alt+S".to_string()),
+            toggle_window: Some("CommandOrControl+Alt+V".to_string()),
+            push_to_talk: None,
+            copy_transcript: Some("CommandOrControl+Alt+C".to_string()),
+        }
+    }
+}
+
+impl HotkeySettings {
+    /// Bound actions and their shortcuts.
+    fn bindings(&self) -> Vec<(HotkeyAction, &str)> {
+        [
+            (HotkeyAction::ToggleCapture, &self.toggle_capture),
+            (HotkeyAction::ToggleStealth, &self.toggle_stealth),
+            (HotkeyAction::ToggleWindow, &self.toggle_window),
+            (HotkeyAction::PushToTalk, &self.push_to_talk),
+            (HotkeyAction::CopyTranscript, &self.copy_transcript),
+        ]
+        .into_iter()
+        .filter_map(|(action, binding)| {
+            let binding = binding.as_deref()?.trim();
+            (!binding.is_empty()).then_some((action, binding))
+        })
+        .collect()
+    }
+}
+
+/// Register `settings`' shortcuts in place of the current ones. Shortcuts
+/// that fail to register (e.g. taken by another app) are skipped and
+/// reported together.
+pub fn register(app: &AppHandle, settings: &HotkeySettings) -> Result<(), String> {
+    // Check every binding before dropping the current ones
+    let mut shortcuts: Vec<(HotkeyAction, &str, Shortcut)> = Vec::new();
+    for (action, binding) in settings.bindings() {
+        let shortcut: Shortcut = binding
+            .parse()
+            .map_err(|e| format!("Invalid shortcut '{binding}': {e}"))?;
+        if let Some((other, _, _)) = shortcuts.iter().find(|(_, _, s)| *s == shortcut) {
+            return Err(format!(
+                "'{binding}' is bound to both {other:?} and {action:?}"
+            ));
+        }
+        shortcuts.push((action, binding, shortcut));
+    }
+
+    let manager = app.global_shortcut();
+    manager
+        .unregister_all()
+        .map_err(|e| format!("Failed to unregister shortcuts: {e}"))?;
+    let mut errors = Vec::new();
+    for (action, binding, shortcut) in shortcuts {
+        let registered = manager.on_shortcut(shortcut, move |app, _, event| {
+            crate::on_hotkey(app, action, event.state());
+        });
+        if let Err(e) = registered {
+            errors.push(format!("'{binding}': {e}"));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to register shortcuts {}",
+            errors.join(", ")
+        ))
+    }
+}
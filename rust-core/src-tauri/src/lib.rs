@@ -1,59 +1,226 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tauri::{
-    menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, WebviewUrl, WebviewWindowBuilder,
+    Emitter, Manager, WebviewUrl, WebviewWindowBuilder,
 };
+use tauri_plugin_global_shortcut::ShortcutState;
 
-const PYTHON_API: &str = "http://localhost:8766";
+mod autostart;
+mod hotkeys;
+mod overlay;
+mod permissions;
+mod session;
+mod settings;
+mod tray;
+
+use hotkeys::HotkeyAction;
+use session::Core;
+use settings::{Settings, SettingsStore};
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct HealthStatus {
     status: String,
+    /// Whether the in-process engine is loaded.
     rust_connected: bool,
     session_active: bool,
+    /// Service translating final transcripts, or "disabled".
     translation_mode: String,
+    /// Engine stats (rolling RTF, GPU memory), once the model is loaded.
+    #[serde(default)]
+    engine_stats: Option<serde_json::Value>,
 }
 
+/// State of the in-process core.
 #[tauri::command]
-async fn check_health() -> Result<HealthStatus, String> {
-    let url = format!("{PYTHON_API}/api/health");
-    let resp = reqwest::get(&url)
-        .await
-        .map_err(|e| format!("Python API unreachable: {e}"))?;
-    let status: HealthStatus = resp
-        .json()
-        .await
-        .map_err(|e| format!("Invalid response: {e}"))?;
-    Ok(status)
+async fn check_health(
+    core: tauri::State<'_, Core>,
+    settings: tauri::State<'_, SettingsStore>,
+) -> Result<HealthStatus, String> {
+    let engine = core.engine();
+    let engine_stats = match engine {
+        Some(ref engine) => engine
+            .stats()
+            .await
+            .ok()
+            .and_then(|stats| serde_json::to_value(stats).ok()),
+        None => None,
+    };
+    let settings = settings.get();
+    Ok(HealthStatus {
+        status: "ok".to_string(),
+        rust_connected: engine.is_some(),
+        session_active: core.session_active(),
+        translation_mode: match settings.translate_to {
+            Some(_) => settings.translator,
+            None => "disabled".to_string(),
+        },
+        engine_stats,
+    })
+}
+
+/// Boost `words` during decoding, from now on and in later sessions.
+#[tauri::command]
+async fn set_hotwords(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    words: Vec<String>,
+) -> Result<(), String> {
+    let settings = Settings {
+        hotwords: words,
+        ..store.get()
+    };
+    settings::set_settings(app, store, settings).await
 }
 
+/// Switch the engine to the latency `preset` ("low-latency", "balanced" or
+/// "accurate").
 #[tauri::command]
-async fn set_stealth_mode(window: tauri::WebviewWindow, enabled: bool) -> Result<(), String> {
+async fn set_latency(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    preset: String,
+) -> Result<(), String> {
+    let settings = Settings {
+        latency: preset,
+        ..store.get()
+    };
+    settings::set_settings(app, store, settings).await
+}
+
+/// Turn punctuation restoration of final transcripts on or off.
+#[tauri::command]
+async fn set_punctuation(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SettingsStore>,
+    enabled: bool,
+) -> Result<(), String> {
+    let settings = Settings {
+        restore_punctuation: enabled,
+        ..store.get()
+    };
+    settings::set_settings(app, store, settings).await
+}
+
+/// `dir`, or the models directory of the settings, by default in the app
+/// data directory.
+fn models_dir(app: &tauri::AppHandle, dir: Option<String>) -> Result<std::path::PathBuf, String> {
+    match dir.or_else(|| app.state::<SettingsStore>().get().models_dir) {
+        Some(dir) => Ok(std::path::PathBuf::from(dir)),
+        None => Ok(app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("No app data directory: {e}"))?
+            .join("models")),
+    }
+}
+
+/// Download the model files into `dir` (default: the settings' models
+/// directory), emitting `model-download-progress` events. Returns the
+/// directory.
+#[tauri::command]
+async fn download_models(app: tauri::AppHandle, dir: Option<String>) -> Result<String, String> {
+    let dir = models_dir(&app, dir)?;
+    let source = voxvault_core::voxtral::models::ModelSource::default();
+    voxvault_core::voxtral::models::download_models(&source, &dir, |progress| {
+        let _ = app.emit("model-download-progress", progress);
+    })
+    .await
+    .map_err(|e| format!("Model download failed: {e:#}"))?;
+    Ok(dir.display().to_string())
+}
+
+/// Content protection (stealth mode) of each window by label, kept across
+/// the window being closed and recreated from the tray.
+#[derive(Default)]
+struct StealthState(Mutex<HashMap<String, bool>>);
+
+impl StealthState {
+    /// Whether `label`'s window is hidden from screen capture; windows
+    /// start hidden.
+    fn get(&self, label: &str) -> bool {
+        self.0.lock().unwrap().get(label).copied().unwrap_or(true)
+    }
+
+    fn set(&self, label: &str, enabled: bool) {
+        self.0.lock().unwrap().insert(label.to_string(), enabled);
+    }
+}
+
+/// Payload of the `stealth-changed` event.
+#[derive(Clone, serde::Serialize)]
+struct StealthChanged {
+    window: String,
+    enabled: bool,
+}
+
+/// Turn `window`'s content protection on or off, remember it and tell the
+/// frontend.
+fn apply_stealth(
+    window: &tauri::WebviewWindow,
+    stealth: &StealthState,
+    enabled: bool,
+) -> Result<(), String> {
     window
         .set_content_protected(enabled)
         .map_err(|e| format!("Failed to set content protection: {e}"))?;
+    stealth.set(window.label(), enabled);
+    if window.label() == "main" {
+        tray::set_stealth(window.app_handle(), enabled);
+    }
+    let _ = window.emit(
+        "stealth-changed",
+        StealthChanged {
+            window: window.label().to_string(),
+            enabled,
+        },
+    );
     Ok(())
 }
 
 #[tauri::command]
-async fn get_stealth_mode(window: tauri::WebviewWindow) -> Result<bool, String> {
-    let _ = window;
-    Ok(true)
+async fn set_stealth_mode(
+    window: tauri::WebviewWindow,
+    stealth: tauri::State<'_, StealthState>,
+    enabled: bool,
+) -> Result<(), String> {
+    apply_stealth(&window, &stealth, enabled)
+}
+
+#[tauri::command]
+async fn get_stealth_mode(
+    window: tauri::WebviewWindow,
+    stealth: tauri::State<'_, StealthState>,
+) -> Result<bool, String> {
+    Ok(stealth.get(window.label()))
 }
 
+/// Create the VoxVault aggregate devices (if missing) and report what was
+/// found and created, for onboarding.
 #[tauri::command]
 async fn setup_audio_devices() -> Result<voxvault_core::audio::setup::SetupResult, String> {
-    Ok(voxvault_core::audio::setup::setup_audio_devices())
+    blocking(voxvault_core::audio::setup::setup_audio_devices).await
 }
 
+/// Destroy the aggregate devices this app created and restore the default
+/// output.
 #[tauri::command]
 async fn teardown_audio_devices() -> Result<voxvault_core::audio::setup::TeardownResult, String> {
-    Ok(voxvault_core::audio::setup::teardown_audio_devices())
+    blocking(voxvault_core::audio::setup::teardown_audio_devices).await
 }
 
+/// Every CoreAudio device (empty off macOS).
 #[tauri::command]
 async fn list_audio_devices() -> Result<Vec<voxvault_core::audio::setup::AudioDeviceInfo>, String> {
-    Ok(voxvault_core::audio::setup::list_devices())
+    blocking(voxvault_core::audio::setup::list_devices).await
+}
+
+/// Run a CoreAudio call off the async runtime: creating and destroying
+/// devices waits on the run loop.
+async fn blocking<T: Send + 'static>(f: fn() -> T) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Audio device task failed: {e}"))
 }
 
 fn show_or_create_window(app: &tauri::AppHandle) {
@@ -66,7 +233,7 @@ fn show_or_create_window(app: &tauri::AppHandle) {
             .inner_size(420.0, 600.0)
             .decorations(false)
             .always_on_top(true)
-            .content_protected(true)
+            .content_protected(app.state::<StealthState>().get("main"))
             .resizable(false)
             .center()
             .visible(true)
@@ -74,13 +241,90 @@ fn show_or_create_window(app: &tauri::AppHandle) {
     }
 }
 
+/// Toggle the main window's stealth mode (from a shortcut or the tray).
+/// Without the window, the mode applies when it is recreated.
+fn toggle_stealth(app: &tauri::AppHandle) {
+    let stealth = app.state::<StealthState>();
+    let enabled = !stealth.get("main");
+    match app.get_webview_window("main") {
+        Some(window) => {
+            if let Err(e) = apply_stealth(&window, &stealth, enabled) {
+                eprintln!("[VoxVault] {e}");
+                // The item checked itself when clicked
+                tray::set_stealth(app, !enabled);
+            }
+        }
+        None => {
+            stealth.set("main", enabled);
+            tray::set_stealth(app, enabled);
+        }
+    }
+}
+
+/// Payload of the `hotkey` event, for the actions the frontend carries
+/// out (capture and copying, which go through its session and transcript).
+#[derive(Clone, serde::Serialize)]
+struct HotkeyEvent {
+    action: HotkeyAction,
+    /// Whether the shortcut was pressed or released (push-to-talk).
+    pressed: bool,
+}
+
+/// Carry out a global shortcut's action.
+fn on_hotkey(app: &tauri::AppHandle, action: HotkeyAction, state: ShortcutState) {
+    let pressed = state == ShortcutState::Pressed;
+    match action {
+        HotkeyAction::ToggleStealth if pressed => toggle_stealth(app),
+        HotkeyAction::ToggleWindow if pressed => match app.get_webview_window("main") {
+            Some(window) if window.is_visible().unwrap_or(false) => {
+                let _ = window.hide();
+            }
+            _ => show_or_create_window(app),
+        },
+        HotkeyAction::PushToTalk => {
+            let _ = app.emit("hotkey", HotkeyEvent { action, pressed });
+        }
+        HotkeyAction::ToggleCapture | HotkeyAction::CopyTranscript if pressed => {
+            let _ = app.emit("hotkey", HotkeyEvent { action, pressed });
+        }
+        _ => {}
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            None,
+        ))
+        .manage(Core::default())
+        .manage(StealthState::default())
+        .manage(tray::TrayStatus::default())
         .invoke_handler(tauri::generate_handler![
             check_health,
+            set_hotwords,
+            set_latency,
+            set_punctuation,
+            download_models,
+            session::load_model,
+            session::start_transcription_session,
+            session::stop_transcription_session,
+            session::pause_transcription_session,
+            session::get_transcription_session,
+            session::list_input_devices,
+            session::select_input_device,
+            settings::get_settings,
+            settings::set_settings,
+            overlay::show_overlay,
+            overlay::set_overlay_style,
+            autostart::set_autostart,
+            autostart::get_autostart,
+            permissions::check_permissions,
+            permissions::open_permission_settings,
             set_stealth_mode,
             get_stealth_mode,
             setup_audio_devices,
@@ -88,10 +332,10 @@ pub fn run() {
             list_audio_devices,
         ])
         .setup(|app| {
+            app.manage(SettingsStore::load(app.handle()));
+
             // Build tray menu
-            let show_item = MenuItem::with_id(app, "show", "Show VoxVault", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
+            let menu = tray::menu(app.handle())?;
 
             // Build tray icon (single instance — no trayIcon in tauri.conf.json)
             let mut builder = TrayIconBuilder::new()
@@ -100,8 +344,12 @@ pub fn run() {
                 .menu(&menu)
                 .show_menu_on_left_click(false)
                 .on_menu_event(|app, event| match event.id.as_ref() {
-                    "show" => show_or_create_window(app),
-                    "quit" => app.exit(0),
+                    tray::SHOW_ID => show_or_create_window(app),
+                    tray::CAPTURE_ID => session::toggle_capture(app),
+                    tray::PAUSE_ID => session::toggle_pause(app),
+                    tray::STEALTH_ID => toggle_stealth(app),
+                    autostart::MENU_ID => autostart::toggle(app),
+                    tray::QUIT_ID => app.exit(0),
                     _ => {}
                 })
                 .on_tray_icon_event(|tray, event| {
@@ -121,6 +369,7 @@ pub fn run() {
 
             let tray = builder.build(app)?;
             app.manage(tray);
+            tray::listen(app.handle());
 
             // Create audio devices on startup
             let setup_result = voxvault_core::audio::setup::setup_audio_devices();
@@ -134,8 +383,15 @@ pub fn run() {
                 eprintln!("[VoxVault] Audio setup warning: {err}");
             }
 
-            // Show window on startup
+            let shortcuts = app.state::<SettingsStore>().get().hotkeys;
+            if let Err(e) = hotkeys::register(app.handle(), &shortcuts) {
+                eprintln!("[VoxVault] Global shortcuts: {e}");
+            }
+
+            // Show window on startup, hidden from screen capture until
+            // stealth mode is turned off
             if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_content_protected(app.state::<StealthState>().get("main"));
                 let _ = window.center();
                 let _ = window.show();
                 let _ = window.set_focus();
@@ -0,0 +1,159 @@
+//! Subtitle overlay: a frameless, transparent, always-on-top window that
+//! mouse clicks go through, showing the transcript as captions over the
+//! meeting app.
+//!
+//! The window renders the same frontend as the main one (it checks its
+//! label) and takes its style from the app [settings](crate::settings).
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, State, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+
+use crate::settings::SettingsStore;
+
+pub const OVERLAY_LABEL: &str = "overlay";
+/// Share of the screen's width the captions take.
+const WIDTH_FRACTION: f64 = 0.8;
+/// Distance from the screen's edge, in logical pixels.
+const MARGIN: f64 = 48.0;
+/// Line height, relative to the font size (matches the overlay's CSS).
+const LINE_HEIGHT: f64 = 1.4;
+/// Vertical padding around the captions, in logical pixels.
+const PADDING: f64 = 24.0;
+
+/// Edge of the screen the captions sit at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+/// How the captions look.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayStyle {
+    /// In logical pixels.
+    pub font_size: f64,
+    /// CSS colors.
+    pub text_color: String,
+    pub background: String,
+    pub position: OverlayPosition,
+    /// Lines of text shown; older text scrolls out.
+    pub lines: u32,
+}
+
+impl Default for OverlayStyle {
+    fn default() -> Self {
+        Self {
+            font_size: 28.0,
+            text_color: "#ffffff".to_string(),
+            background: "rgba(0, 0, 0, 0.6)".to_string(),
+            position: OverlayPosition::Bottom,
+            lines: 2,
+        }
+    }
+}
+
+impl OverlayStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(8.0..=96.0).contains(&self.font_size) {
+            return Err("Overlay font size must be between 8 and 96".to_string());
+        }
+        if !(1..=6).contains(&self.lines) {
+            return Err("Overlay lines must be between 1 and 6".to_string());
+        }
+        Ok(())
+    }
+
+    /// Height of the window, in logical pixels.
+    fn height(&self) -> f64 {
+        self.lines as f64 * self.font_size * LINE_HEIGHT + PADDING
+    }
+}
+
+/// Show the overlay (creating it the first time), or hide it.
+#[tauri::command]
+pub async fn show_overlay(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    visible: bool,
+) -> Result<(), String> {
+    let window = match app.get_webview_window(OVERLAY_LABEL) {
+        Some(window) => window,
+        None if !visible => return Ok(()),
+        None => create(&app).map_err(|e| format!("Failed to create overlay: {e}"))?,
+    };
+    let shown = if visible {
+        place(&window, &settings.get().overlay).and_then(|()| window.show())
+    } else {
+        window.hide()
+    };
+    shown.map_err(|e| format!("Failed to update overlay: {e}"))
+}
+
+/// Save `style` and apply it to the overlay.
+#[tauri::command]
+pub async fn set_overlay_style(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    style: OverlayStyle,
+) -> Result<(), String> {
+    let mut updated = settings.get();
+    updated.overlay = style;
+    updated.validate()?;
+    settings.save(updated.clone())?;
+    restyle(&app, &updated.overlay);
+    let _ = app.emit("settings-changed", updated);
+    Ok(())
+}
+
+/// Fit the overlay, if there is one, to `style`.
+pub fn restyle(app: &AppHandle, style: &OverlayStyle) {
+    if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+        if let Err(e) = place(&window, style) {
+            eprintln!("[VoxVault] Failed to place overlay: {e}");
+        }
+    }
+}
+
+fn create(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    let window = WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::default())
+        .title("VoxVault Captions")
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .content_protected(app.state::<crate::StealthState>().get(OVERLAY_LABEL))
+        .visible(false)
+        .build()?;
+    window.set_ignore_cursor_events(true)?;
+    Ok(window)
+}
+
+/// Size the overlay to `style` and put it at its edge of the screen.
+fn place(window: &WebviewWindow, style: &OverlayStyle) -> tauri::Result<()> {
+    let Some(monitor) = window.current_monitor()?.or(window.primary_monitor()?) else {
+        return Ok(());
+    };
+    let scale = monitor.scale_factor();
+    let screen = monitor.size().to_logical::<f64>(scale);
+    let origin = monitor.position().to_logical::<f64>(scale);
+    let width = screen.width * WIDTH_FRACTION;
+    let height = style.height();
+    let y = match style.position {
+        OverlayPosition::Top => origin.y + MARGIN,
+        OverlayPosition::Bottom => origin.y + screen.height - height - MARGIN,
+    };
+    window.set_size(LogicalSize::new(width, height))?;
+    window.set_position(LogicalPosition::new(
+        origin.x + (screen.width - width) / 2.0,
+        y,
+    ))
+}
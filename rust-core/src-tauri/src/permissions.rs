@@ -0,0 +1,157 @@
+//! macOS privacy permissions (TCC) the app needs: the microphone, for any
+//! capture (BlackHole devices included), and screen recording, which only
+//! ScreenCaptureKit capture needs — the app's capture goes through
+//! BlackHole, so it is reported but never prompted for.
+//!
+//! Other platforms have no such permissions; everything reads as not
+//! applicable there.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether the app may use a protected resource.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    /// Blocked by a policy (parental controls, MDM); the user can't grant it.
+    Restricted,
+    /// Never asked: the system prompt appears on first use or request.
+    NotDetermined,
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Permissions {
+    pub microphone: PermissionStatus,
+    pub screen_recording: PermissionStatus,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Microphone,
+    ScreenRecording,
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{Permission, PermissionStatus};
+    use block2::RcBlock;
+    use objc2::msg_send;
+    use objc2::runtime::{AnyClass, Bool};
+    use objc2_foundation::NSString;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: &'static NSString;
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+    }
+
+    fn capture_device() -> Option<&'static AnyClass> {
+        AnyClass::get(c"AVCaptureDevice")
+    }
+
+    pub fn microphone() -> PermissionStatus {
+        let Some(class) = capture_device() else {
+            return PermissionStatus::NotApplicable;
+        };
+        // AVAuthorizationStatus
+        let status: isize =
+            unsafe { msg_send![class, authorizationStatusForMediaType: AVMediaTypeAudio] };
+        match status {
+            0 => PermissionStatus::NotDetermined,
+            1 => PermissionStatus::Restricted,
+            2 => PermissionStatus::Denied,
+            _ => PermissionStatus::Granted,
+        }
+    }
+
+    /// Show the system prompt for the microphone (only shown while not
+    /// determined).
+    pub fn request_microphone() {
+        let Some(class) = capture_device() else {
+            return;
+        };
+        let handler = RcBlock::new(|_granted: Bool| {});
+        unsafe {
+            let _: () = msg_send![
+                class,
+                requestAccessForMediaType: AVMediaTypeAudio,
+                completionHandler: &*handler
+            ];
+        }
+    }
+
+    /// Screen recording can't be told apart from never asked: both read as
+    /// denied.
+    pub fn screen_recording() -> PermissionStatus {
+        if unsafe { CGPreflightScreenCaptureAccess() } {
+            PermissionStatus::Granted
+        } else {
+            PermissionStatus::Denied
+        }
+    }
+
+    /// The Privacy & Security pane where `permission` is granted.
+    pub fn settings_url(permission: Permission) -> &'static str {
+        match permission {
+            Permission::Microphone => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"
+            }
+            Permission::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+        }
+    }
+}
+
+/// The app's permissions. With `prompt`, a microphone permission never
+/// asked for is asked for now (the answer arrives later: check again).
+#[tauri::command]
+pub async fn check_permissions(prompt: Option<bool>) -> Result<Permissions, String> {
+    #[cfg(target_os = "macos")]
+    {
+        let mut microphone = macos::microphone();
+        if prompt.unwrap_or(false) && microphone == PermissionStatus::NotDetermined {
+            macos::request_microphone();
+            microphone = macos::microphone();
+        }
+        Ok(Permissions {
+            microphone,
+            screen_recording: macos::screen_recording(),
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = prompt;
+        Ok(Permissions {
+            microphone: PermissionStatus::NotApplicable,
+            screen_recording: PermissionStatus::NotApplicable,
+        })
+    }
+}
+
+/// Open the System Settings pane where `permission` is granted, for when
+/// it was denied (the system prompt doesn't come back).
+#[tauri::command]
+pub async fn open_permission_settings(permission: Permission) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(macos::settings_url(permission))
+            .status()
+            .map_err(|e| format!("Failed to open System Settings: {e}"))?;
+        Ok(())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = permission;
+        Err("Permissions are only managed on macOS".to_string())
+    }
+}
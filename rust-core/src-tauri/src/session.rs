@@ -0,0 +1,709 @@
+//! In-process transcription, so the desktop app works without the Python
+//! API and the core's CLI.
+//!
+//! `load_model` moves a [`VoxtralEngine`] onto its inference thread.
+//! `start_transcription_session` loads it if needed, captures a device on
+//! a thread of its own (cpal streams can't move between threads), cuts the
+//! audio into speech segments and transcribes them, emitting the messages
+//! as Tauri events with the WebSocket server's JSON: `transcript:partial`
+//! and `transcript:final` for transcripts, `transcript:translation` for
+//! their translations, `transcript:status` for the rest (status, levels,
+//! errors). The engine boosts the settings' hotwords with their latency,
+//! and final transcripts are post-processed (punctuation, inverse text
+//! normalization, profanity) and translated as the settings say;
+//! `apply_settings` puts changes to these in effect at once.
+//! `stop_transcription_session` transcribes the speech left and stops;
+//! `pause_transcription_session` drops the audio without releasing the
+//! device. The tray icon shows the pipeline's state and the device
+//! captured, and its menu starts, pauses and stops sessions too.
+//!
+//! `list_input_devices` and `select_input_device` back the device picker:
+//! the capture thread moves the session to the picked device, and reports
+//! every device change or loss as an `input-device` event.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::{mpsc, oneshot};
+use voxvault_core::audio::capture::{AudioCapture, AudioChunk, CaptureEvent, CaptureSource};
+use voxvault_core::audio::processor::{AudioProcessor, ProcessorConfigUpdate, SpeechSegment};
+use voxvault_core::server::websocket::{ErrorCode, PipelineState, TranscriptMessage};
+use voxvault_core::voxtral::engine::{EngineConfig, VoxtralEngine};
+use voxvault_core::voxtral::handle::{JobOptions, VoxtralHandle};
+use voxvault_core::voxtral::models::{MODEL_FILE, TOKENIZER_FILE};
+use voxvault_core::voxtral::profanity::ProfanityFilter;
+use voxvault_core::voxtral::translate::{HttpTranslator, Translator};
+use voxvault_core::voxtral::types::TranscriptResult;
+use voxvault_core::voxtral::{itn, punctuation};
+
+use crate::settings::{Settings, SettingsStore};
+use crate::tray;
+
+/// Event carrying partial transcripts.
+const PARTIAL_EVENT: &str = "transcript:partial";
+/// Event carrying final transcripts.
+const FINAL_EVENT: &str = "transcript:final";
+/// Event carrying translations of final transcripts.
+const TRANSLATION_EVENT: &str = "transcript:translation";
+/// Event carrying the in-process core's other messages.
+const STATUS_EVENT: &str = "transcript:status";
+/// Event carrying the capture's [`CaptureEvent`]s.
+const INPUT_DEVICE_EVENT: &str = "input-device";
+/// How often the capture thread checks its device and whether to stop.
+const MONITOR_INTERVAL: Duration = Duration::from_millis(250);
+/// Capture chunks queued for the transcription task.
+const CHUNK_QUEUE: usize = 64;
+
+/// The in-process core: the engine once loaded, and the running session.
+#[derive(Default)]
+pub struct Core {
+    engine: tokio::sync::Mutex<Option<VoxtralHandle>>,
+    session: Mutex<Option<Session>>,
+}
+
+impl Core {
+    /// The engine, if it's loaded (None while it loads).
+    pub fn engine(&self) -> Option<VoxtralHandle> {
+        self.engine
+            .try_lock()
+            .ok()
+            .and_then(|engine| engine.clone())
+    }
+
+    /// Whether a session is running.
+    pub fn session_active(&self) -> bool {
+        self.session.lock().unwrap().is_some()
+    }
+}
+
+struct Session {
+    info: SessionInfo,
+    /// What is done to final transcripts, shared with the transcription
+    /// task.
+    post: Arc<RwLock<Postprocessing>>,
+    /// Tells the capture thread to stop.
+    stop: Arc<AtomicBool>,
+    /// Tells the transcription task to drop the audio.
+    paused: Arc<AtomicBool>,
+    /// Asks the capture thread to move to another device.
+    switches: std::sync::mpsc::Sender<DeviceSwitch>,
+    /// Transcribes the captured audio until the capture stops.
+    transcriber: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// A device to capture instead, and where to report whether it opened.
+type DeviceSwitch = (String, oneshot::Sender<Result<(), String>>);
+
+/// How `start_transcription_session` runs the session; unset fields come
+/// from the settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SessionOptions {
+    /// Device to capture, instead of the first of the settings' devices
+    /// that opens.
+    pub device: Option<String>,
+    /// Segmentation settings to change for this session.
+    pub processor: ProcessorConfigUpdate,
+    /// Text the transcription continues (names, jargon).
+    pub prompt: Option<String>,
+}
+
+/// What is done to a session's final transcripts, as the settings say.
+struct Postprocessing {
+    /// Restore casing and punctuation on transcripts lacking them.
+    restore_punctuation: bool,
+    /// Rewrite spoken numbers, amounts and dates.
+    inverse_normalize: bool,
+    profanity: ProfanityFilter,
+    /// Translator of the transcripts, and the language it translates into.
+    translation: Option<(Arc<HttpTranslator>, String)>,
+}
+
+impl Postprocessing {
+    fn new(settings: &Settings) -> Result<Self, String> {
+        Ok(Self {
+            restore_punctuation: settings.restore_punctuation,
+            inverse_normalize: settings.itn,
+            profanity: settings.profanity_filter()?,
+            translation: settings
+                .translator()?
+                .map(|(translator, target)| (Arc::new(translator), target)),
+        })
+    }
+
+    /// Post-process `result`'s text and translate it in the background,
+    /// emitting the translation. Returns the text before inverse text
+    /// normalization if that changed it, and whether it is profane.
+    fn finish(
+        &self,
+        app: &AppHandle,
+        result: &mut TranscriptResult,
+        timestamp: u64,
+    ) -> (Option<String>, bool) {
+        if self.restore_punctuation {
+            result.text = punctuation::restore(&result.text, &result.language);
+        }
+        let mut raw_text = None;
+        if self.inverse_normalize {
+            let normalized = itn::normalize(&result.text, &result.language);
+            if normalized != result.text {
+                raw_text = Some(std::mem::replace(&mut result.text, normalized));
+            }
+        }
+        let (text, profane) = self.profanity.apply(&result.text, &result.language);
+        result.text = text;
+        let raw_text = raw_text.map(|raw| self.profanity.apply(&raw, &result.language).0);
+        if let Some((ref translator, ref target)) = self.translation {
+            // Transcripts already in the target language are skipped
+            if result.language != *target {
+                let translator = Arc::clone(translator);
+                let app = app.clone();
+                let text = result.text.clone();
+                let source = result.language.clone();
+                let target = target.clone();
+                tauri::async_runtime::spawn(async move {
+                    let msg = match translator.translate(&text, &source, &target).await {
+                        Ok(translated) => {
+                            TranscriptMessage::translation(translated, source, target, timestamp)
+                        }
+                        Err(e) => TranscriptMessage::error(
+                            ErrorCode::TranslationFailed,
+                            format!("Translation failed: {e:#}"),
+                        ),
+                    };
+                    emit(&app, msg);
+                });
+            }
+        }
+        (raw_text, profane)
+    }
+}
+
+/// A running session.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    /// Device captured when the session started.
+    pub device: String,
+    /// Start time, in Unix milliseconds.
+    pub started_ms: u64,
+    pub paused: bool,
+}
+
+/// Load the model from `dir` (default: where `download_models` puts it),
+/// with the language of the settings. Returns the load time in
+/// milliseconds, or None if it was loaded already.
+#[tauri::command]
+pub async fn load_model(
+    app: AppHandle,
+    core: State<'_, Core>,
+    settings: State<'_, SettingsStore>,
+    dir: Option<String>,
+) -> Result<Option<u64>, String> {
+    load_engine(&app, &core, &settings, dir)
+        .await
+        .map(|(_, loaded)| loaded)
+}
+
+/// The engine, loaded from `dir` first if it isn't, and its load time in
+/// milliseconds if it was loaded now.
+async fn load_engine(
+    app: &AppHandle,
+    core: &Core,
+    settings: &SettingsStore,
+    dir: Option<String>,
+) -> Result<(VoxtralHandle, Option<u64>), String> {
+    let dir = crate::models_dir(app, dir)?;
+    let settings = settings.get();
+    let language = settings.language_hint()?;
+    let config = EngineConfig {
+        delay: settings.latency_preset()?.delay(),
+        ..EngineConfig::default()
+    };
+    let mut engine = core.engine.lock().await;
+    let handle = match engine.as_ref() {
+        Some(handle) => handle.clone(),
+        None => {
+            let voxtral = VoxtralEngine::new(dir.join(MODEL_FILE), dir.join(TOKENIZER_FILE))
+                .with_language(language)
+                .with_hotwords(Some(settings.engine_hotwords()))
+                .with_config(config);
+            VoxtralHandle::spawn(voxtral).map_err(|e| format!("{e:#}"))?
+        }
+    };
+    emit(
+        app,
+        TranscriptMessage::status(PipelineState::LoadingModel, "Loading model".to_string()),
+    );
+    match handle.ensure_loaded().await {
+        Ok(loaded) => {
+            *engine = Some(handle.clone());
+            emit(
+                app,
+                TranscriptMessage::status(PipelineState::Ready, "Model loaded".to_string()),
+            );
+            Ok((handle, loaded))
+        }
+        Err(e) => {
+            // Another directory may work; start over from it next time
+            *engine = None;
+            let text = format!("Model load failed: {e:#}");
+            emit(
+                app,
+                TranscriptMessage::error(ErrorCode::ModelLoadFailed, text.clone()),
+            );
+            Err(text)
+        }
+    }
+}
+
+/// Start capturing and transcribing as `options` say, loading the model
+/// (from where `download_models` puts it) if it isn't.
+#[tauri::command]
+pub async fn start_transcription_session(
+    app: AppHandle,
+    core: State<'_, Core>,
+    settings: State<'_, SettingsStore>,
+    options: Option<SessionOptions>,
+) -> Result<SessionInfo, String> {
+    let options = options.unwrap_or_default();
+    let mut config = settings.get().processor_config()?;
+    let post = Arc::new(RwLock::new(Postprocessing::new(&settings.get())?));
+    config
+        .update(&options.processor)
+        .map_err(|e| format!("{e:#}"))?;
+    let buffer_ms = config.buffer_ms;
+    let mut processor = AudioProcessor::from_config(config);
+    if let Some(vad) = settings.get().vad_backend()? {
+        processor = processor.with_vad(vad);
+    }
+    if core.session.lock().unwrap().is_some() {
+        return Err("A session is already running".to_string());
+    }
+    let (engine, _) = load_engine(&app, &core, &settings, None).await?;
+    let devices = match options.device {
+        Some(device) => vec![device],
+        None => settings.get().devices,
+    };
+    let job = JobOptions {
+        prompt: options.prompt,
+        ..JobOptions::default()
+    };
+
+    let (chunks_tx, chunks_rx) = mpsc::channel(CHUNK_QUEUE);
+    let (started_tx, started_rx) = oneshot::channel();
+    let (switches, switches_rx) = std::sync::mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let capture_stop = Arc::clone(&stop);
+    let capture_app = app.clone();
+    std::thread::Builder::new()
+        .name("capture".into())
+        .spawn(move || {
+            capture(
+                &capture_app,
+                &devices,
+                buffer_ms,
+                chunks_tx,
+                &capture_stop,
+                switches_rx,
+                started_tx,
+            )
+        })
+        .map_err(|e| format!("Failed to spawn capture thread: {e}"))?;
+    let device = started_rx
+        .await
+        .map_err(|_| "Audio capture failed".to_string())??;
+
+    let mut session = core.session.lock().unwrap();
+    if session.is_some() {
+        // Started meanwhile
+        stop.store(true, Ordering::Relaxed);
+        return Err("A session is already running".to_string());
+    }
+    let started_ms = now_ms();
+    let info = SessionInfo {
+        id: format!("session-{started_ms}"),
+        device: device.clone(),
+        started_ms,
+        paused: false,
+    };
+    eprintln!("[VoxVault] Session {} capturing {device}", info.id);
+    tray::set_session(&app, Some(&info));
+    emit(
+        &app,
+        TranscriptMessage::status(PipelineState::Capturing, format!("Capturing {device}")),
+    );
+    let paused = Arc::new(AtomicBool::new(false));
+    let transcriber = tauri::async_runtime::spawn(transcribe(
+        app,
+        engine,
+        processor,
+        job,
+        Arc::clone(&post),
+        Arc::clone(&paused),
+        chunks_rx,
+    ));
+    *session = Some(Session {
+        info: info.clone(),
+        post,
+        stop,
+        paused,
+        switches,
+        transcriber,
+    });
+    Ok(info)
+}
+
+/// Stop the running session once its last speech is transcribed. Returns
+/// the session that ran.
+#[tauri::command]
+pub async fn stop_transcription_session(
+    app: AppHandle,
+    core: State<'_, Core>,
+) -> Result<SessionInfo, String> {
+    let Some(session) = core.session.lock().unwrap().take() else {
+        return Err("No session running".to_string());
+    };
+    session.stop.store(true, Ordering::Relaxed);
+    let _ = session.transcriber.await;
+    eprintln!("[VoxVault] Session {} stopped", session.info.id);
+    tray::set_session(&app, None);
+    emit(
+        &app,
+        TranscriptMessage::status(PipelineState::Ready, "Session stopped".to_string()),
+    );
+    Ok(session.info)
+}
+
+/// Pause the running session, or resume it. The capture goes on; the
+/// speech cut by the pause is still transcribed.
+#[tauri::command]
+pub async fn pause_transcription_session(
+    app: AppHandle,
+    core: State<'_, Core>,
+    paused: bool,
+) -> Result<SessionInfo, String> {
+    let mut session = core.session.lock().unwrap();
+    let Some(session) = session.as_mut() else {
+        return Err("No session running".to_string());
+    };
+    session.paused.store(paused, Ordering::Relaxed);
+    session.info.paused = paused;
+    tray::set_session(&app, Some(&session.info));
+    emit(&app, pause_status(paused));
+    Ok(session.info.clone())
+}
+
+/// Start a session with the settings' options, or stop the running one
+/// (the tray menu's "Start Capture" item).
+pub fn toggle_capture(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let core = app.state::<Core>();
+        let running = core.session.lock().unwrap().is_some();
+        let result = if running {
+            stop_transcription_session(app.clone(), core)
+                .await
+                .map(drop)
+        } else {
+            let settings = app.state::<SettingsStore>();
+            start_transcription_session(app.clone(), core, settings, None)
+                .await
+                .map(drop)
+        };
+        if let Err(e) = result {
+            eprintln!("[VoxVault] {e}");
+        }
+    });
+}
+
+/// Pause or resume the running session (the tray menu's "Pause" item).
+pub fn toggle_pause(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let core = app.state::<Core>();
+        let Some(paused) = core.session.lock().unwrap().as_ref().map(|s| s.info.paused) else {
+            return;
+        };
+        if let Err(e) = pause_transcription_session(app.clone(), core, !paused).await {
+            eprintln!("[VoxVault] {e}");
+        }
+    });
+}
+
+/// Put the settings that apply at once in effect: the loaded engine's
+/// hotwords and latency, and the running session's post-processing.
+pub async fn apply_settings(
+    app: &AppHandle,
+    previous: &Settings,
+    settings: &Settings,
+) -> Result<(), String> {
+    let core = app.state::<Core>();
+    let engine = core.engine.lock().await.clone();
+    if let Some(engine) = engine {
+        if settings.hotwords != previous.hotwords {
+            let hotwords = settings.engine_hotwords();
+            engine
+                .run(move |engine| engine.set_hotwords(Some(hotwords)))
+                .await
+                .and_then(|set| set)
+                .map_err(|e| format!("Rejected hotwords: {e:#}"))?;
+        }
+        if settings.latency != previous.latency {
+            let delay = settings.latency_preset()?.delay();
+            engine
+                .run(move |engine| engine.set_delay(delay))
+                .await
+                .and_then(|set| set)
+                .map_err(|e| format!("Rejected latency preset: {e:#}"))?;
+        }
+    }
+    if let Some(session) = core.session.lock().unwrap().as_ref() {
+        *session.post.write().unwrap() = Postprocessing::new(settings)?;
+    }
+    Ok(())
+}
+
+/// The running session, if any.
+#[tauri::command]
+pub async fn get_transcription_session(
+    core: State<'_, Core>,
+) -> Result<Option<SessionInfo>, String> {
+    Ok(core
+        .session
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|session| session.info.clone()))
+}
+
+/// Input devices the capture can open, by name.
+#[tauri::command]
+pub async fn list_input_devices() -> Result<Vec<String>, String> {
+    tauri::async_runtime::spawn_blocking(AudioCapture::list_devices)
+        .await
+        .map_err(|e| format!("Device listing failed: {e}"))?
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Move the running session's capture to `device`. If it can't be opened,
+/// the capture stays on the current device.
+#[tauri::command]
+pub async fn select_input_device(core: State<'_, Core>, device: String) -> Result<(), String> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    {
+        let session = core.session.lock().unwrap();
+        let Some(session) = session.as_ref() else {
+            return Err("No session running".to_string());
+        };
+        session
+            .switches
+            .send((device, reply_tx))
+            .map_err(|_| "The capture has stopped".to_string())?;
+    }
+    reply_rx
+        .await
+        .map_err(|_| "The capture has stopped".to_string())?
+}
+
+/// Capture `devices` (the first that opens) into `chunks` of `buffer_ms`
+/// until `stop` is set, reporting the device opened, or why none did, to
+/// `started`. Moves to the devices of `switches` and emits the capture's
+/// device events.
+fn capture(
+    app: &AppHandle,
+    devices: &[String],
+    buffer_ms: u32,
+    chunks: mpsc::Sender<AudioChunk>,
+    stop: &AtomicBool,
+    switches: std::sync::mpsc::Receiver<DeviceSwitch>,
+    started: oneshot::Sender<Result<String, String>>,
+) {
+    let (events_tx, mut events) = mpsc::unbounded_channel();
+    let capture = AudioCapture::with_priority(devices, CaptureSource::Input, buffer_ms, chunks)
+        .map(|capture| capture.with_events(events_tx))
+        .and_then(|mut capture| {
+            capture.start(buffer_ms)?;
+            Ok(capture)
+        });
+    let mut capture = match capture {
+        Ok(capture) => capture,
+        Err(e) => {
+            let _ = started.send(Err(format!("Audio capture failed: {e:#}")));
+            return;
+        }
+    };
+    let _ = started.send(Ok(capture.active_device().to_string()));
+    while !stop.load(Ordering::Relaxed) {
+        while let Ok((device, reply)) = switches.try_recv() {
+            let switched = capture.switch_device(&device);
+            let _ = reply.send(switched.map_err(|e| format!("Can't capture {device}: {e:#}")));
+        }
+        capture.monitor();
+        while let Ok(event) = events.try_recv() {
+            emit_device_event(app, event);
+        }
+        std::thread::sleep(MONITOR_INTERVAL);
+    }
+    capture.stop();
+    // Dropping the capture closes `chunks`, which ends the transcription
+}
+
+/// Cut `chunks` into speech segments with `processor` and transcribe
+/// them with `job`'s options, post-processing the transcripts as `post`
+/// says, until the capture stops, then transcribe the speech left. The
+/// chunks are dropped while `paused` is set.
+///
+/// Partials carry the engine's language hint, or else the language of the
+/// last final transcript ("auto" before the first).
+async fn transcribe(
+    app: AppHandle,
+    engine: VoxtralHandle,
+    mut processor: AudioProcessor,
+    job: JobOptions,
+    post: Arc<RwLock<Postprocessing>>,
+    paused: Arc<AtomicBool>,
+    mut chunks: mpsc::Receiver<AudioChunk>,
+) {
+    let mut language = engine
+        .run(|engine| engine.language().map(|hint| hint.code().to_string()))
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "auto".to_string());
+    let mut was_paused = false;
+    while let Some(chunk) = chunks.recv().await {
+        if paused.load(Ordering::Relaxed) {
+            if !was_paused {
+                was_paused = true;
+                if let Some(segment) = processor.flush() {
+                    transcribe_segment(&app, &engine, &job, &post, &mut language, segment).await;
+                    // It reported listening
+                    emit(&app, pause_status(true));
+                }
+            }
+            continue;
+        }
+        was_paused = false;
+        let (rms, peak) = chunk.levels();
+        emit(&app, TranscriptMessage::level(rms, peak));
+        if let Some(segment) = processor.feed(chunk) {
+            transcribe_segment(&app, &engine, &job, &post, &mut language, segment).await;
+        }
+    }
+    if let Some(segment) = processor.flush() {
+        transcribe_segment(&app, &engine, &job, &post, &mut language, segment).await;
+    }
+}
+
+/// Transcribe `segment`, emitting its partial transcripts in `language`
+/// as they are decoded and then the final one, post-processed as `post`
+/// says, whose language becomes `language`.
+async fn transcribe_segment(
+    app: &AppHandle,
+    engine: &VoxtralHandle,
+    job: &JobOptions,
+    post: &RwLock<Postprocessing>,
+    language: &mut String,
+    segment: SpeechSegment,
+) {
+    emit(
+        app,
+        TranscriptMessage::status(PipelineState::Transcribing, "Transcribing".to_string()),
+    );
+    let timestamp = now_ms();
+    let (partials_tx, mut partials_rx) = mpsc::unbounded_channel::<String>();
+    let partial_app = app.clone();
+    let partial_language = language.clone();
+    let partials = tauri::async_runtime::spawn(async move {
+        while let Some(text) = partials_rx.recv().await {
+            emit(
+                &partial_app,
+                TranscriptMessage::transcript(
+                    text,
+                    partial_language.clone(),
+                    timestamp,
+                    false,
+                    None,
+                ),
+            );
+        }
+    });
+    let result = engine
+        .transcribe_streaming(segment.buffer, job.clone(), partials_tx)
+        .await;
+    // The last partials go out before the final
+    let _ = partials.await;
+    match result {
+        Ok(mut result) if !result.text.trim().is_empty() => {
+            language.clone_from(&result.language);
+            let (raw_text, profane) = post.read().unwrap().finish(app, &mut result, timestamp);
+            emit(
+                app,
+                TranscriptMessage::transcript(
+                    result.text,
+                    result.language,
+                    timestamp,
+                    true,
+                    result.rtf,
+                )
+                .with_segment(Some(segment.start_ms), Some(segment.end_ms))
+                .with_confidence(result.confidence, result.words)
+                .with_raw_text(raw_text)
+                .with_profane(profane),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => emit(
+            app,
+            TranscriptMessage::error(
+                ErrorCode::TranscriptionFailed,
+                format!("Transcription failed: {e:#}"),
+            ),
+        ),
+    }
+    emit(
+        app,
+        TranscriptMessage::status(PipelineState::Capturing, "Listening".to_string()),
+    );
+}
+
+fn pause_status(paused: bool) -> TranscriptMessage {
+    if paused {
+        TranscriptMessage::status(PipelineState::Ready, "Paused".to_string())
+    } else {
+        TranscriptMessage::status(PipelineState::Capturing, "Listening".to_string())
+    }
+}
+
+fn emit(app: &AppHandle, msg: TranscriptMessage) {
+    let event = match (msg.msg_type.as_str(), msg.is_final) {
+        ("transcript", false) => PARTIAL_EVENT,
+        ("transcript", true) => FINAL_EVENT,
+        ("translation", _) => TRANSLATION_EVENT,
+        _ => STATUS_EVENT,
+    };
+    let _ = app.emit(event, msg);
+}
+
+fn emit_device_event(app: &AppHandle, event: CaptureEvent) {
+    eprintln!("[VoxVault] {event}");
+    match event {
+        CaptureEvent::DeviceLost { ref device } => {
+            tray::set_detail(app, Some(&format!("{device} lost")));
+        }
+        CaptureEvent::DeviceReconnected { ref device }
+        | CaptureEvent::DeviceSwitched { ref device } => tray::set_detail(app, Some(device)),
+        CaptureEvent::StreamRestarted { .. } => {}
+    }
+    let _ = app.emit(INPUT_DEVICE_EVENT, event);
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
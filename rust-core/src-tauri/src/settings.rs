@@ -0,0 +1,302 @@
+//! Desktop app settings, saved as `settings.json` in the app config
+//! directory.
+//!
+//! `set_settings` validates and saves them, then emits `settings-changed`
+//! with the new settings. Shortcuts, the overlay's style, the engine's
+//! hotwords and latency, and the post-processing of final transcripts
+//! apply at once; capture, segmentation and language settings apply from
+//! the next in-process session or model load.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use voxvault_core::audio::processor::{ProcessorConfig, ProcessorConfigUpdate};
+use voxvault_core::audio::vad::{VadBackend, VadKind, WebRtcVad};
+use voxvault_core::voxtral::engine::LatencyPreset;
+use voxvault_core::voxtral::hotwords::Hotwords;
+use voxvault_core::voxtral::language::LanguageHint;
+use voxvault_core::voxtral::profanity::{ProfanityAction, ProfanityFilter};
+use voxvault_core::voxtral::translate::{HttpTranslator, TranslationService};
+
+use crate::hotkeys::{self, HotkeySettings};
+use crate::overlay::{self, OverlayStyle};
+use crate::session;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Share of a chunk's 30 ms frames the WebRTC VAD must find voiced for
+/// the chunk to count as speech (the CLI's `--vad-threshold` default).
+const WEBRTC_VOICED_SHARE: f32 = 0.5;
+
+/// Everything the desktop app lets the user configure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Capture devices of in-process sessions, tried in order.
+    pub devices: Vec<String>,
+    /// Audio per capture chunk, in ms.
+    pub buffer_ms: u32,
+    /// Shortest speech segment transcribed, in seconds.
+    pub min_duration_secs: f32,
+    /// Longest speech segment before it is cut, in seconds.
+    pub max_duration_secs: f32,
+    /// Silence that ends a speech segment, in ms.
+    pub silence_pause_ms: u32,
+    /// RMS energy at which speech starts.
+    pub speech_threshold: f32,
+    /// Voice activity detector: "energy" (RMS against `speech_threshold`)
+    /// or "webrtc".
+    pub vad: String,
+    /// Aggressiveness of the "webrtc" VAD, from 0 (flags the most audio as
+    /// speech) to 3 (most aggressive at rejecting non-speech).
+    pub vad_aggressiveness: u8,
+    /// Spoken language (ISO 639-1 code), or None to detect it.
+    pub language: Option<String>,
+    /// Where models are downloaded to and loaded from (None = the app data
+    /// directory).
+    pub models_dir: Option<String>,
+    /// Names and terms boosted during decoding.
+    pub hotwords: Vec<String>,
+    /// Latency/accuracy trade-off: "low-latency", "balanced" or "accurate".
+    pub latency: String,
+    /// Restore casing and punctuation on final transcripts lacking them.
+    pub restore_punctuation: bool,
+    /// Rewrite spoken numbers, amounts and dates in final transcripts.
+    pub itn: bool,
+    /// What to do with profanity in final transcripts: "keep", "mask" or
+    /// "tag".
+    pub profanity: String,
+    /// Language (ISO 639-1 code) final transcripts are translated into, or
+    /// None to not translate them.
+    pub translate_to: Option<String>,
+    /// Service translating: "libretranslate" or "deepl".
+    pub translator: String,
+    /// Address of the translation server (None = the service's default).
+    pub translator_url: Option<String>,
+    /// API key of the translation service (required by DeepL).
+    pub translator_api_key: Option<String>,
+    /// Port of the Python API on localhost.
+    pub python_api_port: u16,
+    /// Port of the transcription core's WebSocket and REST server on
+    /// localhost.
+    pub core_api_port: u16,
+    pub hotkeys: HotkeySettings,
+    /// Look of the subtitle overlay.
+    pub overlay: OverlayStyle,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let processor = ProcessorConfig::default();
+        Self {
+            devices: vec!["VoxVault Capture".to_string(), "BlackHole 2ch".to_string()],
+            buffer_ms: processor.buffer_ms,
+            min_duration_secs: processor.min_duration_secs,
+            max_duration_secs: processor.max_duration_secs,
+            silence_pause_ms: processor.silence_pause_ms,
+            speech_threshold: processor.speech_threshold,
+            vad: VadKind::default().to_string(),
+            vad_aggressiveness: 2,
+            language: None,
+            models_dir: None,
+            hotwords: Vec::new(),
+            latency: LatencyPreset::default().to_string(),
+            restore_punctuation: false,
+            itn: false,
+            profanity: ProfanityAction::default().to_string(),
+            translate_to: None,
+            translator: TranslationService::default().to_string(),
+            translator_url: None,
+            translator_api_key: None,
+            python_api_port: 8766,
+            core_api_port: 8765,
+            hotkeys: HotkeySettings::default(),
+            overlay: OverlayStyle::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Check the settings hang together.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.devices.iter().all(|d| d.trim().is_empty()) {
+            return Err("At least one capture device is needed".to_string());
+        }
+        if self.buffer_ms == 0 {
+            return Err("buffer_ms must be positive".to_string());
+        }
+        if self.python_api_port == 0 || self.core_api_port == 0 {
+            return Err("Ports must be between 1 and 65535".to_string());
+        }
+        self.processor_config()?;
+        self.vad_backend()?;
+        self.language_hint()?;
+        self.latency_preset()?;
+        self.profanity_filter()?;
+        self.translator()?;
+        self.overlay.validate()?;
+        Ok(())
+    }
+
+    /// Segmentation settings of an in-process session.
+    pub fn processor_config(&self) -> Result<ProcessorConfig, String> {
+        let mut config = ProcessorConfig {
+            buffer_ms: self.buffer_ms,
+            ..ProcessorConfig::default()
+        };
+        config
+            .update(&ProcessorConfigUpdate {
+                min_duration_secs: Some(self.min_duration_secs),
+                max_duration_secs: Some(self.max_duration_secs),
+                silence_pause_ms: Some(self.silence_pause_ms),
+                speech_threshold: Some(self.speech_threshold),
+                ..ProcessorConfigUpdate::default()
+            })
+            .map_err(|e| format!("{e:#}"))?;
+        Ok(config)
+    }
+
+    /// VAD of an in-process session, or None to keep the processor's RMS
+    /// energy one.
+    pub fn vad_backend(&self) -> Result<Option<Box<dyn VadBackend>>, String> {
+        if self.vad_aggressiveness > 3 {
+            return Err(format!(
+                "vad_aggressiveness must be 0–3, got {}",
+                self.vad_aggressiveness
+            ));
+        }
+        match self.vad.parse().map_err(|e| format!("{e:#}"))? {
+            VadKind::Energy => Ok(None),
+            VadKind::WebRtc => WebRtcVad::new(self.vad_aggressiveness, WEBRTC_VOICED_SHARE)
+                .map(|vad| Some(Box::new(vad) as Box<dyn VadBackend>))
+                .map_err(|e| format!("{e:#}")),
+            VadKind::Silero => Err("The app supports the 'energy' and 'webrtc' VADs".to_string()),
+        }
+    }
+
+    /// Language hint of the engine.
+    pub fn language_hint(&self) -> Result<Option<LanguageHint>, String> {
+        self.language
+            .as_deref()
+            .filter(|code| !code.is_empty() && *code != "auto")
+            .map(LanguageHint::new)
+            .transpose()
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Vocabulary boosted by the engine.
+    pub fn engine_hotwords(&self) -> Hotwords {
+        Hotwords::new(self.hotwords.iter().cloned())
+    }
+
+    /// Latency preset of the engine.
+    pub fn latency_preset(&self) -> Result<LatencyPreset, String> {
+        self.latency.parse().map_err(|e| format!("{e:#}"))
+    }
+
+    /// Profanity filter of final transcripts.
+    pub fn profanity_filter(&self) -> Result<ProfanityFilter, String> {
+        let action: ProfanityAction = self.profanity.parse().map_err(|e| format!("{e:#}"))?;
+        Ok(ProfanityFilter::new(action))
+    }
+
+    /// Translator of final transcripts and the language it translates
+    /// into, if they are translated.
+    pub fn translator(&self) -> Result<Option<(HttpTranslator, String)>, String> {
+        let target = self.translate_to.as_deref().map(str::trim);
+        let Some(target) = target.filter(|t| !t.is_empty()) else {
+            return Ok(None);
+        };
+        let service: TranslationService = self.translator.parse().map_err(|e| format!("{e:#}"))?;
+        let mut translator = HttpTranslator::new(service, self.translator_api_key.clone())
+            .map_err(|e| format!("{e:#}"))?;
+        if let Some(ref url) = self.translator_url {
+            translator = translator.with_url(url.clone());
+        }
+        Ok(Some((translator, target.to_ascii_lowercase())))
+    }
+}
+
+/// The settings in effect, managed by Tauri.
+pub struct SettingsStore {
+    /// Where they are saved (None when the app has no config directory).
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// The saved settings, or the defaults if none were saved (or they
+    /// can't be read).
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_config_dir()
+            .ok()
+            .map(|dir| dir.join(SETTINGS_FILE));
+        let settings = match path.as_ref().map(std::fs::read_to_string) {
+            Some(Ok(json)) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!("[VoxVault] Ignoring invalid {SETTINGS_FILE}: {e}");
+                Settings::default()
+            }),
+            _ => Settings::default(),
+        };
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// The settings in effect.
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Save `settings` and put them in effect.
+    pub fn save(&self, settings: Settings) -> Result<(), String> {
+        let Some(ref path) = self.path else {
+            return Err("No app config directory".to_string());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_settings(store: State<'_, SettingsStore>) -> Result<Settings, String> {
+    Ok(store.get())
+}
+
+/// Validate, save and apply `settings`. Invalid settings, or shortcuts
+/// that fail to register, leave the current ones in effect.
+#[tauri::command]
+pub async fn set_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    settings: Settings,
+) -> Result<(), String> {
+    settings.validate()?;
+    let previous = store.get();
+    if settings.hotkeys != previous.hotkeys {
+        if let Err(e) = hotkeys::register(&app, &settings.hotkeys) {
+            let _ = hotkeys::register(&app, &previous.hotkeys);
+            return Err(e);
+        }
+    }
+    if let Err(e) = store.save(settings.clone()) {
+        let _ = hotkeys::register(&app, &previous.hotkeys);
+        return Err(e);
+    }
+    if settings.overlay != previous.overlay {
+        overlay::restyle(&app, &settings.overlay);
+    }
+    session::apply_settings(&app, &previous, &settings).await?;
+    let _ = app.emit("settings-changed", settings);
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//! Tray icon status: a colored dot on the icon and the tooltip show
+//! whether the in-process pipeline is idle, listening, transcribing or
+//! failing.
+//!
+//! The state follows the pipeline's status messages (`transcript:status`
+//! events, see [`listen`]); sessions add the device they capture.
+//!
+//! The tray menu starts, pauses and stops sessions, toggles stealth mode
+//! and previews the last transcript, so the app works from the menu bar
+//! alone.
+
+use std::sync::Mutex;
+
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIcon;
+use tauri::{AppHandle, Listener, Manager, Wry};
+
+use crate::autostart::{self, AutostartItem};
+use crate::session::SessionInfo;
+
+pub const SHOW_ID: &str = "show";
+pub const CAPTURE_ID: &str = "capture";
+pub const PAUSE_ID: &str = "pause";
+pub const STEALTH_ID: &str = "stealth";
+pub const QUIT_ID: &str = "quit";
+/// Characters of the last transcript the menu shows.
+const PREVIEW_CHARS: usize = 48;
+
+/// What the pipeline is doing, as the tray shows it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrayState {
+    /// No session (model loading or loaded).
+    #[default]
+    Idle,
+    Capturing,
+    Transcribing,
+    Error,
+}
+
+impl TrayState {
+    /// The state of a status message's `state`.
+    fn from_pipeline(state: &str) -> Self {
+        match state {
+            "capturing" => Self::Capturing,
+            "transcribing" => Self::Transcribing,
+            "error" => Self::Error,
+            _ => Self::Idle,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Idle => "Idle",
+            Self::Capturing => "Listening",
+            Self::Transcribing => "Transcribing",
+            Self::Error => "Error",
+        }
+    }
+
+    /// RGB of the icon's dot (None = the plain icon).
+    fn color(self) -> Option<[u8; 3]> {
+        match self {
+            Self::Idle => None,
+            Self::Capturing => Some([0x66, 0xbb, 0x6a]),
+            Self::Transcribing => Some([0xff, 0xa7, 0x26]),
+            Self::Error => Some([0xef, 0x53, 0x50]),
+        }
+    }
+}
+
+/// What the tray shows, managed by Tauri.
+#[derive(Default)]
+pub struct TrayStatus(Mutex<(TrayState, Option<String>)>);
+
+/// The tray menu's items that change, managed by Tauri.
+pub struct TrayMenu {
+    capture: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+    stealth: CheckMenuItem<Wry>,
+    /// The last transcript, disabled: it only shows.
+    preview: MenuItem<Wry>,
+}
+
+/// Build the tray menu and manage its items.
+pub fn menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let stealth = app.state::<crate::StealthState>().get("main");
+    let items = TrayMenu {
+        capture: MenuItem::with_id(app, CAPTURE_ID, "Start Capture", true, None::<&str>)?,
+        pause: MenuItem::with_id(app, PAUSE_ID, "Pause", false, None::<&str>)?,
+        stealth: CheckMenuItem::with_id(
+            app,
+            STEALTH_ID,
+            "Stealth Mode",
+            true,
+            stealth,
+            None::<&str>,
+        )?,
+        preview: MenuItem::new(app, "No transcript yet", false, None::<&str>)?,
+    };
+    let autostart_item = autostart::menu_item(app)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &items.preview,
+            &PredefinedMenuItem::separator(app)?,
+            &items.capture,
+            &items.pause,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, SHOW_ID, "Show VoxVault", true, None::<&str>)?,
+            &items.stealth,
+            &autostart_item,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, QUIT_ID, "Quit", true, None::<&str>)?,
+        ],
+    )?;
+    app.manage(items);
+    app.manage(AutostartItem(autostart_item));
+    Ok(menu)
+}
+
+/// Follow the pipeline's status messages and transcripts.
+pub fn listen(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen("transcript:status", move |event| {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        if let Some(state) = msg.get("state").and_then(|s| s.as_str()) {
+            set_state(&handle, TrayState::from_pipeline(state));
+        }
+    });
+    let handle = app.clone();
+    app.listen("transcript:final", move |event| {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        if let Some(text) = msg.get("text").and_then(|t| t.as_str()) {
+            set_preview(&handle, text);
+        }
+    });
+}
+
+/// Show `session`'s controls in the menu and its device in the tooltip, or
+/// the controls to start one.
+pub fn set_session(app: &AppHandle, session: Option<&SessionInfo>) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let (capture, pause) = match session {
+            Some(session) if session.paused => ("Stop Capture", "Resume"),
+            Some(_) => ("Stop Capture", "Pause"),
+            None => ("Start Capture", "Pause"),
+        };
+        let _ = menu.capture.set_text(capture);
+        let _ = menu.pause.set_text(pause);
+        let _ = menu.pause.set_enabled(session.is_some());
+    }
+    set_detail(app, session.map(|session| session.device.as_str()));
+}
+
+/// Check the stealth item if the main window is hidden from screen capture.
+pub fn set_stealth(app: &AppHandle, enabled: bool) {
+    if let Some(menu) = app.try_state::<TrayMenu>() {
+        let _ = menu.stealth.set_checked(enabled);
+    }
+}
+
+/// Show the end of `text` as the last transcript.
+fn set_preview(app: &AppHandle, text: &str) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let text = text.trim();
+    let count = text.chars().count();
+    let preview = if count > PREVIEW_CHARS {
+        let tail: String = text.chars().skip(count - PREVIEW_CHARS).collect();
+        format!("…{}", tail.trim_start())
+    } else {
+        text.to_string()
+    };
+    let _ = menu.preview.set_text(preview);
+}
+
+/// Show `state` in the tray.
+pub fn set_state(app: &AppHandle, state: TrayState) {
+    let Some(status) = app.try_state::<TrayStatus>() else {
+        return;
+    };
+    let mut current = status.0.lock().unwrap();
+    // A failed segment is followed by "listening": the error shows until
+    // the next segment is transcribed
+    let sticky = current.0 == TrayState::Error && state == TrayState::Capturing;
+    if current.0 != state && !sticky {
+        current.0 = state;
+        refresh(app, current.0, current.1.as_deref());
+    }
+}
+
+/// Show `detail` (e.g. the device captured) in the tooltip, or nothing.
+pub fn set_detail(app: &AppHandle, detail: Option<&str>) {
+    let Some(status) = app.try_state::<TrayStatus>() else {
+        return;
+    };
+    let mut current = status.0.lock().unwrap();
+    current.1 = detail.map(str::to_string);
+    refresh(app, current.0, current.1.as_deref());
+}
+
+fn refresh(app: &AppHandle, state: TrayState, detail: Option<&str>) {
+    let Some(tray) = app.try_state::<TrayIcon>() else {
+        return;
+    };
+    let tooltip = match (state, detail) {
+        (TrayState::Idle, _) => "VoxVault".to_string(),
+        (state, Some(detail)) => format!("VoxVault — {} ({detail})", state.label()),
+        (state, None) => format!("VoxVault — {}", state.label()),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+    if let Some(icon) = app.default_window_icon() {
+        // A template icon is drawn in the menu bar's color: keep the dot's
+        let color = state.color();
+        let _ = tray.set_icon(Some(match color {
+            Some(color) => with_dot(icon, color),
+            None => icon.clone().to_owned(),
+        }));
+        let _ = tray.set_icon_as_template(color.is_none());
+    }
+}
+
+/// `icon` with a dot of `color` in its bottom right corner.
+fn with_dot(icon: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    let radius = width.min(height) as f64 / 4.0;
+    let (cx, cy) = (width as f64 - radius, height as f64 - radius);
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&[color[0], color[1], color[2], 0xff]);
+            }
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
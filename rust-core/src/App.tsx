@@ -1,4 +1,7 @@
 import { useCallback, useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { TitleBar } from "./components/TitleBar";
 import { StatusBar } from "./components/StatusBar";
 import { TranscriptView } from "./components/TranscriptView";
@@ -6,6 +9,7 @@ import { SettingsPanel } from "./components/SettingsPanel";
 import { useTranscript } from "./hooks/useTranscript";
 import { useSession } from "./hooks/useSession";
 import { useNotification } from "./hooks/useNotification";
+import type { HotkeyEvent } from "./types";
 
 function App() {
   const {
@@ -20,6 +24,8 @@ function App() {
     clearTranscript,
     getFullText,
     rtf,
+    levels,
+    speaking,
   } = useTranscript();
   const { session, isActive, loading, error, startSession, stopSession } =
     useSession();
@@ -31,6 +37,23 @@ function App() {
   const [duration, setDuration] = useState(0);
   const timerRef = useRef<ReturnType<typeof setInterval> | null>(null);
 
+  // Stealth mode as the backend tracks it for this window
+  useEffect(() => {
+    const label = getCurrentWindow().label;
+    invoke<boolean>("get_stealth_mode")
+      .then(setStealthMode)
+      .catch(() => {});
+    const unlisten = listen<{ window: string; enabled: boolean }>(
+      "stealth-changed",
+      (event) => {
+        if (event.payload.window === label) setStealthMode(event.payload.enabled);
+      }
+    );
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   // Session timer
   useEffect(() => {
     if (isActive) {
@@ -101,6 +124,28 @@ function App() {
     }
   }, [getFullText]);
 
+  // Global shortcuts the backend forwards
+  useEffect(() => {
+    const unlisten = listen<HotkeyEvent>("hotkey", ({ payload }) => {
+      switch (payload.action) {
+        case "toggle_capture":
+          if (isActive) handleStop();
+          else handleStart();
+          break;
+        case "push_to_talk":
+          if (payload.pressed && !isActive) handleStart();
+          else if (!payload.pressed && isActive) handleStop();
+          break;
+        case "copy_transcript":
+          handleCopy();
+          break;
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [isActive, handleStart, handleStop, handleCopy]);
+
   const detectedLang = sourceLang;
 
   return (
@@ -117,6 +162,8 @@ function App() {
         duration={duration}
         statusText={statusText}
         rtf={rtf}
+        levels={levels}
+        speaking={speaking}
       />
 
       <TranscriptView
@@ -0,0 +1,45 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useTranscript } from "../hooks/useTranscript";
+import type { DesktopSettings, OverlayStyle } from "../types";
+
+/** Captions of the click-through overlay window (`show_overlay`). */
+export function Overlay() {
+  const { finalText, partial } = useTranscript();
+  const [style, setStyle] = useState<OverlayStyle | null>(null);
+
+  useEffect(() => {
+    invoke<DesktopSettings>("get_settings")
+      .then((settings) => setStyle(settings.overlay))
+      .catch(() => {});
+    const unlisten = listen<DesktopSettings>("settings-changed", (event) =>
+      setStyle(event.payload.overlay)
+    );
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  if (!style) return null;
+
+  return (
+    <div
+      className={`overlay overlay-${style.position}`}
+      style={{ fontSize: style.font_size, color: style.text_color }}
+    >
+      {(finalText || partial) && (
+        // Keeps `lines` lines; older text is clipped at the top
+        <div
+          className="overlay-captions"
+          style={{ background: style.background, maxHeight: `${style.lines * 1.4}em` }}
+        >
+          <div>
+            {finalText}
+            {partial && <span className="overlay-partial"> {partial}</span>}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,9 +1,25 @@
 import { useCallback, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { AppSettings } from "../types";
+import type {
+  AppSettings,
+  AudioDeviceInfo,
+  DesktopSettings,
+  HotkeySettings,
+  Permissions,
+  SetupResult,
+  TeardownResult,
+} from "../types";
 
 const API_URL = "";
 
+const HOTKEY_LABELS: [keyof HotkeySettings, string][] = [
+  ["toggle_capture", "Start / stop session"],
+  ["push_to_talk", "Push to talk"],
+  ["toggle_stealth", "Toggle stealth mode"],
+  ["toggle_window", "Show / hide window"],
+  ["copy_transcript", "Copy transcript"],
+];
+
 interface SettingsPanelProps {
   visible: boolean;
   onClose: () => void;
@@ -20,6 +36,17 @@ export function SettingsPanel({
   const [settings, setSettings] = useState<AppSettings | null>(null);
   const [translationMode, setTranslationMode] = useState("disabled");
   const [targetLanguage, setTargetLanguage] = useState("pt");
+  const [hotwords, setHotwords] = useState("");
+  const [desktop, setDesktop] = useState<DesktopSettings | null>(null);
+  const [desktopEdited, setDesktopEdited] = useState(false);
+  const [desktopError, setDesktopError] = useState<string | null>(null);
+  const [devices, setDevices] = useState<AudioDeviceInfo[]>([]);
+  const [deviceSetup, setDeviceSetup] = useState<SetupResult | null>(null);
+  const [deviceStatus, setDeviceStatus] = useState<string | null>(null);
+  const [settingUpDevices, setSettingUpDevices] = useState(false);
+  const [overlayVisible, setOverlayVisible] = useState(false);
+  const [autostart, setAutostart] = useState(false);
+  const [permissions, setPermissions] = useState<Permissions | null>(null);
   const [saving, setSaving] = useState(false);
 
   // Load settings on open
@@ -33,6 +60,23 @@ export function SettingsPanel({
         setTargetLanguage(data.target_language);
       })
       .catch(() => {});
+    invoke<DesktopSettings>("get_settings")
+      .then((data) => {
+        setDesktop(data);
+        setHotwords(data.hotwords.join("\n"));
+        setDesktopEdited(false);
+        setDesktopError(null);
+      })
+      .catch(() => {});
+    invoke<AudioDeviceInfo[]>("list_audio_devices")
+      .then(setDevices)
+      .catch(() => {});
+    invoke<boolean>("get_autostart")
+      .then(setAutostart)
+      .catch(() => {});
+    invoke<Permissions>("check_permissions")
+      .then(setPermissions)
+      .catch(() => {});
   }, [visible]);
 
   const handleSave = useCallback(async () => {
@@ -48,10 +92,75 @@ export function SettingsPanel({
       });
     } catch {
       // Silently fail
-    } finally {
-      setSaving(false);
     }
-  }, [translationMode, targetLanguage]);
+    if (desktop && desktopEdited) {
+      try {
+        await invoke("set_settings", { settings: desktop });
+        setDesktopEdited(false);
+        setDesktopError(null);
+      } catch (e) {
+        setDesktopError(String(e));
+      }
+    }
+    setSaving(false);
+  }, [translationMode, targetLanguage, desktop, desktopEdited]);
+
+  const handleDeviceSetup = async () => {
+    setSettingUpDevices(true);
+    try {
+      const result = await invoke<SetupResult>("setup_audio_devices");
+      setDeviceSetup(result);
+      setDeviceStatus(null);
+      setDevices(await invoke<AudioDeviceInfo[]>("list_audio_devices"));
+    } catch (e) {
+      setDeviceStatus(String(e));
+    }
+    setSettingUpDevices(false);
+  };
+
+  const handleDeviceTeardown = async () => {
+    setSettingUpDevices(true);
+    try {
+      const result = await invoke<TeardownResult>("teardown_audio_devices");
+      setDeviceSetup(null);
+      setDeviceStatus(
+        [`Removed ${result.devices_destroyed} device(s)`, ...result.errors].join("; ")
+      );
+      setDevices(await invoke<AudioDeviceInfo[]>("list_audio_devices"));
+    } catch (e) {
+      setDeviceStatus(String(e));
+    }
+    setSettingUpDevices(false);
+  };
+
+  const handleOverlayToggle = async () => {
+    const newValue = !overlayVisible;
+    try {
+      await invoke("show_overlay", { visible: newValue });
+      setOverlayVisible(newValue);
+    } catch (e) {
+      setDesktopError(String(e));
+    }
+  };
+
+  const handleAutostartToggle = async () => {
+    const newValue = !autostart;
+    try {
+      await invoke("set_autostart", { enabled: newValue });
+      setAutostart(newValue);
+    } catch (e) {
+      setDesktopError(String(e));
+    }
+  };
+
+  const handleMicrophoneAccess = async () => {
+    if (permissions?.microphone === "not_determined") {
+      // The answer comes from the system prompt; check again on next open
+      setPermissions(await invoke<Permissions>("check_permissions", { prompt: true }));
+    } else {
+      await invoke("open_permission_settings", { permission: "microphone" });
+    }
+  };
 
   const handleStealthToggle = async () => {
     const newValue = !stealthMode;
@@ -95,6 +204,99 @@ export function SettingsPanel({
             </label>
           </div>
 
+          {/* Captions Overlay */}
+          <div className="setting-group">
+            <label className="setting-label">Captions Overlay</label>
+            <p className="setting-desc">
+              Show the transcript as subtitles over other apps; clicks go through
+            </p>
+            <label className="toggle">
+              <input type="checkbox" checked={overlayVisible} onChange={handleOverlayToggle} />
+              <span className="toggle-slider" />
+            </label>
+          </div>
+
+          {/* Launch at Login */}
+          <div className="setting-group">
+            <label className="setting-label">Launch at Login</label>
+            <p className="setting-desc">Start VoxVault in the menu bar when you log in</p>
+            <label className="toggle">
+              <input type="checkbox" checked={autostart} onChange={handleAutostartToggle} />
+              <span className="toggle-slider" />
+            </label>
+          </div>
+
+          {/* Permissions */}
+          {permissions && permissions.microphone !== "not_applicable" && (
+            <div className="setting-group">
+              <label className="setting-label">Permissions</label>
+              <div className="api-status">
+                <span className={permissions.microphone === "granted" ? "key-set" : "key-missing"}>
+                  Microphone: {permissions.microphone.replace("_", " ")}
+                </span>
+              </div>
+              {permissions.microphone !== "granted" && permissions.microphone !== "restricted" && (
+                <div className="device-actions">
+                  <button className="btn btn-secondary" onClick={handleMicrophoneAccess}>
+                    {permissions.microphone === "not_determined"
+                      ? "Allow Microphone"
+                      : "Open System Settings"}
+                  </button>
+                </div>
+              )}
+            </div>
+          )}
+
+          {/* Audio Devices */}
+          <div className="setting-group">
+            <label className="setting-label">Audio Devices</label>
+            <p className="setting-desc">
+              VoxVault Capture, Mic and Output are built on BlackHole ({devices.length} devices
+              found)
+            </p>
+            {deviceSetup && (
+              <div className="api-status">
+                <span className={deviceSetup.capture_device ? "key-set" : "key-missing"}>
+                  Capture: {deviceSetup.capture_device ?? "Not created"}
+                </span>
+                <span className={deviceSetup.mic_device ? "key-set" : "key-missing"}>
+                  Mic: {deviceSetup.mic_device ?? "Not created"}
+                </span>
+                <span className={deviceSetup.multi_output_device ? "key-set" : "key-missing"}>
+                  Output: {deviceSetup.multi_output_device ?? "Not created"}
+                </span>
+                <span className={deviceSetup.blackhole_2ch_found ? "key-set" : "key-missing"}>
+                  BlackHole 2ch: {deviceSetup.blackhole_2ch_found ? "Found" : "Not installed"}
+                </span>
+                <span className={deviceSetup.blackhole_16ch_found ? "key-set" : "key-missing"}>
+                  BlackHole 16ch: {deviceSetup.blackhole_16ch_found ? "Found" : "Not installed"}
+                </span>
+                {deviceSetup.errors.map((err) => (
+                  <span key={err} className="key-missing">
+                    {err}
+                  </span>
+                ))}
+              </div>
+            )}
+            {deviceStatus && <p className="setting-desc">{deviceStatus}</p>}
+            <div className="device-actions">
+              <button
+                className="btn btn-secondary"
+                onClick={handleDeviceSetup}
+                disabled={settingUpDevices}
+              >
+                Set Up
+              </button>
+              <button
+                className="btn btn-secondary"
+                onClick={handleDeviceTeardown}
+                disabled={settingUpDevices}
+              >
+                Remove
+              </button>
+            </div>
+          </div>
+
           {/* Translation Mode */}
           <div className="setting-group">
             <label className="setting-label">Translation</label>
@@ -129,6 +331,96 @@ export function SettingsPanel({
             </select>
           </div>
 
+          {/* Custom Vocabulary */}
+          <div className="setting-group">
+            <label className="setting-label">Custom Vocabulary</label>
+            <p className="setting-desc">
+              Names and terms to favor in transcription, one per line
+            </p>
+            <textarea
+              className="setting-select setting-textarea"
+              value={hotwords}
+              onChange={(e) => {
+                setHotwords(e.target.value);
+                if (desktop) {
+                  setDesktop({
+                    ...desktop,
+                    hotwords: e.target.value
+                      .split("\n")
+                      .map((w) => w.trim())
+                      .filter(Boolean),
+                  });
+                  setDesktopEdited(true);
+                }
+              }}
+              placeholder={"VoxVault\nVoxtral"}
+              rows={3}
+            />
+          </div>
+
+          {/* Voice Detection */}
+          {desktop && (
+            <div className="setting-group">
+              <label className="setting-label">Voice Detection</label>
+              <p className="setting-desc">
+                WebRTC ignores steady background noise better than the energy level
+              </p>
+              <select
+                className="setting-select"
+                value={desktop.vad}
+                onChange={(e) => {
+                  setDesktop({ ...desktop, vad: e.target.value as DesktopSettings["vad"] });
+                  setDesktopEdited(true);
+                }}
+              >
+                <option value="energy">Energy level</option>
+                <option value="webrtc">WebRTC</option>
+              </select>
+              {desktop.vad === "webrtc" && (
+                <select
+                  className="setting-select"
+                  value={desktop.vad_aggressiveness}
+                  onChange={(e) => {
+                    setDesktop({ ...desktop, vad_aggressiveness: Number(e.target.value) });
+                    setDesktopEdited(true);
+                  }}
+                >
+                  <option value={0}>0 – keep the most speech</option>
+                  <option value={1}>1</option>
+                  <option value={2}>2</option>
+                  <option value={3}>3 – reject the most noise</option>
+                </select>
+              )}
+            </div>
+          )}
+
+          {/* Global Shortcuts */}
+          {desktop && (
+            <div className="setting-group">
+              <label className="setting-label">Global Shortcuts</label>
+              <p className="setting-desc">
+                Work from any app, e.g. CommandOrControl+Alt+R; leave empty to disable
+              </p>
+              {HOTKEY_LABELS.map(([action, label]) => (
+                <div key={action} className="hotkey-row">
+                  <span className="setting-desc">{label}</span>
+                  <input
+                    className="setting-select"
+                    value={desktop.hotkeys[action] ?? ""}
+                    onChange={(e) => {
+                      setDesktop({
+                        ...desktop,
+                        hotkeys: { ...desktop.hotkeys, [action]: e.target.value || null },
+                      });
+                      setDesktopEdited(true);
+                    }}
+                  />
+                </div>
+              ))}
+              {desktopError && <p className="setting-desc key-missing">{desktopError}</p>}
+            </div>
+          )}
+
           {/* API Status */}
           {settings && (
             <div className="setting-group">
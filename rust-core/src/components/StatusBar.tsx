@@ -1,3 +1,5 @@
+import type { InputLevels, SpeakingSources } from "../types";
+
 interface StatusBarProps {
   connected: boolean;
   isRecording: boolean;
@@ -5,6 +7,8 @@ interface StatusBarProps {
   duration: number;
   statusText: string;
   rtf: number | null;
+  levels: InputLevels;
+  speaking: SpeakingSources;
 }
 
 function formatDuration(seconds: number): string {
@@ -13,6 +17,13 @@ function formatDuration(seconds: number): string {
   return `${String(m).padStart(2, "0")}:${String(s).padStart(2, "0")}`;
 }
 
+/** Map a linear level to 0–100% of a -60..0 dBFS meter. */
+function meterPercent(level: number): number {
+  if (level <= 0) return 0;
+  const db = 20 * Math.log10(level);
+  return Math.max(0, Math.min(100, ((db + 60) / 60) * 100));
+}
+
 function rtfLabel(rtf: number): { text: string; className: string } {
   if (rtf < 0.5) return { text: "Fast", className: "rtf-fast" };
   if (rtf <= 1.0) return { text: "Normal", className: "rtf-normal" };
@@ -26,6 +37,8 @@ export function StatusBar({
   duration,
   statusText,
   rtf,
+  levels,
+  speaking,
 }: StatusBarProps) {
   return (
     <div className="status-bar">
@@ -46,6 +59,22 @@ export function StatusBar({
               {rtfLabel(rtf).text}
             </span>
           )}
+          {Object.entries(levels).map(([source, level]) => (
+            <span
+              key={source}
+              className={`level-meter ${speaking[source] ? "speaking" : ""}`}
+              title={speaking[source] ? `${source} (speaking)` : source}
+            >
+              <span
+                className="level-meter-rms"
+                style={{ width: `${meterPercent(level.rms)}%` }}
+              />
+              <span
+                className="level-meter-peak"
+                style={{ left: `${meterPercent(level.peak)}%` }}
+              />
+            </span>
+          ))}
         </div>
       )}
 
@@ -1,10 +1,15 @@
 import { useCallback, useEffect, useRef, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { InputLevels, SpeakingSources } from "../types";
 
 const API_URL = "";
 const RECONNECT_DELAY_MS = 3000;
+/** Tauri events of the in-process pipeline (see `session.rs`). */
+const PIPELINE_EVENTS = ["transcript:partial", "transcript:final", "transcript:status"];
 
 /**
- * Hook for streaming transcript data via SSE.
+ * Hook for streaming transcript data: via SSE from the Python API, and via
+ * Tauri events from the in-process pipeline (no localhost port needed).
  *
  * Maintains O(1) state updates per incoming chunk:
  * - finalText: accumulated confirmed transcript (append-only string)
@@ -23,6 +28,8 @@ export function useTranscript() {
   const [sourceLang, setSourceLang] = useState("auto");
   const [hasContent, setHasContent] = useState(false);
   const [rtf, setRtf] = useState<number | null>(null);
+  const [levels, setLevels] = useState<InputLevels>({});
+  const [speaking, setSpeaking] = useState<SpeakingSources>({});
 
   const esRef = useRef<EventSource | null>(null);
   const reconnectTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
@@ -33,6 +40,85 @@ export function useTranscript() {
   useEffect(() => {
     let cancelled = false;
 
+    function appendTranscript(text: string, isFinal: boolean, lang: string) {
+      setSourceLang(lang);
+      setHasContent(true);
+
+      if (isFinal) {
+        setFinalText((prev) => {
+          const updated = prev ? prev + " " + text : text;
+          fullTextRef.current = updated;
+          return updated;
+        });
+        setPartial("");
+      } else {
+        setPartial(text);
+      }
+    }
+
+    function handleMessage(data: any) {
+      // Status/error message from Rust core
+      if (data.type === "status") {
+        setStatusText(data.text || "");
+        return;
+      }
+      if (data.type === "error") {
+        setStatusText(`Error: ${data.text}`);
+        return;
+      }
+      if (data.type === "level") {
+        const source: string = data.speaker || "input";
+        setLevels((prev) => ({
+          ...prev,
+          [source]: { rms: data.rms, peak: data.peak },
+        }));
+        return;
+      }
+      if (data.type === "speech_start" || data.type === "speech_end") {
+        const source: string = data.speaker || "input";
+        setSpeaking((prev) => ({
+          ...prev,
+          [source]: data.type === "speech_start",
+        }));
+        return;
+      }
+
+      // Translated transcript chunk
+      if (data.original_text !== undefined) {
+        const text: string = data.original_text;
+        const translated: string = data.translated_text || text;
+        const isFinal: boolean = data.is_final;
+        const lang: string = data.source_language || "auto";
+
+        // Detect translation update: same original_text, different translated_text.
+        // The Python orchestrator sends the original first (translated == original),
+        // then sends an update later with the actual translation.
+        if (isFinal && translated !== text) {
+          setHasTranslation(true);
+          setTranslatedText((prev) => {
+            const updated = prev ? prev + " " + translated : translated;
+            fullTranslatedRef.current = updated;
+            return updated;
+          });
+          return;
+        }
+
+        if (data.rtf != null) {
+          setRtf(data.rtf);
+        }
+        appendTranscript(text, isFinal, lang);
+        return;
+      }
+
+      // Transcript from the in-process pipeline (WebSocket JSON)
+      if (data.type === "transcript") {
+        if (data.rtf != null) {
+          setRtf(data.rtf);
+        }
+        appendTranscript(data.text, data.is_final, data.language || "auto");
+      }
+    }
+
     function connect() {
       if (cancelled) return;
 
@@ -46,56 +132,7 @@ export function useTranscript() {
 
       es.onmessage = (event) => {
         try {
-          const data = JSON.parse(event.data);
-
-          // Status/error message from Rust core
-          if (data.type === "status") {
-            setStatusText(data.text || "");
-            return;
-          }
-          if (data.type === "error") {
-            setStatusText(`Error: ${data.text}`);
-            return;
-          }
-
-          // Translated transcript chunk
-          if (data.original_text !== undefined) {
-            const text: string = data.original_text;
-            const translated: string = data.translated_text || text;
-            const isFinal: boolean = data.is_final;
-            const lang: string = data.source_language || "auto";
-
-            // Detect translation update: same original_text, different translated_text.
-            // The Python orchestrator sends the original first (translated == original),
-            // then sends an update later with the actual translation.
-            if (isFinal && translated !== text) {
-              setHasTranslation(true);
-              setTranslatedText((prev) => {
-                const updated = prev ? prev + " " + translated : translated;
-                fullTranslatedRef.current = updated;
-                return updated;
-              });
-              return;
-            }
-
-            if (data.rtf != null) {
-              setRtf(data.rtf);
-            }
-
-            setSourceLang(lang);
-            setHasContent(true);
-
-            if (isFinal) {
-              setFinalText((prev) => {
-                const updated = prev ? prev + " " + text : text;
-                fullTextRef.current = updated;
-                return updated;
-              });
-              setPartial("");
-            } else {
-              setPartial(text);
-            }
-          }
+          handleMessage(JSON.parse(event.data));
         } catch {
           // Ignore malformed SSE data
         }
@@ -116,8 +153,14 @@ export function useTranscript() {
 
     connect();
 
+    // The in-process pipeline pushes its messages: nothing to connect to
+    const unlisteners = PIPELINE_EVENTS.map((name) =>
+      listen(name, (event) => handleMessage(event.payload))
+    );
+
     return () => {
       cancelled = true;
+      unlisteners.forEach((unlisten) => unlisten.then((stop) => stop()));
       if (reconnectTimer.current) {
         clearTimeout(reconnectTimer.current);
       }
@@ -136,6 +179,8 @@ export function useTranscript() {
     setHasContent(false);
     setSourceLang("auto");
     setRtf(null);
+    setLevels({});
+    setSpeaking({});
     fullTextRef.current = "";
     fullTranslatedRef.current = "";
   }, []);
@@ -157,5 +202,7 @@ export function useTranscript() {
     clearTranscript,
     getFullText,
     rtf,
+    levels,
+    speaking,
   };
 }
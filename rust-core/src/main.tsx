@@ -1,10 +1,15 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import App from "./App";
+import { Overlay } from "./components/Overlay";
 import "./App.css";
 
+// The subtitle overlay window loads this page too
+const isOverlay = getCurrentWindow().label === "overlay";
+
 ReactDOM.createRoot(document.getElementById("root")!).render(
   <React.StrictMode>
-    <App />
+    {isOverlay ? <Overlay /> : <App />}
   </React.StrictMode>
 );
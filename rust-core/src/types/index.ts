@@ -8,17 +8,62 @@ export interface TranslatedChunk {
   is_final: boolean;
 }
 
+/** State of the Rust core's transcription pipeline. */
+export type PipelineState =
+  | "loading_model"
+  | "ready"
+  | "capturing"
+  | "transcribing"
+  | "error";
+
+/** Error codes sent by the Rust core. */
+export type ErrorCode =
+  | "invalid_control"
+  | "ingest_refused"
+  | "model_load_failed"
+  | "transcription_failed"
+  | "translation_failed"
+  | "unsupported_protocol";
+
 /** A status or error message from the Rust core (forwarded via Python SSE). */
 export interface StatusMessage {
   type: "status" | "error";
+  /** Pipeline state, for driving UI state rather than parsing `text`. */
+  state?: PipelineState;
+  /** What went wrong, when `state` is "error". */
+  code?: ErrorCode;
   text: string;
   language?: string;
   timestamp: number;
   is_final: boolean;
 }
 
+/** Per-chunk input level from the Rust core, for VU meters. */
+export interface LevelMessage {
+  type: "level";
+  /** Capture source ("me" / "them"); absent for single-device capture. */
+  speaker?: string;
+  rms: number;
+  peak: number;
+  timestamp: number;
+}
+
+/** VAD speech start/end event from the Rust core. */
+export interface SpeechMessage {
+  type: "speech_start" | "speech_end";
+  /** Capture source ("me" / "them"); absent for single-device capture. */
+  speaker?: string;
+  timestamp: number;
+}
+
+/** Input levels keyed by capture source ("input" for single-device capture). */
+export type InputLevels = Record<string, { rms: number; peak: number }>;
+
+/** Whether each capture source is currently speaking. */
+export type SpeakingSources = Record<string, boolean>;
+
 /** Union of possible SSE event payloads. */
-export type SSEEvent = TranslatedChunk | StatusMessage;
+export type SSEEvent = TranslatedChunk | StatusMessage | LevelMessage | SpeechMessage;
 
 /** Session info returned by the Python API. */
 export interface SessionInfo {
@@ -56,6 +101,136 @@ export interface AppSettings {
   rust_ws_url: string;
 }
 
+/** Global shortcut of each action, in Tauri's syntax ("CommandOrControl+Alt+R"); null = unbound. */
+export interface HotkeySettings {
+  toggle_capture: string | null;
+  toggle_stealth: string | null;
+  toggle_window: string | null;
+  push_to_talk: string | null;
+  copy_transcript: string | null;
+}
+
+/** Look of the subtitle overlay (`set_overlay_style`). */
+export interface OverlayStyle {
+  font_size: number;
+  /** CSS colors. */
+  text_color: string;
+  background: string;
+  position: "top" | "bottom";
+  /** Lines of text shown; older text scrolls out. */
+  lines: number;
+}
+
+/** Desktop app settings (`get_settings`/`set_settings`, `settings-changed` event). */
+export interface DesktopSettings {
+  /** Capture devices of in-process sessions, tried in order. */
+  devices: string[];
+  buffer_ms: number;
+  min_duration_secs: number;
+  max_duration_secs: number;
+  silence_pause_ms: number;
+  speech_threshold: number;
+  vad: "energy" | "webrtc";
+  /** WebRTC VAD aggressiveness, 0 (flags the most audio as speech) to 3. */
+  vad_aggressiveness: number;
+  /** ISO 639-1 code; null = detect. */
+  language: string | null;
+  /** null = the app data directory. */
+  models_dir: string | null;
+  /** Names and terms boosted during decoding. */
+  hotwords: string[];
+  latency: "low-latency" | "balanced" | "accurate";
+  /** Restore casing and punctuation on final transcripts lacking them. */
+  restore_punctuation: boolean;
+  /** Rewrite spoken numbers, amounts and dates in final transcripts. */
+  itn: boolean;
+  profanity: "keep" | "mask" | "tag";
+  /** ISO 639-1 code final transcripts are translated into; null = don't translate. */
+  translate_to: string | null;
+  translator: "libretranslate" | "deepl";
+  /** null = the service's default. */
+  translator_url: string | null;
+  translator_api_key: string | null;
+  python_api_port: number;
+  core_api_port: number;
+  hotkeys: HotkeySettings;
+  overlay: OverlayStyle;
+}
+
+/** A CoreAudio device (`list_audio_devices`). */
+export interface AudioDeviceInfo {
+  id: number;
+  uid: string;
+  name: string;
+}
+
+/** Outcome of `setup_audio_devices`: the aggregate devices ready, or why not. */
+export interface SetupResult {
+  capture_device: string | null;
+  mic_device: string | null;
+  multi_output_device: string | null;
+  blackhole_2ch_found: boolean;
+  blackhole_16ch_found: boolean;
+  errors: string[];
+}
+
+/** Outcome of `teardown_audio_devices`. */
+export interface TeardownResult {
+  devices_destroyed: number;
+  errors: string[];
+}
+
+/** Options of `start_transcription_session`; unset fields come from the settings. */
+export interface TranscriptionSessionOptions {
+  device?: string;
+  /** Segmentation settings to change for this session. */
+  processor?: {
+    min_duration_secs?: number;
+    max_duration_secs?: number;
+    silence_pause_ms?: number;
+    speech_threshold?: number;
+  };
+  /** Text the transcription continues (names, jargon). */
+  prompt?: string;
+}
+
+/** An in-process transcription session. */
+export interface TranscriptionSession {
+  id: string;
+  device: string;
+  /** Start time, in Unix milliseconds. */
+  started_ms: number;
+  /** Set by `pause_transcription_session`: the audio is dropped. */
+  paused: boolean;
+}
+
+/** A change of the in-process session's capture device (`input-device` Tauri event). */
+export interface InputDeviceEvent {
+  event: "device_lost" | "device_reconnected" | "stream_restarted" | "device_switched";
+  device: string;
+}
+
+/** macOS privacy permission state (`check_permissions`). */
+export type PermissionStatus =
+  | "granted"
+  | "denied"
+  | "restricted"
+  | "not_determined"
+  | "not_applicable";
+
+export interface Permissions {
+  microphone: PermissionStatus;
+  /** Reported only: capture goes through BlackHole, not screen recording. */
+  screen_recording: PermissionStatus;
+}
+
+/** A global shortcut the frontend carries out (`hotkey` Tauri event). */
+export interface HotkeyEvent {
+  action: "toggle_capture" | "push_to_talk" | "copy_transcript";
+  /** Pressed or released (push-to-talk). */
+  pressed: boolean;
+}
+
 export function isTranslatedChunk(event: SSEEvent): event is TranslatedChunk {
   return "original_text" in event;
 }
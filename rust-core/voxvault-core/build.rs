@@ -0,0 +1,7 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Types and service traits of the gRPC API, from its contract
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/voxvault.proto")
+        .expect("Failed to compile proto/voxvault.proto (is protoc installed?)");
+}
@@ -0,0 +1,221 @@
+//! Acoustic echo cancellation for full-duplex playback + capture, through
+//! the WebRTC audio processing module. Needs the `aec` feature.
+//!
+//! When VoxVault plays audio (e.g. TTS into the virtual mic) the capture
+//! path can pick it back up. Everything played is fed to a
+//! [`PlaybackReference`] (the CLI captures it from `--echo-reference`);
+//! each capture source's [`EchoCanceller`] removes it from the captured
+//! audio, so the transcript doesn't contain VoxVault's own output. The
+//! module estimates the playback → capture delay itself: the two sides
+//! don't need to be aligned.
+//!
+//! The module runs at [`SAMPLE_RATE`] on 10 ms frames; both sides are
+//! resampled to it.
+
+#[cfg(not(feature = "aec"))]
+use anyhow::bail;
+#[cfg(feature = "aec")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "aec")]
+use std::sync::{Arc, Mutex};
+
+/// Rate the canceller works and returns audio at.
+pub const SAMPLE_RATE: u32 = 48000;
+
+/// Echo canceller parameters.
+#[derive(Debug, Clone, Default)]
+pub struct AecConfig {
+    /// Known playback → capture delay, in milliseconds. None lets the
+    /// module estimate it (delay-agnostic mode).
+    pub stream_delay_ms: Option<u32>,
+}
+
+/// Handle the playback side uses to publish what it is playing.
+///
+/// Cheap to clone; all clones feed every canceller created from it.
+#[derive(Clone)]
+pub struct PlaybackReference {
+    #[cfg(feature = "aec")]
+    render: Arc<Mutex<webrtc::Render>>,
+}
+
+impl PlaybackReference {
+    #[cfg(feature = "aec")]
+    pub fn new(config: AecConfig) -> Result<Self> {
+        Ok(Self {
+            render: Arc::new(Mutex::new(webrtc::Render::new(&config))),
+        })
+    }
+
+    #[cfg(not(feature = "aec"))]
+    pub fn new(_config: AecConfig) -> Result<Self> {
+        bail!("Echo cancellation is not available: rebuild with `--features aec`")
+    }
+
+    /// Publish mono samples that were just played, at `sample_rate`.
+    pub fn push(&self, samples: &[f32], sample_rate: u32) {
+        #[cfg(feature = "aec")]
+        self.render.lock().unwrap().push(samples, sample_rate);
+        #[cfg(not(feature = "aec"))]
+        let _ = (samples, sample_rate);
+    }
+
+    /// A canceller for one capture source, fed this playback from now on.
+    pub fn canceller(&self) -> Result<EchoCanceller> {
+        #[cfg(feature = "aec")]
+        {
+            let capture = self
+                .render
+                .lock()
+                .unwrap()
+                .capture()
+                .context("Failed to create echo canceller")?;
+            Ok(EchoCanceller { capture })
+        }
+        #[cfg(not(feature = "aec"))]
+        bail!("Echo cancellation is not available: rebuild with `--features aec`")
+    }
+}
+
+/// Echo canceller of one capture source (see [`PlaybackReference::canceller`]).
+pub struct EchoCanceller {
+    #[cfg(feature = "aec")]
+    capture: webrtc::Capture,
+}
+
+impl EchoCanceller {
+    /// Remove the playback echo from mono `samples` at `sample_rate`.
+    /// Returns the audio at [`SAMPLE_RATE`], lagging by up to one frame.
+    #[cfg(feature = "aec")]
+    pub fn process(&mut self, samples: &[f32], sample_rate: u32) -> Vec<f32> {
+        self.capture.process(samples, sample_rate)
+    }
+
+    /// Never called: there is no canceller without the feature.
+    #[cfg(not(feature = "aec"))]
+    pub fn process(&mut self, _samples: &[f32], _sample_rate: u32) -> Vec<f32> {
+        Vec::new()
+    }
+}
+
+#[cfg(feature = "aec")]
+mod webrtc {
+    use super::{AecConfig, SAMPLE_RATE};
+    use crate::audio::resample::{Resampler, ResamplerQuality};
+    use anyhow::{anyhow, Result};
+    use std::sync::{Arc, Weak};
+    use tracing::warn;
+    use webrtc_audio_processing::{
+        Config, EchoCancellation, EchoCancellationSuppressionLevel, InitializationConfig,
+        Processor, NUM_SAMPLES_PER_FRAME,
+    };
+
+    /// 10 ms at 48 kHz.
+    const FRAME: usize = NUM_SAMPLES_PER_FRAME as usize;
+
+    /// Cuts audio at any rate into the module's frames.
+    struct Framer {
+        resampler: Resampler,
+        /// Samples not yet filling a whole frame.
+        pending: Vec<f32>,
+    }
+
+    impl Framer {
+        fn new() -> Self {
+            Self {
+                resampler: Resampler::new(ResamplerQuality::High, SAMPLE_RATE),
+                pending: Vec::new(),
+            }
+        }
+
+        fn frames(&mut self, samples: &[f32], sample_rate: u32) -> Vec<Vec<f32>> {
+            let resampled = self.resampler.process(samples, sample_rate);
+            self.pending.extend_from_slice(&resampled);
+            let whole = self.pending.len() / FRAME * FRAME;
+            self.pending
+                .drain(..whole)
+                .collect::<Vec<f32>>()
+                .chunks(FRAME)
+                .map(<[f32]>::to_vec)
+                .collect()
+        }
+    }
+
+    /// Playback side: feeds every live capture's processor.
+    pub(super) struct Render {
+        config: Config,
+        /// Each capture's processor, and whether that capture still exists.
+        processors: Vec<(Processor, Weak<()>)>,
+        framer: Framer,
+    }
+
+    impl Render {
+        pub(super) fn new(config: &AecConfig) -> Self {
+            Self {
+                config: Config {
+                    echo_cancellation: Some(EchoCancellation {
+                        suppression_level: EchoCancellationSuppressionLevel::Moderate,
+                        enable_extended_filter: true,
+                        enable_delay_agnostic: config.stream_delay_ms.is_none(),
+                        stream_delay_ms: config.stream_delay_ms.map(|ms| ms as i32),
+                    }),
+                    ..Config::default()
+                },
+                processors: Vec::new(),
+                framer: Framer::new(),
+            }
+        }
+
+        pub(super) fn push(&mut self, samples: &[f32], sample_rate: u32) {
+            self.processors.retain(|(_, capture)| capture.strong_count() > 0);
+            for frame in self.framer.frames(samples, sample_rate) {
+                for (processor, _) in &mut self.processors {
+                    let mut frame = frame.clone();
+                    if let Err(e) = processor.process_render_frame(&mut frame) {
+                        warn!("Echo canceller rejected playback frame: {:?}", e);
+                    }
+                }
+            }
+        }
+
+        pub(super) fn capture(&mut self) -> Result<Capture> {
+            let mut processor = Processor::new(&InitializationConfig {
+                num_capture_channels: 1,
+                num_render_channels: 1,
+                ..InitializationConfig::default()
+            })
+            .map_err(|e| anyhow!("{:?}", e))?;
+            processor.set_config(self.config.clone());
+            let alive = Arc::new(());
+            // Clones share the module: render frames reach the capture's
+            self.processors.push((processor.clone(), Arc::downgrade(&alive)));
+            Ok(Capture {
+                processor,
+                framer: Framer::new(),
+                _alive: alive,
+            })
+        }
+    }
+
+    /// Capture side of one source.
+    pub(super) struct Capture {
+        processor: Processor,
+        framer: Framer,
+        /// Dropped with the capture, so the playback side stops feeding it.
+        _alive: Arc<()>,
+    }
+
+    impl Capture {
+        pub(super) fn process(&mut self, samples: &[f32], sample_rate: u32) -> Vec<f32> {
+            let mut out = Vec::with_capacity(samples.len() * 2);
+            for mut frame in self.framer.frames(samples, sample_rate) {
+                if let Err(e) = self.processor.process_capture_frame(&mut frame) {
+                    warn!("Echo canceller rejected capture frame: {:?}", e);
+                }
+                out.extend_from_slice(&frame);
+            }
+            out
+        }
+    }
+}
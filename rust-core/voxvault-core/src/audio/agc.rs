@@ -0,0 +1,94 @@
+//! Automatic gain control between capture and the AudioProcessor.
+//!
+//! Quiet speakers can sit below the VAD speech threshold even though the
+//! processor peak-normalizes buffers later. The AGC measures each chunk's
+//! RMS and moves its gain toward `target_rms / rms`: quickly when the gain
+//! has to come down (attack) and slowly when it goes up (release), ramping
+//! across the chunk so gain changes don't click.
+
+use tracing::debug;
+
+use super::capture::AudioChunk;
+
+/// AGC parameters.
+#[derive(Debug, Clone)]
+pub struct AgcConfig {
+    /// RMS level the AGC steers towards (e.g. 0.05 ≈ -26 dBFS).
+    pub target_rms: f32,
+    /// Maximum gain applied (linear). Bounds how much noise gets boosted.
+    pub max_gain: f32,
+    /// Minimum gain applied (linear). Values below 1.0 also tame loud input.
+    pub min_gain: f32,
+    /// Time constant for reducing gain when the input gets louder.
+    pub attack_ms: f32,
+    /// Time constant for increasing gain when the input gets quieter.
+    pub release_ms: f32,
+    /// Chunks quieter than this RMS are treated as silence: the gain is held
+    /// instead of boosting room noise into the VAD speech range.
+    pub noise_gate: f32,
+}
+
+impl Default for AgcConfig {
+    fn default() -> Self {
+        Self {
+            target_rms: 0.05,
+            max_gain: 16.0,
+            min_gain: 1.0,
+            attack_ms: 100.0,
+            release_ms: 2000.0,
+            noise_gate: 0.001,
+        }
+    }
+}
+
+/// Stateful AGC; keep one instance per audio source.
+pub struct AutomaticGainControl {
+    config: AgcConfig,
+    gain: f32,
+}
+
+impl AutomaticGainControl {
+    /// Create an AGC starting at unity gain.
+    pub fn new(config: AgcConfig) -> Self {
+        Self { config, gain: 1.0 }
+    }
+
+    /// Current linear gain.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Apply gain to a chunk in place, updating the gain from its level.
+    pub fn process(&mut self, chunk: &mut AudioChunk) {
+        if chunk.samples.is_empty() {
+            return;
+        }
+
+        let sum_sq: f32 = chunk.samples.iter().map(|&s| s * s).sum();
+        let rms = (sum_sq / chunk.samples.len() as f32).sqrt();
+
+        let start_gain = self.gain;
+        if rms >= self.config.noise_gate {
+            let desired =
+                (self.config.target_rms / rms).clamp(self.config.min_gain, self.config.max_gain);
+            let time_constant_ms = if desired < self.gain {
+                self.config.attack_ms
+            } else {
+                self.config.release_ms
+            };
+            let chunk_ms = chunk.samples.len() as f32 * 1000.0 / chunk.sample_rate as f32;
+            let coef = 1.0 - (-chunk_ms / time_constant_ms.max(1.0)).exp();
+            self.gain += (desired - self.gain) * coef;
+        }
+
+        // Linear ramp from the previous gain to the new one across the chunk
+        let n = chunk.samples.len() as f32;
+        let step = (self.gain - start_gain) / n;
+        for (i, sample) in chunk.samples.iter_mut().enumerate() {
+            let g = start_gain + step * i as f32;
+            *sample = (*sample * g).clamp(-1.0, 1.0);
+        }
+
+        debug!(rms, gain = self.gain, "AGC");
+    }
+}
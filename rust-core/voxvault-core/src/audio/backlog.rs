@@ -0,0 +1,280 @@
+//! Overflow backlog between the capture callback and the processing channel.
+//!
+//! The stream callback only pushes finished chunks into a preallocated,
+//! lock-free SPSC ring ([`BacklogProducer`]). A forwarder thread drains the
+//! ring and sends the chunks on; when the consumer lags, chunks that don't
+//! fit in the channel wait in the forwarder instead of being dropped. Past
+//! `capacity` waiting chunks, the oldest chunk without speech is dropped;
+//! speech is never dropped; when every waiting chunk is speech the backlog
+//! grows past `capacity` instead. A slow transcription delays speech but
+//! never loses any.
+//!
+//! If the forwarder stalls for a whole ring of chunks, the callback drops
+//! new silence and holds new speech back itself, handing it to the ring
+//! once there is room again.
+//!
+//! The callback never locks or logs, and only allocates when it holds back
+//! more speech than its preallocated room: it only updates the
+//! [`CaptureMetrics`] counters. Logging happens in the forwarder.
+
+use anyhow::{Context, Result};
+use rtrb::{Consumer, Producer, PushError, RingBuffer};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{info, warn};
+
+use super::capture::AudioChunk;
+use super::metrics::CaptureMetrics;
+
+/// How often the forwarder checks the ring and the channel, and a
+/// dropped producer retries the speech it holds.
+const FORWARD_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Backlog parameters.
+#[derive(Debug, Clone)]
+pub struct BacklogConfig {
+    /// Chunks kept waiting behind a full channel before the oldest
+    /// non-speech chunk is dropped. Speech is kept past it.
+    pub capacity: usize,
+    /// RMS energy at or above which a chunk counts as speech and is never
+    /// dropped. Measured on the raw capture, before AGC/denoising.
+    pub speech_threshold: f32,
+}
+
+impl Default for BacklogConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 32,
+            speech_threshold: 0.005,
+        }
+    }
+}
+
+/// Callback side of the backlog.
+pub(crate) struct BacklogProducer {
+    producer: Producer<AudioChunk>,
+    speech_threshold: f32,
+    /// Speech the ring had no room for, oldest first.
+    held: VecDeque<AudioChunk>,
+    metrics: Arc<CaptureMetrics>,
+}
+
+impl BacklogProducer {
+    /// Hand a chunk to the forwarder. If the forwarder has stalled for a
+    /// whole ring of chunks, silence is dropped (and counted) and speech
+    /// held back until the ring has room.
+    pub(crate) fn push(&mut self, chunk: AudioChunk) {
+        self.push_held();
+        let chunk = if self.held.is_empty() {
+            match self.producer.push(chunk) {
+                Ok(()) => return,
+                Err(PushError::Full(chunk)) => chunk,
+            }
+        } else {
+            // Behind the speech held back, to keep the order
+            chunk
+        };
+        if rms(&chunk.samples) >= self.speech_threshold {
+            self.held.push_back(chunk);
+        } else {
+            self.metrics.record_dropped();
+        }
+    }
+
+    /// Move held-back speech into the ring while it has room.
+    fn push_held(&mut self) {
+        while let Some(chunk) = self.held.pop_front() {
+            if let Err(PushError::Full(chunk)) = self.producer.push(chunk) {
+                self.held.push_front(chunk);
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for BacklogProducer {
+    /// Hand the forwarder the speech still held back (the stream has
+    /// stopped, so waiting is fine).
+    fn drop(&mut self) {
+        while !self.held.is_empty() && !self.producer.is_abandoned() {
+            self.push_held();
+            if !self.held.is_empty() {
+                thread::sleep(FORWARD_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Start the forwarder of a stream's chunks to `sender`. It runs until
+/// the returned producer is dropped (with the stream) and everything it
+/// took is delivered.
+pub(crate) fn spawn(
+    config: BacklogConfig,
+    sender: mpsc::Sender<AudioChunk>,
+    metrics: Arc<CaptureMetrics>,
+) -> Result<BacklogProducer> {
+    let (producer, consumer) = RingBuffer::new(config.capacity.max(1));
+    let producer = BacklogProducer {
+        producer,
+        speech_threshold: config.speech_threshold,
+        held: VecDeque::with_capacity(config.capacity),
+        metrics: Arc::clone(&metrics),
+    };
+    let forwarder = Forwarder {
+        chunks: VecDeque::with_capacity(config.capacity + 1),
+        config,
+        ring: consumer,
+        sender,
+        metrics,
+        dropped: 0,
+        overflowed: false,
+    };
+    thread::Builder::new()
+        .name("audio-backlog".into())
+        .spawn(move || forwarder.run())
+        .context("Failed to spawn audio backlog thread")?;
+    Ok(producer)
+}
+
+struct Forwarder {
+    config: BacklogConfig,
+    ring: Consumer<AudioChunk>,
+    sender: mpsc::Sender<AudioChunk>,
+    metrics: Arc<CaptureMetrics>,
+    /// Chunks waiting for room in the channel, oldest first, with their
+    /// speech flag.
+    chunks: VecDeque<(AudioChunk, bool)>,
+    /// Chunks dropped since the backlog last overflowed (for logging).
+    dropped: u64,
+    /// Whether the backlog has overflowed since it last caught up.
+    overflowed: bool,
+}
+
+impl Forwarder {
+    fn run(mut self) {
+        loop {
+            let abandoned = self.ring.is_abandoned();
+            while let Ok(chunk) = self.ring.pop() {
+                let is_speech = rms(&chunk.samples) >= self.config.speech_threshold;
+                self.chunks.push_back((chunk, is_speech));
+                self.enforce_capacity();
+            }
+            if !self.flush() {
+                // Nobody is listening anymore
+                return;
+            }
+            self.metrics.record_backlog(self.chunks.len());
+            if abandoned && self.ring.is_empty() {
+                break;
+            }
+            thread::sleep(FORWARD_INTERVAL);
+        }
+
+        // The stream closed: deliver what is left
+        for (chunk, _) in self.chunks.drain(..) {
+            if self.sender.blocking_send(chunk).is_err() {
+                break;
+            }
+            self.metrics.record_sent();
+        }
+        self.metrics.record_backlog(0);
+    }
+
+    /// Send waiting chunks until the channel is full. Returns false once
+    /// the channel is closed.
+    fn flush(&mut self) -> bool {
+        while let Some((chunk, is_speech)) = self.chunks.pop_front() {
+            match self.sender.try_send(chunk) {
+                Ok(()) => self.metrics.record_sent(),
+                Err(TrySendError::Full(chunk)) => {
+                    self.chunks.push_front((chunk, is_speech));
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    self.chunks.clear();
+                    return false;
+                }
+            }
+        }
+        if self.overflowed {
+            info!(dropped = self.dropped, "Audio backlog caught up");
+            self.dropped = 0;
+            self.overflowed = false;
+        }
+        true
+    }
+
+    /// Drop the oldest non-speech chunks while over capacity. Speech is
+    /// kept, growing the backlog past capacity.
+    fn enforce_capacity(&mut self) {
+        if self.chunks.len() <= self.config.capacity {
+            return;
+        }
+        if !self.overflowed {
+            self.overflowed = true;
+            warn!(
+                capacity = self.config.capacity,
+                "Audio backlog full: receiver not keeping up, dropping silence"
+            );
+        }
+        while self.chunks.len() > self.config.capacity {
+            let Some(oldest_silence) = self.chunks.iter().position(|(_, speech)| !speech) else {
+                // All speech: keep it
+                break;
+            };
+            self.chunks.remove(oldest_silence);
+            self.metrics.record_dropped();
+            self.dropped += 1;
+        }
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
+    (sum_sq / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech(index: usize) -> AudioChunk {
+        AudioChunk {
+            samples: vec![0.1 + index as f32 * 1e-3; 160],
+            sample_rate: 16000,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn speech_is_never_dropped() {
+        let config = BacklogConfig {
+            capacity: 4,
+            ..BacklogConfig::default()
+        };
+        let (sender, mut receiver) = mpsc::channel(1);
+        let metrics = Arc::new(CaptureMetrics::default());
+        let mut producer = spawn(config, sender, Arc::clone(&metrics)).unwrap();
+        // Nothing is received meanwhile: the channel, the forwarder's
+        // backlog and the ring all fill up
+        let count = 100;
+        for index in 0..count {
+            producer.push(speech(index));
+        }
+        drop(producer);
+
+        let mut received = Vec::new();
+        while let Some(chunk) = receiver.blocking_recv() {
+            received.push(chunk.samples[0]);
+        }
+        let expected: Vec<f32> = (0..count).map(|index| speech(index).samples[0]).collect();
+        assert_eq!(received, expected);
+        assert_eq!(metrics.snapshot("test", None, 0, 0).chunks_dropped, 0);
+    }
+}
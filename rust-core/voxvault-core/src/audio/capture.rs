@@ -1,24 +1,287 @@
 use anyhow::{bail, Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
+use cpal::{
+    BufferSize, Device, FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+    SupportedBufferSize,
+};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 
+use super::backlog::{self, BacklogConfig, BacklogProducer};
+use super::metrics::{CaptureMetrics, CaptureStats};
+
+/// Source label for the local microphone in dual-capture mode.
+pub const LABEL_ME: &str = "me";
+/// Source label for system/meeting audio in dual-capture mode.
+pub const LABEL_THEM: &str = "them";
+
+/// Default time without stream callbacks before the watchdog restarts it.
+pub const DEFAULT_WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Captured audio chunk with metadata.
 pub struct AudioChunk {
     /// PCM samples as f32.
     pub samples: Vec<f32>,
     /// Sample rate of the captured audio.
     pub sample_rate: u32,
+    /// Label of the capture that produced this chunk (e.g. "me" / "them"),
+    /// used to route chunks to per-source processors.
+    pub label: Option<String>,
+}
+
+impl AudioChunk {
+    /// RMS and peak level of the samples, both in 0.0–1.0.
+    pub fn levels(&self) -> (f32, f32) {
+        if self.samples.is_empty() {
+            return (0.0, 0.0);
+        }
+        let sum_sq: f32 = self.samples.iter().map(|&s| s * s).sum();
+        let peak = self.samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+        ((sum_sq / self.samples.len() as f32).sqrt(), peak)
+    }
+}
+
+/// Where an AudioCapture pulls its audio from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureSource {
+    /// A regular input device (microphone, BlackHole, aggregate device).
+    #[default]
+    Input,
+    /// Loopback of an output device (what the speakers are playing).
+    ///
+    /// Uses WASAPI loopback on Windows, so meeting audio can be captured
+    /// without third-party virtual drivers.
+    Loopback,
+}
+
+/// Device buffer size requested for the capture stream.
+///
+/// Smaller buffers mean more frequent callbacks and lower latency, at the
+/// cost of more risk of underruns on a busy machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StreamBufferSize {
+    /// Let the host pick (usually a safe, moderately large buffer).
+    #[default]
+    Default,
+    /// Request this many frames per callback, clamped to the device's range.
+    Fixed(u32),
+    /// The smallest buffer the device supports (low-latency mode).
+    Smallest,
+}
+
+impl StreamBufferSize {
+    /// Resolve to a cpal buffer size within what the device supports.
+    fn resolve(self, supported: &SupportedBufferSize) -> BufferSize {
+        match (self, supported) {
+            (Self::Default, _) => BufferSize::Default,
+            (Self::Fixed(frames), SupportedBufferSize::Range { min, max }) => {
+                let clamped = frames.clamp(*min, *max);
+                if clamped != frames {
+                    warn!(
+                        requested = frames,
+                        min, max, "Stream buffer size out of device range; clamping"
+                    );
+                }
+                BufferSize::Fixed(clamped)
+            }
+            (Self::Fixed(frames), SupportedBufferSize::Unknown) => BufferSize::Fixed(frames),
+            (Self::Smallest, SupportedBufferSize::Range { min, .. }) => BufferSize::Fixed(*min),
+            (Self::Smallest, SupportedBufferSize::Unknown) => {
+                warn!("Device does not report its buffer sizes; using the default");
+                BufferSize::Default
+            }
+        }
+    }
+}
+
+/// Which channels of a multichannel device feed the mono signal.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ChannelSelection {
+    /// Average all channels (default).
+    #[default]
+    Mix,
+    /// Average only these channels (0-based indices).
+    Channels(Vec<usize>),
+    /// Keep every channel as its own mono stream, labelled by channel
+    /// (`ch1`, `ch2`, ...), so e.g. a remote party on the left channel and
+    /// the local mic on the right are transcribed separately.
+    Split,
+}
+
+impl ChannelSelection {
+    /// Select a single channel (0-based).
+    pub fn only(channel: usize) -> Self {
+        Self::Channels(vec![channel])
+    }
+
+    /// Check that every selected channel exists on a device with `channels` channels.
+    pub fn validate(&self, channels: usize) -> Result<()> {
+        if let Self::Channels(selected) = self {
+            if selected.is_empty() {
+                bail!("Channel selection is empty");
+            }
+            if let Some(&bad) = selected.iter().find(|&&c| c >= channels) {
+                bail!(
+                    "Channel {} selected, but the device only has {} channel(s)",
+                    bad + 1,
+                    channels
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Reduce interleaved `data` with `channels` channels to mono.
+    ///
+    /// `Split` has no single mono signal and downmixes like `Mix`; use
+    /// [`Self::streams`] to keep the channels apart.
+    pub fn downmix(&self, data: &[f32], channels: usize) -> Vec<f32> {
+        match self {
+            _ if channels == 1 => data.to_vec(),
+            Self::Mix | Self::Split => data
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                .collect(),
+            Self::Channels(selected) if selected.len() == 1 => {
+                let c = selected[0];
+                data.chunks(channels).map(|frame| frame[c]).collect()
+            }
+            Self::Channels(selected) => data
+                .chunks(channels)
+                .map(|frame| {
+                    selected.iter().map(|&c| frame[c]).sum::<f32>() / selected.len() as f32
+                })
+                .collect(),
+        }
+    }
+
+    /// Turn interleaved `data` into the mono streams this selection produces:
+    /// one per channel for `Split`, a single downmix otherwise.
+    pub fn streams(&self, data: &[f32], channels: usize) -> Vec<Vec<f32>> {
+        match self {
+            Self::Split => (0..channels)
+                .map(|c| data.chunks(channels).map(|frame| frame[c]).collect())
+                .collect(),
+            _ => vec![self.downmix(data, channels)],
+        }
+    }
+
+    /// Labels for the streams returned by [`Self::streams`]. With `Split`,
+    /// each channel's label is `ch<N>` (1-based), prefixed by `base` if set.
+    pub fn stream_labels(&self, base: Option<&str>, channels: usize) -> Vec<Option<String>> {
+        match self {
+            Self::Split => (1..=channels)
+                .map(|n| match base {
+                    Some(base) => Some(format!("{}-ch{}", base, n)),
+                    None => Some(format!("ch{}", n)),
+                })
+                .collect(),
+            _ => vec![base.map(str::to_string)],
+        }
+    }
+}
+
+impl FromStr for ChannelSelection {
+    type Err = anyhow::Error;
+
+    /// Parse `mix`, `split`, `left`/`l`, `right`/`r`, or 1-based channel
+    /// numbers separated by commas (e.g. `1` or `3,4`).
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mix" | "all" => Ok(Self::Mix),
+            "split" => Ok(Self::Split),
+            "left" | "l" => Ok(Self::only(0)),
+            "right" | "r" => Ok(Self::only(1)),
+            list => {
+                let channels = list
+                    .split(',')
+                    .map(|c| match c.trim().parse::<usize>() {
+                        Ok(n) if n >= 1 => Ok(n - 1),
+                        _ => bail!("Invalid channel '{}': expected a number starting at 1", c),
+                    })
+                    .collect::<Result<Vec<usize>>>()?;
+                Ok(Self::Channels(channels))
+            }
+        }
+    }
+}
+
+/// Device lifecycle events emitted by AudioCapture.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CaptureEvent {
+    /// The active device disappeared or its stream failed.
+    DeviceLost { device: String },
+    /// Capture resumed on `device` (the original device or a fallback).
+    DeviceReconnected { device: String },
+    /// The stream on `device` stopped delivering audio without an error
+    /// (e.g. after sleep/wake) and was rebuilt by the watchdog.
+    StreamRestarted { device: String },
+    /// Capture moved to `device` on request ([`AudioCapture::switch_device`]).
+    DeviceSwitched { device: String },
+}
+
+impl fmt::Display for CaptureEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeviceLost { device } => write!(f, "Audio device lost: {}", device),
+            Self::DeviceReconnected { device } => {
+                write!(f, "Audio device reconnected: {}", device)
+            }
+            Self::StreamRestarted { device } => {
+                write!(f, "Audio stream stalled, restarted: {}", device)
+            }
+            Self::DeviceSwitched { device } => write!(f, "Audio device switched: {}", device),
+        }
+    }
 }
 
 /// Real-time audio capture from a system audio device via cpal.
 pub struct AudioCapture {
     device: Device,
+    /// Sound-server monitor source `device`'s streams capture (Linux).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    monitor: Option<String>,
+    source: CaptureSource,
     sample_rate: u32,
     sender: mpsc::Sender<AudioChunk>,
-    stream: Option<Stream>,
+    stream: Option<OpenStream>,
+    label: Option<String>,
+    channels: ChannelSelection,
+
+    // --- Hot-plug monitoring ---
+    /// Device name requested by the user.
+    device_name: String,
+    /// Name of the device the stream is currently attached to.
+    active_device: String,
+    /// Devices to attach to, in order, when the requested one is gone.
+    fallback_devices: Vec<String>,
+    /// Buffer duration from the last `start()`, reused on reconnect.
+    buffer_duration_ms: u32,
+    /// Whether the user asked for capture to run (survives device loss).
+    running: bool,
+    /// Whether the active device was lost and we are waiting to re-attach.
+    device_lost: bool,
+    /// Set by the stream error callback when the device goes away.
+    stream_failed: Arc<AtomicBool>,
+    events: Option<mpsc::UnboundedSender<CaptureEvent>>,
+    /// Health counters updated from the stream callback.
+    metrics: Arc<CaptureMetrics>,
+    /// Overflow backlog settings used for every stream opened.
+    backlog: BacklogConfig,
+    stream_buffer: StreamBufferSize,
+
+    // --- Stall watchdog ---
+    /// Time without callbacks before the stream is rebuilt (None = off).
+    watchdog_timeout: Option<Duration>,
+    /// Callback count at the last observed progress, and when it was seen.
+    watchdog_callbacks: u64,
+    watchdog_progress: Instant,
 }
 
 impl AudioCapture {
@@ -30,44 +293,247 @@ impl AudioCapture {
         buffer_duration_ms: u32,
         sender: mpsc::Sender<AudioChunk>,
     ) -> Result<Self> {
-        let device = Self::find_device(device_name)?;
+        Self::with_source(device_name, CaptureSource::Input, buffer_duration_ms, sender)
+    }
 
-        let config = device
-            .default_input_config()
-            .context("Failed to get default input config")?;
+    /// Create a new AudioCapture reading from the given source.
+    ///
+    /// For `CaptureSource::Loopback`, `device_name` names an output device;
+    /// an empty name selects the default output device.
+    pub fn with_source(
+        device_name: &str,
+        source: CaptureSource,
+        buffer_duration_ms: u32,
+        sender: mpsc::Sender<AudioChunk>,
+    ) -> Result<Self> {
+        let (device, monitor) = Self::resolve_device(device_name, source)?;
+
+        let config = Self::default_config(&device, source)?;
 
         let sample_rate = config.sample_rate().0;
         info!(
             device = device_name,
+            ?source,
             sample_rate,
             channels = config.channels(),
             format = ?config.sample_format(),
             "Audio device configured"
         );
 
-        let _ = buffer_duration_ms; // used in start() for buffer sizing
-
         Ok(Self {
             device,
+            monitor,
+            source,
             sample_rate,
             sender,
             stream: None,
+            label: None,
+            channels: ChannelSelection::Mix,
+            device_name: device_name.to_string(),
+            active_device: device_name.to_string(),
+            fallback_devices: Vec::new(),
+            buffer_duration_ms,
+            running: false,
+            device_lost: false,
+            stream_failed: Arc::new(AtomicBool::new(false)),
+            events: None,
+            metrics: Arc::new(CaptureMetrics::default()),
+            backlog: BacklogConfig::default(),
+            stream_buffer: StreamBufferSize::Default,
+            watchdog_timeout: Some(DEFAULT_WATCHDOG_TIMEOUT),
+            watchdog_callbacks: 0,
+            watchdog_progress: Instant::now(),
         })
     }
 
+    /// Create an AudioCapture on the first available device of a priority
+    /// list, e.g. `["VoxVault Capture", "BlackHole 2ch", "MacBook Pro Microphone"]`.
+    ///
+    /// The selected device is logged and available via `active_device()`.
+    /// The remaining names become hot-plug fallbacks, and the first name
+    /// stays the preferred device to re-attach to.
+    pub fn with_priority(
+        device_names: &[String],
+        source: CaptureSource,
+        buffer_duration_ms: u32,
+        sender: mpsc::Sender<AudioChunk>,
+    ) -> Result<Self> {
+        let Some((preferred, fallbacks)) = device_names.split_first() else {
+            bail!("No audio device names given");
+        };
+
+        let mut failures = Vec::new();
+        for name in device_names {
+            match Self::with_source(name, source, buffer_duration_ms, sender.clone()) {
+                Ok(mut capture) => {
+                    if name == preferred {
+                        info!(device = name, "Selected audio device");
+                    } else {
+                        warn!(
+                            requested = preferred,
+                            selected = name,
+                            "Preferred audio device unavailable; using fallback"
+                        );
+                    }
+                    capture.device_name = preferred.clone();
+                    capture.fallback_devices = fallbacks.to_vec();
+                    return Ok(capture);
+                }
+                Err(e) => {
+                    warn!(device = name, "Audio device unavailable: {:#}", e);
+                    failures.push(format!("'{}'", name));
+                }
+            }
+        }
+
+        bail!(
+            "None of the audio devices are available: {}",
+            failures.join(", ")
+        )
+    }
+
+    /// Select which device channels feed the mono signal (default: mix all).
+    pub fn with_channels(mut self, channels: ChannelSelection) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Devices to re-attach to, in order, if the requested device disappears
+    /// and doesn't come back.
+    pub fn with_fallback_devices(mut self, devices: Vec<String>) -> Self {
+        self.fallback_devices = devices;
+        self
+    }
+
+    /// Send device lifecycle events (loss, reconnect) to this channel.
+    pub fn with_events(mut self, events: mpsc::UnboundedSender<CaptureEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Configure the overflow backlog used when the consumer lags.
+    pub fn with_backlog(mut self, backlog: BacklogConfig) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Request a device buffer size (trades latency for stability).
+    pub fn with_stream_buffer(mut self, stream_buffer: StreamBufferSize) -> Self {
+        self.stream_buffer = stream_buffer;
+        self
+    }
+
+    /// Rebuild the stream when no callback arrives for `timeout` while
+    /// capture is running (None disables the watchdog).
+    pub fn with_watchdog(mut self, timeout: Option<Duration>) -> Self {
+        self.watchdog_timeout = timeout;
+        self
+    }
+
+    /// Tag every chunk produced by this capture with a source label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     /// List all available input audio devices.
+    ///
+    /// On Linux, PipeWire/PulseAudio monitor sources are listed alongside
+    /// regular inputs and can be selected by their description.
     pub fn list_devices() -> Result<Vec<String>> {
         let host = cpal::default_host();
-        let devices: Vec<String> = host
+        #[allow(unused_mut)]
+        let mut devices: Vec<String> = host
             .input_devices()
             .context("Failed to enumerate input devices")?
             .filter_map(|d| d.name().ok())
             .collect();
+
+        #[cfg(target_os = "linux")]
+        devices.extend(
+            super::linux::list_monitor_sources()
+                .into_iter()
+                .map(|s| s.description),
+        );
+
         Ok(devices)
     }
 
-    /// Find a device by name.
-    fn find_device(name: &str) -> Result<Device> {
+    /// List all output devices that can be used as loopback sources.
+    pub fn list_loopback_devices() -> Result<Vec<String>> {
+        let host = cpal::default_host();
+        let devices: Vec<String> = host
+            .output_devices()
+            .context("Failed to enumerate output devices")?
+            .filter_map(|d| d.name().ok())
+            .collect();
+        Ok(devices)
+    }
+
+    /// Find an output device for loopback capture. An empty name selects
+    /// the default output device.
+    #[cfg(target_os = "windows")]
+    fn find_loopback_device(name: &str) -> Result<Device> {
+        let host = cpal::default_host();
+
+        if name.is_empty() {
+            return host
+                .default_output_device()
+                .context("No default output device available for loopback");
+        }
+
+        let devices = host
+            .output_devices()
+            .context("Failed to enumerate output devices")?;
+
+        for device in devices {
+            if let Ok(device_name) = device.name() {
+                if device_name == name {
+                    return Ok(device);
+                }
+            }
+        }
+
+        bail!(
+            "Output device '{}' not found. Available devices: {:?}",
+            name,
+            Self::list_loopback_devices().unwrap_or_default()
+        );
+    }
+
+    /// Loopback capture is only implemented for WASAPI.
+    #[cfg(not(target_os = "windows"))]
+    fn find_loopback_device(_name: &str) -> Result<Device> {
+        bail!("Loopback capture is only supported on Windows (WASAPI)");
+    }
+
+    /// Get the default stream config for a device, respecting the capture source.
+    ///
+    /// WASAPI opens an input stream on an output device in loopback mode, so
+    /// loopback streams use the output device's mix format.
+    fn default_config(
+        device: &Device,
+        source: CaptureSource,
+    ) -> Result<cpal::SupportedStreamConfig> {
+        match source {
+            CaptureSource::Input => device
+                .default_input_config()
+                .context("Failed to get default input config"),
+            CaptureSource::Loopback => device
+                .default_output_config()
+                .context("Failed to get default output config for loopback"),
+        }
+    }
+
+    /// Find a device by name, and the monitor source it captures if it is
+    /// one (Linux).
+    fn find_device(name: &str) -> Result<(Device, Option<String>)> {
+        #[cfg(target_os = "linux")]
+        if let Some(monitor) = super::linux::find_monitor_source(name) {
+            let device = super::linux::open_monitor_device(&monitor)?;
+            return Ok((device, Some(monitor.name)));
+        }
+
         let host = cpal::default_host();
         let devices = host
             .input_devices()
@@ -76,7 +542,7 @@ impl AudioCapture {
         for device in devices {
             if let Ok(device_name) = device.name() {
                 if device_name == name {
-                    return Ok(device);
+                    return Ok((device, None));
                 }
             }
         }
@@ -88,77 +554,303 @@ impl AudioCapture {
         );
     }
 
+    /// Resolve a device name for the given capture source.
+    fn resolve_device(name: &str, source: CaptureSource) -> Result<(Device, Option<String>)> {
+        match source {
+            CaptureSource::Input => Self::find_device(name),
+            CaptureSource::Loopback => Ok((Self::find_loopback_device(name)?, None)),
+        }
+    }
+
+    /// Whether a device with this name is currently present.
+    fn device_present(name: &str, source: CaptureSource) -> bool {
+        let host = cpal::default_host();
+        match source {
+            CaptureSource::Loopback if name.is_empty() => host.default_output_device().is_some(),
+            CaptureSource::Loopback => Self::list_loopback_devices()
+                .map(|d| d.iter().any(|n| n == name))
+                .unwrap_or(false),
+            CaptureSource::Input => Self::list_devices()
+                .map(|d| d.iter().any(|n| n == name))
+                .unwrap_or(false),
+        }
+    }
+
     /// Start capturing audio. Chunks are sent through the mpsc channel.
     pub fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
         if self.stream.is_some() {
             bail!("Audio capture already running");
         }
 
-        let config = self
-            .device
-            .default_input_config()
-            .context("Failed to get input config")?;
+        self.buffer_duration_ms = buffer_duration_ms;
+        self.open_stream()?;
+        self.running = true;
+        self.device_lost = false;
+        Ok(())
+    }
+
+    /// Check the active device and re-attach after a hot-unplug.
+    ///
+    /// cpal streams can't move between threads, so the owner of the capture
+    /// calls this periodically (e.g. once per second). When the active device
+    /// disappears or its stream fails, the stream is torn down and a
+    /// `DeviceLost` event is emitted; on later calls the requested device,
+    /// then each fallback, is tried until one opens (`DeviceReconnected`).
+    ///
+    /// A stream that silently stops calling back (no error, device still
+    /// present) is rebuilt by the watchdog (`StreamRestarted`).
+    pub fn monitor(&mut self) {
+        if !self.running {
+            return;
+        }
+
+        if !self.device_lost {
+            let failed = self.stream_failed.swap(false, Ordering::SeqCst);
+            if failed || !Self::device_present(&self.active_device, self.source) {
+                self.mark_device_lost();
+            } else if self.is_stalled() {
+                self.restart_stalled_stream();
+            }
+            return;
+        }
+
+        let candidates: Vec<String> = std::iter::once(self.device_name.clone())
+            .chain(self.fallback_devices.iter().cloned())
+            .collect();
+
+        for name in candidates {
+            if !Self::device_present(&name, self.source) {
+                continue;
+            }
+            let (device, monitor) = match Self::resolve_device(&name, self.source) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            self.device = device;
+            self.monitor = monitor;
+            if let Err(e) = self.open_stream() {
+                warn!(device = name, "Failed to re-attach audio device: {:#}", e);
+                continue;
+            }
+            info!(device = name, "Audio device reconnected");
+            self.active_device = name.clone();
+            self.device_lost = false;
+            self.emit(CaptureEvent::DeviceReconnected { device: name });
+            return;
+        }
+    }
+
+    /// Whether no callback arrived within the watchdog timeout.
+    fn is_stalled(&mut self) -> bool {
+        let Some(timeout) = self.watchdog_timeout else {
+            return false;
+        };
+        // WASAPI loopback delivers no callbacks while nothing is playing
+        if cfg!(target_os = "windows") && self.source == CaptureSource::Loopback {
+            return false;
+        }
+        let callbacks = self.metrics.callbacks();
+        if callbacks != self.watchdog_callbacks {
+            self.watchdog_callbacks = callbacks;
+            self.watchdog_progress = Instant::now();
+            return false;
+        }
+        self.watchdog_progress.elapsed() >= timeout
+    }
+
+    fn restart_stalled_stream(&mut self) {
+        warn!(
+            device = self.active_device,
+            stalled = ?self.watchdog_progress.elapsed(),
+            "Audio stream stalled; restarting"
+        );
+        self.stream = None;
+        match self.open_stream() {
+            Ok(()) => {
+                info!(device = self.active_device, "Audio stream restarted");
+                self.emit(CaptureEvent::StreamRestarted {
+                    device: self.active_device.clone(),
+                });
+            }
+            Err(e) => {
+                warn!(
+                    device = self.active_device,
+                    "Failed to restart audio stream: {:#}", e
+                );
+                self.mark_device_lost();
+            }
+        }
+    }
+
+    /// Tear down the stream and wait for a device to re-attach to.
+    fn mark_device_lost(&mut self) {
+        warn!(device = self.active_device, "Audio device lost");
+        self.stream = None;
+        self.device_lost = true;
+        self.emit(CaptureEvent::DeviceLost {
+            device: self.active_device.clone(),
+        });
+    }
+
+    /// Move capture to `name`, which becomes the requested device (the
+    /// fallbacks stay). If it can't be opened, capture goes back to the
+    /// active device.
+    pub fn switch_device(&mut self, name: &str) -> Result<()> {
+        let (device, monitor) = Self::resolve_device(name, self.source)?;
+        let previous = std::mem::replace(&mut self.device, device);
+        let previous_monitor = std::mem::replace(&mut self.monitor, monitor);
+        if self.running {
+            // Some devices can't be opened twice: close the current stream first
+            self.stream = None;
+            if let Err(e) = self.open_stream() {
+                warn!(device = name, "Failed to switch audio device: {:#}", e);
+                self.device = previous;
+                self.monitor = previous_monitor;
+                if !self.device_lost {
+                    if let Err(e) = self.open_stream() {
+                        warn!(
+                            device = self.active_device,
+                            "Failed to reopen audio device: {:#}", e
+                        );
+                        self.mark_device_lost();
+                    }
+                }
+                return Err(e);
+            }
+        }
+        info!(
+            from = self.active_device,
+            to = name,
+            "Switched audio device"
+        );
+        self.device_name = name.to_string();
+        self.active_device = name.to_string();
+        self.device_lost = false;
+        self.emit(CaptureEvent::DeviceSwitched {
+            device: name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Whether capture is waiting for a lost device to come back.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost
+    }
+
+    /// Name of the device the capture is attached to (may be a fallback).
+    pub fn active_device(&self) -> &str {
+        &self.active_device
+    }
+
+    /// Snapshot of dropped chunks, underruns and buffering latency.
+    pub fn stats(&self) -> CaptureStats {
+        let queued = self.sender.max_capacity() - self.sender.capacity();
+        self.metrics.snapshot(
+            &self.active_device,
+            self.label.as_deref(),
+            queued,
+            self.buffer_duration_ms,
+        )
+    }
+
+    fn emit(&self, event: CaptureEvent) {
+        if let Some(ref events) = self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Build and start the cpal stream on the current device.
+    fn open_stream(&mut self) -> Result<()> {
+        let buffer_duration_ms = self.buffer_duration_ms;
+        let config = Self::default_config(&self.device, self.source)?;
 
         let sample_rate = config.sample_rate().0;
+        self.sample_rate = sample_rate;
         let channels = config.channels() as usize;
         let sample_format = config.sample_format();
+        let device_buffer = self.stream_buffer.resolve(config.buffer_size());
 
         // Calculate buffer size based on desired duration
         let buffer_size = (sample_rate as usize * buffer_duration_ms as usize) / 1000;
 
-        let sender = self.sender.clone();
-        let buffer = Arc::new(std::sync::Mutex::new(Vec::with_capacity(buffer_size)));
-        let buffer_clone = Arc::clone(&buffer);
+        self.channels.validate(channels)?;
+        let labels = self.channels.stream_labels(self.label.as_deref(), channels);
+        let assembler = ChunkAssembler {
+            channels,
+            selection: self.channels.clone(),
+            buffers: vec![Vec::with_capacity(buffer_size); labels.len()],
+            buffer_size,
+            sample_rate,
+            labels,
+            metrics: Arc::clone(&self.metrics),
+            backlog: backlog::spawn(
+                self.backlog.clone(),
+                self.sender.clone(),
+                Arc::clone(&self.metrics),
+            )?,
+            last_capture: None,
+            last_frames: 0,
+        };
 
-        let stream_config: StreamConfig = config.into();
+        let mut stream_config: StreamConfig = config.into();
+        stream_config.buffer_size = device_buffer;
 
-        let err_fn = |err: cpal::StreamError| {
+        self.stream_failed.store(false, Ordering::SeqCst);
+        let stream_failed = Arc::clone(&self.stream_failed);
+        let err_fn = move |err: cpal::StreamError| {
             error!("Audio stream error: {}", err);
+            if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                stream_failed.store(true, Ordering::SeqCst);
+            }
         };
 
+        let build = || self.build_stream(sample_format, &stream_config, assembler, err_fn);
+        #[cfg(target_os = "linux")]
+        let (stream, monitor) = super::linux::open_stream(self.monitor.as_deref(), build)?;
+        #[cfg(not(target_os = "linux"))]
+        let stream = build()?;
+        self.stream = Some(OpenStream {
+            _stream: stream,
+            #[cfg(target_os = "linux")]
+            _monitor: monitor,
+        });
+        self.watchdog_callbacks = self.metrics.callbacks();
+        self.watchdog_progress = Instant::now();
+
+        info!(
+            buffer_duration_ms,
+            buffer_size, "Audio capture started"
+        );
+
+        Ok(())
+    }
+
+    /// Build and start a stream on the current device, feeding `assembler`.
+    fn build_stream(
+        &self,
+        sample_format: SampleFormat,
+        stream_config: &StreamConfig,
+        mut assembler: ChunkAssembler,
+        err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<Stream> {
         let stream = match sample_format {
             SampleFormat::F32 => self.device.build_input_stream(
-                &stream_config,
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    process_samples(data, channels, &buffer_clone, buffer_size, &sender, sample_rate);
-                },
-                err_fn,
-                None,
-            ),
-            SampleFormat::I16 => self.device.build_input_stream(
-                &stream_config,
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    let float_data: Vec<f32> =
-                        data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
-                    process_samples(
-                        &float_data,
-                        channels,
-                        &buffer_clone,
-                        buffer_size,
-                        &sender,
-                        sample_rate,
-                    );
-                },
-                err_fn,
-                None,
-            ),
-            SampleFormat::I32 => self.device.build_input_stream(
-                &stream_config,
-                move |data: &[i32], _: &cpal::InputCallbackInfo| {
-                    let float_data: Vec<f32> =
-                        data.iter().map(|&s| s as f32 / i32::MAX as f32).collect();
-                    process_samples(
-                        &float_data,
-                        channels,
-                        &buffer_clone,
-                        buffer_size,
-                        &sender,
-                        sample_rate,
-                    );
+                stream_config,
+                move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                    assembler.push(data, info);
                 },
                 err_fn,
                 None,
             ),
+            SampleFormat::I8 => self.converting_stream::<i8>(stream_config, assembler, err_fn),
+            SampleFormat::I16 => self.converting_stream::<i16>(stream_config, assembler, err_fn),
+            SampleFormat::I32 => self.converting_stream::<i32>(stream_config, assembler, err_fn),
+            SampleFormat::I64 => self.converting_stream::<i64>(stream_config, assembler, err_fn),
+            SampleFormat::U8 => self.converting_stream::<u8>(stream_config, assembler, err_fn),
+            SampleFormat::U16 => self.converting_stream::<u16>(stream_config, assembler, err_fn),
+            SampleFormat::U32 => self.converting_stream::<u32>(stream_config, assembler, err_fn),
+            SampleFormat::U64 => self.converting_stream::<u64>(stream_config, assembler, err_fn),
+            SampleFormat::F64 => self.converting_stream::<f64>(stream_config, assembler, err_fn),
             format => {
                 bail!("Unsupported sample format: {:?}", format);
             }
@@ -166,18 +858,39 @@ impl AudioCapture {
         .context("Failed to build input stream")?;
 
         stream.play().context("Failed to start audio stream")?;
-        self.stream = Some(stream);
-
-        info!(
-            buffer_duration_ms,
-            buffer_size, "Audio capture started"
-        );
+        Ok(stream)
+    }
 
-        Ok(())
+    /// Build an input stream for a non-f32 sample format, converting each
+    /// callback's samples to f32 in [-1.0, 1.0] before assembling chunks.
+    ///
+    /// 24-bit devices are delivered by cpal as I32 (left-justified), so they
+    /// take the I32 path.
+    fn converting_stream<T>(
+        &self,
+        config: &StreamConfig,
+        mut assembler: ChunkAssembler,
+        err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> std::result::Result<Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        self.device.build_input_stream(
+            config,
+            move |data: &[T], info: &cpal::InputCallbackInfo| {
+                let float_data: Vec<f32> = data.iter().map(|&s| s.to_sample::<f32>()).collect();
+                assembler.push(&float_data, info);
+            },
+            err_fn,
+            None,
+        )
     }
 
     /// Stop capturing audio.
     pub fn stop(&mut self) {
+        self.running = false;
+        self.device_lost = false;
         if let Some(stream) = self.stream.take() {
             drop(stream);
             info!("Audio capture stopped");
@@ -188,39 +901,181 @@ impl AudioCapture {
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
+
+    /// Get the source this capture reads from.
+    pub fn source(&self) -> CaptureSource {
+        self.source
+    }
 }
 
-/// Process incoming audio samples: downmix to mono, buffer, and send when full.
-fn process_samples(
-    data: &[f32],
+/// An open cpal stream, and what it holds until it closes.
+struct OpenStream {
+    _stream: Stream,
+    /// Released after the stream closes (fields drop in order).
+    #[cfg(target_os = "linux")]
+    _monitor: Option<super::linux::MonitorClaim>,
+}
+
+/// Per-stream state turning cpal callbacks into fixed-size mono AudioChunks.
+struct ChunkAssembler {
     channels: usize,
-    buffer: &Arc<std::sync::Mutex<Vec<f32>>>,
+    selection: ChannelSelection,
+    /// One buffer per output stream (several only in `Split` mode).
+    buffers: Vec<Vec<f32>>,
     buffer_size: usize,
-    sender: &mpsc::Sender<AudioChunk>,
     sample_rate: u32,
-) {
-    // Downmix to mono by averaging channels
-    let mono: Vec<f32> = if channels == 1 {
-        data.to_vec()
-    } else {
-        data.chunks(channels)
-            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
-            .collect()
-    };
-
-    let mut buf = buffer.lock().unwrap();
-    buf.extend_from_slice(&mono);
-
-    // Send chunks when buffer is full
-    while buf.len() >= buffer_size {
-        let chunk: Vec<f32> = buf.drain(..buffer_size).collect();
-        let audio_chunk = AudioChunk {
-            samples: chunk,
-            sample_rate,
-        };
-        if sender.try_send(audio_chunk).is_err() {
-            warn!("Audio chunk dropped: receiver not keeping up");
+    /// Label of each output stream, parallel to `buffers`.
+    labels: Vec<Option<String>>,
+    metrics: Arc<CaptureMetrics>,
+    /// Hands chunks to the forwarder sending them on.
+    backlog: BacklogProducer,
+    /// Capture timestamp and frame count of the previous callback.
+    last_capture: Option<cpal::StreamInstant>,
+    last_frames: usize,
+}
+
+impl ChunkAssembler {
+    /// Process incoming audio samples: downmix (or split) to mono, buffer,
+    /// and send when full.
+    fn push(&mut self, data: &[f32], info: &cpal::InputCallbackInfo) {
+        self.metrics.record_callback();
+        self.track_timing(info, data.len() / self.channels.max(1));
+
+        let streams = self.selection.streams(data, self.channels);
+        for (i, mono) in streams.into_iter().enumerate() {
+            self.buffers[i].extend_from_slice(&mono);
+
+            // Send chunks when buffer is full
+            while self.buffers[i].len() >= self.buffer_size {
+                let chunk: Vec<f32> = self.buffers[i].drain(..self.buffer_size).collect();
+                let audio_chunk = AudioChunk {
+                    samples: chunk,
+                    sample_rate: self.sample_rate,
+                    label: self.labels[i].clone(),
+                };
+                self.backlog.push(audio_chunk);
+            }
+        }
+    }
+
+    /// Record device latency, and count an underrun when this callback's
+    /// audio starts noticeably later than the previous callback's ended.
+    fn track_timing(&mut self, info: &cpal::InputCallbackInfo, frames: usize) {
+        let timestamp = info.timestamp();
+        if let Some(latency) = timestamp.callback.duration_since(&timestamp.capture) {
+            self.metrics.record_device_latency(latency);
         }
+
+        let interval = Duration::from_secs_f64(frames as f64 / self.sample_rate as f64);
+        self.metrics.record_callback_interval(interval);
+
+        if let Some(last) = self.last_capture {
+            let expected =
+                Duration::from_secs_f64(self.last_frames as f64 / self.sample_rate as f64);
+            if let Some(gap) = timestamp.capture.duration_since(&last) {
+                // Allow for timestamp jitter before calling it a gap
+                if gap > expected + expected / 2 + Duration::from_millis(1) {
+                    self.metrics.record_underrun();
+                    warn!(?gap, ?expected, "Audio callback underrun");
+                }
+            }
+        } else {
+            info!(frames, ?interval, "Audio callback interval");
+        }
+        self.last_capture = Some(timestamp.capture);
+        self.last_frames = frames;
+    }
+}
+
+/// Simultaneous capture of the local microphone and system/meeting audio.
+///
+/// Both streams feed the same channel; chunks are tagged with
+/// [`LABEL_ME`] and [`LABEL_THEM`] so the consumer can run an independent
+/// AudioProcessor per source and attribute transcripts accordingly.
+pub struct DualCapture {
+    mic: AudioCapture,
+    system: AudioCapture,
+}
+
+impl DualCapture {
+    /// Create a dual capture from a microphone and a system-audio device.
+    pub fn new(
+        mic_device: &str,
+        system_device: &str,
+        system_source: CaptureSource,
+        buffer_duration_ms: u32,
+        sender: mpsc::Sender<AudioChunk>,
+    ) -> Result<Self> {
+        let mic = AudioCapture::new(mic_device, buffer_duration_ms, sender.clone())
+            .context("Failed to initialize microphone capture")?
+            .with_label(LABEL_ME);
+        let system =
+            AudioCapture::with_source(system_device, system_source, buffer_duration_ms, sender)
+                .context("Failed to initialize system audio capture")?
+                .with_label(LABEL_THEM);
+        Ok(Self { mic, system })
+    }
+
+    /// Select channels for the microphone and system devices.
+    pub fn with_channels(mut self, mic: ChannelSelection, system: ChannelSelection) -> Self {
+        self.mic = self.mic.with_channels(mic);
+        self.system = self.system.with_channels(system);
+        self
+    }
+
+    /// Send device lifecycle events from both captures to this channel.
+    pub fn with_events(mut self, events: mpsc::UnboundedSender<CaptureEvent>) -> Self {
+        self.mic = self.mic.with_events(events.clone());
+        self.system = self.system.with_events(events);
+        self
+    }
+
+    /// Check both devices and re-attach after a hot-unplug.
+    pub fn monitor(&mut self) {
+        self.mic.monitor();
+        self.system.monitor();
+    }
+
+    /// Start both streams. If the second fails, the first is stopped again.
+    pub fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        self.mic.start(buffer_duration_ms)?;
+        if let Err(e) = self.system.start(buffer_duration_ms) {
+            self.mic.stop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Stop both streams.
+    pub fn stop(&mut self) {
+        self.mic.stop();
+        self.system.stop();
+    }
+
+    /// Request a device buffer size for both streams.
+    pub fn with_stream_buffer(mut self, stream_buffer: StreamBufferSize) -> Self {
+        self.mic = self.mic.with_stream_buffer(stream_buffer);
+        self.system = self.system.with_stream_buffer(stream_buffer);
+        self
+    }
+
+    /// Configure the overflow backlog of both streams.
+    pub fn with_backlog(mut self, backlog: BacklogConfig) -> Self {
+        self.mic = self.mic.with_backlog(backlog.clone());
+        self.system = self.system.with_backlog(backlog);
+        self
+    }
+
+    /// Configure the stall watchdog of both streams.
+    pub fn with_watchdog(mut self, timeout: Option<Duration>) -> Self {
+        self.mic = self.mic.with_watchdog(timeout);
+        self.system = self.system.with_watchdog(timeout);
+        self
+    }
+
+    /// Health metrics of the microphone and system streams.
+    pub fn stats(&self) -> Vec<CaptureStats> {
+        vec![self.mic.stats(), self.system.stats()]
     }
 }
 
@@ -0,0 +1,109 @@
+//! RNNoise-based noise suppression (via nnnoiseless).
+//!
+//! RNNoise works on 10 ms frames of 48 kHz audio in i16 scale. Input at
+//! other rates is brought to 48 kHz by linear interpolation from the 16 kHz
+//! pipeline rate and decimated back afterwards; speech content sits well
+//! below 8 kHz, so the round trip costs nothing audible for transcription.
+
+use nnnoiseless::DenoiseState;
+
+const RNNOISE_RATE: u32 = 48000;
+const FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
+const I16_SCALE: f32 = i16::MAX as f32;
+
+/// Streaming noise suppressor. Keep one instance per audio source.
+pub struct NoiseSuppressor {
+    state: Box<DenoiseState<'static>>,
+    /// 48 kHz input not yet processed (less than one frame).
+    pending: Vec<f32>,
+    /// Denoised 48 kHz output not yet handed back.
+    output: Vec<f32>,
+    /// Speech probability RNNoise reported for the last processed frame.
+    last_vad_probability: f32,
+}
+
+impl NoiseSuppressor {
+    /// Create a suppressor with fresh RNNoise state.
+    pub fn new() -> Self {
+        Self {
+            state: DenoiseState::new(),
+            pending: Vec::with_capacity(FRAME_SIZE * 2),
+            output: Vec::new(),
+            last_vad_probability: 0.0,
+        }
+    }
+
+    /// Denoise samples at `sample_rate` (48000 or 16000).
+    ///
+    /// Output lags input by less than one 10 ms frame, so the returned
+    /// buffer may be slightly shorter or longer than the input.
+    pub fn process(&mut self, samples: &[f32], sample_rate: u32) -> Vec<f32> {
+        if sample_rate == RNNOISE_RATE {
+            return self.process_48k(samples);
+        }
+
+        let factor = (RNNOISE_RATE / sample_rate.max(1)) as usize;
+        let upsampled = upsample(samples, factor);
+        let denoised = self.process_48k(&upsampled);
+        decimate(&denoised, factor)
+    }
+
+    /// RNNoise speech probability (0.0–1.0) of the most recent frame.
+    pub fn last_vad_probability(&self) -> f32 {
+        self.last_vad_probability
+    }
+
+    /// Drop buffered audio (e.g. when the processor is reset).
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.output.clear();
+    }
+
+    fn process_48k(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.pending.extend(samples.iter().map(|&s| s * I16_SCALE));
+
+        let mut frame_out = [0.0f32; FRAME_SIZE];
+        let frames = self.pending.len() / FRAME_SIZE;
+        for i in 0..frames {
+            let frame = &self.pending[i * FRAME_SIZE..(i + 1) * FRAME_SIZE];
+            self.last_vad_probability = self.state.process_frame(&mut frame_out, frame);
+            self.output
+                .extend(frame_out.iter().map(|&s| (s / I16_SCALE).clamp(-1.0, 1.0)));
+        }
+        self.pending.drain(..frames * FRAME_SIZE);
+
+        std::mem::take(&mut self.output)
+    }
+}
+
+impl Default for NoiseSuppressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Linear-interpolation upsampling by an integer factor.
+fn upsample(samples: &[f32], factor: usize) -> Vec<f32> {
+    if factor <= 1 {
+        return samples.to_vec();
+    }
+    let mut out = Vec::with_capacity(samples.len() * factor);
+    for (i, &s) in samples.iter().enumerate() {
+        let next = samples.get(i + 1).copied().unwrap_or(s);
+        for k in 0..factor {
+            out.push(s + (next - s) * k as f32 / factor as f32);
+        }
+    }
+    out
+}
+
+/// Decimate by an integer factor, averaging each group as a crude low-pass.
+fn decimate(samples: &[f32], factor: usize) -> Vec<f32> {
+    if factor <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(factor)
+        .map(|group| group.iter().sum::<f32>() / group.len() as f32)
+        .collect()
+}
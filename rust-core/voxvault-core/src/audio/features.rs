@@ -0,0 +1,141 @@
+//! Spectral shape features that veto non-speech sounds loud enough to pass
+//! the VAD.
+//!
+//! - Zero-crossing rate (ZCR): fraction of adjacent samples changing sign.
+//!   Hiss and broadband noise cross zero far more often than voiced speech.
+//! - Spectral flatness: geometric / arithmetic mean of the power spectrum,
+//!   from 0 (a pure tone) to 1 (white noise). Alert beeps and music beds
+//!   are tonal and score much lower than speech.
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Analysis frame for spectral flatness (32 ms at 16 kHz).
+const FRAME_SIZE: usize = 512;
+/// Band flatness is measured over, in Hz (where speech energy lives).
+const BAND_HZ: (f32, f32) = (100.0, 4000.0);
+/// Frames quieter than this RMS are skipped (their spectrum is noise).
+const MIN_FRAME_RMS: f32 = 1e-4;
+
+/// Fraction of adjacent sample pairs that change sign (0.0–1.0).
+pub fn zero_crossing_rate(samples: &[f32]) -> f32 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let crossings = samples
+        .windows(2)
+        .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+        .count();
+    crossings as f32 / (samples.len() - 1) as f32
+}
+
+/// Measures spectral flatness of 16 kHz audio, averaged over frames.
+pub struct SpectralFlatness {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    /// FFT bins covering `BAND_HZ`.
+    band: std::ops::Range<usize>,
+}
+
+impl SpectralFlatness {
+    /// Create an analyzer for audio at `sample_rate`.
+    pub fn new(sample_rate: u32) -> Self {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FRAME_SIZE);
+        let window = (0..FRAME_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FRAME_SIZE as f32).cos())
+            .collect();
+        let bin_hz = sample_rate as f32 / FRAME_SIZE as f32;
+        let bins = FRAME_SIZE / 2 + 1;
+        let low = ((BAND_HZ.0 / bin_hz).ceil() as usize).min(bins - 1);
+        let high = ((BAND_HZ.1 / bin_hz).floor() as usize + 1).clamp(low + 1, bins);
+        Self {
+            input: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            fft,
+            window,
+            band: low..high,
+        }
+    }
+
+    /// Mean flatness (0.0–1.0) of the non-silent frames of `samples`, or
+    /// None if no frame is loud enough to measure.
+    pub fn measure(&mut self, samples: &[f32]) -> Option<f32> {
+        let mut total = 0.0f32;
+        let mut frames = 0;
+        for frame in samples.chunks_exact(FRAME_SIZE) {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_SIZE as f32).sqrt();
+            if rms < MIN_FRAME_RMS {
+                continue;
+            }
+            for ((x, s), w) in self.input.iter_mut().zip(frame).zip(&self.window) {
+                *x = s * w;
+            }
+            if self
+                .fft
+                .process(&mut self.input, &mut self.spectrum)
+                .is_err()
+            {
+                continue;
+            }
+
+            let band = &self.spectrum[self.band.clone()];
+            let power = || band.iter().map(|c| c.norm_sqr() + 1e-12);
+            let log_mean = power().map(f32::ln).sum::<f32>() / band.len() as f32;
+            let mean = power().sum::<f32>() / band.len() as f32;
+            total += log_mean.exp() / mean;
+            frames += 1;
+        }
+        (frames > 0).then(|| total / frames as f32)
+    }
+}
+
+/// Rejects VAD-positive chunks whose ZCR or spectral flatness rules out
+/// speech. Each check is optional.
+pub struct FeatureGate {
+    max_zero_crossing_rate: Option<f32>,
+    min_spectral_flatness: Option<f32>,
+    flatness: SpectralFlatness,
+}
+
+impl FeatureGate {
+    /// Create a gate, or None when neither threshold is set.
+    pub fn new(
+        sample_rate: u32,
+        max_zero_crossing_rate: Option<f32>,
+        min_spectral_flatness: Option<f32>,
+    ) -> Option<Self> {
+        if max_zero_crossing_rate.is_none() && min_spectral_flatness.is_none() {
+            return None;
+        }
+        Some(Self {
+            max_zero_crossing_rate,
+            min_spectral_flatness,
+            flatness: SpectralFlatness::new(sample_rate),
+        })
+    }
+
+    /// Whether `samples` look like speech by every configured feature.
+    pub fn accepts(&mut self, samples: &[f32]) -> bool {
+        if let Some(max) = self.max_zero_crossing_rate {
+            let zcr = zero_crossing_rate(samples);
+            if zcr > max {
+                debug!(zcr, max, "Rejected as speech: zero-crossing rate too high");
+                return false;
+            }
+        }
+        if let Some(min) = self.min_spectral_flatness {
+            if let Some(flatness) = self.flatness.measure(samples) {
+                if flatness < min {
+                    debug!(flatness, min, "Rejected as speech: tonal spectrum");
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
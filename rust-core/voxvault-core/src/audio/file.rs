@@ -0,0 +1,276 @@
+//! Audio file decoding and a file-backed [`AudioSource`].
+//!
+//! Decodes WAV, FLAC, MP3 and Ogg/Vorbis via symphonia, downmixes to mono
+//! and emits AudioChunks exactly like a live capture would, so recordings
+//! can be transcribed through the same processor/engine pipeline.
+
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::capture::AudioChunk;
+use super::source::AudioSource;
+
+/// Streaming decoder yielding mono f32 samples packet by packet.
+pub struct AudioFileDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    sample_rate: u32,
+}
+
+impl AudioFileDecoder {
+    /// Open an audio file, using its extension as a format hint.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let extension = path.extension().and_then(|e| e.to_str());
+        Self::from_source(Box::new(file), extension)
+    }
+
+    /// Open audio from any symphonia media source (file, in-memory cursor).
+    pub fn from_source(source: Box<dyn MediaSource>, extension: Option<&str>) -> Result<Self> {
+        let mss = MediaSourceStream::new(source, Default::default());
+        let mut hint = Hint::new();
+        if let Some(ext) = extension {
+            hint.with_extension(ext);
+        }
+
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                mss,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .context("Unsupported or corrupt audio file")?;
+        let format = probed.format;
+
+        let track = format
+            .default_track()
+            .context("Audio file has no audio track")?;
+        let track_id = track.id;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .context("Audio file does not declare a sample rate")?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .context("Unsupported audio codec")?;
+
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+            sample_rate,
+        })
+    }
+
+    /// Sample rate of the decoded audio.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Decode the next packet into mono samples. Returns None at end of file.
+    pub fn next_samples(&mut self) -> Result<Option<Vec<f32>>> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(p) => p,
+                Err(SymphoniaError::IoError(e))
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(None);
+                }
+                Err(e) => return Err(e).context("Failed to read audio packet"),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(d) => d,
+                Err(SymphoniaError::DecodeError(e)) => {
+                    warn!("Skipping undecodable audio packet: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(e).context("Failed to decode audio"),
+            };
+
+            let spec = *decoded.spec();
+            let channels = spec.channels.count().max(1);
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+
+            // Downmix to mono by averaging channels
+            let mono: Vec<f32> = if channels == 1 {
+                buffer.samples().to_vec()
+            } else {
+                buffer
+                    .samples()
+                    .chunks(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                    .collect()
+            };
+            return Ok(Some(mono));
+        }
+    }
+
+    /// Decode everything that's left into one mono buffer.
+    pub fn decode_all(mut self) -> Result<Vec<f32>> {
+        let mut samples = Vec::new();
+        while let Some(chunk) = self.next_samples()? {
+            samples.extend_from_slice(&chunk);
+        }
+        Ok(samples)
+    }
+}
+
+/// Decode a whole audio file to mono samples. Returns `(samples, sample_rate)`.
+pub fn decode_file(path: &Path) -> Result<(Vec<f32>, u32)> {
+    let decoder = AudioFileDecoder::open(path)?;
+    let sample_rate = decoder.sample_rate();
+    Ok((decoder.decode_all()?, sample_rate))
+}
+
+/// How fast a FileSource emits chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pacing {
+    /// One chunk per chunk duration, like a live device.
+    #[default]
+    RealTime,
+    /// As fast as the consumer accepts chunks.
+    MaxSpeed,
+}
+
+/// An [`AudioSource`] that plays an audio file into the pipeline.
+///
+/// Decoding runs on its own thread. The channel sender is handed to that
+/// thread, so the receiver sees the channel close once the file is done.
+pub struct FileSource {
+    path: PathBuf,
+    pacing: Pacing,
+    label: Option<String>,
+    sender: Option<mpsc::Sender<AudioChunk>>,
+    stop_flag: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FileSource {
+    /// Create a file source. The file is opened when `start()` is called.
+    pub fn new(path: impl Into<PathBuf>, pacing: Pacing, sender: mpsc::Sender<AudioChunk>) -> Self {
+        Self {
+            path: path.into(),
+            pacing,
+            label: None,
+            sender: Some(sender),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            worker: None,
+        }
+    }
+
+    /// Tag every chunk produced by this source with a label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Whether the whole file has been emitted (or playback was stopped).
+    pub fn is_finished(&self) -> bool {
+        self.worker
+            .as_ref()
+            .map(|w| w.is_finished())
+            .unwrap_or(true)
+    }
+}
+
+impl AudioSource for FileSource {
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        let Some(sender) = self.sender.take() else {
+            bail!("File source already started");
+        };
+
+        let mut decoder = AudioFileDecoder::open(&self.path)?;
+        let sample_rate = decoder.sample_rate();
+        let chunk_size = (sample_rate as usize * buffer_duration_ms.max(1) as usize) / 1000;
+        let chunk_duration = Duration::from_millis(buffer_duration_ms as u64);
+        let pacing = self.pacing;
+        let label = self.label.clone();
+        let stop_flag = Arc::clone(&self.stop_flag);
+        let path = self.path.clone();
+
+        info!(path = %path.display(), sample_rate, ?pacing, "File source started");
+
+        let worker = std::thread::Builder::new()
+            .name("file-source".into())
+            .spawn(move || {
+                let started = Instant::now();
+                let mut pending: Vec<f32> = Vec::with_capacity(chunk_size * 2);
+                let mut sent_chunks: u32 = 0;
+                let mut finished = false;
+
+                while !stop_flag.load(Ordering::SeqCst) {
+                    if !finished && pending.len() < chunk_size {
+                        match decoder.next_samples() {
+                            Ok(Some(samples)) => pending.extend_from_slice(&samples),
+                            Ok(None) => finished = true,
+                            Err(e) => {
+                                tracing::error!("File source decode error: {:#}", e);
+                                finished = true;
+                            }
+                        }
+                        continue;
+                    }
+
+                    if pending.is_empty() {
+                        break;
+                    }
+
+                    let take = pending.len().min(chunk_size);
+                    let chunk = AudioChunk {
+                        samples: pending.drain(..take).collect(),
+                        sample_rate,
+                        label: label.clone(),
+                    };
+
+                    if pacing == Pacing::RealTime {
+                        let due = started + chunk_duration * sent_chunks;
+                        if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                            std::thread::sleep(wait);
+                        }
+                    }
+
+                    // Blocking send gives natural backpressure in MaxSpeed mode
+                    if sender.blocking_send(chunk).is_err() {
+                        break;
+                    }
+                    sent_chunks += 1;
+                }
+
+                info!(path = %path.display(), chunks = sent_chunks, "File source finished");
+            })
+            .context("Failed to spawn file source thread")?;
+
+        self.worker = Some(worker);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        // The worker may be parked in blocking_send; it exits once the
+        // receiver drops, so don't join here.
+        self.worker = None;
+    }
+}
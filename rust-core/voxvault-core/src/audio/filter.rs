@@ -0,0 +1,74 @@
+//! High-pass filtering of DC offset and low-frequency rumble.
+//!
+//! Some interfaces deliver audio with a DC bias, and desks, HVAC and
+//! handling noise add energy below the voice band. Both inflate RMS (so
+//! the energy VAD fires on them) without carrying any speech.
+
+use std::f32::consts::PI;
+
+/// Second-order (biquad) Butterworth high-pass filter, RBJ cookbook form.
+pub struct HighPassFilter {
+    cutoff_hz: f32,
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    /// Previous two inputs and outputs (direct form I).
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl HighPassFilter {
+    /// Create a filter removing content below `cutoff_hz` at `sample_rate`.
+    pub fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        // Stay below Nyquist so the coefficients remain stable
+        let cutoff = cutoff_hz.clamp(1.0, sample_rate as f32 * 0.45);
+        let w0 = 2.0 * PI * cutoff / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let a0 = 1.0 + alpha;
+        Self {
+            cutoff_hz: cutoff,
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    /// Filter `samples` in place, continuing from the previous call.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for s in samples {
+            let x = *s;
+            let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+                - self.a1 * self.y1
+                - self.a2 * self.y2;
+            self.x2 = self.x1;
+            self.x1 = x;
+            self.y2 = self.y1;
+            self.y1 = y;
+            *s = y;
+        }
+    }
+
+    /// Forget filter history (e.g. when the processor is reset).
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+
+    /// Cutoff frequency in Hz.
+    pub fn cutoff_hz(&self) -> f32 {
+        self.cutoff_hz
+    }
+}
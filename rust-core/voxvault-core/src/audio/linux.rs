@@ -0,0 +1,157 @@
+//! Linux capture of meeting audio via PipeWire/PulseAudio monitor sources.
+//!
+//! cpal talks to ALSA on Linux, which only exposes regular inputs. Every
+//! PulseAudio sink (and every PipeWire sink, through pipewire-pulse) has a
+//! `.monitor` source carrying what the sink is playing. We discover those
+//! with `pactl` and open them through the ALSA `pulse`/`pipewire` PCM, with
+//! `PULSE_SOURCE` pointing libpulse at the chosen monitor.
+//!
+//! `PULSE_SOURCE` is process-wide, so it is only set while a monitor stream
+//! is being opened (see [`open_stream`]): streams opened before or after
+//! get their own source. For the same reason only one monitor source can be
+//! captured at a time.
+
+use anyhow::{bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::Device;
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tracing::info;
+
+/// ALSA PCM names routed through the sound server, in order of preference.
+const SOUND_SERVER_PCMS: &[&str] = &["pipewire", "pulse"];
+
+/// Monitor source captured by an open stream, if any. Locked while any
+/// stream opens, so `PULSE_SOURCE` never leaks into another stream.
+static ACTIVE_MONITOR: Mutex<Option<String>> = Mutex::new(None);
+
+/// A sink monitor source that can be captured like an input device.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MonitorSource {
+    /// Source name, e.g. `alsa_output.pci-0000_00_1f.3.analog-stereo.monitor`.
+    pub name: String,
+    /// Human-readable label, e.g. `Monitor of Built-in Audio Analog Stereo`.
+    pub description: String,
+}
+
+/// List all monitor sources known to the sound server.
+///
+/// Returns an empty list when `pactl` is missing or no server is running.
+pub fn list_monitor_sources() -> Vec<MonitorSource> {
+    let output = match Command::new("pactl").args(["list", "sources"]).output() {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    parse_sources(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|s| s.name.ends_with(".monitor"))
+        .collect()
+}
+
+/// Parse the long-form output of `pactl list sources`.
+fn parse_sources(text: &str) -> Vec<MonitorSource> {
+    let mut sources = Vec::new();
+    let mut name: Option<String> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Description:") {
+            if let Some(name) = name.take() {
+                sources.push(MonitorSource {
+                    name,
+                    description: value.trim().to_string(),
+                });
+            }
+        }
+    }
+
+    sources
+}
+
+/// Find a monitor source by its description or source name.
+pub fn find_monitor_source(name: &str) -> Option<MonitorSource> {
+    list_monitor_sources()
+        .into_iter()
+        .find(|s| s.description == name || s.name == name)
+}
+
+/// Find the cpal device streams of the given monitor source open on; open
+/// them with [`open_stream`].
+pub fn open_monitor_device(source: &MonitorSource) -> Result<Device> {
+    let host = cpal::default_host();
+    let devices: Vec<Device> = host
+        .input_devices()
+        .context("Failed to enumerate input devices")?
+        .collect();
+
+    for pcm in SOUND_SERVER_PCMS {
+        if let Some(device) = devices
+            .iter()
+            .find(|d| d.name().map(|n| n == *pcm).unwrap_or(false))
+        {
+            info!(
+                monitor = source.name,
+                pcm, "Capturing monitor source through sound server"
+            );
+            return Ok(device.clone());
+        }
+    }
+
+    bail!(
+        "Monitor source '{}' found, but no ALSA '{}' device is available. \
+         Install pipewire-alsa or the ALSA PulseAudio plugin.",
+        source.description,
+        SOUND_SERVER_PCMS.join("' or '")
+    );
+}
+
+/// Held while a stream captures a monitor source; released when dropped.
+#[derive(Debug)]
+pub struct MonitorClaim(());
+
+impl Drop for MonitorClaim {
+    fn drop(&mut self) {
+        *active_monitor() = None;
+    }
+}
+
+fn active_monitor() -> MutexGuard<'static, Option<String>> {
+    ACTIVE_MONITOR.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Open a stream with `open`, capturing `monitor` (a monitor source name)
+/// if given. Keep the returned claim as long as the stream: a second
+/// monitor stream is refused meanwhile.
+pub fn open_stream<T>(
+    monitor: Option<&str>,
+    open: impl FnOnce() -> Result<T>,
+) -> Result<(T, Option<MonitorClaim>)> {
+    let mut active = active_monitor();
+    let Some(monitor) = monitor else {
+        return Ok((open()?, None));
+    };
+    if let Some(ref other) = *active {
+        bail!(
+            "Monitor source '{}' is already being captured; \
+             only one monitor source can be captured at a time",
+            other
+        );
+    }
+
+    // libpulse (used by both ALSA plugins) picks the default source from
+    // PULSE_SOURCE when the stream connects, which happens while it opens.
+    let previous = std::env::var_os("PULSE_SOURCE");
+    std::env::set_var("PULSE_SOURCE", monitor);
+    let stream = open();
+    match previous {
+        Some(previous) => std::env::set_var("PULSE_SOURCE", previous),
+        None => std::env::remove_var("PULSE_SOURCE"),
+    }
+
+    let stream = stream?;
+    *active = Some(monitor.to_string());
+    Ok((stream, Some(MonitorClaim(()))))
+}
@@ -0,0 +1,131 @@
+//! Capture health metrics: dropped chunks, callback underruns and latency.
+//!
+//! The cpal callback updates lock-free counters in [`CaptureMetrics`]; the
+//! capture owner snapshots them into a [`CaptureStats`] via
+//! `AudioCapture::stats()`.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters shared between a capture and its stream callback.
+///
+/// Survives device reconnects, so the counts cover the whole session.
+#[derive(Debug, Default)]
+pub(crate) struct CaptureMetrics {
+    /// Stream callbacks received (watchdog liveness).
+    callbacks: AtomicU64,
+    chunks_sent: AtomicU64,
+    chunks_dropped: AtomicU64,
+    underruns: AtomicU64,
+    /// Chunks waiting in the overflow backlog.
+    backlogged_chunks: AtomicU64,
+    /// Device-side latency (capture → callback) of the last callback, in µs.
+    device_latency_us: AtomicU64,
+    /// Audio delivered per callback, in µs.
+    callback_interval_us: AtomicU64,
+}
+
+impl CaptureMetrics {
+    pub(crate) fn record_callback(&self) {
+        self.callbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn callbacks(&self) -> u64 {
+        self.callbacks.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_sent(&self) {
+        self.chunks_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dropped(&self) {
+        self.chunks_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_underrun(&self) {
+        self.underruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_backlog(&self, len: usize) {
+        self.backlogged_chunks.store(len as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_device_latency(&self, latency: Duration) {
+        self.device_latency_us
+            .store(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_callback_interval(&self, interval: Duration) {
+        self.callback_interval_us
+            .store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Snapshot the counters. `queued_chunks` and `chunk_ms` describe what
+    /// is waiting in the channel, which adds to the device latency.
+    pub(crate) fn snapshot(
+        &self,
+        device: &str,
+        label: Option<&str>,
+        queued_chunks: usize,
+        chunk_ms: u32,
+    ) -> CaptureStats {
+        let device_latency_ms = self.device_latency_us.load(Ordering::Relaxed) as f32 / 1000.0;
+        let backlogged_chunks = self.backlogged_chunks.load(Ordering::Relaxed) as usize;
+        CaptureStats {
+            device: device.to_string(),
+            label: label.map(str::to_string),
+            chunks_sent: self.chunks_sent.load(Ordering::Relaxed),
+            chunks_dropped: self.chunks_dropped.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            queued_chunks,
+            backlogged_chunks,
+            callback_ms: self.callback_interval_us.load(Ordering::Relaxed) as f32 / 1000.0,
+            latency_ms: device_latency_ms
+                + ((queued_chunks + backlogged_chunks) as u32 * chunk_ms) as f32,
+        }
+    }
+}
+
+/// Point-in-time health of one capture stream.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CaptureStats {
+    /// Device the stream is attached to.
+    pub device: String,
+    /// Source label ("me" / "them") in dual-capture mode.
+    pub label: Option<String>,
+    /// Chunks handed to the processing channel.
+    pub chunks_sent: u64,
+    /// Chunks dropped because the backlog overflowed (consumer too slow);
+    /// only silence is dropped.
+    pub chunks_dropped: u64,
+    /// Gaps between callbacks longer than the audio they delivered
+    /// (xruns: the device overwrote audio before it was read).
+    pub underruns: u64,
+    /// Chunks waiting in the channel for the processor.
+    pub queued_chunks: usize,
+    /// Chunks waiting in the overflow backlog behind a full channel.
+    pub backlogged_chunks: usize,
+    /// Audio delivered per device callback (the achieved buffer size).
+    pub callback_ms: f32,
+    /// Estimated capture-to-processor latency: device latency plus the
+    /// audio queued in the channel and the backlog.
+    pub latency_ms: f32,
+}
+
+impl fmt::Display for CaptureStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Capture stats ({}): sent={} dropped={} underruns={} queued={} backlog={} callback={:.1}ms latency={:.0}ms",
+            self.device,
+            self.chunks_sent,
+            self.chunks_dropped,
+            self.underruns,
+            self.queued_chunks,
+            self.backlogged_chunks,
+            self.callback_ms,
+            self.latency_ms
+        )
+    }
+}
@@ -0,0 +1,286 @@
+//! Multi-device capture mixed into a single mono stream.
+//!
+//! Each device runs its own [`AudioCapture`]; a mixer thread resamples
+//! every device's chunks to 16 kHz (one streaming [`Resampler`] per
+//! device), applies the per-device gain and sums them, so e.g. a
+//! meeting's system audio and a room microphone feed one transcript.
+//! Unlike [`super::capture::DualCapture`], the sources are not
+//! attributed separately.
+
+use anyhow::{bail, Context, Result};
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::info;
+
+use super::backlog::BacklogConfig;
+use super::capture::{AudioCapture, AudioChunk, CaptureEvent, CaptureSource, StreamBufferSize};
+use super::metrics::CaptureStats;
+use super::resample::{Resampler, ResamplerQuality};
+use super::source::AudioSource;
+
+/// Rate the mixed stream is produced at (the processor's target rate).
+const MIX_SAMPLE_RATE: u32 = 16000;
+
+/// One device feeding the mix.
+#[derive(Debug, Clone)]
+pub struct MixInput {
+    pub device: String,
+    pub source: CaptureSource,
+    /// Linear gain applied before summing.
+    pub gain: f32,
+}
+
+impl MixInput {
+    /// An input device at unity gain.
+    pub fn new(device: impl Into<String>) -> Self {
+        Self {
+            device: device.into(),
+            source: CaptureSource::Input,
+            gain: 1.0,
+        }
+    }
+}
+
+impl FromStr for MixInput {
+    type Err = anyhow::Error;
+
+    /// Parse `NAME` or `NAME=GAIN` (e.g. `"BlackHole 2ch=0.8"`).
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some((device, gain)) = s.rsplit_once('=') {
+            if let Ok(gain) = gain.trim().parse::<f32>() {
+                if !gain.is_finite() || gain < 0.0 {
+                    bail!("Invalid gain '{}' for device '{}'", gain, device);
+                }
+                return Ok(Self {
+                    gain,
+                    ..Self::new(device.trim())
+                });
+            }
+        }
+        Ok(Self::new(s.trim()))
+    }
+}
+
+/// Captures several devices and mixes them into one mono stream.
+pub struct MixedCapture {
+    inputs: Vec<MixInput>,
+    captures: Vec<AudioCapture>,
+    /// Receives every device's chunks, labelled with the input index.
+    mix_rx: Option<mpsc::Receiver<AudioChunk>>,
+    sender: mpsc::Sender<AudioChunk>,
+    label: Option<String>,
+    resampler: ResamplerQuality,
+    mixer: Option<JoinHandle<()>>,
+}
+
+impl MixedCapture {
+    /// Open every device of `inputs`. Mixed chunks are sent to `sender`.
+    pub fn new(
+        inputs: Vec<MixInput>,
+        buffer_duration_ms: u32,
+        sender: mpsc::Sender<AudioChunk>,
+    ) -> Result<Self> {
+        if inputs.is_empty() {
+            bail!("No devices to mix");
+        }
+
+        let (mix_tx, mix_rx) = mpsc::channel(32 * inputs.len());
+        let captures = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                AudioCapture::with_source(
+                    &input.device,
+                    input.source,
+                    buffer_duration_ms,
+                    mix_tx.clone(),
+                )
+                .with_context(|| format!("Failed to initialize capture of '{}'", input.device))
+                .map(|c| c.with_label(i.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            inputs,
+            captures,
+            mix_rx: Some(mix_rx),
+            sender,
+            label: None,
+            resampler: ResamplerQuality::default(),
+            mixer: None,
+        })
+    }
+
+    /// Tag every mixed chunk with a label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Select the resampler used to bring each device to 16 kHz.
+    pub fn with_resampler(mut self, quality: ResamplerQuality) -> Self {
+        self.resampler = quality;
+        self
+    }
+
+    /// Send device lifecycle events from every device to this channel.
+    pub fn with_events(mut self, events: mpsc::UnboundedSender<CaptureEvent>) -> Self {
+        self.captures = self
+            .captures
+            .into_iter()
+            .map(|c| c.with_events(events.clone()))
+            .collect();
+        self
+    }
+
+    /// Request a device buffer size for every device.
+    pub fn with_stream_buffer(mut self, stream_buffer: StreamBufferSize) -> Self {
+        self.captures = self
+            .captures
+            .into_iter()
+            .map(|c| c.with_stream_buffer(stream_buffer))
+            .collect();
+        self
+    }
+
+    /// Configure the overflow backlog of every device.
+    pub fn with_backlog(mut self, backlog: BacklogConfig) -> Self {
+        self.captures = self
+            .captures
+            .into_iter()
+            .map(|c| c.with_backlog(backlog.clone()))
+            .collect();
+        self
+    }
+
+    /// Configure the stall watchdog of every device.
+    pub fn with_watchdog(mut self, timeout: Option<Duration>) -> Self {
+        self.captures = self
+            .captures
+            .into_iter()
+            .map(|c| c.with_watchdog(timeout))
+            .collect();
+        self
+    }
+
+    /// Health metrics of every device stream.
+    pub fn stats(&self) -> Vec<CaptureStats> {
+        self.captures.iter().map(|c| c.stats()).collect()
+    }
+}
+
+impl AudioSource for MixedCapture {
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        let Some(mix_rx) = self.mix_rx.take() else {
+            bail!("Mixed capture already started");
+        };
+
+        for i in 0..self.captures.len() {
+            if let Err(e) = self.captures[i].start(buffer_duration_ms) {
+                for started in &mut self.captures[..i] {
+                    started.stop();
+                }
+                return Err(e);
+            }
+        }
+
+        let mixer = Mixer {
+            gains: self.inputs.iter().map(|i| i.gain).collect(),
+            queues: vec![VecDeque::new(); self.inputs.len()],
+            resamplers: (0..self.inputs.len())
+                .map(|_| Resampler::new(self.resampler, MIX_SAMPLE_RATE))
+                .collect(),
+            chunk_size: (MIX_SAMPLE_RATE as usize * buffer_duration_ms.max(1) as usize) / 1000,
+            sender: self.sender.clone(),
+            label: self.label.clone(),
+        };
+        let worker = std::thread::Builder::new()
+            .name("audio-mixer".into())
+            .spawn(move || mixer.run(mix_rx))
+            .context("Failed to spawn audio mixer thread")?;
+        self.mixer = Some(worker);
+
+        info!(devices = self.inputs.len(), "Mixed capture started");
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        for capture in &mut self.captures {
+            capture.stop();
+        }
+        // The mixer exits once every capture (and its sender) is dropped
+        self.mixer = None;
+    }
+
+    fn monitor(&mut self) {
+        for capture in &mut self.captures {
+            capture.monitor();
+        }
+    }
+
+    fn stats(&self) -> Vec<CaptureStats> {
+        MixedCapture::stats(self)
+    }
+}
+
+/// Mixer thread state: one 16 kHz FIFO per input.
+struct Mixer {
+    gains: Vec<f32>,
+    queues: Vec<VecDeque<f32>>,
+    resamplers: Vec<Resampler>,
+    chunk_size: usize,
+    sender: mpsc::Sender<AudioChunk>,
+    label: Option<String>,
+}
+
+impl Mixer {
+    fn run(mut self, mut rx: mpsc::Receiver<AudioChunk>) {
+        while let Some(chunk) = rx.blocking_recv() {
+            let Some(index) = chunk.label.as_deref().and_then(|l| l.parse::<usize>().ok()) else {
+                continue;
+            };
+            let Some(gain) = self.gains.get(index).copied() else {
+                continue;
+            };
+
+            let samples = self.resamplers[index].process(&chunk.samples, chunk.sample_rate);
+            self.queues[index].extend(samples.iter().map(|&s| s * gain));
+
+            while self.chunk_ready() {
+                let mixed = self.mix_chunk();
+                if self.sender.blocking_send(mixed).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// A chunk is ready once every input has one buffered, or once any input
+    /// is two chunks ahead (another device stalled or was unplugged; its
+    /// share is mixed in as silence rather than holding everything back).
+    fn chunk_ready(&self) -> bool {
+        self.queues.iter().all(|q| q.len() >= self.chunk_size)
+            || self.queues.iter().any(|q| q.len() >= self.chunk_size * 2)
+    }
+
+    fn mix_chunk(&mut self) -> AudioChunk {
+        let mut mixed = vec![0.0f32; self.chunk_size];
+        for queue in &mut self.queues {
+            let take = queue.len().min(self.chunk_size);
+            for (out, s) in mixed.iter_mut().zip(queue.drain(..take)) {
+                *out += s;
+            }
+        }
+        for s in &mut mixed {
+            *s = s.clamp(-1.0, 1.0);
+        }
+        AudioChunk {
+            samples: mixed,
+            sample_rate: MIX_SAMPLE_RATE,
+            label: self.label.clone(),
+        }
+    }
+}
@@ -1,3 +1,22 @@
+pub mod aec;
+pub mod agc;
+pub mod backlog;
 pub mod capture;
+pub mod denoise;
+pub mod features;
+pub mod file;
+pub mod filter;
+#[cfg(target_os = "linux")]
+pub mod linux;
+pub mod metrics;
+pub mod mixer;
+pub mod net;
+pub mod opus;
+pub mod process_tap;
 pub mod processor;
+pub mod recorder;
+pub mod resample;
 pub mod setup;
+pub mod source;
+pub mod speaker;
+pub mod vad;
@@ -0,0 +1,337 @@
+//! Network audio ingest: PCM streamed to a UDP port by another machine.
+//!
+//! Packets carry either raw interleaved PCM (no header) or RTP with an
+//! L16 payload (RFC 3551: 16-bit big-endian). For example, from ffmpeg:
+//!
+//! ```text
+//! ffmpeg -f avfoundation -i :0 -ac 1 -ar 16000 -f rtp rtp://HOST:5004  # RTP L16
+//! ffmpeg -i talk.wav -ac 1 -ar 16000 -f s16le udp://HOST:5004           # raw
+//! ```
+//!
+//! The stream format isn't negotiated, so the sender's sample rate and
+//! channel count must match the [`NetConfig`].
+
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::net::{SocketAddr, UdpSocket};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::capture::AudioChunk;
+use super::source::AudioSource;
+
+/// Largest UDP datagram accepted.
+const MAX_PACKET_BYTES: usize = 65536;
+/// How often the receive loop checks for `stop()`.
+const RECV_TIMEOUT: Duration = Duration::from_millis(200);
+/// Lost RTP packets replaced by silence at most (longer gaps are skipped).
+const MAX_CONCEALED_PACKETS: u16 = 50;
+
+/// Packet layout of the incoming stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PacketFormat {
+    /// Raw 16-bit little-endian PCM (`ffmpeg -f s16le`).
+    #[default]
+    S16Le,
+    /// Raw 32-bit little-endian float PCM (`ffmpeg -f f32le`).
+    F32Le,
+    /// RTP with an L16 (16-bit big-endian) payload (`ffmpeg -f rtp`).
+    RtpL16,
+}
+
+impl FromStr for PacketFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "s16le" | "pcm16" => Ok(Self::S16Le),
+            "f32le" | "float" => Ok(Self::F32Le),
+            "rtp" | "rtp-l16" => Ok(Self::RtpL16),
+            other => bail!(
+                "Unknown packet format '{}': expected 's16le', 'f32le' or 'rtp'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PacketFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::S16Le => write!(f, "s16le"),
+            Self::F32Le => write!(f, "f32le"),
+            Self::RtpL16 => write!(f, "rtp"),
+        }
+    }
+}
+
+/// Format of the audio a [`NetSource`] receives.
+#[derive(Debug, Clone)]
+pub struct NetConfig {
+    pub format: PacketFormat,
+    pub sample_rate: u32,
+    /// Interleaved channels per frame; downmixed to mono.
+    pub channels: usize,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        Self {
+            format: PacketFormat::S16Le,
+            sample_rate: 16000,
+            channels: 1,
+        }
+    }
+}
+
+/// An [`AudioSource`] listening for PCM/RTP packets on a UDP port.
+///
+/// Like [`super::file::FileSource`], the receive loop runs on its own
+/// thread and owns the channel sender.
+pub struct NetSource {
+    bind: SocketAddr,
+    config: NetConfig,
+    label: Option<String>,
+    sender: Option<mpsc::Sender<AudioChunk>>,
+    stop_flag: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl NetSource {
+    /// Create a network source. The socket is bound when `start()` is called.
+    pub fn new(bind: SocketAddr, config: NetConfig, sender: mpsc::Sender<AudioChunk>) -> Self {
+        Self {
+            bind,
+            config,
+            label: None,
+            sender: Some(sender),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            worker: None,
+        }
+    }
+
+    /// Tag every chunk produced by this source with a label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl AudioSource for NetSource {
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        if self.config.channels == 0 || self.config.sample_rate == 0 {
+            bail!("Network audio needs a non-zero sample rate and channel count");
+        }
+        let Some(sender) = self.sender.take() else {
+            bail!("Network source already started");
+        };
+
+        let socket = UdpSocket::bind(self.bind)
+            .with_context(|| format!("Failed to bind UDP socket on {}", self.bind))?;
+        socket
+            .set_read_timeout(Some(RECV_TIMEOUT))
+            .context("Failed to configure UDP socket")?;
+
+        let chunk_size =
+            (self.config.sample_rate as usize * buffer_duration_ms.max(1) as usize) / 1000;
+        let mut receiver = PacketReceiver {
+            config: self.config.clone(),
+            chunk_size,
+            pending: Vec::with_capacity(chunk_size * 2),
+            label: self.label.clone(),
+            sender,
+            peer: None,
+            last_seq: None,
+            last_packet_frames: 0,
+        };
+        let stop_flag = Arc::clone(&self.stop_flag);
+
+        info!(
+            bind = %self.bind,
+            format = %self.config.format,
+            sample_rate = self.config.sample_rate,
+            channels = self.config.channels,
+            "Network audio source listening"
+        );
+
+        let worker = std::thread::Builder::new()
+            .name("net-source".into())
+            .spawn(move || receiver.run(socket, &stop_flag))
+            .context("Failed to spawn network source thread")?;
+        self.worker = Some(worker);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        // The receive loop notices within RECV_TIMEOUT; don't join here
+        self.worker = None;
+    }
+}
+
+/// Receive-thread state: decodes packets and assembles chunks.
+struct PacketReceiver {
+    config: NetConfig,
+    chunk_size: usize,
+    /// Mono samples not yet sent as a chunk.
+    pending: Vec<f32>,
+    label: Option<String>,
+    sender: mpsc::Sender<AudioChunk>,
+    /// Address of the current sender, logged when it changes.
+    peer: Option<SocketAddr>,
+    /// Sequence number of the last RTP packet accepted.
+    last_seq: Option<u16>,
+    /// Frames in the last RTP packet, used to size loss concealment.
+    last_packet_frames: usize,
+}
+
+impl PacketReceiver {
+    fn run(&mut self, socket: UdpSocket, stop_flag: &AtomicBool) {
+        let mut buf = vec![0u8; MAX_PACKET_BYTES];
+        while !stop_flag.load(Ordering::SeqCst) {
+            let (len, peer) = match socket.recv_from(&mut buf) {
+                Ok(r) => r,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(e) => {
+                    tracing::error!("Network audio receive failed: {}", e);
+                    break;
+                }
+            };
+
+            if self.peer != Some(peer) {
+                info!(%peer, "Receiving network audio");
+                self.peer = Some(peer);
+                self.last_seq = None;
+            }
+
+            let interleaved = match self.decode(&buf[..len]) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    debug!(%peer, "Ignoring network audio packet: {:#}", e);
+                    continue;
+                }
+            };
+            self.push_interleaved(&interleaved);
+
+            while self.pending.len() >= self.chunk_size {
+                let chunk = AudioChunk {
+                    samples: self.pending.drain(..self.chunk_size).collect(),
+                    sample_rate: self.config.sample_rate,
+                    label: self.label.clone(),
+                };
+                if self.sender.blocking_send(chunk).is_err() {
+                    return;
+                }
+            }
+        }
+        info!("Network audio source stopped");
+    }
+
+    /// Decode one packet to interleaved f32 samples.
+    fn decode(&mut self, packet: &[u8]) -> Result<Vec<f32>> {
+        match self.config.format {
+            PacketFormat::S16Le => Ok(s16le_samples(packet)),
+            PacketFormat::F32Le => Ok(f32le_samples(packet)),
+            PacketFormat::RtpL16 => {
+                let (seq, payload) = parse_rtp(packet)?;
+                let mut samples = Vec::new();
+                if let Some(last) = self.last_seq {
+                    let delta = seq.wrapping_sub(last);
+                    if delta == 0 || delta > u16::MAX / 2 {
+                        bail!("duplicate or late RTP packet {}", seq);
+                    }
+                    let lost = delta - 1;
+                    if lost > 0 {
+                        warn!(lost, "Network audio packets lost");
+                        if lost <= MAX_CONCEALED_PACKETS {
+                            let frames = lost as usize * self.last_packet_frames;
+                            samples.resize(frames * self.config.channels, 0.0);
+                        }
+                    }
+                }
+                self.last_seq = Some(seq);
+
+                samples.extend(
+                    payload
+                        .chunks_exact(2)
+                        .map(|b| i16::from_be_bytes([b[0], b[1]]) as f32 / 32768.0),
+                );
+                self.last_packet_frames = payload.len() / 2 / self.config.channels;
+                Ok(samples)
+            }
+        }
+    }
+
+    /// Downmix interleaved samples to mono and buffer them.
+    fn push_interleaved(&mut self, samples: &[f32]) {
+        let channels = self.config.channels;
+        if channels == 1 {
+            self.pending.extend_from_slice(samples);
+        } else {
+            self.pending.extend(
+                samples
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+            );
+        }
+    }
+}
+
+/// Samples of raw 16-bit little-endian PCM (a trailing odd byte is dropped).
+pub fn s16le_samples(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+        .collect()
+}
+
+/// Samples of raw 32-bit little-endian float PCM (trailing bytes short of
+/// a sample are dropped).
+pub fn f32le_samples(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Split an RTP packet into its sequence number and payload.
+fn parse_rtp(packet: &[u8]) -> Result<(u16, &[u8])> {
+    if packet.len() < 12 {
+        bail!("RTP packet too short ({} bytes)", packet.len());
+    }
+    if packet[0] >> 6 != 2 {
+        bail!("not an RTP version 2 packet");
+    }
+    let padding = packet[0] & 0x20 != 0;
+    let extension = packet[0] & 0x10 != 0;
+    let csrc_count = (packet[0] & 0x0f) as usize;
+    let seq = u16::from_be_bytes([packet[2], packet[3]]);
+
+    let mut start = 12 + 4 * csrc_count;
+    if extension {
+        let Some(header) = packet.get(start..start + 4) else {
+            bail!("truncated RTP header extension");
+        };
+        start += 4 + 4 * u16::from_be_bytes([header[2], header[3]]) as usize;
+    }
+    let mut end = packet.len();
+    if padding {
+        end = end.saturating_sub(packet[packet.len() - 1] as usize);
+    }
+    if start > end {
+        bail!("truncated RTP packet");
+    }
+    Ok((seq, &packet[start..end]))
+}
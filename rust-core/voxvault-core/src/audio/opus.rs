@@ -0,0 +1,62 @@
+//! Opus packet decoding for audio streamed by remote (browser) clients.
+//!
+//! Packets are decoded straight to 16 kHz mono — libopus resamples
+//! internally — so decoded audio needs no further resampling before VAD.
+//! Needs the `opus` feature.
+
+#[cfg(not(feature = "opus"))]
+use anyhow::bail;
+#[cfg(feature = "opus")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "opus")]
+use audiopus::{coder::Decoder, packet::Packet, Channels, MutSignals, SampleRate};
+
+/// Rate decoded audio is produced at.
+pub const OPUS_DECODE_RATE: u32 = 16000;
+/// Longest Opus packet (120 ms) at the decode rate, in samples.
+#[cfg(feature = "opus")]
+const MAX_FRAME_SAMPLES: usize = OPUS_DECODE_RATE as usize * 120 / 1000;
+
+/// Stateful decoder for one Opus stream (one per client connection).
+pub struct OpusDecoder {
+    #[cfg(feature = "opus")]
+    decoder: Decoder,
+    #[cfg(feature = "opus")]
+    output: Vec<f32>,
+}
+
+impl OpusDecoder {
+    /// Create a decoder producing 16 kHz mono (stereo streams are downmixed).
+    #[cfg(feature = "opus")]
+    pub fn new() -> Result<Self> {
+        let decoder = Decoder::new(SampleRate::Hz16000, Channels::Mono)
+            .context("Failed to create Opus decoder")?;
+        Ok(Self {
+            decoder,
+            output: vec![0.0; MAX_FRAME_SAMPLES],
+        })
+    }
+
+    #[cfg(not(feature = "opus"))]
+    pub fn new() -> Result<Self> {
+        bail!("Opus decoding is not available: rebuild with `--features opus`")
+    }
+
+    /// Decode one Opus packet to mono samples at [`OPUS_DECODE_RATE`].
+    #[cfg(feature = "opus")]
+    pub fn decode(&mut self, packet: &[u8]) -> Result<Vec<f32>> {
+        let packet = Packet::try_from(packet).context("Invalid Opus packet")?;
+        let output = MutSignals::try_from(&mut self.output[..])?;
+        let samples = self
+            .decoder
+            .decode_float(Some(packet), output, false)
+            .context("Failed to decode Opus packet")?;
+        Ok(self.output[..samples].to_vec())
+    }
+
+    #[cfg(not(feature = "opus"))]
+    pub fn decode(&mut self, _packet: &[u8]) -> Result<Vec<f32>> {
+        bail!("Opus decoding is not available: rebuild with `--features opus`")
+    }
+}
@@ -0,0 +1,453 @@
+//! Per-application audio capture via CoreAudio process taps (macOS 14.4+).
+//!
+//! Instead of capturing the whole system output through BlackHole, a
+//! process tap records only the audio of selected apps (e.g. Zoom, Teams),
+//! so music and notification sounds stay out of the transcript.
+//!
+//! [`ProcessTap::create`] taps the matching processes and wraps the tap in a
+//! private aggregate device. That device shows up to cpal like any other
+//! input, so it is captured with a regular `AudioCapture` opened on
+//! [`ProcessTap::device_name`]. The tap and its device are destroyed when
+//! the `ProcessTap` is dropped.
+//!
+//! The app bundle needs an `NSAudioCaptureUsageDescription` entry, and the
+//! user is asked for permission the first time a tap is created.
+
+/// A process that is currently registered with CoreAudio.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioProcessInfo {
+    pub pid: i32,
+    pub bundle_id: String,
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::AudioProcessInfo;
+    use anyhow::{bail, Result};
+    use core_foundation::array::CFArray;
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::CFString;
+    use core_foundation_sys::runloop::{kCFRunLoopDefaultMode, CFRunLoopRunInMode};
+    use coreaudio_sys::{
+        kAudioDevicePropertyDeviceUID, kAudioHardwareNoError,
+        kAudioHardwarePropertyDefaultSystemOutputDevice, kAudioObjectPropertyElementMain,
+        kAudioObjectPropertyScopeGlobal, kAudioObjectSystemObject,
+        AudioHardwareCreateAggregateDevice, AudioHardwareDestroyAggregateDevice,
+        AudioObjectGetPropertyData, AudioObjectGetPropertyDataSize, AudioObjectID,
+        AudioObjectPropertyAddress, OSStatus,
+    };
+    use std::ffi::{c_char, c_void, CStr};
+    use std::mem;
+    use tracing::{info, warn};
+
+    // Selectors added in the macOS 14.2/14.4 SDKs (not in coreaudio-sys)
+    const fn fourcc(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+    const PROPERTY_PROCESS_OBJECT_LIST: u32 = fourcc(b"prs#");
+    const PROPERTY_PROCESS_PID: u32 = fourcc(b"ppid");
+    const PROPERTY_PROCESS_BUNDLE_ID: u32 = fourcc(b"pbid");
+    const PROPERTY_TAP_UID: u32 = fourcc(b"tuid");
+
+    // Aggregate device dictionary keys for taps
+    const AGGREGATE_DEVICE_NAME_KEY: &str = "name";
+    const AGGREGATE_DEVICE_UID_KEY: &str = "uid";
+    const AGGREGATE_DEVICE_PRIVATE_KEY: &str = "private";
+    const AGGREGATE_DEVICE_SUB_LIST_KEY: &str = "subdevices";
+    const AGGREGATE_DEVICE_MASTER_KEY: &str = "master";
+    const AGGREGATE_DEVICE_TAP_LIST_KEY: &str = "taps";
+    const AGGREGATE_DEVICE_TAP_AUTO_START_KEY: &str = "tapautostart";
+    const SUB_DEVICE_UID_KEY: &str = "uid";
+    const SUB_TAP_UID_KEY: &str = "uid";
+    const SUB_TAP_DRIFT_COMPENSATION_KEY: &str = "drift";
+
+    const TAP_DEVICE_NAME: &str = "VoxVault App Tap";
+    const TAP_DEVICE_UID: &str = "com.voxvault.apptap";
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioHardwareCreateProcessTap(
+            description: *mut c_void,
+            out_tap_id: *mut AudioObjectID,
+        ) -> OSStatus;
+        fn AudioHardwareDestroyProcessTap(tap_id: AudioObjectID) -> OSStatus;
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> *mut c_void;
+        fn sel_registerName(name: *const c_char) -> *mut c_void;
+        fn objc_msgSend();
+    }
+
+    /// Send an Objective-C message with no arguments returning an object.
+    unsafe fn msg_send(receiver: *mut c_void, selector: &CStr) -> *mut c_void {
+        let send: unsafe extern "C" fn(*mut c_void, *mut c_void) -> *mut c_void =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel_registerName(selector.as_ptr()))
+    }
+
+    /// Send an Objective-C message with one pointer argument.
+    unsafe fn msg_send_ptr(
+        receiver: *mut c_void,
+        selector: &CStr,
+        arg: *const c_void,
+    ) -> *mut c_void {
+        let send: unsafe extern "C" fn(*mut c_void, *mut c_void, *const c_void) -> *mut c_void =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel_registerName(selector.as_ptr()), arg)
+    }
+
+    /// Send an Objective-C message with one BOOL argument.
+    unsafe fn msg_send_bool(receiver: *mut c_void, selector: &CStr, arg: bool) {
+        let send: unsafe extern "C" fn(*mut c_void, *mut c_void, bool) =
+            mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel_registerName(selector.as_ptr()), arg)
+    }
+
+    fn global_address(selector: u32) -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            mSelector: selector,
+            mScope: kAudioObjectPropertyScopeGlobal,
+            mElement: kAudioObjectPropertyElementMain,
+        }
+    }
+
+    fn get_string_property(object: AudioObjectID, selector: u32) -> Option<String> {
+        let address = global_address(selector);
+        let mut value: coreaudio_sys::CFStringRef = std::ptr::null();
+        let mut size = mem::size_of::<coreaudio_sys::CFStringRef>() as u32;
+
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                object,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut _ as *mut c_void,
+            )
+        };
+
+        if status != kAudioHardwareNoError as i32 || value.is_null() {
+            return None;
+        }
+
+        // Returned with a +1 retain count
+        let cf_string: CFString = unsafe { TCFType::wrap_under_create_rule(value as *const _) };
+        Some(cf_string.to_string())
+    }
+
+    fn get_pid(process: AudioObjectID) -> Option<i32> {
+        let address = global_address(PROPERTY_PROCESS_PID);
+        let mut pid: i32 = 0;
+        let mut size = mem::size_of::<i32>() as u32;
+
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                process,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut pid as *mut _ as *mut c_void,
+            )
+        };
+
+        (status == kAudioHardwareNoError as i32).then_some(pid)
+    }
+
+    /// UID of the system output device, used as the tap device's clock.
+    fn default_output_uid() -> Option<String> {
+        let address = global_address(kAudioHardwarePropertyDefaultSystemOutputDevice);
+        let mut device_id: AudioObjectID = 0;
+        let mut size = mem::size_of::<AudioObjectID>() as u32;
+
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                kAudioObjectSystemObject,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut device_id as *mut _ as *mut c_void,
+            )
+        };
+
+        if status != kAudioHardwareNoError as i32 || device_id == 0 {
+            return None;
+        }
+        get_string_property(device_id, kAudioDevicePropertyDeviceUID)
+    }
+
+    fn process_objects() -> Vec<AudioObjectID> {
+        let address = global_address(PROPERTY_PROCESS_OBJECT_LIST);
+
+        let mut data_size: u32 = 0;
+        let status = unsafe {
+            AudioObjectGetPropertyDataSize(
+                kAudioObjectSystemObject,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut data_size,
+            )
+        };
+        if status != kAudioHardwareNoError as i32 || data_size == 0 {
+            return Vec::new();
+        }
+
+        let mut ids =
+            vec![0 as AudioObjectID; data_size as usize / mem::size_of::<AudioObjectID>()];
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                kAudioObjectSystemObject,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut data_size,
+                ids.as_mut_ptr() as *mut c_void,
+            )
+        };
+        if status != kAudioHardwareNoError as i32 {
+            return Vec::new();
+        }
+        ids
+    }
+
+    /// List processes currently registered with CoreAudio.
+    pub fn list_audio_processes() -> Vec<AudioProcessInfo> {
+        process_objects()
+            .into_iter()
+            .filter_map(|id| {
+                Some(AudioProcessInfo {
+                    pid: get_pid(id)?,
+                    bundle_id: get_string_property(id, PROPERTY_PROCESS_BUNDLE_ID)?,
+                })
+            })
+            .collect()
+    }
+
+    /// A CoreAudio process tap wrapped in a private aggregate input device.
+    pub struct ProcessTap {
+        tap_id: AudioObjectID,
+        aggregate_id: AudioObjectID,
+    }
+
+    impl ProcessTap {
+        /// Tap every audio process whose bundle ID starts with one of
+        /// `bundle_ids` (so helper processes such as `com.microsoft.teams2.*`
+        /// are included) and mix them down to stereo.
+        pub fn create(bundle_ids: &[String]) -> Result<Self> {
+            let processes: Vec<AudioObjectID> = process_objects()
+                .into_iter()
+                .filter(|&id| {
+                    get_string_property(id, PROPERTY_PROCESS_BUNDLE_ID)
+                        .is_some_and(|b| bundle_ids.iter().any(|want| b.starts_with(want.as_str())))
+                })
+                .collect();
+            if processes.is_empty() {
+                bail!(
+                    "No running audio process matches {:?} (is the app open and in a call?)",
+                    bundle_ids
+                );
+            }
+
+            let tap_id = unsafe { create_tap(&processes)? };
+            let tap_uid = match get_string_property(tap_id, PROPERTY_TAP_UID) {
+                Some(uid) => uid,
+                None => {
+                    unsafe { AudioHardwareDestroyProcessTap(tap_id) };
+                    bail!("Failed to read the process tap UID");
+                }
+            };
+
+            let aggregate_id = match create_tap_aggregate(&tap_uid) {
+                Ok(id) => id,
+                Err(e) => {
+                    unsafe { AudioHardwareDestroyProcessTap(tap_id) };
+                    return Err(e);
+                }
+            };
+
+            info!(
+                processes = processes.len(),
+                ?bundle_ids,
+                device = TAP_DEVICE_NAME,
+                "Process tap created"
+            );
+            Ok(Self {
+                tap_id,
+                aggregate_id,
+            })
+        }
+
+        /// Name of the input device carrying the tapped audio.
+        pub fn device_name(&self) -> &str {
+            TAP_DEVICE_NAME
+        }
+    }
+
+    impl Drop for ProcessTap {
+        fn drop(&mut self) {
+            let status = unsafe { AudioHardwareDestroyAggregateDevice(self.aggregate_id) };
+            if status != kAudioHardwareNoError as i32 {
+                warn!(status, "Failed to destroy process tap device");
+            }
+            let status = unsafe { AudioHardwareDestroyProcessTap(self.tap_id) };
+            if status != kAudioHardwareNoError as i32 {
+                warn!(status, "Failed to destroy process tap");
+            }
+            info!("Process tap destroyed");
+        }
+    }
+
+    /// Create a private, unmuted stereo-mixdown tap of `processes`.
+    unsafe fn create_tap(processes: &[AudioObjectID]) -> Result<AudioObjectID> {
+        let class = objc_getClass(c"CATapDescription".as_ptr());
+        if class.is_null() {
+            bail!("Process taps require macOS 14.4 or later");
+        }
+
+        // CFArray<CFNumber> is toll-free bridged to NSArray<NSNumber>
+        let numbers: Vec<CFNumber> = processes
+            .iter()
+            .map(|&id| CFNumber::from(id as i32))
+            .collect();
+        let array = CFArray::from_CFTypes(&numbers);
+
+        let description = msg_send(class, c"alloc");
+        let description = msg_send_ptr(
+            description,
+            c"initStereoMixdownOfProcesses:",
+            array.as_concrete_TypeRef() as *const c_void,
+        );
+        if description.is_null() {
+            bail!("Failed to create the process tap description");
+        }
+        msg_send_bool(description, c"setPrivate:", true);
+
+        let mut tap_id: AudioObjectID = 0;
+        let status = AudioHardwareCreateProcessTap(description, &mut tap_id);
+        msg_send(description, c"release");
+
+        if status != kAudioHardwareNoError as i32 {
+            bail!(
+                "AudioHardwareCreateProcessTap failed: status={} (was audio capture permission denied?)",
+                status
+            );
+        }
+        Ok(tap_id)
+    }
+
+    /// Wrap a tap in a private aggregate device so cpal can open it.
+    fn create_tap_aggregate(tap_uid: &str) -> Result<AudioObjectID> {
+        let tap_dict = CFDictionary::from_CFType_pairs(&[
+            (
+                CFString::new(SUB_TAP_UID_KEY).as_CFType(),
+                CFString::new(tap_uid).as_CFType(),
+            ),
+            (
+                CFString::new(SUB_TAP_DRIFT_COMPENSATION_KEY).as_CFType(),
+                CFBoolean::true_value().as_CFType(),
+            ),
+        ]);
+        let tap_list = CFArray::from_CFTypes(&[tap_dict]);
+
+        let mut pairs = vec![
+            (
+                CFString::new(AGGREGATE_DEVICE_NAME_KEY).as_CFType(),
+                CFString::new(TAP_DEVICE_NAME).as_CFType(),
+            ),
+            (
+                CFString::new(AGGREGATE_DEVICE_UID_KEY).as_CFType(),
+                CFString::new(TAP_DEVICE_UID).as_CFType(),
+            ),
+            (
+                CFString::new(AGGREGATE_DEVICE_PRIVATE_KEY).as_CFType(),
+                CFBoolean::true_value().as_CFType(),
+            ),
+            (
+                CFString::new(AGGREGATE_DEVICE_TAP_LIST_KEY).as_CFType(),
+                tap_list.as_CFType(),
+            ),
+            (
+                CFString::new(AGGREGATE_DEVICE_TAP_AUTO_START_KEY).as_CFType(),
+                CFBoolean::true_value().as_CFType(),
+            ),
+        ];
+
+        // Clock the device from the system output, which the tapped apps
+        // play through anyway
+        if let Some(output_uid) = default_output_uid() {
+            let sub_dict = CFDictionary::from_CFType_pairs(&[(
+                CFString::new(SUB_DEVICE_UID_KEY).as_CFType(),
+                CFString::new(&output_uid).as_CFType(),
+            )]);
+            pairs.push((
+                CFString::new(AGGREGATE_DEVICE_SUB_LIST_KEY).as_CFType(),
+                CFArray::from_CFTypes(&[sub_dict]).as_CFType(),
+            ));
+            pairs.push((
+                CFString::new(AGGREGATE_DEVICE_MASTER_KEY).as_CFType(),
+                CFString::new(&output_uid).as_CFType(),
+            ));
+        }
+        let agg_dict = CFDictionary::from_CFType_pairs(&pairs);
+
+        let mut device_id: AudioObjectID = 0;
+        let status = unsafe {
+            AudioHardwareCreateAggregateDevice(
+                agg_dict.as_concrete_TypeRef() as coreaudio_sys::CFDictionaryRef,
+                &mut device_id,
+            )
+        };
+
+        // Give CoreAudio time to publish the device
+        unsafe {
+            CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.1, 0);
+        }
+
+        if status != kAudioHardwareNoError as i32 {
+            bail!(
+                "AudioHardwareCreateAggregateDevice failed for '{}': status={}",
+                TAP_DEVICE_NAME,
+                status
+            );
+        }
+        Ok(device_id)
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use macos::*;
+
+#[cfg(not(target_os = "macos"))]
+mod fallback {
+    use super::AudioProcessInfo;
+    use anyhow::{bail, Result};
+
+    pub fn list_audio_processes() -> Vec<AudioProcessInfo> {
+        Vec::new()
+    }
+
+    pub struct ProcessTap {
+        _private: (),
+    }
+
+    impl ProcessTap {
+        pub fn create(_bundle_ids: &[String]) -> Result<Self> {
+            bail!("Per-application capture requires macOS 14.4 or later")
+        }
+
+        pub fn device_name(&self) -> &str {
+            ""
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub use fallback::*;
@@ -1,7 +1,166 @@
-use tracing::info;
-use voxtral_mini_realtime::audio::{AudioBuffer, resample::resample_to_16k};
+use anyhow::{bail, Result};
+use std::collections::VecDeque;
+use tracing::{debug, info};
+use voxtral_mini_realtime::audio::AudioBuffer;
 
+use super::aec::{self, EchoCanceller};
 use super::capture::AudioChunk;
+use super::denoise::NoiseSuppressor;
+use super::features::FeatureGate;
+use super::filter::HighPassFilter;
+use super::resample::{Resampler, ResamplerQuality, ResamplerStats};
+use super::speaker::SpeakerChangeDetector;
+use super::vad::{EnergyVad, VadBackend};
+
+/// Frame length used to find the speech edges when trimming silence.
+const TRIM_FRAME_MS: usize = 20;
+
+/// Segmentation and VAD settings of an [`AudioProcessor`].
+#[derive(Debug, Clone)]
+pub struct ProcessorConfig {
+    /// Minimum audio duration before yielding for transcription.
+    pub min_duration_secs: f32,
+    /// Maximum audio duration to accumulate.
+    pub max_duration_secs: f32,
+    /// Milliseconds of silence before yielding (e.g. 1000 = 1s).
+    pub silence_pause_ms: u32,
+    /// Audio chunk duration (used to calculate the silence chunk count).
+    pub buffer_ms: u32,
+    /// RMS energy at which speech starts (onset), e.g. 0.005.
+    pub speech_threshold: f32,
+    /// RMS energy below which ongoing speech counts as silence (offset).
+    /// Lower than `speech_threshold` keeps soft trailing speech; None uses
+    /// the onset threshold for both.
+    pub offset_threshold: Option<f32>,
+    /// Speech must last this long before accumulation starts, so brief
+    /// clicks are ignored. Measured in whole chunks (0 = first chunk).
+    pub min_speech_ms: u32,
+    /// Audio carried from the end of a segment cut at `max_duration_secs`
+    /// into the next one, so a word split by the cut is heard whole once.
+    pub overlap_ms: u32,
+    /// Reject VAD-positive chunks with a higher zero-crossing rate
+    /// (hiss, broadband noise). Typical speech stays below ~0.25.
+    pub max_zero_crossing_rate: Option<f32>,
+    /// Reject VAD-positive chunks with a lower spectral flatness (alert
+    /// tones, music beds). Typical speech scores above ~0.05.
+    pub min_spectral_flatness: Option<f32>,
+    /// Silence kept before speech onset and prepended to the segment, so
+    /// slow-starting speakers don't lose their first syllables.
+    pub pre_roll_ms: u32,
+    /// Trim audio quieter than the offset threshold from both ends of each
+    /// segment, keeping this many ms around the speech, so the engine
+    /// doesn't spend mel frames and decode steps on silence. None keeps
+    /// segments whole.
+    pub trim_margin_ms: Option<u32>,
+    /// Flag likely speaker changes within segments (pitch discontinuities),
+    /// reported in [`SpeechSegment::speaker_changes_ms`].
+    pub speaker_change_hints: bool,
+}
+
+impl Default for ProcessorConfig {
+    fn default() -> Self {
+        // 3s min, 30s max, 1000ms silence pause, 500ms buffer, 0.005 threshold
+        Self {
+            min_duration_secs: 3.0,
+            max_duration_secs: 30.0,
+            silence_pause_ms: 1000,
+            buffer_ms: 500,
+            speech_threshold: 0.005,
+            offset_threshold: None,
+            min_speech_ms: 0,
+            overlap_ms: 0,
+            max_zero_crossing_rate: None,
+            min_spectral_flatness: None,
+            pre_roll_ms: 500,
+            trim_margin_ms: None,
+            speaker_change_hints: false,
+        }
+    }
+}
+
+/// A partial [`ProcessorConfig`] update, e.g. from a client control
+/// message. Unset fields keep their value; for the optional thresholds,
+/// 0 disables them. `buffer_ms` is fixed by the capture and can't change.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct ProcessorConfigUpdate {
+    pub min_duration_secs: Option<f32>,
+    pub max_duration_secs: Option<f32>,
+    pub silence_pause_ms: Option<u32>,
+    pub speech_threshold: Option<f32>,
+    pub offset_threshold: Option<f32>,
+    pub min_speech_ms: Option<u32>,
+    pub overlap_ms: Option<u32>,
+    pub max_zero_crossing_rate: Option<f32>,
+    pub min_spectral_flatness: Option<f32>,
+    pub pre_roll_ms: Option<u32>,
+}
+
+impl ProcessorConfig {
+    /// Apply `update`, rejecting it (and leaving `self` unchanged) if the
+    /// result is inconsistent.
+    pub fn update(&mut self, update: &ProcessorConfigUpdate) -> Result<()> {
+        let nonzero = |v: f32| (v > 0.0).then_some(v);
+        let mut next = self.clone();
+        macro_rules! set {
+            ($field:ident) => {
+                if let Some(v) = update.$field {
+                    next.$field = v;
+                }
+            };
+            ($field:ident, optional) => {
+                if let Some(v) = update.$field {
+                    next.$field = nonzero(v);
+                }
+            };
+        }
+        set!(min_duration_secs);
+        set!(max_duration_secs);
+        set!(silence_pause_ms);
+        set!(speech_threshold);
+        set!(offset_threshold, optional);
+        set!(min_speech_ms);
+        set!(overlap_ms);
+        set!(max_zero_crossing_rate, optional);
+        set!(min_spectral_flatness, optional);
+        set!(pre_roll_ms);
+
+        if !next.max_duration_secs.is_finite() || next.max_duration_secs <= 0.0 {
+            bail!("max_duration_secs must be positive");
+        }
+        if !(0.0..=next.max_duration_secs).contains(&next.min_duration_secs) {
+            bail!(
+                "min_duration_secs must be between 0 and max_duration_secs ({})",
+                next.max_duration_secs
+            );
+        }
+        if !next.speech_threshold.is_finite() || next.speech_threshold < 0.0 {
+            bail!("speech_threshold must not be negative");
+        }
+        *self = next;
+        Ok(())
+    }
+}
+
+/// A speech segment ready for transcription, with its position in the stream.
+#[derive(Debug, Clone)]
+pub struct SpeechSegment {
+    /// Peak-normalized audio at 16 kHz.
+    pub buffer: AudioBuffer,
+    /// Start of the segment, in ms of stream time since the first chunk fed.
+    pub start_ms: u64,
+    /// End of the segment, in ms of stream time.
+    pub end_ms: u64,
+    /// Leading audio repeated from the previous segment (after a hard cut),
+    /// in ms. Its transcript should be stitched to the previous one.
+    pub overlap_ms: u64,
+    /// Stream times (ms) within the segment where a different speaker
+    /// likely took over (empty unless speaker-change hints are enabled).
+    pub speaker_changes_ms: Vec<u64>,
+    /// Mean VAD speech probability (0.0–1.0) over the segment's speech,
+    /// weighted by duration. Low values suggest noise mistaken for speech.
+    pub speech_confidence: f32,
+}
 
 /// Processes raw audio chunks into AudioBuffers suitable for Voxtral inference.
 ///
@@ -16,10 +175,38 @@ pub struct AudioProcessor {
     min_samples: usize,
     /// Maximum number of samples to accumulate.
     max_samples: usize,
+    /// Samples carried into the next segment after a hard cut.
+    overlap_samples: usize,
+    /// Samples at the start of `accumulated` carried over from a hard cut.
+    carried: usize,
+    /// Samples processed since the processor was created (the stream clock).
+    stream_samples: u64,
+    /// Stream position of the first accumulated sample.
+    accumulated_start: u64,
 
     // --- VAD (Voice Activity Detection) ---
-    /// RMS energy threshold below which audio is considered silence.
-    speech_threshold: f32,
+    /// Speech detector (RMS energy unless replaced via `with_vad`).
+    vad: Box<dyn VadBackend>,
+    /// Speech probability of the last chunk fed.
+    speech_probability: f32,
+    /// Optional ZCR / spectral flatness veto on the VAD's decision.
+    feature_gate: Option<FeatureGate>,
+    /// Optional pitch-based speaker-change detector.
+    speaker_detector: Option<SpeakerChangeDetector>,
+    /// Stream positions of likely speaker changes not yet yielded.
+    speaker_changes: Vec<u64>,
+    /// Offset threshold as a fraction of the VAD's onset threshold
+    /// (hysteresis; 1.0 = none).
+    offset_ratio: f32,
+    /// Speech samples needed before accumulation starts.
+    min_speech_samples: usize,
+    /// Speech held back until it lasts `min_speech_samples`.
+    onset: Vec<f32>,
+    /// Sum of speech probability × samples over the accumulated speech,
+    /// and the samples summed (for the segment's mean confidence).
+    confidence: (f64, usize),
+    /// The same for `onset`.
+    onset_confidence: (f64, usize),
     /// Number of consecutive silent chunks observed.
     silence_count: usize,
     /// Number of silent chunks after speech before yielding (speech pause).
@@ -27,9 +214,25 @@ pub struct AudioProcessor {
     silence_pause_chunks: usize,
     /// Whether any speech was detected in the current accumulation.
     has_speech: bool,
-    /// Pre-roll buffer: last silent chunk kept for context so we don't
-    /// clip the beginning of speech.
-    pre_roll: Vec<f32>,
+    /// Whether the speaker is talking: from speech onset until a full
+    /// silence pause (unlike `has_speech`, unaffected by segment cuts).
+    speaking: bool,
+    /// Pre-roll ring buffer: the latest silence kept for context so we
+    /// don't clip the beginning of speech.
+    pre_roll: VecDeque<f32>,
+    /// Capacity of `pre_roll` in samples.
+    pre_roll_samples: usize,
+
+    /// Optional RNNoise pre-filter applied before RMS/VAD analysis.
+    denoiser: Option<NoiseSuppressor>,
+    /// Optional echo canceller removing VoxVault's own playback.
+    echo_canceller: Option<EchoCanceller>,
+    /// Optional high-pass removing DC offset and rumble before analysis.
+    high_pass: Option<HighPassFilter>,
+    /// Converts capture audio to `target_sample_rate`.
+    resampler: Resampler,
+    /// Settings the fields above were derived from.
+    config: ProcessorConfig,
 }
 
 impl AudioProcessor {
@@ -47,69 +250,293 @@ impl AudioProcessor {
         buffer_ms: u32,
         speech_threshold: f32,
     ) -> Self {
+        Self::from_config(ProcessorConfig {
+            min_duration_secs,
+            max_duration_secs,
+            silence_pause_ms,
+            buffer_ms,
+            speech_threshold,
+            ..ProcessorConfig::default()
+        })
+    }
+
+    /// Create a processor from a full [`ProcessorConfig`].
+    pub fn from_config(config: ProcessorConfig) -> Self {
         let target_sample_rate = 16000;
+        let mut processor = Self {
+            target_sample_rate,
+            accumulated: Vec::new(),
+            min_samples: 0,
+            max_samples: 0,
+            overlap_samples: 0,
+            carried: 0,
+            stream_samples: 0,
+            accumulated_start: 0,
+            vad: Box::new(EnergyVad::new(config.speech_threshold)),
+            speech_probability: 0.0,
+            feature_gate: None,
+            speaker_detector: None,
+            speaker_changes: Vec::new(),
+            offset_ratio: 1.0,
+            min_speech_samples: 0,
+            onset: Vec::new(),
+            confidence: (0.0, 0),
+            onset_confidence: (0.0, 0),
+            silence_count: 0,
+            silence_pause_chunks: 0,
+            has_speech: false,
+            speaking: false,
+            pre_roll: VecDeque::new(),
+            pre_roll_samples: 0,
+            denoiser: None,
+            echo_canceller: None,
+            high_pass: None,
+            resampler: Resampler::new(ResamplerQuality::default(), target_sample_rate),
+            config: config.clone(),
+        };
+        processor.apply_config(config);
+        processor
+    }
+
+    /// Change segmentation and VAD settings while running. Audio already
+    /// accumulated is kept and segmented under the new limits; a changed
+    /// `speech_threshold` is passed on to the VAD backend.
+    pub fn set_config(&mut self, config: ProcessorConfig) {
+        if config.speech_threshold != self.config.speech_threshold {
+            self.vad.set_speech_threshold(config.speech_threshold);
+        }
+        self.apply_config(config);
+    }
+
+    /// Current segmentation and VAD settings.
+    pub fn config(&self) -> &ProcessorConfig {
+        &self.config
+    }
+
+    /// Derive the sample counts and thresholds used by `feed` from `config`.
+    fn apply_config(&mut self, config: ProcessorConfig) {
+        let target_sample_rate = self.target_sample_rate;
+        let ms_to_samples = |ms: u32| (target_sample_rate as usize * ms as usize) / 1000;
+        let ProcessorConfig {
+            min_duration_secs,
+            max_duration_secs,
+            silence_pause_ms,
+            buffer_ms,
+            speech_threshold,
+            offset_threshold,
+            min_speech_ms,
+            overlap_ms,
+            max_zero_crossing_rate,
+            min_spectral_flatness,
+            pre_roll_ms,
+            trim_margin_ms,
+            speaker_change_hints,
+        } = config;
         let silence_pause_chunks = (silence_pause_ms / buffer_ms.max(1)) as usize;
+        let offset_threshold = offset_threshold.unwrap_or(speech_threshold);
         info!(
             silence_pause_ms,
             buffer_ms,
             silence_pause_chunks,
             speech_threshold,
+            offset_threshold,
+            min_speech_ms,
+            overlap_ms,
+            ?max_zero_crossing_rate,
+            ?min_spectral_flatness,
+            pre_roll_ms,
+            ?trim_margin_ms,
+            speaker_change_hints,
             "AudioProcessor VAD config"
         );
-        Self {
+        self.offset_ratio = if speech_threshold > 0.0 {
+            (offset_threshold / speech_threshold).min(1.0)
+        } else {
+            1.0
+        };
+        self.min_samples = (target_sample_rate as f32 * min_duration_secs) as usize;
+        self.max_samples = (target_sample_rate as f32 * max_duration_secs) as usize;
+        // At most half a segment, so every cut still makes progress
+        self.overlap_samples = ms_to_samples(overlap_ms).min(self.max_samples / 2);
+        self.min_speech_samples = ms_to_samples(min_speech_ms);
+        self.silence_pause_chunks = silence_pause_chunks;
+        self.feature_gate = FeatureGate::new(
             target_sample_rate,
-            accumulated: Vec::new(),
-            min_samples: (target_sample_rate as f32 * min_duration_secs) as usize,
-            max_samples: (target_sample_rate as f32 * max_duration_secs) as usize,
-            speech_threshold,
-            silence_count: 0,
-            silence_pause_chunks,
-            has_speech: false,
-            pre_roll: Vec::new(),
+            max_zero_crossing_rate,
+            min_spectral_flatness,
+        );
+        if speaker_change_hints != self.speaker_detector.is_some() {
+            self.speaker_detector =
+                speaker_change_hints.then(|| SpeakerChangeDetector::new(target_sample_rate));
         }
+        self.pre_roll_samples = ms_to_samples(pre_roll_ms);
+        let excess = self.pre_roll.len().saturating_sub(self.pre_roll_samples);
+        self.pre_roll.drain(..excess);
+        self.config = config;
     }
 
-    /// Calculate RMS (Root Mean Square) energy of audio samples.
-    fn rms(samples: &[f32]) -> f32 {
-        if samples.is_empty() {
-            return 0.0;
-        }
-        let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
-        (sum_sq / samples.len() as f32).sqrt()
+    /// Replace the RMS energy VAD (e.g. with [`super::vad::SileroVad`]).
+    /// The offset threshold keeps its ratio to the new backend's threshold.
+    pub fn with_vad(mut self, vad: Box<dyn VadBackend>) -> Self {
+        info!(
+            backend = vad.name(),
+            threshold = vad.threshold(),
+            "AudioProcessor VAD backend"
+        );
+        self.vad = vad;
+        self
+    }
+
+    /// Select the resampler used to bring capture audio to 16 kHz.
+    pub fn with_resampler(mut self, quality: ResamplerQuality) -> Self {
+        self.resampler = Resampler::new(quality, self.target_sample_rate);
+        self
     }
 
-    /// Feed a raw audio chunk. Returns an AudioBuffer if enough speech
+    /// Cumulative cost of resampling this source's audio.
+    pub fn resample_stats(&self) -> ResamplerStats {
+        self.resampler.stats()
+    }
+
+    /// Cancel VoxVault's own playback from this source's audio. The
+    /// playback side feeds the `PlaybackReference` the canceller came from.
+    pub fn with_echo_cancellation(mut self, canceller: EchoCanceller) -> Self {
+        self.echo_canceller = Some(canceller);
+        self
+    }
+
+    /// Enable RNNoise noise suppression on incoming chunks, so fan noise and
+    /// keyboard clatter don't trigger the VAD or reach the transcript.
+    pub fn with_noise_suppression(mut self, enabled: bool) -> Self {
+        self.denoiser = enabled.then(NoiseSuppressor::new);
+        self
+    }
+
+    /// Remove DC offset and content below `cutoff_hz` (e.g. 80 Hz) before
+    /// VAD and transcription, so bias and rumble don't inflate RMS.
+    /// None disables the filter.
+    pub fn with_high_pass(mut self, cutoff_hz: Option<f32>) -> Self {
+        self.high_pass = cutoff_hz
+            .filter(|&hz| hz > 0.0)
+            .map(|hz| HighPassFilter::new(hz, self.target_sample_rate));
+        self
+    }
+
+    /// Feed a raw audio chunk. Returns a speech segment if enough speech
     /// audio has accumulated, or None if still waiting/silence.
-    pub fn feed(&mut self, chunk: AudioChunk) -> Option<AudioBuffer> {
-        let samples = if chunk.sample_rate != self.target_sample_rate {
-            let buffer = AudioBuffer::new(chunk.samples, chunk.sample_rate);
-            match resample_to_16k(&buffer) {
-                Ok(resampled) => resampled.samples,
-                Err(e) => {
-                    tracing::error!("Resampling failed: {}", e);
-                    return None;
-                }
+    pub fn feed(&mut self, mut chunk: AudioChunk) -> Option<SpeechSegment> {
+        // Denoise 48 kHz input natively (RNNoise's own rate); anything else
+        // is denoised after resampling to the target rate below. Echo
+        // cancellation needs the linear signal, so with AEC enabled the
+        // denoiser always runs after it.
+        let denoise_native = chunk.sample_rate == 48000 && self.echo_canceller.is_none();
+        if denoise_native {
+            if let Some(ref mut denoiser) = self.denoiser {
+                chunk.samples = denoiser.process(&chunk.samples, chunk.sample_rate);
             }
-        } else {
-            chunk.samples
-        };
+        }
+        // The canceller works at its own rate, before resampling
+        if let Some(ref mut aec) = self.echo_canceller {
+            chunk.samples = aec.process(&chunk.samples, chunk.sample_rate);
+            chunk.sample_rate = aec::SAMPLE_RATE;
+        }
+        if chunk.samples.is_empty() {
+            return None;
+        }
 
-        let energy = Self::rms(&samples);
-        let is_speech = energy >= self.speech_threshold;
+        let mut samples = self.resampler.process(&chunk.samples, chunk.sample_rate);
+        if samples.is_empty() {
+            return None;
+        }
 
-        if is_speech {
-            // Speech detected
-            if !self.has_speech && !self.pre_roll.is_empty() {
-                // Prepend pre-roll so we don't clip the start of speech
-                self.accumulated.extend_from_slice(&self.pre_roll);
-                self.pre_roll.clear();
+        if let Some(ref mut high_pass) = self.high_pass {
+            high_pass.process(&mut samples);
+        }
+
+        let samples = match self.denoiser {
+            Some(ref mut denoiser) if !denoise_native => {
+                denoiser.process(&samples, self.target_sample_rate)
             }
-            self.has_speech = true;
+            _ => samples,
+        };
+        self.stream_samples += samples.len() as u64;
+
+        self.speech_probability = self.vad.speech_probability(&samples);
+        // Hysteresis: ongoing speech only ends below the (lower) offset threshold
+        let threshold = if self.has_speech {
+            self.vad.threshold() * self.offset_ratio
+        } else {
+            self.vad.threshold()
+        };
+        let is_speech = self.speech_probability >= threshold
+            && self
+                .feature_gate
+                .as_mut()
+                .is_none_or(|gate| gate.accepts(&samples));
+        debug!(
+            vad = self.vad.name(),
+            probability = self.speech_probability,
+            is_speech,
+            "VAD"
+        );
+
+        if is_speech && self.has_speech {
             self.silence_count = 0;
+            if let Some(ref mut detector) = self.speaker_detector {
+                let position = self.stream_samples - samples.len() as u64;
+                if let Some(change) = detector.process(&samples, position) {
+                    self.speaker_changes.push(change);
+                }
+            }
             self.accumulated.extend_from_slice(&samples);
+            add_confidence(&mut self.confidence, self.speech_probability, samples.len());
+        } else if is_speech {
+            // Speech onset: hold it back until it lasts min_speech_samples
+            self.onset.extend_from_slice(&samples);
+            add_confidence(
+                &mut self.onset_confidence,
+                self.speech_probability,
+                samples.len(),
+            );
+            if self.onset.len() >= self.min_speech_samples {
+                info!(
+                    vad = self.vad.name(),
+                    probability = self.speech_probability,
+                    "Speech started"
+                );
+                // Prepend pre-roll so we don't clip the start of speech
+                if self.accumulated.is_empty() {
+                    let held = (self.pre_roll.len() + self.onset.len()) as u64;
+                    self.accumulated_start = self.stream_samples.saturating_sub(held);
+                    // A new segment: whoever speaks now is its first speaker
+                    if let Some(ref mut detector) = self.speaker_detector {
+                        detector.reset();
+                    }
+                }
+                self.accumulated.extend(self.pre_roll.drain(..));
+                self.accumulated.append(&mut self.onset);
+                let (sum, count) = std::mem::take(&mut self.onset_confidence);
+                self.confidence.0 += sum;
+                self.confidence.1 += count;
+                self.has_speech = true;
+                self.speaking = true;
+                self.silence_count = 0;
+            }
         } else {
             // Silence
             self.silence_count += 1;
+            if self.speaking && self.silence_count >= self.silence_pause_chunks.max(1) {
+                debug!("Speech ended");
+                self.speaking = false;
+            }
+            if !self.onset.is_empty() {
+                debug!(
+                    samples = self.onset.len(),
+                    "Ignoring speech shorter than min_speech_ms"
+                );
+                self.onset.clear();
+                self.onset_confidence = (0.0, 0);
+            }
 
             if self.has_speech {
                 // Still accumulating — include trailing silence for context
@@ -119,17 +546,20 @@ impl AudioProcessor {
                 if self.silence_count >= self.silence_pause_chunks
                     && self.accumulated.len() >= self.min_samples
                 {
-                    return Some(self.take_buffer());
+                    return Some(self.take_buffer(0));
                 }
             } else {
                 // Pure silence, no speech yet — just keep as pre-roll
-                self.pre_roll = samples;
+                self.pre_roll.extend(samples);
+                let excess = self.pre_roll.len().saturating_sub(self.pre_roll_samples);
+                self.pre_roll.drain(..excess);
             }
         }
 
-        // Hard limits: yield at max_samples regardless
+        // Hard limits: yield at max_samples regardless. The cut may fall
+        // mid-word, so the tail is carried into the next segment
         if self.accumulated.len() >= self.max_samples {
-            return Some(self.take_buffer());
+            return Some(self.take_buffer(self.overlap_samples));
         }
 
         // Yield at min_samples if we have speech and silence pause
@@ -137,53 +567,175 @@ impl AudioProcessor {
             && self.accumulated.len() >= self.min_samples
             && self.silence_count >= self.silence_pause_chunks
         {
-            return Some(self.take_buffer());
+            return Some(self.take_buffer(0));
         }
 
         None
     }
 
-    /// Force-flush any accumulated audio into a buffer (e.g., at session end).
-    pub fn flush(&mut self) -> Option<AudioBuffer> {
+    /// Force-flush any accumulated audio into a segment (e.g., at session end).
+    pub fn flush(&mut self) -> Option<SpeechSegment> {
         if self.accumulated.is_empty() || !self.has_speech {
             self.reset();
             return None;
         }
-        Some(self.take_buffer())
+        Some(self.take_buffer(0))
     }
 
-    /// Take accumulated samples and create an AudioBuffer, applying peak normalization.
-    fn take_buffer(&mut self) -> AudioBuffer {
+    /// Take accumulated samples and create a segment, applying peak
+    /// normalization. The last `overlap` samples are kept to start the
+    /// next accumulation (speech continues across the cut).
+    fn take_buffer(&mut self, overlap: usize) -> SpeechSegment {
         // Cap at max_samples to avoid excessive memory usage
         let take_len = self.accumulated.len().min(self.max_samples);
         let samples: Vec<f32> = self.accumulated.drain(..take_len).collect();
+        let start = self.accumulated_start;
+        let end = start + take_len as u64;
+        let leading = self.carried;
 
-        // Reset VAD state for next accumulation
-        self.has_speech = false;
+        let overlap = overlap.min(take_len);
+        self.accumulated
+            .splice(0..0, samples[take_len - overlap..].iter().copied());
+        self.accumulated_start = end - overlap as u64;
+        self.carried = overlap;
+
+        // Reset VAD state for next accumulation (unless speech carries over)
+        self.has_speech = overlap > 0;
         self.silence_count = 0;
         self.pre_roll.clear();
 
+        let (samples, trimmed_start, trimmed_end) = self.trim_silence(samples);
+        let (sum, count) = std::mem::take(&mut self.confidence);
+        let speech_confidence = if count > 0 {
+            (sum / count as f64) as f32
+        } else {
+            0.0
+        };
+        let (changes, later): (Vec<u64>, Vec<u64>) =
+            self.speaker_changes.drain(..).partition(|&c| c < end);
+        self.speaker_changes = later;
+
         let mut buffer = AudioBuffer::new(samples, self.target_sample_rate);
         // Critical for Q4 inference: quiet audio needs normalization
         buffer.peak_normalize(0.95);
 
+        let segment = SpeechSegment {
+            buffer,
+            start_ms: self.samples_to_ms(start + trimmed_start as u64),
+            end_ms: self.samples_to_ms(end - trimmed_end as u64),
+            overlap_ms: self.samples_to_ms(leading.saturating_sub(trimmed_start) as u64),
+            speaker_changes_ms: changes.into_iter().map(|c| self.samples_to_ms(c)).collect(),
+            speech_confidence,
+        };
         info!(
-            samples = buffer.samples.len(),
-            duration_secs = buffer.samples.len() as f32 / self.target_sample_rate as f32,
+            samples = segment.buffer.samples.len(),
+            duration_secs = segment.buffer.samples.len() as f32 / self.target_sample_rate as f32,
+            start_ms = segment.start_ms,
+            end_ms = segment.end_ms,
+            speaker_changes = segment.speaker_changes_ms.len(),
+            speech_confidence = segment.speech_confidence,
             "Audio buffer ready for transcription"
         );
 
-        buffer
+        segment
+    }
+
+    /// Cut leading and trailing silence (per `trim_margin_ms`). Returns the
+    /// remaining samples and how many were cut from the start and the end.
+    fn trim_silence(&self, mut samples: Vec<f32>) -> (Vec<f32>, usize, usize) {
+        let Some(margin_ms) = self.config.trim_margin_ms else {
+            return (samples, 0, 0);
+        };
+        let threshold = self
+            .config
+            .offset_threshold
+            .unwrap_or(self.config.speech_threshold);
+        let frame = (self.target_sample_rate as usize * TRIM_FRAME_MS) / 1000;
+        let loud =
+            |f: &[f32]| (f.iter().map(|s| s * s).sum::<f32>() / f.len() as f32).sqrt() >= threshold;
+
+        let frames = samples.chunks(frame);
+        let (Some(first), Some(last)) = (
+            frames.clone().position(loud),
+            frames.clone().rposition(loud),
+        ) else {
+            // Nothing above the RMS threshold (e.g. quiet speech caught by a
+            // neural VAD): keep the segment whole
+            return (samples, 0, 0);
+        };
+
+        let margin = (self.target_sample_rate as usize * margin_ms as usize) / 1000;
+        let keep_start = (first * frame).saturating_sub(margin);
+        let keep_end = ((last + 1) * frame + margin).min(samples.len());
+        let trimmed_end = samples.len() - keep_end;
+        samples.truncate(keep_end);
+        samples.drain(..keep_start);
+        if keep_start > 0 || trimmed_end > 0 {
+            debug!(
+                leading = keep_start,
+                trailing = trimmed_end,
+                "Trimmed silence from segment"
+            );
+        }
+        (samples, keep_start, trimmed_end)
+    }
+
+    fn samples_to_ms(&self, samples: u64) -> u64 {
+        samples * 1000 / self.target_sample_rate as u64
     }
 
-    /// Reset the processor, discarding any accumulated audio.
+    /// Reset the processor, discarding any accumulated audio. The stream
+    /// clock keeps running, so later segments stay aligned.
     pub fn reset(&mut self) {
+        if let Some(ref mut denoiser) = self.denoiser {
+            denoiser.reset();
+        }
+        self.resampler.reset();
+        if let Some(ref mut high_pass) = self.high_pass {
+            high_pass.reset();
+        }
+        if let Some(ref mut detector) = self.speaker_detector {
+            detector.reset();
+        }
+        self.speaker_changes.clear();
+        self.vad.reset();
+        self.speech_probability = 0.0;
+        self.onset.clear();
+        self.onset_confidence = (0.0, 0);
+        self.confidence = (0.0, 0);
         self.accumulated.clear();
+        self.carried = 0;
         self.has_speech = false;
+        self.speaking = false;
         self.silence_count = 0;
         self.pre_roll.clear();
     }
 
+    /// Whether speech is ongoing: true from speech onset until the
+    /// configured silence pause has elapsed.
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Speech probability (0.0–1.0) the VAD gave the last chunk.
+    pub fn speech_probability(&self) -> f32 {
+        self.speech_probability
+    }
+
+    /// Copy of the speech accumulated so far in the current segment, from
+    /// sample `from` on, normalized like a finished segment (None when no
+    /// speech is accumulating past `from`). For decoding a segment before
+    /// it's complete.
+    pub fn pending_speech(&self, from: usize) -> Option<AudioBuffer> {
+        if !self.has_speech || self.accumulated.len() <= from {
+            return None;
+        }
+        let samples = self.accumulated[from..].to_vec();
+        let mut buffer = AudioBuffer::new(samples, self.target_sample_rate);
+        buffer.peak_normalize(0.95);
+        Some(buffer)
+    }
+
     /// Get the number of currently accumulated samples.
     pub fn accumulated_samples(&self) -> usize {
         self.accumulated.len()
@@ -195,9 +747,14 @@ impl AudioProcessor {
     }
 }
 
+/// Add a chunk's speech probability, weighted by its length, to a sum.
+fn add_confidence(total: &mut (f64, usize), probability: f32, samples: usize) {
+    total.0 += probability as f64 * samples as f64;
+    total.1 += samples;
+}
+
 impl Default for AudioProcessor {
     fn default() -> Self {
-        // 3s min, 30s max, 1000ms silence pause, 500ms buffer, 0.005 threshold
-        Self::new(3.0, 30.0, 1000, 500, 0.005)
+        Self::from_config(ProcessorConfig::default())
     }
 }
@@ -0,0 +1,157 @@
+//! Raw audio recording to WAV alongside transcription.
+//!
+//! Each session gets its own file(s) named after the session start time,
+//! so a directory passed to `--record-to` accumulates one recording per
+//! session. In dual-capture mode every source label is written to its own
+//! file (`<session>-me.wav`, `<session>-them.wav`).
+
+use anyhow::{Context, Result};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::capture::AudioChunk;
+
+struct Track {
+    writer: WavWriter<BufWriter<File>>,
+    path: PathBuf,
+    sample_rate: u32,
+}
+
+/// Writes captured chunks to 32-bit float mono WAV files, one set per session.
+pub struct WavRecorder {
+    dir: PathBuf,
+    session_id: String,
+    tracks: HashMap<Option<String>, Track>,
+}
+
+impl WavRecorder {
+    /// Create a recorder writing into `dir` (created if missing).
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create recording directory {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            session_id: new_session_id(),
+            tracks: HashMap::new(),
+        })
+    }
+
+    /// Append a chunk to its source's file, opening the file on first use.
+    ///
+    /// If the sample rate changes (e.g. after reconnecting to a different
+    /// device), the current file is finalized and a new one is started.
+    pub fn write(&mut self, chunk: &AudioChunk) -> Result<()> {
+        if let Some(track) = self.tracks.get(&chunk.label) {
+            if track.sample_rate != chunk.sample_rate {
+                warn!(
+                    from = track.sample_rate,
+                    to = chunk.sample_rate,
+                    "Sample rate changed; starting a new recording file"
+                );
+                self.close_track(&chunk.label.clone())?;
+            }
+        }
+
+        if !self.tracks.contains_key(&chunk.label) {
+            let track = self.open_track(chunk.label.as_deref(), chunk.sample_rate)?;
+            self.tracks.insert(chunk.label.clone(), track);
+        }
+
+        let track = self
+            .tracks
+            .get_mut(&chunk.label)
+            .expect("track just opened");
+        for &sample in &chunk.samples {
+            track
+                .writer
+                .write_sample(sample)
+                .context("Failed to write WAV sample")?;
+        }
+        Ok(())
+    }
+
+    /// Finalize the current files and start a new session.
+    pub fn rotate(&mut self) -> Result<()> {
+        self.finalize()?;
+        self.session_id = new_session_id();
+        Ok(())
+    }
+
+    /// Finalize all open files (writes WAV headers).
+    pub fn finalize(&mut self) -> Result<()> {
+        let labels: Vec<Option<String>> = self.tracks.keys().cloned().collect();
+        for label in labels {
+            self.close_track(&label)?;
+        }
+        Ok(())
+    }
+
+    /// Paths of the files currently being written.
+    pub fn paths(&self) -> Vec<&Path> {
+        self.tracks.values().map(|t| t.path.as_path()).collect()
+    }
+
+    fn open_track(&self, label: Option<&str>, sample_rate: u32) -> Result<Track> {
+        let stem = match label {
+            Some(label) => format!("{}-{}", self.session_id, label),
+            None => self.session_id.clone(),
+        };
+        let path = unique_path(&self.dir, &stem);
+
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let writer = WavWriter::create(&path, spec)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+
+        info!(path = %path.display(), sample_rate, "Recording audio");
+        Ok(Track {
+            writer,
+            path,
+            sample_rate,
+        })
+    }
+
+    fn close_track(&mut self, label: &Option<String>) -> Result<()> {
+        if let Some(track) = self.tracks.remove(label) {
+            track
+                .writer
+                .finalize()
+                .with_context(|| format!("Failed to finalize {}", track.path.display()))?;
+            info!(path = %track.path.display(), "Recording saved");
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WavRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.finalize() {
+            tracing::error!("Failed to finalize recording: {:#}", e);
+        }
+    }
+}
+
+fn new_session_id() -> String {
+    format!("session-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+/// `dir/stem.wav`, or `dir/stem-N.wav` if that file already exists.
+fn unique_path(dir: &Path, stem: &str) -> PathBuf {
+    let path = dir.join(format!("{}.wav", stem));
+    if !path.exists() {
+        return path;
+    }
+    (2..)
+        .map(|n| dir.join(format!("{}-{}.wav", stem, n)))
+        .find(|p| !p.exists())
+        .expect("unbounded range")
+}
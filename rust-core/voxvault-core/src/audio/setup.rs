@@ -633,16 +633,57 @@ mod macos {
 #[cfg(target_os = "macos")]
 pub use macos::*;
 
+/// Fallback for platforms without CoreAudio aggregate devices.
+///
+/// Mirrors the macOS API so callers compile everywhere; setup reports a
+/// single error and capture goes straight to the selected cpal device.
 #[cfg(not(target_os = "macos"))]
-pub fn setup_audio_devices() -> serde_json::Value {
-    serde_json::json!({
-        "error": "Audio device setup is only supported on macOS"
-    })
+mod fallback {
+    #[derive(Debug, Clone, serde::Serialize)]
+    pub struct AudioDeviceInfo {
+        pub id: u32,
+        pub uid: String,
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, serde::Serialize)]
+    pub struct SetupResult {
+        pub capture_device: Option<String>,
+        pub mic_device: Option<String>,
+        pub multi_output_device: Option<String>,
+        pub blackhole_2ch_found: bool,
+        pub blackhole_16ch_found: bool,
+        pub errors: Vec<String>,
+    }
+
+    #[derive(Debug, Clone, serde::Serialize)]
+    pub struct TeardownResult {
+        pub devices_destroyed: usize,
+        pub errors: Vec<String>,
+    }
+
+    pub fn setup_audio_devices() -> SetupResult {
+        SetupResult {
+            capture_device: None,
+            mic_device: None,
+            multi_output_device: None,
+            blackhole_2ch_found: false,
+            blackhole_16ch_found: false,
+            errors: vec!["Audio device setup is only supported on macOS".to_string()],
+        }
+    }
+
+    pub fn teardown_audio_devices() -> TeardownResult {
+        TeardownResult {
+            devices_destroyed: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn list_devices() -> Vec<AudioDeviceInfo> {
+        Vec::new()
+    }
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn teardown_audio_devices() -> serde_json::Value {
-    serde_json::json!({
-        "error": "Audio device teardown is only supported on macOS"
-    })
-}
+pub use fallback::*;
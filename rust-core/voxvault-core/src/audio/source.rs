@@ -0,0 +1,62 @@
+use anyhow::Result;
+
+use super::capture::{AudioCapture, DualCapture};
+use super::metrics::CaptureStats;
+
+/// Anything that produces AudioChunks into the processing pipeline.
+///
+/// Live devices ([`AudioCapture`], [`DualCapture`]) and recordings
+/// ([`super::file::FileSource`]) share this interface so the same
+/// processor/engine loop can run on either.
+pub trait AudioSource {
+    /// Start producing chunks of roughly `buffer_duration_ms` each.
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()>;
+
+    /// Stop producing chunks.
+    fn stop(&mut self);
+
+    /// Periodic housekeeping (device hot-plug checks). Called by the owner
+    /// about once per second; no-op for sources that don't need it.
+    fn monitor(&mut self) {}
+
+    /// Health metrics of each live stream; empty for non-device sources.
+    fn stats(&self) -> Vec<CaptureStats> {
+        Vec::new()
+    }
+}
+
+impl AudioSource for AudioCapture {
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        AudioCapture::start(self, buffer_duration_ms)
+    }
+
+    fn stop(&mut self) {
+        AudioCapture::stop(self)
+    }
+
+    fn monitor(&mut self) {
+        AudioCapture::monitor(self)
+    }
+
+    fn stats(&self) -> Vec<CaptureStats> {
+        vec![AudioCapture::stats(self)]
+    }
+}
+
+impl AudioSource for DualCapture {
+    fn start(&mut self, buffer_duration_ms: u32) -> Result<()> {
+        DualCapture::start(self, buffer_duration_ms)
+    }
+
+    fn stop(&mut self) {
+        DualCapture::stop(self)
+    }
+
+    fn monitor(&mut self) {
+        DualCapture::monitor(self)
+    }
+
+    fn stats(&self) -> Vec<CaptureStats> {
+        DualCapture::stats(self)
+    }
+}
@@ -0,0 +1,125 @@
+//! Speaker-change hints from pitch discontinuities.
+//!
+//! Within one accumulated segment, a different speaker taking over usually
+//! shows up as a sustained jump in fundamental frequency (F0). The detector
+//! estimates each speech chunk's median F0 by autocorrelation and tracks
+//! the current speaker's running pitch; a shift that persists over
+//! consecutive chunks is flagged. It is a cheap hint, not diarization: one
+//! speaker changing register can trigger it, and similar voices won't.
+
+/// Analysis frame for pitch estimation (40 ms at 16 kHz).
+const FRAME_MS: usize = 40;
+/// Pitch range searched, in Hz (covers adult and children's voices).
+const PITCH_RANGE_HZ: (f32, f32) = (70.0, 400.0);
+/// Normalized autocorrelation a frame needs to count as voiced.
+const VOICING_THRESHOLD: f32 = 0.5;
+/// Frames quieter than this RMS are not analyzed.
+const MIN_FRAME_RMS: f32 = 0.002;
+/// Voiced frames a chunk needs for a pitch estimate.
+const MIN_VOICED_FRAMES: usize = 3;
+/// Pitch shift (ratio) considered a discontinuity, ~4 semitones.
+const CHANGE_RATIO: f32 = 1.26;
+/// Consecutive shifted chunks needed before a change is flagged.
+const CONFIRM_CHUNKS: usize = 2;
+/// Weight of each new chunk in the running pitch of the current speaker.
+const PROFILE_ALPHA: f32 = 0.2;
+
+/// Flags likely speaker changes in a stream of speech chunks.
+pub struct SpeakerChangeDetector {
+    sample_rate: u32,
+    /// Running pitch (Hz) of the current speaker.
+    profile_hz: Option<f32>,
+    /// Consecutive chunks shifted from the profile, and the pitch and
+    /// stream position of the first one.
+    shifted: Option<(usize, f32, u64)>,
+}
+
+impl SpeakerChangeDetector {
+    /// Create a detector for audio at `sample_rate`.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            profile_hz: None,
+            shifted: None,
+        }
+    }
+
+    /// Analyze a speech chunk starting at stream position `position`.
+    /// Returns the position where a new speaker likely started, once the
+    /// shift is confirmed.
+    pub fn process(&mut self, samples: &[f32], position: u64) -> Option<u64> {
+        let pitch = self.median_pitch(samples)?;
+        let Some(profile) = self.profile_hz else {
+            self.profile_hz = Some(pitch);
+            return None;
+        };
+
+        let ratio = (pitch / profile).max(profile / pitch);
+        if ratio < CHANGE_RATIO {
+            self.shifted = None;
+            self.profile_hz = Some(profile + PROFILE_ALPHA * (pitch - profile));
+            return None;
+        }
+
+        let (count, first_pitch, first_position) = match self.shifted {
+            Some((count, first_pitch, first_position)) => (count + 1, first_pitch, first_position),
+            None => (1, pitch, position),
+        };
+        if count < CONFIRM_CHUNKS {
+            self.shifted = Some((count, first_pitch, first_position));
+            return None;
+        }
+
+        tracing::debug!(
+            from_hz = profile,
+            to_hz = pitch,
+            position = first_position,
+            "Likely speaker change"
+        );
+        self.profile_hz = Some((first_pitch + pitch) / 2.0);
+        self.shifted = None;
+        Some(first_position)
+    }
+
+    /// Forget the current speaker (e.g. at the start of a new segment).
+    pub fn reset(&mut self) {
+        self.profile_hz = None;
+        self.shifted = None;
+    }
+
+    /// Median F0 of the voiced frames of `samples`, if enough are voiced.
+    fn median_pitch(&self, samples: &[f32]) -> Option<f32> {
+        let frame = self.sample_rate as usize * FRAME_MS / 1000;
+        let mut pitches: Vec<f32> = samples
+            .chunks_exact(frame)
+            .filter_map(|f| self.frame_pitch(f))
+            .collect();
+        if pitches.len() < MIN_VOICED_FRAMES {
+            return None;
+        }
+        pitches.sort_by(|a, b| a.total_cmp(b));
+        Some(pitches[pitches.len() / 2])
+    }
+
+    /// F0 of one frame by normalized autocorrelation, if it is voiced.
+    fn frame_pitch(&self, frame: &[f32]) -> Option<f32> {
+        let energy: f32 = frame.iter().map(|s| s * s).sum();
+        if (energy / frame.len() as f32).sqrt() < MIN_FRAME_RMS {
+            return None;
+        }
+        let min_lag = (self.sample_rate as f32 / PITCH_RANGE_HZ.1) as usize;
+        let max_lag = ((self.sample_rate as f32 / PITCH_RANGE_HZ.0) as usize).min(frame.len() / 2);
+
+        let (lag, score) = (min_lag..=max_lag)
+            .map(|lag| {
+                let corr: f32 = frame[..frame.len() - lag]
+                    .iter()
+                    .zip(&frame[lag..])
+                    .map(|(a, b)| a * b)
+                    .sum();
+                (lag, corr / energy)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        (score >= VOICING_THRESHOLD).then(|| self.sample_rate as f32 / lag as f32)
+    }
+}
@@ -0,0 +1,454 @@
+//! Voice activity detection backends for [`super::processor::AudioProcessor`].
+//!
+//! - [`EnergyVad`]: RMS energy against a fixed threshold, or one that
+//!   follows the room's noise floor. No model, but triggers on steady
+//!   background hum and misses whispering.
+//! - [`WebRtcVad`]: the WebRTC GMM detector. Cheap and model-free, much
+//!   less hum-sensitive than RMS. Needs the `webrtc-vad` feature.
+//! - [`SileroVad`]: the Silero neural VAD (ONNX), robust to both. Needs the
+//!   `silero-vad` feature and the model file (`silero_vad.onnx`, v5).
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::debug;
+
+/// Noise-floor time constant while the floor is falling (noise went away).
+const NOISE_FLOOR_FALL_SECS: f32 = 1.0;
+/// Noise-floor time constant while rising on non-speech chunks.
+const NOISE_FLOOR_RISE_SECS: f32 = 5.0;
+/// Noise-floor time constant on speech chunks, so a noise source that
+/// starts (and would otherwise count as endless speech) is absorbed.
+const NOISE_FLOOR_SPEECH_SECS: f32 = 30.0;
+
+/// Decides whether a chunk of 16 kHz mono audio contains speech.
+pub trait VadBackend: Send {
+    /// Short name for logs ("energy", "silero").
+    fn name(&self) -> &'static str;
+
+    /// Probability (0.0–1.0) that `samples` contain speech.
+    fn speech_probability(&mut self, samples: &[f32]) -> f32;
+
+    /// Probability at or above which audio counts as speech.
+    fn threshold(&self) -> f32;
+
+    /// Apply a new RMS `speech_threshold` from the processor config.
+    /// Backends not driven by an RMS threshold ignore it.
+    fn set_speech_threshold(&mut self, _speech_threshold: f32) {}
+
+    /// Forget stream state (e.g. when the processor is reset).
+    fn reset(&mut self) {}
+}
+
+/// Which VAD backend to build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VadKind {
+    /// RMS energy threshold.
+    #[default]
+    Energy,
+    /// WebRTC GMM VAD.
+    WebRtc,
+    /// Silero neural VAD.
+    Silero,
+}
+
+impl FromStr for VadKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "energy" | "rms" => Ok(Self::Energy),
+            "webrtc" => Ok(Self::WebRtc),
+            "silero" => Ok(Self::Silero),
+            other => bail!(
+                "Unknown VAD '{}': expected 'energy', 'webrtc' or 'silero'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for VadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Energy => write!(f, "energy"),
+            Self::WebRtc => write!(f, "webrtc"),
+            Self::Silero => write!(f, "silero"),
+        }
+    }
+}
+
+/// RMS energy VAD. Its "probability" is the chunk's RMS relative to the
+/// threshold, scaled so the threshold maps to 0.5.
+pub struct EnergyVad {
+    rms_threshold: f32,
+    /// Noise-floor tracking; None for a fixed threshold.
+    adaptive: Option<NoiseFloor>,
+}
+
+/// Running estimate of the ambient RMS level.
+struct NoiseFloor {
+    floor: f32,
+    margin: f32,
+}
+
+impl EnergyVad {
+    /// Treat chunks with RMS at or above `rms_threshold` (e.g. 0.005) as speech.
+    pub fn new(rms_threshold: f32) -> Self {
+        Self {
+            rms_threshold,
+            adaptive: None,
+        }
+    }
+
+    /// Track the noise floor from non-speech chunks and use
+    /// `noise_floor + margin` as the threshold, starting at
+    /// `initial_threshold`.
+    pub fn adaptive(initial_threshold: f32, margin: f32) -> Self {
+        Self {
+            rms_threshold: initial_threshold,
+            adaptive: Some(NoiseFloor {
+                floor: (initial_threshold - margin).max(0.0),
+                margin,
+            }),
+        }
+    }
+
+    /// RMS level currently separating speech from silence.
+    pub fn current_threshold(&self) -> f32 {
+        self.rms_threshold
+    }
+
+    /// Move the noise floor towards `rms` and re-derive the threshold.
+    fn adapt(&mut self, rms: f32, is_speech: bool, duration_secs: f32) {
+        let Some(ref mut noise) = self.adaptive else {
+            return;
+        };
+        let time_constant = if is_speech {
+            NOISE_FLOOR_SPEECH_SECS
+        } else if rms < noise.floor {
+            NOISE_FLOOR_FALL_SECS
+        } else {
+            NOISE_FLOOR_RISE_SECS
+        };
+        let alpha = 1.0 - (-duration_secs / time_constant).exp();
+        noise.floor += alpha * (rms - noise.floor);
+        self.rms_threshold = noise.floor + noise.margin;
+        debug!(
+            noise_floor = noise.floor,
+            threshold = self.rms_threshold,
+            "VAD noise floor"
+        );
+    }
+}
+
+impl VadBackend for EnergyVad {
+    fn name(&self) -> &'static str {
+        "energy"
+    }
+
+    fn speech_probability(&mut self, samples: &[f32]) -> f32 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let rms = (samples.iter().map(|&s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        let probability = if self.rms_threshold <= 0.0 {
+            1.0
+        } else {
+            (0.5 * rms / self.rms_threshold).min(1.0)
+        };
+        // Samples are at 16 kHz (see VadBackend)
+        self.adapt(rms, probability >= 0.5, samples.len() as f32 / 16000.0);
+        probability
+    }
+
+    fn threshold(&self) -> f32 {
+        0.5
+    }
+
+    fn set_speech_threshold(&mut self, speech_threshold: f32) {
+        self.rms_threshold = speech_threshold;
+        // An adaptive threshold restarts tracking from the new value
+        if let Some(ref mut noise) = self.adaptive {
+            noise.floor = (speech_threshold - noise.margin).max(0.0);
+        }
+    }
+}
+
+/// WebRTC VAD (libfvad) at one of its four aggressiveness modes.
+///
+/// Classifies 30 ms frames; a chunk's probability is the fraction of its
+/// frames classified as voiced, and samples left over are carried into
+/// the next chunk.
+pub struct WebRtcVad {
+    #[cfg(feature = "webrtc-vad")]
+    inner: webrtc::Detector,
+    threshold: f32,
+}
+
+impl WebRtcVad {
+    /// Create a detector. `aggressiveness` ranges from 0 (quality: flags
+    /// the most audio as speech) to 3 (very aggressive); `threshold` is the
+    /// fraction of voiced frames at which a chunk counts as speech.
+    #[cfg(feature = "webrtc-vad")]
+    pub fn new(aggressiveness: u8, threshold: f32) -> Result<Self> {
+        if aggressiveness > 3 {
+            bail!(
+                "WebRTC VAD aggressiveness must be 0–3, got {}",
+                aggressiveness
+            );
+        }
+        Ok(Self {
+            inner: webrtc::Detector::new(aggressiveness),
+            threshold,
+        })
+    }
+
+    #[cfg(not(feature = "webrtc-vad"))]
+    pub fn new(_aggressiveness: u8, _threshold: f32) -> Result<Self> {
+        bail!("WebRTC VAD is not available: rebuild with `--features webrtc-vad`")
+    }
+}
+
+impl VadBackend for WebRtcVad {
+    fn name(&self) -> &'static str {
+        "webrtc"
+    }
+
+    #[cfg(feature = "webrtc-vad")]
+    fn speech_probability(&mut self, samples: &[f32]) -> f32 {
+        self.inner.process(samples)
+    }
+
+    #[cfg(not(feature = "webrtc-vad"))]
+    fn speech_probability(&mut self, _samples: &[f32]) -> f32 {
+        0.0
+    }
+
+    fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    fn reset(&mut self) {
+        #[cfg(feature = "webrtc-vad")]
+        self.inner.reset();
+    }
+}
+
+/// Silero VAD v5 running on ONNX Runtime.
+///
+/// The model scores 32 ms windows (512 samples at 16 kHz); a chunk's
+/// probability is the highest of its windows, and samples left over are
+/// carried into the next chunk.
+pub struct SileroVad {
+    #[cfg(feature = "silero-vad")]
+    inner: silero::Model,
+    threshold: f32,
+}
+
+impl SileroVad {
+    /// Load the Silero ONNX model. `threshold` is the speech probability
+    /// cut-off (Silero's default is 0.5).
+    #[cfg(feature = "silero-vad")]
+    pub fn new(model_path: &Path, threshold: f32) -> Result<Self> {
+        Ok(Self {
+            inner: silero::Model::load(model_path)?,
+            threshold,
+        })
+    }
+
+    #[cfg(not(feature = "silero-vad"))]
+    pub fn new(_model_path: &Path, _threshold: f32) -> Result<Self> {
+        bail!("Silero VAD is not available: rebuild with `--features silero-vad`")
+    }
+}
+
+impl VadBackend for SileroVad {
+    fn name(&self) -> &'static str {
+        "silero"
+    }
+
+    #[cfg(feature = "silero-vad")]
+    fn speech_probability(&mut self, samples: &[f32]) -> f32 {
+        match self.inner.process(samples) {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!("Silero VAD inference failed: {:#}", e);
+                0.0
+            }
+        }
+    }
+
+    #[cfg(not(feature = "silero-vad"))]
+    fn speech_probability(&mut self, _samples: &[f32]) -> f32 {
+        0.0
+    }
+
+    fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    fn reset(&mut self) {
+        #[cfg(feature = "silero-vad")]
+        self.inner.reset();
+    }
+}
+
+#[cfg(feature = "silero-vad")]
+mod silero {
+    use anyhow::{Context, Result};
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+
+    /// Samples scored per inference at 16 kHz.
+    const WINDOW: usize = 512;
+    /// Trailing samples of the previous window prepended to each input (v5).
+    const CONTEXT: usize = 64;
+    /// Shape of the recurrent state tensor.
+    const STATE_SHAPE: [usize; 3] = [2, 1, 128];
+    const STATE_LEN: usize = 2 * 128;
+
+    pub(super) struct Model {
+        session: Session,
+        state: Vec<f32>,
+        context: Vec<f32>,
+        /// Samples not yet filling a whole window.
+        pending: Vec<f32>,
+        last_probability: f32,
+    }
+
+    impl Model {
+        pub(super) fn load(path: &Path) -> Result<Self> {
+            let session = Session::builder()
+                .and_then(|b| b.commit_from_file(path))
+                .with_context(|| format!("Failed to load Silero VAD model {}", path.display()))?;
+            Ok(Self {
+                session,
+                state: vec![0.0; STATE_LEN],
+                context: vec![0.0; CONTEXT],
+                pending: Vec::new(),
+                last_probability: 0.0,
+            })
+        }
+
+        pub(super) fn process(&mut self, samples: &[f32]) -> Result<f32> {
+            self.pending.extend_from_slice(samples);
+            let mut max: Option<f32> = None;
+            let mut start = 0;
+            while start + WINDOW <= self.pending.len() {
+                let mut input = Vec::with_capacity(CONTEXT + WINDOW);
+                input.extend_from_slice(&self.context);
+                input.extend_from_slice(&self.pending[start..start + WINDOW]);
+                let p = self.infer(input)?;
+                max = Some(max.map_or(p, |m| m.max(p)));
+                start += WINDOW;
+            }
+            self.pending.drain(..start);
+
+            if let Some(p) = max {
+                self.last_probability = p;
+            }
+            Ok(self.last_probability)
+        }
+
+        /// Score one window (with context), updating the recurrent state.
+        fn infer(&mut self, input: Vec<f32>) -> Result<f32> {
+            self.context
+                .copy_from_slice(&input[input.len() - CONTEXT..]);
+
+            let len = input.len();
+            let inputs = ort::inputs! {
+                "input" => Tensor::from_array(([1, len], input))?,
+                "state" => Tensor::from_array((STATE_SHAPE, self.state.clone()))?,
+                "sr" => Tensor::from_array((Vec::<i64>::new(), vec![16000i64]))?,
+            };
+            let outputs = self.session.run(inputs)?;
+            let (_, probability) = outputs["output"].try_extract_tensor::<f32>()?;
+            let probability = probability.first().copied().unwrap_or(0.0);
+            let (_, state) = outputs["stateN"].try_extract_tensor::<f32>()?;
+            self.state.copy_from_slice(state);
+            Ok(probability)
+        }
+
+        pub(super) fn reset(&mut self) {
+            self.state.iter_mut().for_each(|s| *s = 0.0);
+            self.context.iter_mut().for_each(|s| *s = 0.0);
+            self.pending.clear();
+            self.last_probability = 0.0;
+        }
+    }
+}
+
+#[cfg(feature = "webrtc-vad")]
+mod webrtc {
+    use webrtc_vad::{SampleRate, Vad, VadMode};
+
+    /// 30 ms at 16 kHz, the longest frame libfvad accepts.
+    const FRAME: usize = 480;
+
+    pub(super) struct Detector {
+        vad: Vad,
+        mode: u8,
+        /// Samples not yet filling a whole frame.
+        pending: Vec<i16>,
+        last_probability: f32,
+    }
+
+    // SAFETY: the libfvad handle is owned exclusively by this detector and
+    // only used through `&mut self`; libfvad keeps no thread-local state.
+    unsafe impl Send for Detector {}
+
+    impl Detector {
+        pub(super) fn new(mode: u8) -> Self {
+            Self {
+                vad: Self::build(mode),
+                mode,
+                pending: Vec::new(),
+                last_probability: 0.0,
+            }
+        }
+
+        fn build(mode: u8) -> Vad {
+            let mode = match mode {
+                0 => VadMode::Quality,
+                1 => VadMode::LowBitrate,
+                2 => VadMode::Aggressive,
+                _ => VadMode::VeryAggressive,
+            };
+            Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, mode)
+        }
+
+        pub(super) fn process(&mut self, samples: &[f32]) -> f32 {
+            self.pending.extend(
+                samples
+                    .iter()
+                    .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+            );
+
+            let (mut frames, mut voiced) = (0usize, 0usize);
+            let mut start = 0;
+            while start + FRAME <= self.pending.len() {
+                let frame = &self.pending[start..start + FRAME];
+                if self.vad.is_voice_segment(frame).unwrap_or(false) {
+                    voiced += 1;
+                }
+                frames += 1;
+                start += FRAME;
+            }
+            self.pending.drain(..start);
+
+            if frames > 0 {
+                self.last_probability = voiced as f32 / frames as f32;
+            }
+            self.last_probability
+        }
+
+        pub(super) fn reset(&mut self) {
+            self.vad = Self::build(self.mode);
+            self.pending.clear();
+            self.last_probability = 0.0;
+        }
+    }
+}
@@ -1,27 +1,187 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use burn::backend::wgpu::WgpuDevice;
+use clap::{Parser, Subcommand};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
 use std::path::PathBuf;
-use tokio::sync::mpsc;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tracing::info;
 
-use voxvault_core::audio::capture::AudioCapture;
-use voxvault_core::audio::processor::AudioProcessor;
+use voxtral_mini_realtime::audio::AudioBuffer;
+use voxvault_core::audio::aec::{AecConfig, PlaybackReference};
+use voxvault_core::audio::agc::{AgcConfig, AutomaticGainControl};
+use voxvault_core::audio::backlog::BacklogConfig;
+use voxvault_core::audio::capture::{
+    AudioCapture, AudioChunk, CaptureSource, ChannelSelection, DualCapture, StreamBufferSize,
+};
+use voxvault_core::audio::file::{FileSource, Pacing};
+use voxvault_core::audio::mixer::{MixInput, MixedCapture};
+use voxvault_core::audio::net::{NetConfig, NetSource, PacketFormat};
+use voxvault_core::audio::process_tap::{self, ProcessTap};
+use voxvault_core::audio::processor::{AudioProcessor, ProcessorConfig, SpeechSegment};
+use voxvault_core::audio::recorder::WavRecorder;
+use voxvault_core::audio::resample::ResamplerQuality;
 use voxvault_core::audio::setup;
-use voxvault_core::server::websocket::{TranscriptMessage, TranscriptServer};
-use voxvault_core::voxtral::engine::VoxtralEngine;
+use voxvault_core::audio::source::AudioSource;
+use voxvault_core::audio::vad::{EnergyVad, SileroVad, VadBackend, VadKind, WebRtcVad};
+use voxvault_core::server::websocket::{
+    ControlMessage, ErrorCode, HeartbeatConfig, PipelineState, TranscribeRequest,
+    TranscriptMessage, TranscriptServer, DEFAULT_PING_INTERVAL, DEFAULT_PONG_TIMEOUT,
+    DEFAULT_REPLAY_TRANSCRIPTS,
+};
+use voxvault_core::voxtral::cancel::{CancelToken, Cancelled};
+use voxvault_core::voxtral::engine::{
+    EngineConfig, LatencyPreset, ModelFiles, NamedModel, TranscribeOptions, VoxtralEngine,
+    DEFAULT_MAX_MEL_FRAMES,
+};
+use voxvault_core::voxtral::gpu::{self, AdapterSelector};
+use voxvault_core::voxtral::hallucination::HallucinationAction;
+use voxvault_core::voxtral::hotwords::Hotwords;
+use voxvault_core::voxtral::itn;
+use voxvault_core::voxtral::language::LanguageHint;
+use voxvault_core::voxtral::models::{self, ModelSource};
+use voxvault_core::voxtral::profanity::{ProfanityAction, ProfanityFilter};
+use voxvault_core::voxtral::punctuation;
+use voxvault_core::voxtral::stitch::stitch_overlap;
+use voxvault_core::voxtral::transcriber::{EngineKind, TranscriptionEngine};
+use voxvault_core::voxtral::translate::{HttpTranslator, TranslationService, Translator};
+use voxvault_core::voxtral::types::TranscriptResult;
+use voxvault_core::voxtral::whisper::WhisperEngine;
+
+/// Longest wait for WebSocket clients to be closed on shutdown.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+/// Chunk size of the echo reference capture: short, so playback reaches
+/// the canceller before its echo does.
+const ECHO_REFERENCE_BUFFER_MS: u32 = 20;
+/// Speech (16 kHz samples) an incremental decode covers before the text
+/// of its start is settled and the window moves on, so each step costs
+/// the same however long the segment runs.
+const INCREMENTAL_WINDOW_SAMPLES: usize = 8 * 16000;
+/// Words ending this close to the window's end are still decoded again,
+/// since the audio after them can change them.
+const INCREMENTAL_HOLDBACK_MS: u64 = 2000;
+/// Settled audio decoded ahead of the window for continuity, its text
+/// dropped.
+const INCREMENTAL_CONTEXT_SAMPLES: usize = 16000;
 
 #[derive(Parser)]
 #[command(name = "voxvault-cli")]
 #[command(about = "VoxVault CLI — real-time audio transcription via Voxtral")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// List available audio input devices and exit.
     #[arg(long)]
     list_devices: bool,
 
+    /// List the GPU adapters the model can run on and exit.
+    #[arg(long)]
+    list_gpus: bool,
+
     /// Audio input device name (e.g., "BlackHole 2ch").
     #[arg(short, long, default_value = "BlackHole 2ch")]
     device: String,
 
+    /// Capture what an output device is playing (WASAPI loopback, Windows only).
+    /// `--device` then names an output device; pass "" for the default output.
+    #[arg(long)]
+    loopback: bool,
+
+    /// Capture only this app's audio instead of `--device`, by bundle ID
+    /// (e.g. "us.zoom.xos", "com.microsoft.teams2"). Repeatable. Uses
+    /// CoreAudio process taps; macOS 14.4+ only.
+    #[arg(long, value_name = "BUNDLE_ID", conflicts_with = "loopback")]
+    app: Vec<String>,
+
+    /// Also capture this microphone and transcribe it separately, attributing
+    /// its transcripts to "me" and the `--device` audio to "them".
+    #[arg(long)]
+    mic_device: Option<String>,
+
+    /// Mix another device into `--device` for a single transcript, as
+    /// `NAME` or `NAME=GAIN` (e.g. "MacBook Pro Microphone=0.7"). Repeatable.
+    #[arg(long, value_name = "NAME[=GAIN]", conflicts_with = "mic_device")]
+    mix_device: Vec<MixInput>,
+
+    /// Channels of `--device` to transcribe: "mix" (default), "left", "right",
+    /// 1-based channel numbers such as "1" or "3,4", or "split" to transcribe
+    /// each channel separately (labelled "ch1", "ch2", ...).
+    #[arg(long, default_value = "mix")]
+    channels: ChannelSelection,
+
+    /// Channels of `--mic-device` to transcribe (same format as `--channels`).
+    #[arg(long, default_value = "mix")]
+    mic_channels: ChannelSelection,
+
+    /// Device to use if `--device` is missing at startup or unplugged
+    /// mid-session (repeatable; tried in order, e.g. `--fallback-device
+    /// "BlackHole 2ch" --fallback-device "MacBook Pro Microphone"`).
+    #[arg(long)]
+    fallback_device: Vec<String>,
+
+    /// Directory to save the session's raw audio to (one WAV per session).
+    #[arg(long, value_name = "PATH")]
+    record_to: Option<PathBuf>,
+
+    /// Transcribe an audio file (WAV/FLAC/MP3/OGG) instead of a live device.
+    /// The CLI exits once the whole file has been transcribed.
+    #[arg(long, value_name = "PATH")]
+    input_file: Option<PathBuf>,
+
+    /// Feed `--input-file` as fast as the engine can take it instead of in real time.
+    #[arg(long)]
+    max_speed: bool,
+
+    /// Transcribe audio streamed to this UDP address (e.g. "0.0.0.0:5004")
+    /// by another machine instead of a local device.
+    #[arg(long, value_name = "ADDR", conflicts_with = "input_file")]
+    listen_udp: Option<SocketAddr>,
+
+    /// Transcribe audio streamed by WebSocket clients (binary messages, one
+    /// Opus packet each, e.g. from a browser microphone) instead of a local
+    /// device. Each connection is transcribed separately. Clients on
+    /// `/ingest` may also send PCM (`?format=s16le&sample_rate=48000&channels=2`)
+    /// and get their own transcripts back on the same socket. Opus needs the
+    /// `opus` feature.
+    #[arg(long, conflicts_with_all = ["input_file", "listen_udp"])]
+    ws_audio: bool,
+
+    /// Packet format of `--listen-udp`: "s16le" (raw PCM), "f32le" or "rtp" (L16).
+    #[arg(long, default_value = "s16le")]
+    net_format: PacketFormat,
+
+    /// Sample rate of the `--listen-udp` stream.
+    #[arg(long, default_value_t = 16000)]
+    net_sample_rate: u32,
+
+    /// Interleaved channels of the `--listen-udp` stream (downmixed to mono).
+    #[arg(long, default_value_t = 1)]
+    net_channels: usize,
+
+    /// Seconds between capture health reports (dropped chunks, underruns,
+    /// latency) and engine `stats` messages (rolling RTF, tokens/s,
+    /// first-partial latency) sent to WebSocket clients. 0 disables them.
+    #[arg(long, default_value_t = 10)]
+    stats_interval_secs: u64,
+
+    /// Seconds without audio callbacks before a stalled capture stream is
+    /// rebuilt (e.g. after sleep/wake). 0 disables the watchdog.
+    #[arg(long, default_value_t = 5)]
+    watchdog_secs: u64,
+
+    /// Transcription engine: voxtral, or whisper (needs the `whisper`
+    /// feature and --whisper-model).
+    #[arg(long, default_value_t = EngineKind::Voxtral)]
+    engine: EngineKind,
+
+    /// Path to the whisper.cpp GGML model file (for --engine whisper).
+    #[arg(long, default_value = "../../models/ggml-base.bin")]
+    whisper_model: PathBuf,
+
     /// Path to the Voxtral Q4 GGUF model file.
     #[arg(long, default_value = "../../models/voxtral-q4.gguf")]
     model_path: String,
@@ -30,10 +190,219 @@ struct Cli {
     #[arg(long, default_value = "../../models/tekken.json")]
     tokenizer_path: String,
 
+    /// Additional model variant to switch to at runtime with a `model`
+    /// control message, as NAME=PATH (e.g. "accurate=../../models/a.gguf";
+    /// repeatable). --model-path is registered as "default".
+    #[arg(long, value_name = "NAME=PATH")]
+    model: Vec<NamedModel>,
+
+    /// Language to bias transcription towards (ISO 639-1 code, e.g. "pt").
+    /// Also reported as the transcript language. Default: detected from
+    /// the transcribed text.
+    #[arg(long, value_name = "CODE")]
+    language: Option<LanguageHint>,
+
+    /// Rule out text in other scripts than --language's instead of only
+    /// penalizing it.
+    #[arg(long, requires = "language")]
+    force_language: bool,
+
+    /// Penalize text tokens already generated in a chunk (1.0 = off;
+    /// ~1.2 breaks phrase loops on long segments).
+    #[arg(long, default_value_t = 1.0)]
+    repetition_penalty: f32,
+
+    /// Never repeat an n-gram of this many tokens within a chunk (0 = off).
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    no_repeat_ngram: usize,
+
+    /// What to do with transcripts that look hallucinated (sign-offs and
+    /// subtitle credits heard in silence, implausibly many words, very
+    /// low token probability): drop, flag (sent as tentative) or keep.
+    #[arg(long, value_name = "ACTION", default_value_t = HallucinationAction::Drop)]
+    hallucinations: HallucinationAction,
+
+    /// File of terms (names, jargon) to boost during decoding, one per
+    /// line; lines starting with # are comments.
+    #[arg(long, value_name = "PATH")]
+    hotwords_file: Option<PathBuf>,
+
+    /// Text to condition the decoder on before a source's first segment
+    /// (names, topic, style), as if it had just been transcribed.
+    #[arg(long, value_name = "TEXT")]
+    initial_prompt: Option<String>,
+
+    /// Condition each segment on the previous segment's transcript of the
+    /// same source, for casing, names and sentences that continue.
+    #[arg(long)]
+    context_from_previous: bool,
+
+    /// Decode this many milliseconds of the previous segment's audio of the
+    /// same source ahead of each segment (its text is dropped), so
+    /// sentences continuing across a pause come out coherent. 0 disables.
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    context_audio_ms: u32,
+
+    /// Experimental: while a segment is still being spoken, decode the
+    /// last seconds of its speech every this many milliseconds of new
+    /// audio and send the text so far as partials, so text appears before
+    /// the pause that ends the segment. Costs a decode of at most ~10 s of
+    /// audio per step. 0 disables.
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    incremental_ms: u32,
+
+    /// Restore sentence casing and closing punctuation on final transcripts
+    /// that come back as bare lowercase words. Clients can toggle it with a
+    /// `punctuation` control message.
+    #[arg(long)]
+    restore_punctuation: bool,
+
+    /// Write spelled-out numbers, amounts, percentages and dates in final
+    /// transcripts as digits and symbols ("twenty five dollars" → "$25"),
+    /// for English and Portuguese. The decoded text is sent as `raw_text`.
+    #[arg(long)]
+    itn: bool,
+
+    /// What to do with profanity in final transcripts, for captions shown
+    /// publicly: keep, mask ("f***") or tag (sent with `profane`).
+    #[arg(long, value_name = "ACTION", default_value_t = ProfanityAction::Keep)]
+    profanity: ProfanityAction,
+
+    /// File of extra words for --profanity, one per line; lines starting
+    /// with # are comments.
+    #[arg(long, value_name = "PATH")]
+    profanity_file: Option<PathBuf>,
+
+    /// Translate final transcripts into this language (ISO 639-1 code),
+    /// sent as separate `translation` messages.
+    #[arg(long, value_name = "CODE")]
+    translate_to: Option<String>,
+
+    /// Service translating for --translate-to: libretranslate or deepl.
+    #[arg(long, value_name = "SERVICE", default_value_t = TranslationService::LibreTranslate)]
+    translator: TranslationService,
+
+    /// Address of the translation server (default: DeepL's API, or a
+    /// LibreTranslate server on localhost:5000).
+    #[arg(long, value_name = "URL")]
+    translator_url: Option<String>,
+
+    /// API key of the translation service (required by DeepL).
+    #[arg(long, value_name = "KEY")]
+    translator_api_key: Option<String>,
+
+    /// Stop decoding a segment after this many seconds and send the text
+    /// decoded so far, flagged `timed_out`, so one slow segment can't stall
+    /// the session. 0 disables the timeout.
+    #[arg(long, value_name = "SECS", default_value_t = 0.0)]
+    segment_timeout_secs: f32,
+
+    /// Run the model on wgpu's CPU adapter even if a GPU is available.
+    #[arg(long)]
+    cpu: bool,
+
+    /// GPU adapter to run the model on, by index or part of its name as
+    /// shown by --list-gpus (default: wgpu's high-performance choice).
+    #[arg(long, value_name = "GPU", conflicts_with = "cpu")]
+    gpu: Option<AdapterSelector>,
+
+    /// Fail to start instead of falling back to the CPU adapter when no
+    /// usable GPU adapter is found.
+    #[arg(long, conflicts_with = "cpu")]
+    no_cpu_fallback: bool,
+
+    /// Skip the warm-up transcription after loading the model (faster
+    /// startup, slower first segment).
+    #[arg(long)]
+    no_warm_up: bool,
+
+    /// Unload the model after this many minutes without speech to free GPU
+    /// memory; it reloads on the next segment. 0 keeps it loaded.
+    #[arg(long, value_name = "MINUTES", default_value_t = 0.0)]
+    idle_unload_mins: f32,
+
+    /// Chunks of a long segment decoded concurrently (bounded by GPU
+    /// memory; 1 decodes them one at a time).
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    parallel_chunks: usize,
+
+    /// Latency/accuracy trade-off: low-latency (240 ms decoder delay),
+    /// balanced (480 ms) or accurate (960 ms).
+    #[arg(long, value_name = "PRESET", default_value_t = LatencyPreset::Balanced)]
+    latency: LatencyPreset,
+
+    /// Decoder delay in tokens of 80 ms (1-30), overriding --latency.
+    #[arg(long, value_name = "TOKENS")]
+    delay_tokens: Option<usize>,
+
+    /// Mel frames (10 ms each) per chunk long segments are decoded in,
+    /// 200-3000. GPU memory per decode grows with it; lower it if the GPU
+    /// runs out, at the cost of more sentences cut between chunks.
+    #[arg(
+        long,
+        value_name = "FRAMES",
+        env = "VOXVAULT_MAX_MEL_FRAMES",
+        default_value_t = DEFAULT_MAX_MEL_FRAMES
+    )]
+    max_mel_frames: usize,
+
+    /// Mel frames consecutive chunks share, so words at a cut aren't lost
+    /// (default: the model's; under half of --max-mel-frames).
+    #[arg(long, value_name = "FRAMES", env = "VOXVAULT_CHUNK_OVERLAP_FRAMES")]
+    chunk_overlap_frames: Option<usize>,
+
+    /// Seed the model's backend so the same audio always gives the same
+    /// tokens, for regression testing (needs --parallel-chunks 1).
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
     /// WebSocket server port.
     #[arg(long, default_value_t = 8765)]
     ws_port: u16,
 
+    /// Address the WebSocket server listens on: an interface's address,
+    /// or "0.0.0.0" for every interface, to serve other machines (e.g.
+    /// `--ws-audio` clients streaming to `/ingest`).
+    #[arg(long, visible_alias = "bind", default_value = "127.0.0.1")]
+    ws_host: String,
+
+    /// Also serve the gRPC API (transcript stream, controls, file
+    /// transcription) on this port of `--ws-host`. Needs a build with
+    /// `--features grpc`.
+    #[arg(long)]
+    grpc_port: Option<u16>,
+
+    /// Origin allowed to call the REST and SSE endpoints from a browser
+    /// (repeatable, e.g. `--cors-origin https://captions.example.com`;
+    /// "*" allows any).
+    #[arg(long)]
+    cors_origin: Vec<String>,
+
+    /// When the WebSocket port is taken, try up to this many ports after
+    /// it (0 = fail instead).
+    #[arg(long, default_value_t = 10)]
+    ws_port_fallback: u16,
+
+    /// Final transcripts sent again to each WebSocket client that connects,
+    /// so reconnecting dashboards keep the session's context (0 = off).
+    #[arg(long, default_value_t = DEFAULT_REPLAY_TRANSCRIPTS)]
+    replay_transcripts: usize,
+
+    /// Seconds between server pings to WebSocket clients (0 = no pings).
+    /// Clients that miss a pong are dropped.
+    #[arg(long, default_value_t = DEFAULT_PING_INTERVAL.as_secs())]
+    ws_ping_secs: u64,
+
+    /// Seconds a WebSocket client has to answer a ping.
+    #[arg(long, default_value_t = DEFAULT_PONG_TIMEOUT.as_secs())]
+    ws_pong_timeout_secs: u64,
+
+    /// Most partial transcripts per second sent to each WebSocket client,
+    /// keeping the newest of those in between (0 = no limit). Finals are
+    /// never delayed; clients can pick their own rate in `subscribe`.
+    #[arg(long, default_value_t = 0.0)]
+    ws_partial_rate: f32,
+
     /// Audio buffer duration in milliseconds before sending to processor.
     #[arg(long, default_value_t = 500)]
     buffer_ms: u32,
@@ -46,13 +415,180 @@ struct Cli {
     #[arg(long, default_value_t = 30.0)]
     max_duration: f32,
 
+    /// Milliseconds of audio repeated into the next segment when one is
+    /// cut at `--max-duration`, so a word split by the cut isn't lost.
+    /// Text transcribed twice is removed.
+    #[arg(long, default_value_t = 500)]
+    segment_overlap_ms: u32,
+
     /// Milliseconds of silence before splitting a speech segment (VAD).
     #[arg(long, default_value_t = 1000)]
     silence_pause_ms: u32,
 
     /// RMS energy threshold for speech detection (lower = more sensitive).
+    /// Used by the "energy" VAD; the starting point with `--adaptive-threshold`.
     #[arg(long, default_value_t = 0.005)]
     speech_threshold: f32,
+
+    /// RMS energy below which ongoing speech ends (hysteresis). Lower than
+    /// `--speech-threshold` keeps soft trailing speech. Defaults to it.
+    #[arg(long)]
+    speech_offset_threshold: Option<f32>,
+
+    /// Milliseconds speech must last before a segment starts, so brief
+    /// clicks are ignored (rounded up to whole `--buffer-ms` chunks).
+    #[arg(long, default_value_t = 0)]
+    min_speech_ms: u32,
+
+    /// Milliseconds of audio before speech onset kept at the start of each
+    /// segment, so slow-starting speakers don't lose their first syllables.
+    #[arg(long, default_value_t = 500)]
+    pre_roll_ms: u32,
+
+    /// Milliseconds kept around the speech when trimming leading and
+    /// trailing silence from each segment before transcription.
+    #[arg(long, default_value_t = 200)]
+    trim_margin_ms: u32,
+
+    /// Transcribe segments whole, without trimming silence.
+    #[arg(long)]
+    no_trim: bool,
+
+    /// Skip segments whose mean VAD speech probability (0.0–1.0) is below
+    /// this, instead of transcribing likely noise.
+    #[arg(long, default_value_t = 0.0)]
+    min_speech_confidence: f32,
+
+    /// Mark transcripts of segments whose mean VAD speech probability is
+    /// below this as tentative.
+    #[arg(long, default_value_t = 0.0)]
+    tentative_confidence: f32,
+
+    /// Flag likely speaker changes within segments (pitch discontinuities)
+    /// in final transcript messages (`speaker_changes_ms`).
+    #[arg(long)]
+    speaker_change_hints: bool,
+
+    /// Don't count chunks with a zero-crossing rate above this (0.0–1.0)
+    /// as speech, so hiss and broadband noise are ignored (e.g. 0.25).
+    #[arg(long)]
+    max_zcr: Option<f32>,
+
+    /// Don't count chunks with a spectral flatness below this (0.0–1.0)
+    /// as speech, so alert tones and music beds are ignored (e.g. 0.05).
+    #[arg(long)]
+    min_spectral_flatness: Option<f32>,
+
+    /// Follow the room's noise floor: the "energy" VAD threshold becomes
+    /// the estimated ambient RMS plus `--noise-margin`.
+    #[arg(long)]
+    adaptive_threshold: bool,
+
+    /// RMS above the noise floor counted as speech with `--adaptive-threshold`.
+    #[arg(long, default_value_t = 0.003)]
+    noise_margin: f32,
+
+    /// Voice activity detector: "energy" (RMS threshold), "webrtc" (needs
+    /// the `webrtc-vad` build feature) or "silero" (neural, needs the
+    /// `silero-vad` build feature and `--vad-model`).
+    #[arg(long, default_value = "energy")]
+    vad: VadKind,
+
+    /// Aggressiveness of the "webrtc" VAD, from 0 (flags the most audio as
+    /// speech) to 3 (most aggressive at rejecting non-speech).
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(0..=3))]
+    vad_aggressiveness: u8,
+
+    /// Path to the Silero VAD ONNX model (v5).
+    #[arg(long, default_value = "../../models/silero_vad.onnx")]
+    vad_model: PathBuf,
+
+    /// Speech probability threshold for the "silero" VAD (0.0–1.0); for
+    /// "webrtc", the fraction of 30 ms frames that must be voiced.
+    #[arg(long, default_value_t = 0.5)]
+    vad_threshold: f32,
+
+    /// Device buffer size in frames per callback (smaller = lower latency,
+    /// more risk of dropouts). Defaults to the host's choice.
+    #[arg(long, value_name = "FRAMES", conflicts_with = "low_latency")]
+    stream_buffer_frames: Option<u32>,
+
+    /// Use the smallest device buffer supported; the achieved callback
+    /// interval is logged when capture starts.
+    #[arg(long)]
+    low_latency: bool,
+
+    /// Resampler used to convert capture audio to 16 kHz: "high"
+    /// (windowed sinc) or "fast" (linear interpolation, less CPU).
+    #[arg(long, value_name = "QUALITY", default_value_t = ResamplerQuality::High)]
+    resampler: ResamplerQuality,
+
+    /// Audio chunks held back while transcription lags before the oldest
+    /// silent chunk is discarded; speech is never discarded.
+    #[arg(long, default_value_t = 32)]
+    backlog_chunks: usize,
+
+    /// Enable RNNoise noise suppression before VAD (fans, keyboard clatter).
+    #[arg(long)]
+    denoise: bool,
+
+    /// Cancel the audio of this device from the capture (acoustic echo
+    /// cancellation): the device VoxVault's playback goes to, captured as
+    /// the reference. Needs the `aec` feature.
+    #[arg(long, value_name = "DEVICE")]
+    echo_reference: Option<String>,
+
+    /// High-pass cutoff (Hz) removing DC offset and low-frequency rumble
+    /// before VAD and transcription; 0 disables.
+    #[arg(long, default_value_t = 80.0)]
+    high_pass_hz: f32,
+
+    /// Enable automatic gain control before VAD, boosting quiet speakers.
+    #[arg(long)]
+    agc: bool,
+
+    /// AGC target RMS level.
+    #[arg(long, default_value_t = 0.05)]
+    agc_target_rms: f32,
+
+    /// AGC maximum linear gain.
+    #[arg(long, default_value_t = 16.0)]
+    agc_max_gain: f32,
+
+    /// AGC attack time in milliseconds (how fast gain drops on loud input).
+    #[arg(long, default_value_t = 100.0)]
+    agc_attack_ms: f32,
+
+    /// AGC release time in milliseconds (how fast gain rises on quiet input).
+    #[arg(long, default_value_t = 2000.0)]
+    agc_release_ms: f32,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage model files.
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// Download the model and tokenizer (resuming partial downloads).
+    Download {
+        /// Directory to download into.
+        #[arg(long, default_value = "../../models")]
+        dir: PathBuf,
+
+        /// Hugging Face repository to download from.
+        #[arg(long, default_value = models::DEFAULT_REPO)]
+        repo: String,
+
+        /// Base URL serving the files instead of Hugging Face.
+        #[arg(long, conflicts_with = "repo")]
+        url: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -67,6 +603,10 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
+    if let Some(Command::Models { action }) = cli.command {
+        return run_models_command(action).await;
+    }
+
     // Set up audio devices (Multi-Output + Capture aggregates)
     let setup_result = setup::setup_audio_devices();
     if let Some(ref mo) = setup_result.multi_output_device {
@@ -81,146 +621,574 @@ async fn main() -> Result<()> {
         }
     }
 
+    if cli.list_gpus {
+        let adapters = gpu::list_adapters();
+        println!("Available GPU adapters:");
+        for adapter in &adapters {
+            println!("  {}", adapter);
+        }
+        if adapters.is_empty() {
+            println!("  (none)");
+        }
+        return Ok(());
+    }
+
     // List devices mode
     if cli.list_devices {
+        if cli.loopback {
+            let devices = AudioCapture::list_loopback_devices()?;
+            println!("Available loopback (output) devices:");
+            for (i, name) in devices.iter().enumerate() {
+                println!("  [{}] {}", i, name);
+            }
+            return Ok(());
+        }
         let devices = AudioCapture::list_devices()?;
         println!("Available audio input devices:");
         for (i, name) in devices.iter().enumerate() {
             println!("  [{}] {}", i, name);
         }
+        let processes = process_tap::list_audio_processes();
+        if !processes.is_empty() {
+            println!("Apps registered with CoreAudio (for --app):");
+            for p in &processes {
+                println!("  {} (pid {})", p.bundle_id, p.pid);
+            }
+        }
         return Ok(());
     }
 
     // Start WebSocket server
-    let server = TranscriptServer::new(cli.ws_port);
+    let server = TranscriptServer::new(cli.ws_port)
+        .with_host(cli.ws_host.clone())
+        .with_port_fallback(cli.ws_port_fallback)
+        .with_cors_origins(&cli.cors_origin)?
+        .with_replay(cli.replay_transcripts)
+        .with_partial_rate(Some(cli.ws_partial_rate))
+        .with_heartbeat((cli.ws_ping_secs > 0).then(|| HeartbeatConfig {
+            interval: Duration::from_secs(cli.ws_ping_secs),
+            timeout: Duration::from_secs(cli.ws_pong_timeout_secs),
+        }));
     let ws_sender = server.sender();
+    let clients = server.connected_clients();
+    let shutdown = server.shutdown_token();
+    let ws_ingest = server.audio_ingest();
+    let mut controls = server.controls();
+    let mut file_requests = server.file_requests();
 
-    let ws_handle = tokio::spawn(async move {
-        if let Err(e) = server.run().await {
+    if let Some(port) = cli.grpc_port {
+        let grpc = server.grpc(port);
+        tokio::spawn(async move {
+            if let Err(e) = grpc.run().await {
+                tracing::error!("gRPC server error: {:#}", e);
+            }
+        });
+    }
+    let listener = server.bind().await?;
+    let ws_port = listener.local_addr()?.port();
+    let mut ws_handle = tokio::spawn(async move {
+        if let Err(e) = server.serve(listener).await {
             tracing::error!("WebSocket server error: {}", e);
         }
     });
 
-    info!(port = cli.ws_port, "WebSocket server started");
-
-    // Load Voxtral engine
-    let mut engine = VoxtralEngine::new(
-        PathBuf::from(&cli.model_path),
-        PathBuf::from(&cli.tokenizer_path),
-    );
+    info!(port = ws_port, "WebSocket server started");
 
-    info!("Loading Voxtral model (this may take 3-5 seconds)...");
+    // Load the transcription engine
+    let language = match cli.language.clone() {
+        Some(hint) if cli.force_language => Some(hint.forced()),
+        hint => hint,
+    };
+    info!(engine = %cli.engine, "Loading model (this may take 3-5 seconds)...");
     let _ = ws_sender.send(TranscriptMessage::status(
+        PipelineState::LoadingModel,
         "Loading model...".to_string(),
     ));
-    let load_ms = engine.load().context("Failed to load Voxtral model")?;
-    info!(load_ms, "Model loaded");
-    let _ = ws_sender.send(TranscriptMessage::status("Ready".to_string()));
+    let mut ready = "Ready";
+    let mut engine: Box<dyn TranscriptionEngine> = match cli.engine {
+        EngineKind::Voxtral => {
+            let mut engine = voxtral_engine(&cli, language)?;
+            let load_ms = engine.load().context("Failed to load Voxtral model")?;
+            info!(load_ms, device = ?engine.device(), "Model loaded");
+            if engine.on_cpu() {
+                ready = "Ready (running on CPU: transcription will be slow)";
+            }
+            Box::new(engine)
+        }
+        EngineKind::Whisper => {
+            let mut engine = WhisperEngine::new(cli.whisper_model.clone()).with_language(language);
+            let load_ms = engine.load().context("Failed to load Whisper model")?;
+            info!(load_ms, "Model loaded");
+            Box::new(engine)
+        }
+    };
+    let _ = ws_sender.send(TranscriptMessage::status(
+        PipelineState::Ready,
+        ready.to_string(),
+    ));
 
     // Set up audio capture pipeline
     let (audio_tx, mut audio_rx) = mpsc::channel(32);
-    let mut capture =
-        AudioCapture::new(&cli.device, cli.buffer_ms, audio_tx)
-            .context("Failed to initialize audio capture")?;
+    let source = if cli.loopback {
+        CaptureSource::Loopback
+    } else {
+        CaptureSource::Input
+    };
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let stream_buffer = match cli.stream_buffer_frames {
+        Some(frames) => StreamBufferSize::Fixed(frames),
+        None if cli.low_latency => StreamBufferSize::Smallest,
+        None => StreamBufferSize::Default,
+    };
+    let backlog = BacklogConfig {
+        capacity: cli.backlog_chunks,
+        speech_threshold: cli.speech_threshold,
+    };
+    let watchdog = (cli.watchdog_secs > 0).then(|| Duration::from_secs(cli.watchdog_secs));
+    // Per-app capture replaces `--device` with the tap's private device
+    // (declared before the capture, so it is destroyed after it)
+    let process_tap = if cli.app.is_empty() {
+        None
+    } else {
+        Some(ProcessTap::create(&cli.app).context("Failed to create app audio tap")?)
+    };
+    let system_device = match process_tap {
+        Some(ref tap) => tap.device_name().to_string(),
+        None => cli.device.clone(),
+    };
+    let devices: Vec<String> = std::iter::once(system_device.clone())
+        .chain(cli.fallback_device.iter().cloned())
+        .collect();
+    let mut capture: Box<dyn AudioSource> = if let Some(ref path) = cli.input_file {
+        let pacing = if cli.max_speed {
+            Pacing::MaxSpeed
+        } else {
+            Pacing::RealTime
+        };
+        Box::new(FileSource::new(path, pacing, audio_tx))
+    } else if let Some(addr) = cli.listen_udp {
+        let config = NetConfig {
+            format: cli.net_format,
+            sample_rate: cli.net_sample_rate,
+            channels: cli.net_channels,
+        };
+        Box::new(NetSource::new(addr, config, audio_tx))
+    } else if cli.ws_audio {
+        Box::new(ws_ingest.with_sender(audio_tx))
+    } else if !cli.mix_device.is_empty() {
+        let inputs = std::iter::once(MixInput {
+            source,
+            ..MixInput::new(&system_device)
+        })
+        .chain(cli.mix_device.iter().cloned())
+        .collect();
+        Box::new(
+            MixedCapture::new(inputs, cli.buffer_ms, audio_tx)
+                .context("Failed to initialize mixed audio capture")?
+                .with_stream_buffer(stream_buffer)
+                .with_backlog(backlog)
+                .with_watchdog(watchdog)
+                .with_resampler(cli.resampler)
+                .with_events(event_tx),
+        )
+    } else if let Some(ref mic_device) = cli.mic_device {
+        Box::new(
+            DualCapture::new(mic_device, &system_device, source, cli.buffer_ms, audio_tx)
+                .context("Failed to initialize dual audio capture")?
+                .with_channels(cli.mic_channels.clone(), cli.channels.clone())
+                .with_stream_buffer(stream_buffer)
+                .with_backlog(backlog)
+                .with_watchdog(watchdog)
+                .with_events(event_tx),
+        )
+    } else {
+        Box::new(
+            AudioCapture::with_priority(&devices, source, cli.buffer_ms, audio_tx)
+                .context("Failed to initialize audio capture")?
+                .with_channels(cli.channels.clone())
+                .with_stream_buffer(stream_buffer)
+                .with_backlog(backlog)
+                .with_watchdog(watchdog)
+                .with_events(event_tx),
+        )
+    };
+
+    // Echo cancellation: the reference device is captured on its own and
+    // fed to every source's canceller
+    let mut echo_capture = None;
+    let echo_reference = match cli.echo_reference {
+        Some(ref device) => {
+            let reference = PlaybackReference::new(AecConfig::default())
+                .context("Failed to set up echo cancellation")?;
+            let (reference_tx, mut reference_rx) = mpsc::channel::<AudioChunk>(32);
+            let mut reference_capture =
+                AudioCapture::new(device, ECHO_REFERENCE_BUFFER_MS, reference_tx)
+                    .context("Failed to open the echo reference device")?;
+            reference_capture
+                .start(ECHO_REFERENCE_BUFFER_MS)
+                .context("Failed to start the echo reference capture")?;
+            let playback = reference.clone();
+            tokio::spawn(async move {
+                while let Some(chunk) = reference_rx.recv().await {
+                    playback.push(&chunk.samples, chunk.sample_rate);
+                }
+            });
+            info!(device, "Cancelling echo of the reference device");
+            echo_capture = Some(reference_capture);
+            Some(reference)
+        }
+        None => None,
+    };
+
+    let status_sender = ws_sender.clone();
+
+    // Forward device hot-plug events to WebSocket clients
+    let event_sender = ws_sender.clone();
+    tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            let msg = TranscriptMessage::status(PipelineState::Capturing, event.to_string());
+            let _ = event_sender.send(msg);
+        }
+    });
 
     capture
         .start(cli.buffer_ms)
         .context("Failed to start audio capture")?;
+    let _ = ws_sender.send(TranscriptMessage::status(
+        PipelineState::Capturing,
+        "Capturing audio".to_string(),
+    ));
 
-    info!(device = cli.device, "Audio capture started. Press Ctrl+C to stop.");
+    match (&cli.input_file, cli.listen_udp) {
+        (Some(path), _) => info!(file = %path.display(), "Transcribing file. Press Ctrl+C to stop."),
+        (None, Some(addr)) => info!(%addr, "Listening for network audio. Press Ctrl+C to stop."),
+        (None, None) if cli.ws_audio => {
+            info!(port = ws_port, "Waiting for WebSocket client audio. Press Ctrl+C to stop.")
+        }
+        (None, None) => info!("Audio capture started. Press Ctrl+C to stop."),
+    }
 
     // Processing loop — runs on a dedicated OS thread so GPU inference
     // doesn't block the tokio runtime (which serves WebSocket connections).
-    let mut processor = AudioProcessor::new(
-        cli.min_duration,
-        cli.max_duration,
-        cli.silence_pause_ms,
-        cli.buffer_ms,
-        cli.speech_threshold,
-    );
+    // One pipeline (AGC + processor) per capture source, so gain and VAD
+    // state for "me" and "them" stay independent in dual-capture mode.
+    let mut processor_config = ProcessorConfig {
+        min_duration_secs: cli.min_duration,
+        max_duration_secs: cli.max_duration,
+        silence_pause_ms: cli.silence_pause_ms,
+        buffer_ms: cli.buffer_ms,
+        speech_threshold: cli.speech_threshold,
+        offset_threshold: cli.speech_offset_threshold,
+        min_speech_ms: cli.min_speech_ms,
+        overlap_ms: cli.segment_overlap_ms,
+        max_zero_crossing_rate: cli.max_zcr,
+        min_spectral_flatness: cli.min_spectral_flatness,
+        pre_roll_ms: cli.pre_roll_ms,
+        trim_margin_ms: (!cli.no_trim).then_some(cli.trim_margin_ms),
+        speaker_change_hints: cli.speaker_change_hints,
+    };
+    let speech_threshold = cli.speech_threshold;
+    let denoise = cli.denoise;
+    let high_pass_hz = cli.high_pass_hz;
+    let confidence = ConfidencePolicy {
+        skip_below: cli.min_speech_confidence,
+        tentative_below: cli.tentative_confidence,
+    };
+    let profanity = match cli.profanity_file.as_deref() {
+        Some(path) => ProfanityFilter::new(cli.profanity).with_words_file(path)?,
+        None => ProfanityFilter::new(cli.profanity),
+    };
+    let translation = match cli.translate_to.clone() {
+        Some(target) => {
+            let mut translator =
+                HttpTranslator::new(cli.translator, cli.translator_api_key.clone())
+                    .context("Failed to set up translation")?;
+            if let Some(ref url) = cli.translator_url {
+                translator = translator.with_url(url.clone());
+            }
+            info!(service = %cli.translator, %target, "Translating transcripts");
+            Some(TranslationPolicy {
+                translator: Arc::new(translator),
+                target,
+                runtime: tokio::runtime::Handle::current(),
+            })
+        }
+        None => None,
+    };
+    // Cancelled on shutdown, so an in-flight decode doesn't hold it up
+    let cancel = CancelToken::new();
+    let mut decode = DecodePolicy {
+        initial_prompt: cli.initial_prompt.clone(),
+        prompt_from_previous: cli.context_from_previous,
+        context_audio_samples: cli.context_audio_ms as usize * 16,
+        incremental_samples: cli.incremental_ms as usize * 16,
+        restore_punctuation: cli.restore_punctuation,
+        inverse_normalize: cli.itn,
+        profanity,
+        translation,
+        cancel: cancel.clone(),
+        timeout: (cli.segment_timeout_secs > 0.0)
+            .then(|| Duration::from_secs_f32(cli.segment_timeout_secs)),
+    };
+    let resampler = cli.resampler;
+    let (vad, vad_model, vad_threshold) = (cli.vad, cli.vad_model.clone(), cli.vad_threshold);
+    let vad_aggressiveness = cli.vad_aggressiveness;
+    let (adaptive_threshold, noise_margin) = (cli.adaptive_threshold, cli.noise_margin);
+    let new_vad = move || -> Result<Option<Box<dyn VadBackend>>> {
+        Ok(match vad {
+            VadKind::Energy if adaptive_threshold => {
+                Some(Box::new(EnergyVad::adaptive(speech_threshold, noise_margin)))
+            }
+            VadKind::Energy => None,
+            VadKind::WebRtc => Some(Box::new(WebRtcVad::new(vad_aggressiveness, vad_threshold)?)),
+            VadKind::Silero => Some(Box::new(SileroVad::new(&vad_model, vad_threshold)?)),
+        })
+    };
+    // Fail at startup rather than in the first pipeline
+    new_vad().with_context(|| format!("Failed to initialize {} VAD", vad))?;
+    let agc_config = cli.agc.then(|| AgcConfig {
+        target_rms: cli.agc_target_rms,
+        max_gain: cli.agc_max_gain,
+        attack_ms: cli.agc_attack_ms,
+        release_ms: cli.agc_release_ms,
+        ..AgcConfig::default()
+    });
+    let new_pipeline = move |config: &ProcessorConfig| {
+        let mut processor = AudioProcessor::from_config(config.clone())
+            .with_noise_suppression(denoise)
+            .with_high_pass(Some(high_pass_hz))
+            .with_resampler(resampler);
+        if let Some(ref reference) = echo_reference {
+            match reference.canceller() {
+                Ok(canceller) => processor = processor.with_echo_cancellation(canceller),
+                Err(e) => tracing::error!("Echo cancellation unavailable: {:#}", e),
+            }
+        }
+        match new_vad() {
+            Ok(Some(backend)) => processor = processor.with_vad(backend),
+            Ok(None) => {}
+            Err(e) => tracing::error!("{} VAD unavailable, using energy VAD: {:#}", vad, e),
+        }
+        SourcePipeline {
+            agc: agc_config.clone().map(AutomaticGainControl::new),
+            processor,
+            last_text: String::new(),
+            last_audio: Vec::new(),
+            incremental: IncrementalDecode::default(),
+            speaking: false,
+        }
+    };
+    let mut pipelines: HashMap<Option<String>, SourcePipeline> = HashMap::new();
+    let mut recorder = cli
+        .record_to
+        .as_ref()
+        .map(WavRecorder::new)
+        .transpose()
+        .context("Failed to initialize audio recorder")?;
     let rt_handle = tokio::runtime::Handle::current();
+    let (done_tx, mut done_rx) = oneshot::channel::<()>();
+    let stats_every =
+        (cli.stats_interval_secs > 0).then(|| Duration::from_secs(cli.stats_interval_secs));
 
     let process_join = std::thread::Builder::new()
         .name("transcription".into())
         .spawn(move || {
+            let mut pending = VecDeque::new();
+            let mut stats_sent = Instant::now();
+
+            // Receive audio chunks via the tokio channel from a blocking context,
+            // serving uploaded files in between (the loop ends when the audio
+            // channel closes)
             loop {
-                // Receive audio chunks via the tokio channel from a blocking context
-                let chunk = match rt_handle.block_on(audio_rx.recv()) {
-                    Some(c) => c,
-                    None => break, // channel closed
+                let input = rt_handle.block_on(async {
+                    tokio::select! {
+                        chunk = audio_rx.recv() => chunk.map(LoopInput::Audio),
+                        Some(request) = file_requests.recv() => Some(LoopInput::Upload(request)),
+                    }
+                });
+                let chunk = match input {
+                    Some(LoopInput::Audio(chunk)) => chunk,
+                    Some(LoopInput::Upload(request)) => {
+                        transcribe_upload(engine.as_mut(), &ws_sender, request);
+                        continue;
+                    }
+                    None => break,
                 };
+                apply_controls(
+                    &mut controls,
+                    engine.as_mut(),
+                    &mut processor_config,
+                    &mut pipelines,
+                    &mut decode,
+                    &ws_sender,
+                );
+                if engine.unload_if_idle() {
+                    let _ = ws_sender.send(TranscriptMessage::status(
+                        PipelineState::Capturing,
+                        "Model unloaded (idle)".to_string(),
+                    ));
+                }
+                if stats_every.is_some_and(|every| stats_sent.elapsed() >= every) {
+                    let clients = clients.load(Ordering::Relaxed);
+                    let _ = ws_sender.send(TranscriptMessage::stats(engine.stats(), clients));
+                    stats_sent = Instant::now();
+                }
+                record_chunk(&mut recorder, &chunk);
+                send_level(&ws_sender, &chunk);
 
-                // Feed chunk to processor (VAD filters silence automatically)
-                if let Some(audio_buffer) = processor.feed(chunk) {
+                // Feed chunk to its source's pipeline (VAD filters silence automatically)
+                let label = chunk.label.clone();
+                let pipeline = pipelines
+                    .entry(label.clone())
+                    .or_insert_with(|| new_pipeline(&processor_config));
+                let segment = pipeline.feed(chunk);
+                send_vad(&ws_sender, &label, pipeline);
+                if let Some(segment) = segment {
+                    pipeline.incremental = IncrementalDecode::default();
+                    pending.push_back((label, segment));
+                } else if decode.incremental_samples > 0
+                    && pipeline.incremental_due(decode.incremental_samples)
+                    && ensure_loaded(engine.as_mut(), &ws_sender)
+                {
+                    transcribe_incremental(engine.as_ref(), &ws_sender, label, pipeline, &decode);
+                }
+
+                while let Some((label, segment)) = pending.pop_front() {
                     // Drain any stale chunks that arrived during transcription
                     // so we don't fall further behind
                     let mut drained = 0;
                     while let Ok(stale) = audio_rx.try_recv() {
+                        record_chunk(&mut recorder, &stale);
+                        send_level(&ws_sender, &stale);
                         // Still feed to processor so VAD state stays consistent
-                        if let Some(_) = processor.feed(stale) {
-                            // Discard extra buffers — we'll process fresh audio next
-                            drained += 1;
+                        let stale_label = stale.label.clone();
+                        let pipeline = pipelines
+                            .entry(stale_label.clone())
+                            .or_insert_with(|| new_pipeline(&processor_config));
+                        let segment = pipeline.feed(stale);
+                        send_vad(&ws_sender, &stale_label, pipeline);
+                        if let Some(segment) = segment {
+                            // Keep one segment from each other source so the other
+                            // speaker isn't lost; discard extra buffers otherwise
+                            if stale_label != label
+                                && !pending.iter().any(|(l, _)| *l == stale_label)
+                            {
+                                pending.push_back((stale_label, segment));
+                            } else {
+                                drained += 1;
+                            }
                         }
                     }
                     if drained > 0 {
                         tracing::debug!(drained, "Discarded stale audio buffers");
                     }
 
-                    let partial_sender = ws_sender.clone();
-                    let partial_ts = chrono::Utc::now().timestamp_millis() as u64;
-
-                    // Transcribe with per-token streaming (blocking GPU work)
-                    match engine.transcribe_streaming(audio_buffer, |partial_text: &str| {
-                        let msg = TranscriptMessage::transcript(
-                            partial_text.to_string(),
-                            "auto".to_string(),
-                            partial_ts,
-                            false,
-                            None, // no RTF for partials
-                        );
-                        let _ = partial_sender.send(msg);
-                    }) {
-                        Ok(result) => {
-                            if !result.text.is_empty() {
-                                if let Some(rtf) = result.rtf {
-                                    println!("[{}] (RTF={:.2}) {}", result.language, rtf, result.text);
-                                } else {
-                                    println!("[{}] {}", result.language, result.text);
-                                }
-
-                                let msg = TranscriptMessage::transcript(
-                                    result.text,
-                                    result.language,
-                                    result.timestamp_ms,
-                                    true,
-                                    result.rtf,
-                                );
-                                let _ = ws_sender.send(msg);
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Transcription error: {}", e);
-                            let _ = ws_sender.send(TranscriptMessage::error(e.to_string()));
-                        }
+                    if !ensure_loaded(engine.as_mut(), &ws_sender) {
+                        continue;
+                    }
+                    let pipeline = pipelines
+                        .entry(label.clone())
+                        .or_insert_with(|| new_pipeline(&processor_config));
+                    transcribe_segment(
+                        engine.as_ref(),
+                        &ws_sender,
+                        label,
+                        pipeline,
+                        confidence,
+                        &decode,
+                        segment,
+                    );
+                }
+            }
+
+            // Source finished (end of file or capture stopped): transcribe
+            // whatever speech is still accumulated
+            for (label, pipeline) in pipelines.iter_mut() {
+                if let Some(segment) = pipeline.processor.flush() {
+                    if !ensure_loaded(engine.as_mut(), &ws_sender) {
+                        break;
                     }
+                    transcribe_segment(
+                        engine.as_ref(),
+                        &ws_sender,
+                        label.clone(),
+                        pipeline,
+                        confidence,
+                        &decode,
+                        segment,
+                    );
                 }
             }
+
+            let stats = engine.stats();
+            info!(
+                transcribed = stats.transcribed,
+                suppressed = stats.suppressed,
+                flagged = stats.flagged,
+                rtf = ?stats.rtf,
+                tokens_per_sec = ?stats.tokens_per_sec,
+                first_partial_ms = ?stats.first_partial_ms,
+                "Engine stats"
+            );
+            let _ = done_tx.send(());
         })
         .expect("Failed to spawn transcription thread");
 
-    // Wait for Ctrl+C
-    tokio::signal::ctrl_c()
-        .await
-        .context("Failed to listen for ctrl+c")?;
+    // Wait for Ctrl+C (or the end of an input file), checking the capture
+    // device for hot-unplug and reporting its health meanwhile
+    let mut monitor_interval = tokio::time::interval(Duration::from_secs(1));
+    let mut stats_interval =
+        tokio::time::interval(Duration::from_secs(cli.stats_interval_secs.max(1)));
+    stats_interval.tick().await;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            result = &mut ctrl_c => {
+                result.context("Failed to listen for ctrl+c")?;
+                cancel.cancel();
+                break;
+            }
+            _ = &mut done_rx => {
+                info!("Input finished");
+                break;
+            }
+            _ = monitor_interval.tick() => {
+                capture.monitor();
+                if let Some(ref mut reference) = echo_capture {
+                    reference.monitor();
+                }
+            }
+            _ = stats_interval.tick(), if cli.stats_interval_secs > 0 => {
+                for stats in capture.stats() {
+                    info!("{}", stats);
+                    let msg = TranscriptMessage::status(PipelineState::Capturing, stats.to_string())
+                        .with_speaker(stats.label.clone());
+                    let _ = status_sender.send(msg);
+                }
+            }
+        }
+    }
 
     info!("Shutting down...");
     capture.stop();
+    if let Some(ref mut reference) = echo_capture {
+        reference.stop();
+    }
 
-    // Cancel tasks — dropping capture closes the audio channel,
-    // which will cause the transcription thread to exit its loop.
+    // Dropping capture closes the audio channel: the transcription thread
+    // flushes the processors and exits its loop. Then the server sends
+    // clients what's left and closes their connections.
     drop(capture);
     let _ = process_join.join();
-    ws_handle.abort();
+    shutdown.cancel();
+    if tokio::time::timeout(SHUTDOWN_GRACE, &mut ws_handle)
+        .await
+        .is_err()
+    {
+        tracing::warn!("WebSocket server didn't stop in time, aborting it");
+        ws_handle.abort();
+    }
 
     // Tear down audio devices (restore original output, destroy aggregates)
     let teardown = setup::teardown_audio_devices();
@@ -232,3 +1200,682 @@ async fn main() -> Result<()> {
     info!("VoxVault CLI shut down cleanly.");
     Ok(())
 }
+
+/// Build the Voxtral engine the command line describes (not loaded yet).
+fn voxtral_engine(cli: &Cli, language: Option<LanguageHint>) -> Result<VoxtralEngine> {
+    let hotwords = cli
+        .hotwords_file
+        .as_deref()
+        .map(Hotwords::from_file)
+        .transpose()?;
+    if let Some(ref hotwords) = hotwords {
+        info!(count = hotwords.words().len(), "Loaded hotwords");
+    }
+    let engine_config = EngineConfig {
+        repetition_penalty: cli.repetition_penalty,
+        no_repeat_ngram_size: cli.no_repeat_ngram,
+        hallucinations: cli.hallucinations,
+        cpu_fallback: !cli.no_cpu_fallback,
+        warm_up: !cli.no_warm_up,
+        idle_unload_after: (cli.idle_unload_mins > 0.0)
+            .then(|| Duration::from_secs_f32(cli.idle_unload_mins * 60.0)),
+        parallel_chunks: cli.parallel_chunks,
+        delay: cli.delay_tokens.unwrap_or(cli.latency.delay()),
+        max_mel_frames: cli.max_mel_frames,
+        chunk_overlap_frames: cli.chunk_overlap_frames,
+        seed: cli.seed,
+    };
+    engine_config
+        .validate()
+        .context("Invalid engine settings")?;
+    let mut engine = VoxtralEngine::new(
+        PathBuf::from(&cli.model_path),
+        PathBuf::from(&cli.tokenizer_path),
+    )
+    .with_language(language)
+    .with_hotwords(hotwords)
+    .with_config(engine_config);
+    for extra in &cli.model {
+        engine = engine.with_model(
+            &extra.name,
+            ModelFiles {
+                model: extra.path.clone(),
+                tokenizer: PathBuf::from(&cli.tokenizer_path),
+            },
+        );
+    }
+    if cli.cpu {
+        engine = engine.with_device(WgpuDevice::Cpu);
+    } else if let Some(ref selector) = cli.gpu {
+        let adapter = selector.select()?;
+        info!(%adapter, "Selected GPU adapter");
+        engine = engine.with_device(adapter.device);
+    }
+    Ok(engine)
+}
+
+async fn run_models_command(action: ModelsCommand) -> Result<()> {
+    match action {
+        ModelsCommand::Download { dir, repo, url } => {
+            let source = match url {
+                Some(url) => ModelSource::url(&url),
+                None => ModelSource::hugging_face(&repo),
+            };
+            let mut last_percent = None;
+            let (model, tokenizer) = models::download_models(&source, &dir, |p| {
+                let Some(total) = p.total.filter(|&t| t > 0) else {
+                    return;
+                };
+                let percent = p.downloaded * 100 / total;
+                if last_percent != Some((p.file.clone(), percent)) {
+                    eprint!(
+                        "\r{}: {:.1} / {:.1} MB ({}%)",
+                        p.file,
+                        p.downloaded as f64 / 1e6,
+                        total as f64 / 1e6,
+                        percent
+                    );
+                    if p.downloaded >= total {
+                        eprintln!();
+                    }
+                    last_percent = Some((p.file.clone(), percent));
+                }
+            })
+            .await
+            .context("Model download failed")?;
+            println!("Model: {}", model.display());
+            println!("Tokenizer: {}", tokenizer.display());
+            Ok(())
+        }
+    }
+}
+
+/// Per-source processing stages between capture and the engine.
+struct SourcePipeline {
+    agc: Option<AutomaticGainControl>,
+    processor: AudioProcessor,
+    /// Final text of the last segment, to stitch overlapping segments.
+    last_text: String,
+    /// Tail of the last segment's audio, decoded ahead of the next one.
+    last_audio: Vec<f32>,
+    /// Incremental decoding of the segment in progress.
+    incremental: IncrementalDecode,
+    /// Speech state last announced to clients.
+    speaking: bool,
+}
+
+impl SourcePipeline {
+    fn feed(&mut self, mut chunk: AudioChunk) -> Option<SpeechSegment> {
+        if let Some(ref mut agc) = self.agc {
+            agc.process(&mut chunk);
+        }
+        self.processor.feed(chunk)
+    }
+
+    /// Whether `step` more samples of the segment in progress arrived
+    /// since its last incremental decode.
+    fn incremental_due(&mut self, step: usize) -> bool {
+        let accumulated = self.processor.accumulated_samples();
+        if accumulated < self.incremental.decoded_samples {
+            // A new segment started
+            self.incremental = IncrementalDecode::default();
+        }
+        accumulated >= self.incremental.decoded_samples + step
+    }
+
+    /// Resampling Real-Time Factor so far (None while no resampling ran).
+    fn resample_rtf(&self) -> Option<f64> {
+        self.processor.resample_stats().rtf()
+    }
+}
+
+/// Progress of the incremental decoding of a segment in progress. Only a
+/// window at the end of the speech is decoded at each step; the text
+/// before it is settled.
+#[derive(Default)]
+struct IncrementalDecode {
+    /// Accumulated samples the last decode covered.
+    decoded_samples: usize,
+    /// Accumulated sample the window starts at.
+    window_start: usize,
+    /// Text of the speech before the window.
+    settled: String,
+}
+
+impl IncrementalDecode {
+    /// Settle the words of `result` (the decode of the window, `window_len`
+    /// samples long) that the audio after them can no longer change, once
+    /// the window has grown past its size.
+    fn advance(&mut self, result: &TranscriptResult, window_len: usize) {
+        if window_len < INCREMENTAL_WINDOW_SAMPLES {
+            return;
+        }
+        let until_ms = (window_len / 16) as u64 - INCREMENTAL_HOLDBACK_MS;
+        let timed = !result.words.is_empty() && result.words.iter().all(|w| w.end_ms.is_some());
+        let (words, end_ms) = if timed {
+            let words: Vec<_> = result
+                .words
+                .iter()
+                .take_while(|w| w.end_ms.is_some_and(|end| end <= until_ms))
+                .collect();
+            let end_ms = words.last().and_then(|w| w.end_ms).unwrap_or(until_ms);
+            (words.iter().map(|w| w.word.as_str()).collect(), end_ms)
+        } else {
+            // No word timings: settle the whole window
+            (vec![result.text.trim()], (window_len / 16) as u64)
+        };
+        for word in words.into_iter().filter(|w| !w.is_empty()) {
+            if !self.settled.is_empty() {
+                self.settled.push(' ');
+            }
+            self.settled.push_str(word);
+        }
+        self.window_start += end_ms as usize * 16;
+    }
+}
+
+/// What to do with segments of low VAD speech confidence.
+#[derive(Clone, Copy)]
+struct ConfidencePolicy {
+    /// Segments below this aren't transcribed.
+    skip_below: f32,
+    /// Transcripts of segments below this are marked tentative.
+    tentative_below: f32,
+}
+
+/// How segments are decoded: the text and audio the decoder is
+/// conditioned on, the session's cancellation and the per-segment timeout.
+struct DecodePolicy {
+    /// Prompt used when there is no previous transcript to continue from.
+    initial_prompt: Option<String>,
+    /// Continue from the source's previous transcript.
+    prompt_from_previous: bool,
+    /// Samples (16 kHz) of the source's previous segment decoded ahead of
+    /// each segment (0 = none).
+    context_audio_samples: usize,
+    /// New samples of a segment in progress between incremental decodes
+    /// (0 = only decode finished segments).
+    incremental_samples: usize,
+    /// Restore casing and punctuation on final transcripts lacking them.
+    restore_punctuation: bool,
+    /// Rewrite spelled-out numbers, amounts and dates in final transcripts.
+    inverse_normalize: bool,
+    profanity: ProfanityFilter,
+    translation: Option<TranslationPolicy>,
+    cancel: CancelToken,
+    timeout: Option<Duration>,
+}
+
+/// Translation of final transcripts, run on the async runtime so the
+/// transcription thread doesn't wait for the service.
+struct TranslationPolicy {
+    translator: Arc<dyn Translator>,
+    /// ISO 639-1 code translated into.
+    target: String,
+    runtime: tokio::runtime::Handle,
+}
+
+impl TranslationPolicy {
+    /// Translate a final transcript in the background and broadcast the
+    /// translation. Transcripts already in the target language are skipped.
+    fn spawn(
+        &self,
+        ws_sender: &broadcast::Sender<TranscriptMessage>,
+        text: String,
+        source: String,
+        timestamp: u64,
+        speaker: Option<String>,
+    ) {
+        if source == self.target {
+            return;
+        }
+        let translator = Arc::clone(&self.translator);
+        let target = self.target.clone();
+        let ws_sender = ws_sender.clone();
+        self.runtime.spawn(async move {
+            match translator.translate(&text, &source, &target).await {
+                Ok(translated) => {
+                    let msg = TranscriptMessage::translation(translated, source, target, timestamp)
+                        .with_speaker(speaker);
+                    let _ = ws_sender.send(msg);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        translator = translator.name(),
+                        "Translation failed: {:#}",
+                        e
+                    );
+                    let _ = ws_sender.send(TranscriptMessage::error(
+                        ErrorCode::TranslationFailed,
+                        format!("Translation failed: {:#}", e),
+                    ));
+                }
+            }
+        });
+    }
+}
+
+/// Transcribe one speech segment, streaming partials and the final result
+/// to WebSocket clients.
+fn transcribe_segment(
+    engine: &dyn TranscriptionEngine,
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+    label: Option<String>,
+    pipeline: &mut SourcePipeline,
+    confidence: ConfidencePolicy,
+    decode: &DecodePolicy,
+    segment: SpeechSegment,
+) {
+    if segment.speech_confidence < confidence.skip_below {
+        info!(
+            speech_confidence = segment.speech_confidence,
+            "Skipping low-confidence segment"
+        );
+        return;
+    }
+    let _ = ws_sender.send(
+        TranscriptMessage::status(PipelineState::Transcribing, "Transcribing".to_string())
+            .with_speaker(label.clone()),
+    );
+    let tentative = segment.speech_confidence < confidence.tentative_below;
+    let partial_ts = chrono::Utc::now().timestamp_millis() as u64;
+    let resample_rtf = pipeline.resample_rtf();
+    let language = engine.language().map_or("auto", LanguageHint::code);
+    // A segment starting with audio repeated from a hard cut repeats the
+    // previous segment's last words too
+    let previous = (segment.overlap_ms > 0).then(|| pipeline.last_text.clone());
+    let stitch = |text: &str| match previous {
+        Some(ref previous) => stitch_overlap(previous, text),
+        None => text.to_string(),
+    };
+
+    let prompt = match pipeline.last_text.as_str() {
+        previous if decode.prompt_from_previous && !previous.is_empty() => {
+            Some(previous.to_string())
+        }
+        _ => decode.initial_prompt.clone(),
+    };
+
+    let mut on_partial = |partial_text: &str| {
+        let msg = TranscriptMessage::transcript(
+            stitch(partial_text),
+            language.to_string(),
+            partial_ts,
+            false,
+            None, // no RTF for partials
+        )
+        .with_speaker(label.clone());
+        let _ = ws_sender.send(msg);
+    };
+
+    let context_audio = std::mem::take(&mut pipeline.last_audio);
+    let samples = &segment.buffer.samples;
+    let tail = samples.len().saturating_sub(decode.context_audio_samples);
+    pipeline.last_audio = samples[tail..].to_vec();
+
+    // Transcribe with per-token streaming (blocking GPU work)
+    let options = TranscribeOptions {
+        prompt: prompt.as_deref(),
+        cancel: Some(&decode.cancel),
+        timeout: decode.timeout,
+        // A segment starting with overlap already repeats the previous audio
+        context_audio: Some(context_audio.as_slice())
+            .filter(|a| !a.is_empty() && segment.overlap_ms == 0),
+    };
+    match engine.transcribe_streaming_with(segment.buffer, options, &mut on_partial) {
+        Ok(mut result) => {
+            let decoded_words = result.text.split_whitespace().count();
+            result.text = stitch(&result.text);
+            // Drop the confidences of words stitching removed
+            let removed = decoded_words - result.text.split_whitespace().count();
+            result.words.drain(..removed.min(result.words.len()));
+            result.start_ms = Some(segment.start_ms);
+            result.end_ms = Some(segment.end_ms);
+            pipeline.last_text = result.text.clone();
+            if decode.restore_punctuation {
+                // After stitching, which compares raw text with raw text
+                result.text = punctuation::restore(&result.text, &result.language);
+            }
+            let mut raw_text = None;
+            if decode.inverse_normalize {
+                let normalized = itn::normalize(&result.text, &result.language);
+                if normalized != result.text {
+                    raw_text = Some(std::mem::replace(&mut result.text, normalized));
+                }
+            }
+            let (text, profane) = decode.profanity.apply(&result.text, &result.language);
+            result.text = text;
+            raw_text = raw_text.map(|raw| decode.profanity.apply(&raw, &result.language).0);
+            if !result.text.is_empty() {
+                let prefix = match label {
+                    Some(ref l) => format!("[{}] <{}>", result.language, l),
+                    None => format!("[{}]", result.language),
+                };
+                match (result.rtf, resample_rtf) {
+                    (Some(rtf), Some(rs)) => println!(
+                        "{} (RTF={:.2}, resample RTF={:.4}) {}",
+                        prefix, rtf, rs, result.text
+                    ),
+                    (Some(rtf), None) => println!("{} (RTF={:.2}) {}", prefix, rtf, result.text),
+                    _ => println!("{} {}", prefix, result.text),
+                }
+
+                if let Some(ref translation) = decode.translation {
+                    translation.spawn(
+                        ws_sender,
+                        result.text.clone(),
+                        result.language.clone(),
+                        result.timestamp_ms,
+                        label.clone(),
+                    );
+                }
+                let msg = TranscriptMessage::transcript(
+                    result.text,
+                    result.language,
+                    result.timestamp_ms,
+                    true,
+                    result.rtf,
+                )
+                .with_speaker(label.clone())
+                .with_resample_rtf(resample_rtf)
+                .with_segment(result.start_ms, result.end_ms)
+                .with_speaker_changes(segment.speaker_changes_ms)
+                .with_speech_confidence(
+                    segment.speech_confidence,
+                    tentative || result.hallucination.is_some(),
+                )
+                .with_confidence(result.confidence, result.words)
+                .with_timed_out(result.timed_out)
+                .with_raw_text(raw_text)
+                .with_profane(profane);
+                let _ = ws_sender.send(msg);
+            }
+        }
+        Err(e) if e.is::<Cancelled>() => info!("Transcription cancelled"),
+        Err(e) => {
+            tracing::error!("Transcription error: {}", e);
+            let _ = ws_sender.send(TranscriptMessage::error(
+                ErrorCode::TranscriptionFailed,
+                e.to_string(),
+            ));
+        }
+    }
+    let _ = ws_sender.send(
+        TranscriptMessage::status(PipelineState::Capturing, "Capturing audio".to_string())
+            .with_speaker(label),
+    );
+}
+
+/// What the transcription loop waits for.
+enum LoopInput {
+    Audio(AudioChunk),
+    Upload(TranscribeRequest),
+}
+
+/// Transcribe a file uploaded to `POST /api/transcribe`, replying with the
+/// engine's result as is. Goes through the streaming decode loop, which
+/// scores and times words.
+fn transcribe_upload(
+    engine: &mut dyn TranscriptionEngine,
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+    request: TranscribeRequest,
+) {
+    let audio_secs = request.audio.samples.len() as f64 / request.audio.sample_rate as f64;
+    info!(audio_secs, "Transcribing uploaded file");
+    let _ = ws_sender.send(TranscriptMessage::status(
+        PipelineState::Transcribing,
+        "Transcribing uploaded file".to_string(),
+    ));
+    let result = if ensure_loaded(engine, ws_sender) {
+        engine.transcribe_streaming_with(request.audio, TranscribeOptions::default(), &mut |_| {})
+    } else {
+        Err(anyhow::anyhow!("Failed to load the model"))
+    };
+    let _ = ws_sender.send(TranscriptMessage::status(
+        PipelineState::Capturing,
+        "Capturing audio".to_string(),
+    ));
+    if let Err(ref e) = result {
+        tracing::warn!("Uploaded file transcription failed: {:#}", e);
+    }
+    let _ = request.reply.send(result);
+}
+
+/// Decode the end of the speech of a segment still in progress, sending
+/// the text so far as partials. The segment is transcribed again once it
+/// ends.
+fn transcribe_incremental(
+    engine: &dyn TranscriptionEngine,
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+    label: Option<String>,
+    pipeline: &mut SourcePipeline,
+    decode: &DecodePolicy,
+) {
+    let incremental = &mut pipeline.incremental;
+    let context_start = incremental
+        .window_start
+        .saturating_sub(INCREMENTAL_CONTEXT_SAMPLES);
+    let Some(mut speech) = pipeline.processor.pending_speech(context_start) else {
+        return;
+    };
+    incremental.decoded_samples = pipeline.processor.accumulated_samples();
+    let window = speech.samples.split_off(incremental.window_start - context_start);
+    let window_len = window.len();
+    let context_audio = speech.samples;
+    let speech = AudioBuffer::new(window, speech.sample_rate);
+
+    let partial_ts = chrono::Utc::now().timestamp_millis() as u64;
+    let language = engine.language().map_or("auto", LanguageHint::code);
+    let prompt = match pipeline.last_text.as_str() {
+        _ if !incremental.settled.is_empty() => Some(incremental.settled.as_str()),
+        previous if decode.prompt_from_previous && !previous.is_empty() => Some(previous),
+        _ => decode.initial_prompt.as_deref(),
+    };
+    let settled = &incremental.settled;
+    let mut on_partial = |partial_text: &str| {
+        let text = match settled.as_str() {
+            "" => partial_text.to_string(),
+            settled => format!("{} {}", settled, partial_text),
+        };
+        let msg = TranscriptMessage::transcript(
+            text,
+            language.to_string(),
+            partial_ts,
+            false,
+            None,
+        )
+        .with_speaker(label.clone());
+        let _ = ws_sender.send(msg);
+    };
+    let options = TranscribeOptions {
+        prompt,
+        cancel: Some(&decode.cancel),
+        timeout: decode.timeout,
+        context_audio: Some(context_audio.as_slice()),
+    };
+    let result = engine.preview_streaming_with(speech, options, &mut on_partial);
+    match result {
+        Ok(result) => {
+            tracing::debug!(text = %result.text, "Incremental decode");
+            incremental.advance(&result, window_len);
+        }
+        Err(e) if e.is::<Cancelled>() => {}
+        Err(e) => tracing::warn!("Incremental decode failed: {:#}", e),
+    }
+}
+
+/// Reload the model if it was unloaded while idle, reporting the reload
+/// latency. Returns false if it couldn't be loaded.
+fn ensure_loaded(
+    engine: &mut dyn TranscriptionEngine,
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+) -> bool {
+    match engine.ensure_loaded() {
+        Ok(None) => true,
+        Ok(Some(load_ms)) => {
+            let _ = ws_sender.send(TranscriptMessage::status(
+                PipelineState::Capturing,
+                format!("Model reloaded in {} ms", load_ms),
+            ));
+            true
+        }
+        Err(e) => {
+            tracing::error!("Failed to reload model: {:#}", e);
+            let _ = ws_sender.send(TranscriptMessage::error(
+                ErrorCode::ModelLoadFailed,
+                format!("Failed to reload model: {:#}", e),
+            ));
+            false
+        }
+    }
+}
+
+/// Apply control messages received from WebSocket clients since the last
+/// chunk. Configuration changes reach every pipeline, and pipelines
+/// created later.
+fn apply_controls(
+    controls: &mut broadcast::Receiver<ControlMessage>,
+    engine: &mut dyn TranscriptionEngine,
+    config: &mut ProcessorConfig,
+    pipelines: &mut HashMap<Option<String>, SourcePipeline>,
+    decode: &mut DecodePolicy,
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+) {
+    loop {
+        let control = match controls.try_recv() {
+            Ok(control) => control,
+            Err(broadcast::error::TryRecvError::Lagged(n)) => {
+                tracing::warn!(skipped = n, "Control messages dropped");
+                continue;
+            }
+            Err(_) => return,
+        };
+        match control {
+            ControlMessage::Configure(update) => {
+                if let Err(e) = config.update(&update) {
+                    tracing::warn!("Rejected processor configuration: {:#}", e);
+                    let _ = ws_sender.send(TranscriptMessage::error(
+                        ErrorCode::InvalidControl,
+                        format!("Rejected processor configuration: {:#}", e),
+                    ));
+                    continue;
+                }
+                for pipeline in pipelines.values_mut() {
+                    pipeline.processor.set_config(config.clone());
+                }
+                info!("Processor configuration updated");
+                let _ = ws_sender.send(TranscriptMessage::status(
+                    PipelineState::Capturing,
+                    "Processor configuration updated".to_string(),
+                ));
+            }
+            ControlMessage::Model { name } => match engine.switch_model(&name) {
+                Ok(load_ms) => {
+                    let _ = ws_sender.send(TranscriptMessage::status(
+                        PipelineState::Capturing,
+                        format!("Using model '{}' (loaded in {} ms)", name, load_ms),
+                    ));
+                }
+                Err(e) => {
+                    tracing::warn!("Model switch failed: {:#}", e);
+                    let _ = ws_sender.send(TranscriptMessage::error(
+                        ErrorCode::ModelLoadFailed,
+                        format!("Model switch failed: {:#}", e),
+                    ));
+                }
+            },
+            ControlMessage::Latency { preset } => {
+                let result = preset
+                    .parse::<LatencyPreset>()
+                    .and_then(|preset| engine.set_delay(preset.delay()));
+                match result {
+                    Ok(()) => {
+                        info!(%preset, "Latency preset changed");
+                        let _ = ws_sender.send(TranscriptMessage::status(
+                            PipelineState::Capturing,
+                            format!("Latency preset: {}", preset),
+                        ));
+                    }
+                    Err(e) => {
+                        tracing::warn!("Rejected latency preset: {:#}", e);
+                        let _ = ws_sender.send(TranscriptMessage::error(
+                            ErrorCode::InvalidControl,
+                            format!("Rejected latency preset: {:#}", e),
+                        ));
+                    }
+                }
+            }
+            ControlMessage::Punctuation { enabled } => {
+                decode.restore_punctuation = enabled;
+                info!(enabled, "Punctuation restoration toggled");
+                let _ = ws_sender.send(TranscriptMessage::status(
+                    PipelineState::Capturing,
+                    format!(
+                        "Punctuation restoration {}",
+                        if enabled { "enabled" } else { "disabled" }
+                    ),
+                ));
+            }
+            ControlMessage::Hotwords { words } => {
+                let hotwords = Hotwords::new(words);
+                let count = hotwords.words().len();
+                match engine.set_hotwords(Some(hotwords)) {
+                    Ok(()) => {
+                        info!(count, "Hotwords updated");
+                        let _ = ws_sender.send(TranscriptMessage::status(
+                            PipelineState::Capturing,
+                            format!("Hotwords updated ({} terms)", count),
+                        ));
+                    }
+                    Err(e) => {
+                        tracing::warn!("Rejected hotwords: {:#}", e);
+                        let _ = ws_sender.send(TranscriptMessage::error(
+                            ErrorCode::InvalidControl,
+                            format!("Rejected hotwords: {:#}", e),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Broadcast a chunk's raw input level so clients can show a VU meter.
+fn send_level(ws_sender: &broadcast::Sender<TranscriptMessage>, chunk: &AudioChunk) {
+    let (rms, peak) = chunk.levels();
+    let _ = ws_sender.send(TranscriptMessage::level(rms, peak).with_speaker(chunk.label.clone()));
+}
+
+/// Broadcast the VAD's speech probability for the chunk a pipeline just took,
+/// and a `speech_start`/`speech_end` event when the speech state changed.
+fn send_vad(
+    ws_sender: &broadcast::Sender<TranscriptMessage>,
+    label: &Option<String>,
+    pipeline: &mut SourcePipeline,
+) {
+    let msg = TranscriptMessage::vad(pipeline.processor.speech_probability())
+        .with_speaker(label.clone());
+    let _ = ws_sender.send(msg);
+
+    let speaking = pipeline.processor.is_speaking();
+    if speaking != pipeline.speaking {
+        pipeline.speaking = speaking;
+        let msg = if speaking {
+            TranscriptMessage::speech_start()
+        } else {
+            TranscriptMessage::speech_end()
+        };
+        let _ = ws_sender.send(msg.with_speaker(label.clone()));
+    }
+}
+
+/// Write a chunk to the session recording, if enabled. Recording errors
+/// are logged and stop the recording without interrupting transcription.
+fn record_chunk(recorder: &mut Option<WavRecorder>, chunk: &AudioChunk) {
+    if let Some(ref mut r) = recorder {
+        if let Err(e) = r.write(chunk) {
+            tracing::error!("Audio recording failed, disabling: {:#}", e);
+            *recorder = None;
+        }
+    }
+}
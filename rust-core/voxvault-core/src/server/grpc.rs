@@ -0,0 +1,328 @@
+//! gRPC API, served next to the WebSocket server.
+//!
+//! For programmatic consumers that prefer typed protobuf contracts
+//! (`proto/voxvault.proto`) to the WebSocket server's JSON. The
+//! `Transcription` service shares that server's state: it streams the
+//! messages WebSocket clients get, queues control messages for the
+//! processing loop and hands files to the engine like
+//! `POST /api/transcribe`. Needs the `grpc` feature (tonic; protoc at
+//! build time).
+
+#[cfg(not(feature = "grpc"))]
+use anyhow::bail;
+use anyhow::Result;
+use std::sync::Arc;
+
+use super::websocket::ServerState;
+
+/// Types and service traits generated from `proto/voxvault.proto`.
+#[cfg(feature = "grpc")]
+pub mod proto {
+    tonic::include_proto!("voxvault.v1");
+}
+
+/// gRPC server sharing a [`TranscriptServer`](super::websocket::TranscriptServer)'s
+/// state, from [`TranscriptServer::grpc`](super::websocket::TranscriptServer::grpc).
+pub struct GrpcServer {
+    host: String,
+    port: u16,
+    #[cfg_attr(not(feature = "grpc"), allow(dead_code))]
+    state: Arc<ServerState>,
+}
+
+impl GrpcServer {
+    pub(super) fn new(state: Arc<ServerState>, host: String, port: u16) -> Self {
+        Self { host, port, state }
+    }
+
+    /// Run the server until the WebSocket server's shutdown token is
+    /// cancelled.
+    #[cfg(feature = "grpc")]
+    pub async fn run(self) -> Result<()> {
+        use anyhow::Context;
+        use proto::transcription_server::TranscriptionServer;
+
+        let addr = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .with_context(|| format!("Invalid gRPC address {}:{}", self.host, self.port))?
+            .next()
+            .with_context(|| format!("No address for {}", self.host))?;
+        let shutdown = self.state.shutdown.clone();
+        let service = TranscriptionServer::new(service::Transcription { state: self.state })
+            .max_decoding_message_size(super::websocket::MAX_UPLOAD_BYTES);
+        tracing::info!(%addr, "gRPC server listening");
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_shutdown(addr, shutdown.cancelled_owned())
+            .await
+            .with_context(|| format!("gRPC server on {} failed", addr))?;
+        tracing::info!("gRPC server stopped");
+        Ok(())
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    pub async fn run(self) -> Result<()> {
+        bail!(
+            "gRPC API is not available ({}:{}): rebuild with `--features grpc`",
+            self.host,
+            self.port
+        )
+    }
+}
+
+#[cfg(feature = "grpc")]
+mod service {
+    use futures::stream::{self, Stream};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+    use tonic::{Request, Response, Status};
+    use tracing::{info, warn};
+
+    use super::proto;
+    use crate::audio::processor::ProcessorConfigUpdate;
+    use crate::server::websocket::{
+        ControlMessage, ErrorCode, PipelineState, ServerState, Subscription, TranscriptMessage,
+        UploadError,
+    };
+    use crate::voxtral::engine::EngineStats;
+    use crate::voxtral::types::{TranscriptResult, WordConfidence};
+
+    /// The `Transcription` service.
+    pub(super) struct Transcription {
+        pub(super) state: Arc<ServerState>,
+    }
+
+    type EventStream = Pin<Box<dyn Stream<Item = Result<proto::TranscriptEvent, Status>> + Send>>;
+
+    #[tonic::async_trait]
+    impl proto::transcription_server::Transcription for Transcription {
+        type StreamTranscriptsStream = EventStream;
+
+        async fn stream_transcripts(
+            &self,
+            request: Request<proto::StreamTranscriptsRequest>,
+        ) -> Result<Response<EventStream>, Status> {
+            let request = request.into_inner();
+            let session = Some(request.session).filter(|s| !s.is_empty());
+            info!(session, "gRPC client streaming transcripts");
+            let rx = match session {
+                Some(ref session) => self.state.join(session),
+                None => self.state.tx.subscribe(),
+            };
+            let scoped = session.is_some();
+            let subscription = Subscription {
+                types: request.types,
+                finals_only: request.finals_only,
+                max_partial_rate: None,
+            };
+            let events = stream::unfold(
+                (rx, Arc::clone(&self.state), subscription),
+                move |(mut rx, state, subscription)| async move {
+                    loop {
+                        // Queued messages go out before the stream ends
+                        let result = tokio::select! {
+                            biased;
+                            result = rx.recv() => result,
+                            _ = state.shutdown.cancelled() => return None,
+                        };
+                        match result {
+                            // Other sessions' messages are for their rooms
+                            Ok(msg) if !scoped && state.session_of(&msg).is_some() => {}
+                            Ok(msg) if !subscription.wants(&msg) => {}
+                            Ok(msg) => {
+                                let event = proto::TranscriptEvent::from(msg);
+                                return Some((Ok(event), (rx, state, subscription)));
+                            }
+                            Err(broadcast::error::RecvError::Lagged(n)) => {
+                                warn!(skipped = n, "gRPC client lagging behind, skipped messages");
+                            }
+                            Err(broadcast::error::RecvError::Closed) => return None,
+                        }
+                    }
+                },
+            );
+            Ok(Response::new(Box::pin(events)))
+        }
+
+        async fn control(
+            &self,
+            request: Request<proto::ControlRequest>,
+        ) -> Result<Response<proto::ControlReply>, Status> {
+            let control = ControlMessage::try_from(request.into_inner())?;
+            info!(?control, "gRPC control request received");
+            let _ = self.state.controls.send(control);
+            Ok(Response::new(proto::ControlReply {}))
+        }
+
+        async fn transcribe_file(
+            &self,
+            request: Request<proto::TranscribeFileRequest>,
+        ) -> Result<Response<proto::TranscriptResult>, Status> {
+            let request = request.into_inner();
+            info!(
+                file = request.file_name,
+                bytes = request.audio.len(),
+                "gRPC file transcription requested"
+            );
+            match self
+                .state
+                .transcribe_file(request.audio, request.file_name)
+                .await
+            {
+                Ok(result) => Ok(Response::new(result.into())),
+                Err(UploadError::Unavailable(e)) => Err(Status::unavailable(e)),
+                Err(UploadError::BadAudio(e)) => Err(Status::invalid_argument(e)),
+                Err(UploadError::Failed(e)) => Err(Status::internal(e)),
+            }
+        }
+    }
+
+    impl TryFrom<proto::ControlRequest> for ControlMessage {
+        type Error = Status;
+
+        fn try_from(request: proto::ControlRequest) -> Result<Self, Status> {
+            use proto::control_request::Control;
+
+            let Some(control) = request.control else {
+                return Err(Status::invalid_argument("Empty control request"));
+            };
+            Ok(match control {
+                Control::Configure(c) => ControlMessage::Configure(ProcessorConfigUpdate {
+                    min_duration_secs: c.min_duration_secs,
+                    max_duration_secs: c.max_duration_secs,
+                    silence_pause_ms: c.silence_pause_ms,
+                    speech_threshold: c.speech_threshold,
+                    offset_threshold: c.offset_threshold,
+                    min_speech_ms: c.min_speech_ms,
+                    overlap_ms: c.overlap_ms,
+                    max_zero_crossing_rate: c.max_zero_crossing_rate,
+                    min_spectral_flatness: c.min_spectral_flatness,
+                    pre_roll_ms: c.pre_roll_ms,
+                }),
+                Control::Hotwords(h) => ControlMessage::Hotwords { words: h.words },
+                Control::Model(name) => ControlMessage::Model { name },
+                Control::Latency(preset) => ControlMessage::Latency { preset },
+                Control::Punctuation(enabled) => ControlMessage::Punctuation { enabled },
+            })
+        }
+    }
+
+    impl From<TranscriptMessage> for proto::TranscriptEvent {
+        fn from(msg: TranscriptMessage) -> Self {
+            let (state, code) = match msg.state {
+                Some(state) => {
+                    let (state, code) = pipeline_state(state);
+                    (Some(state as i32), code.map(|c| c as i32))
+                }
+                None => (None, None),
+            };
+            Self {
+                r#type: msg.msg_type,
+                text: msg.text,
+                language: msg.language,
+                timestamp: msg.timestamp,
+                is_final: msg.is_final,
+                rtf: msg.rtf,
+                resample_rtf: msg.resample_rtf,
+                speaker: msg.speaker,
+                session: msg.session,
+                rms: msg.rms,
+                peak: msg.peak,
+                speech_probability: msg.speech_probability,
+                start_ms: msg.start_ms,
+                end_ms: msg.end_ms,
+                speaker_changes_ms: msg.speaker_changes_ms.unwrap_or_default(),
+                speech_confidence: msg.speech_confidence,
+                tentative: msg.tentative,
+                confidence: msg.confidence,
+                words: msg
+                    .words
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                timed_out: msg.timed_out,
+                raw_text: msg.raw_text,
+                profane: msg.profane,
+                source_language: msg.source_language,
+                target_language: msg.target_language,
+                stats: msg.stats.map(Into::into),
+                clients: msg.clients.map(|c| c as u64),
+                state,
+                code,
+                replayed: msg.replayed,
+                protocol_version: msg.protocol_version,
+                supported_versions: msg.supported_versions.unwrap_or_default(),
+            }
+        }
+    }
+
+    /// `state` as a protobuf state, and its error code if it is one.
+    fn pipeline_state(state: PipelineState) -> (proto::PipelineState, Option<proto::ErrorCode>) {
+        match state {
+            PipelineState::LoadingModel => (proto::PipelineState::LoadingModel, None),
+            PipelineState::Ready => (proto::PipelineState::Ready, None),
+            PipelineState::Capturing => (proto::PipelineState::Capturing, None),
+            PipelineState::Transcribing => (proto::PipelineState::Transcribing, None),
+            PipelineState::Error { code } => {
+                let code = match code {
+                    ErrorCode::InvalidControl => proto::ErrorCode::InvalidControl,
+                    ErrorCode::IngestRefused => proto::ErrorCode::IngestRefused,
+                    ErrorCode::ModelLoadFailed => proto::ErrorCode::ModelLoadFailed,
+                    ErrorCode::TranscriptionFailed => proto::ErrorCode::TranscriptionFailed,
+                    ErrorCode::TranslationFailed => proto::ErrorCode::TranslationFailed,
+                    ErrorCode::UnsupportedProtocol => proto::ErrorCode::UnsupportedProtocol,
+                };
+                (proto::PipelineState::Error, Some(code))
+            }
+        }
+    }
+
+    impl From<WordConfidence> for proto::Word {
+        fn from(word: WordConfidence) -> Self {
+            Self {
+                word: word.word,
+                confidence: word.confidence,
+                start_ms: word.start_ms,
+                end_ms: word.end_ms,
+            }
+        }
+    }
+
+    impl From<EngineStats> for proto::EngineStats {
+        fn from(stats: EngineStats) -> Self {
+            Self {
+                transcribed: stats.transcribed,
+                suppressed: stats.suppressed,
+                flagged: stats.flagged,
+                rtf: stats.rtf,
+                tokens_per_sec: stats.tokens_per_sec,
+                first_partial_ms: stats.first_partial_ms,
+                model_memory_bytes: stats.model_memory_bytes,
+                memory_in_use_bytes: stats.memory.map(|m| m.in_use),
+                memory_reserved_bytes: stats.memory.map(|m| m.reserved),
+                peak_inference_bytes: stats.peak_inference_bytes,
+            }
+        }
+    }
+
+    impl From<TranscriptResult> for proto::TranscriptResult {
+        fn from(result: TranscriptResult) -> Self {
+            Self {
+                text: result.text,
+                language: result.language,
+                timestamp_ms: result.timestamp_ms,
+                rtf: result.rtf,
+                start_ms: result.start_ms,
+                end_ms: result.end_ms,
+                confidence: result.confidence,
+                words: result.words.into_iter().map(Into::into).collect(),
+                avg_logprob: result.avg_logprob,
+                hallucination: result.hallucination,
+                timed_out: result.timed_out,
+                tokens: result.tokens as u64,
+            }
+        }
+    }
+}
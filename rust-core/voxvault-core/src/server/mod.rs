@@ -1 +1,3 @@
+pub mod grpc;
+pub mod subtitles;
 pub mod websocket;
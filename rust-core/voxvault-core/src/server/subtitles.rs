@@ -0,0 +1,231 @@
+//! Subtitle files (SRT, WebVTT) of a session's final transcripts.
+//!
+//! Each final transcript covers a segment of the stream, up to
+//! `--max-duration` long, which is too much text for one subtitle. So
+//! segments are split into cues of at most [`CUE_LINES`] lines, timed
+//! from their words' timings when the text still matches them, else in
+//! proportion to the text before and in each cue.
+
+use std::fmt::Write;
+
+use super::websocket::TranscriptMessage;
+
+/// Characters per subtitle line.
+const LINE_CHARS: usize = 42;
+/// Lines per cue.
+const CUE_LINES: usize = 2;
+
+/// Subtitle file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    /// File extension.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
+
+    /// MIME type.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Srt => "application/x-subrip; charset=utf-8",
+            Self::Vtt => "text/vtt; charset=utf-8",
+        }
+    }
+}
+
+/// A final transcript, as subtitles need it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Stream time the segment starts at, in ms.
+    pub start_ms: u64,
+    /// Stream time the segment ends at, in ms.
+    pub end_ms: u64,
+    pub text: String,
+    /// Source the transcript is attributed to, if any.
+    pub speaker: Option<String>,
+    /// Start and end of each word of `text` in the segment, in ms (empty
+    /// when unknown, or when the text no longer matches the decoded words).
+    pub word_times: Vec<(u64, u64)>,
+}
+
+impl Segment {
+    /// Segment of a final transcript message with stream times and text.
+    pub fn from_message(msg: &TranscriptMessage) -> Option<Self> {
+        if msg.msg_type != "transcript" || !msg.is_final || msg.text.trim().is_empty() {
+            return None;
+        }
+        let (start_ms, end_ms) = (msg.start_ms?, msg.end_ms?);
+        // Rewritten text (inverse normalization) has other words than the
+        // decoded ones
+        let words = msg.words.as_deref().unwrap_or_default();
+        let word_times = match msg.raw_text {
+            None if words.len() == msg.text.split_whitespace().count() => words
+                .iter()
+                .map(|w| Some((w.start_ms?, w.end_ms?)))
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        Some(Self {
+            start_ms,
+            end_ms,
+            text: msg.text.trim().to_string(),
+            speaker: msg.speaker.clone(),
+            word_times,
+        })
+    }
+}
+
+/// One subtitle: text shown between two stream times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Lines of text.
+    pub lines: Vec<String>,
+    pub speaker: Option<String>,
+}
+
+/// Cues of `segments`, in stream order and not overlapping.
+pub fn cues(segments: &[Segment]) -> Vec<Cue> {
+    let mut segments: Vec<&Segment> = segments.iter().collect();
+    segments.sort_by_key(|s| s.start_ms);
+    let mut cues: Vec<Cue> = Vec::new();
+    for segment in segments {
+        for mut cue in segment_cues(segment) {
+            // Segments cut with overlap repeat a little audio
+            if let Some(previous) = cues.last() {
+                cue.start_ms = cue.start_ms.max(previous.end_ms);
+            }
+            if cue.end_ms > cue.start_ms {
+                cues.push(cue);
+            }
+        }
+    }
+    cues
+}
+
+/// `segments` as an SRT file.
+pub fn srt(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for (index, cue) in cues(segments).iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{}\n{} --> {}\n{}\n",
+            index + 1,
+            timestamp(cue.start_ms, ','),
+            timestamp(cue.end_ms, ','),
+            cue.lines.join("\n")
+        );
+    }
+    out
+}
+
+/// `segments` as a WebVTT file, with speakers as voice spans.
+pub fn vtt(segments: &[Segment]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues(segments) {
+        let text = cue.lines.join("\n");
+        let _ = writeln!(
+            out,
+            "{} --> {}\n{}\n",
+            timestamp(cue.start_ms, '.'),
+            timestamp(cue.end_ms, '.'),
+            match cue.speaker {
+                Some(ref speaker) => format!("<v {}>{}", speaker, text),
+                None => text,
+            }
+        );
+    }
+    out
+}
+
+/// Render `segments` in `format`.
+pub fn render(format: SubtitleFormat, segments: &[Segment]) -> String {
+    match format {
+        SubtitleFormat::Srt => srt(segments),
+        SubtitleFormat::Vtt => vtt(segments),
+    }
+}
+
+/// Split a segment's words into cues of at most [`CUE_LINES`] lines of
+/// [`LINE_CHARS`], and time them.
+fn segment_cues(segment: &Segment) -> Vec<Cue> {
+    let words: Vec<&str> = segment.text.split_whitespace().collect();
+    // Word ranges of each cue's lines
+    let mut cues: Vec<Vec<(usize, usize)>> = Vec::new();
+    let mut line_start = 0;
+    let mut line_len = 0;
+    for (i, word) in words.iter().enumerate() {
+        let len = word.chars().count();
+        if line_len > 0 && line_len + 1 + len > LINE_CHARS {
+            push_line(&mut cues, (line_start, i));
+            line_start = i;
+            line_len = 0;
+        }
+        line_len += if line_len > 0 { 1 + len } else { len };
+    }
+    if line_start < words.len() {
+        push_line(&mut cues, (line_start, words.len()));
+    }
+
+    let duration = segment.end_ms.saturating_sub(segment.start_ms);
+    let total_chars = segment.text.chars().count().max(1) as u64;
+    // Characters of the text before word `i`, for proportional timing
+    let chars_before = |i: usize| -> u64 {
+        words[..i]
+            .iter()
+            .map(|w| w.chars().count() as u64 + 1)
+            .sum()
+    };
+    cues.into_iter()
+        .map(|lines| {
+            let first = lines[0].0;
+            let last = lines[lines.len() - 1].1;
+            let (start, end) = if segment.word_times.len() == words.len() {
+                (segment.word_times[first].0, segment.word_times[last - 1].1)
+            } else {
+                (
+                    duration * chars_before(first).min(total_chars) / total_chars,
+                    duration * chars_before(last).min(total_chars) / total_chars,
+                )
+            };
+            Cue {
+                start_ms: segment.start_ms + start.min(duration),
+                end_ms: segment.start_ms + end.min(duration),
+                lines: lines
+                    .iter()
+                    .map(|&(from, until)| words[from..until].join(" "))
+                    .collect(),
+                speaker: segment.speaker.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Add a line to the last cue, or start a new cue if that one is full.
+fn push_line(cues: &mut Vec<Vec<(usize, usize)>>, line: (usize, usize)) {
+    match cues.last_mut() {
+        Some(cue) if cue.len() < CUE_LINES => cue.push(line),
+        _ => cues.push(vec![line]),
+    }
+}
+
+/// `ms` as `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT).
+fn timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
@@ -1,22 +1,159 @@
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        DefaultBodyLimit, Multipart, Path, Query, State,
     },
-    response::IntoResponse,
-    routing::get,
-    Router,
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
 };
-use serde::Serialize;
-use std::sync::Arc;
-use tokio::sync::broadcast;
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
+use std::fmt;
+use std::io::{Cursor, ErrorKind};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{error, info, warn};
+use voxtral_mini_realtime::audio::AudioBuffer;
+
+use super::grpc::GrpcServer;
+use super::subtitles::{self, Segment, SubtitleFormat};
+use crate::audio::capture::AudioChunk;
+use crate::audio::file::AudioFileDecoder;
+use crate::audio::net::{f32le_samples, s16le_samples};
+use crate::audio::opus::{OpusDecoder, OPUS_DECODE_RATE};
+use crate::audio::processor::ProcessorConfigUpdate;
+use crate::audio::resample::{Resampler, ResamplerQuality};
+use crate::audio::source::AudioSource;
+use crate::voxtral::engine::EngineStats;
+use crate::voxtral::types::{TranscriptResult, WordConfidence};
+
+/// Largest file accepted by `POST /api/transcribe` (about 50 minutes of
+/// 16-bit stereo WAV at 44.1 kHz).
+pub(super) const MAX_UPLOAD_BYTES: usize = 512 * 1024 * 1024;
+/// Final transcripts kept per session for its subtitles (about 80 hours
+/// of 30-second segments).
+const MAX_SUBTITLE_SEGMENTS: usize = 10_000;
+/// Rate uploaded files are resampled to for the engine.
+const UPLOAD_SAMPLE_RATE: u32 = 16000;
+/// How long the session of a client that disconnected is remembered, for
+/// the transcripts of the audio it sent last.
+const CLIENT_SESSION_GRACE: Duration = Duration::from_secs(60);
+/// How long the history and subtitles of a session without clients are
+/// kept, for clients that rejoin and subtitle downloads.
+const SESSION_RETENTION: Duration = Duration::from_secs(30 * 60);
+/// How often disconnected clients and abandoned sessions are forgotten.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// State of the transcription pipeline, carried by status and error
+/// messages so frontends can drive a state machine off `state` rather
+/// than parse `text`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PipelineState {
+    /// Loading the model at startup.
+    LoadingModel,
+    /// Model loaded, audio capture not started yet.
+    Ready,
+    /// Listening for speech.
+    Capturing,
+    /// Decoding a segment.
+    Transcribing,
+    /// Something failed; the pipeline carries on unless it says otherwise.
+    Error { code: ErrorCode },
+}
+
+/// What an error message is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A control message was malformed or its values were rejected.
+    InvalidControl,
+    /// An `/ingest` connection was refused.
+    IngestRefused,
+    /// The model couldn't be loaded or switched.
+    ModelLoadFailed,
+    /// Decoding a segment failed.
+    TranscriptionFailed,
+    /// The translation service failed.
+    TranslationFailed,
+    /// A client's `hello` listed no protocol version the server speaks.
+    UnsupportedProtocol,
+}
+
+/// Version of the message format, carried by every message. Bumped when
+/// a change would break existing clients.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Protocol versions the server can speak.
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+
+/// Newest protocol version among a client's `versions` that the server
+/// speaks too.
+pub fn negotiate_version(versions: &[u32]) -> Option<u32> {
+    versions
+        .iter()
+        .copied()
+        .filter(|v| SUPPORTED_VERSIONS.contains(v))
+        .max()
+}
+
+/// Encoding of the messages sent to a WebSocket client, picked in its
+/// `hello`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// JSON in text frames.
+    #[default]
+    Json,
+    /// MessagePack maps in binary frames: cheaper to produce and parse
+    /// when partials stream at tens of messages per second.
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+impl Encoding {
+    /// Every encoding the server can send.
+    pub const ALL: &'static [Encoding] = &[Encoding::Json, Encoding::MessagePack];
+
+    /// `msg` as a WebSocket frame in this encoding.
+    fn frame(self, msg: &TranscriptMessage) -> anyhow::Result<Message> {
+        Ok(match self {
+            Self::Json => Message::Text(serde_json::to_string(msg)?),
+            Self::MessagePack => Message::Binary(rmp_serde::to_vec_named(msg)?),
+        })
+    }
+}
 
 /// Message sent to WebSocket clients.
 #[derive(Debug, Clone, Serialize)]
 pub struct TranscriptMessage {
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// Version of the message format ([`PROTOCOL_VERSION`], or the one
+    /// negotiated in a `hello` reply).
+    pub protocol_version: u32,
+    /// Protocol versions the server speaks. Only set for hello messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supported_versions: Option<Vec<u32>>,
+    /// Encoding of the messages from this one on. Only set for hello
+    /// messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<Encoding>,
+    /// Encodings the server can send. Only set for hello messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supported_encodings: Option<Vec<Encoding>>,
     pub text: String,
     pub language: String,
     pub timestamp: u64,
@@ -24,6 +161,78 @@ pub struct TranscriptMessage {
     /// Real-Time Factor (processing_time / audio_duration). Only set for final transcripts.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rtf: Option<f64>,
+    /// Real-Time Factor of resampling alone, cumulative for the source. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resample_rtf: Option<f64>,
+    /// Source the transcript is attributed to ("me" / "them") in dual-capture mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Session (room) the message belongs to. Messages without one go to
+    /// the clients of `/`, with one to the clients of `/ws/{session}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// RMS level (0.0–1.0) of the latest captured chunk. Only set for level messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rms: Option<f32>,
+    /// Peak level (0.0–1.0) of the latest captured chunk. Only set for level messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak: Option<f32>,
+    /// VAD speech probability (0.0–1.0) of the latest processed chunk. Only set for vad messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_probability: Option<f32>,
+    /// Stream time (ms) where the transcribed segment starts. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ms: Option<u64>,
+    /// Stream time (ms) where the transcribed segment ends. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ms: Option<u64>,
+    /// Stream times (ms) of likely speaker changes within the segment. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_changes_ms: Option<Vec<u64>>,
+    /// Mean VAD speech probability of the segment. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_confidence: Option<f32>,
+    /// Whether the segment's speech confidence was low or the text looks
+    /// hallucinated, so it may come from noise mistaken for speech.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub tentative: bool,
+    /// Decoder confidence (0.0–1.0) in the text. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Decoder confidence per word, for graying out unsure text. Only set for final transcripts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<WordConfidence>>,
+    /// Whether decoding hit the segment timeout, so the text may be cut short.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Text as decoded, before inverse text normalization rewrote numbers,
+    /// amounts and dates; `words` refer to it. Only set when they differ.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_text: Option<String>,
+    /// Whether the text contains profanity (masked, unless the filter only
+    /// tags it).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub profane: bool,
+    /// Language translated from. Only set for translation messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_language: Option<String>,
+    /// Language translated into. Only set for translation messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_language: Option<String>,
+    /// Engine counters and rolling performance. Only set for stats messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<EngineStats>,
+    /// WebSocket and SSE clients connected. Only set for stats messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clients: Option<usize>,
+    /// State of the pipeline, as `state` (and `code` for errors). Only set
+    /// for status and error messages.
+    #[serde(flatten)]
+    pub state: Option<PipelineState>,
+    /// Whether this is an earlier final transcript, sent again to a client
+    /// that just connected.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replayed: bool,
 }
 
 impl TranscriptMessage {
@@ -36,49 +245,762 @@ impl TranscriptMessage {
         rtf: Option<f64>,
     ) -> Self {
         Self {
-            msg_type: "transcript".to_string(),
             text,
             language,
             timestamp,
             is_final,
             rtf,
+            ..Self::new("transcript")
         }
     }
 
-    /// Create a status message.
-    pub fn status(text: String) -> Self {
+    /// Attribute this message to a capture source.
+    pub fn with_speaker(mut self, speaker: Option<String>) -> Self {
+        self.speaker = speaker;
+        self
+    }
+
+    /// Scope this message to a session, so only that room's clients get it.
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Attach the resampler's Real-Time Factor.
+    pub fn with_resample_rtf(mut self, resample_rtf: Option<f64>) -> Self {
+        self.resample_rtf = resample_rtf;
+        self
+    }
+
+    /// Attach the segment's stream-time bounds (for subtitle alignment).
+    pub fn with_segment(mut self, start_ms: Option<u64>, end_ms: Option<u64>) -> Self {
+        self.start_ms = start_ms;
+        self.end_ms = end_ms;
+        self
+    }
+
+    /// Attach likely speaker-change times within the segment, if any.
+    pub fn with_speaker_changes(mut self, speaker_changes_ms: Vec<u64>) -> Self {
+        self.speaker_changes_ms = (!speaker_changes_ms.is_empty()).then_some(speaker_changes_ms);
+        self
+    }
+
+    /// Attach the segment's speech confidence, marking the message
+    /// tentative if it is suspect.
+    pub fn with_speech_confidence(mut self, confidence: f32, tentative: bool) -> Self {
+        self.speech_confidence = Some(confidence);
+        self.tentative = tentative;
+        self
+    }
+
+    /// Mark the transcript as cut short by the segment timeout.
+    pub fn with_timed_out(mut self, timed_out: bool) -> Self {
+        self.timed_out = timed_out;
+        self
+    }
+
+    /// Attach the text as decoded, if inverse text normalization changed it.
+    pub fn with_raw_text(mut self, raw_text: Option<String>) -> Self {
+        self.raw_text = raw_text;
+        self
+    }
+
+    /// Tag the transcript as containing profanity.
+    pub fn with_profane(mut self, profane: bool) -> Self {
+        self.profane = profane;
+        self
+    }
+
+    /// Attach the decoder's confidence in the text and in each word.
+    pub fn with_confidence(mut self, confidence: Option<f32>, words: Vec<WordConfidence>) -> Self {
+        self.confidence = confidence;
+        self.words = (!words.is_empty()).then_some(words);
+        self
+    }
+
+    /// Create a status message: the pipeline's `state`, and `text` for
+    /// humans.
+    pub fn status(state: PipelineState, text: String) -> Self {
         Self {
-            msg_type: "status".to_string(),
             text,
+            state: Some(state),
+            ..Self::new("status")
+        }
+    }
+
+    /// Message of type `msg_type` with every optional field unset.
+    fn new(msg_type: &str) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            text: String::new(),
             language: String::new(),
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
             is_final: false,
             rtf: None,
+            resample_rtf: None,
+            speaker: None,
+            session: None,
+            rms: None,
+            peak: None,
+            speech_probability: None,
+            start_ms: None,
+            end_ms: None,
+            speaker_changes_ms: None,
+            speech_confidence: None,
+            tentative: false,
+            confidence: None,
+            words: None,
+            timed_out: false,
+            raw_text: None,
+            profane: false,
+            source_language: None,
+            target_language: None,
+            stats: None,
+            clients: None,
+            state: None,
+            protocol_version: PROTOCOL_VERSION,
+            supported_versions: None,
+            encoding: None,
+            supported_encodings: None,
+            replayed: false,
+        }
+    }
+
+    /// Create an input level message for VU meters.
+    pub fn level(rms: f32, peak: f32) -> Self {
+        Self {
+            rms: Some(rms),
+            peak: Some(peak),
+            ..Self::new("level")
+        }
+    }
+
+    /// Create a VAD message carrying the speech probability of a chunk.
+    pub fn vad(speech_probability: f32) -> Self {
+        Self {
+            speech_probability: Some(speech_probability),
+            ..Self::new("vad")
         }
     }
 
-    /// Create an error message.
-    pub fn error(text: String) -> Self {
+    /// Create a hello message: sent to each client that connects, and in
+    /// reply to its own `hello` with the `version` both speak and the
+    /// `encoding` of the messages that follow.
+    pub fn hello(version: u32, encoding: Encoding) -> Self {
+        Self {
+            protocol_version: version,
+            supported_versions: Some(SUPPORTED_VERSIONS.to_vec()),
+            encoding: Some(encoding),
+            supported_encodings: Some(Encoding::ALL.to_vec()),
+            ..Self::new("hello")
+        }
+    }
+
+    /// Create a message announcing that speech started on a source.
+    pub fn speech_start() -> Self {
+        Self::new("speech_start")
+    }
+
+    /// Create a message announcing that speech ended (a silence pause).
+    pub fn speech_end() -> Self {
+        Self::new("speech_end")
+    }
+
+    /// Create a message with the engine's counters and rolling
+    /// performance, and the clients connected, for dashboards.
+    pub fn stats(stats: EngineStats, clients: usize) -> Self {
+        Self {
+            stats: Some(stats),
+            clients: Some(clients),
+            ..Self::new("stats")
+        }
+    }
+
+    /// Create a message with the translation of a final transcript,
+    /// carrying that transcript's timestamp so clients can pair them.
+    pub fn translation(text: String, source: String, target: String, timestamp: u64) -> Self {
         Self {
-            msg_type: "error".to_string(),
             text,
-            language: String::new(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            is_final: false,
-            rtf: None,
+            language: target.clone(),
+            timestamp,
+            is_final: true,
+            source_language: Some(source),
+            target_language: Some(target),
+            ..Self::new("translation")
+        }
+    }
+
+    /// Create an error message, with a `code` for clients to act on.
+    pub fn error(code: ErrorCode, text: String) -> Self {
+        Self {
+            text,
+            state: Some(PipelineState::Error { code }),
+            ..Self::new("error")
         }
     }
 }
 
+/// Control message received from a WebSocket client (JSON text message).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    /// Tune the audio processors live, e.g.
+    /// `{"type": "configure", "speech_threshold": 0.01, "silence_pause_ms": 700}`.
+    Configure(ProcessorConfigUpdate),
+    /// Replace the custom vocabulary boosted during decoding, e.g.
+    /// `{"type": "hotwords", "words": ["VoxVault", "Voxtral"]}`.
+    Hotwords { words: Vec<String> },
+    /// Switch to another registered model variant, e.g.
+    /// `{"type": "model", "name": "accurate"}`.
+    Model { name: String },
+    /// Change the latency/accuracy preset, e.g.
+    /// `{"type": "latency", "preset": "accurate"}`.
+    Latency { preset: String },
+    /// Toggle casing and punctuation restoration on final transcripts,
+    /// e.g. `{"type": "punctuation", "enabled": true}`.
+    Punctuation { enabled: bool },
+}
+
+/// Messages a WebSocket client asks for with a `subscribe` message, e.g.
+/// `{"type": "subscribe", "types": ["transcript"], "finals_only": true}`
+/// for a caption overlay. Each `subscribe` replaces the previous one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Subscription {
+    /// Message types to send (`transcript`, `translation`, `status`...);
+    /// empty for all of them.
+    pub types: Vec<String>,
+    /// Leave out partial transcripts.
+    pub finals_only: bool,
+    /// Most partial transcripts per second and source, coalesced to the
+    /// newest one in between (0 = no limit; None = the server's
+    /// `--ws-partial-rate`). Finals always go out at once. WebSocket
+    /// clients only.
+    pub max_partial_rate: Option<f32>,
+}
+
+impl Subscription {
+    /// Whether the client wants `msg`.
+    pub fn wants(&self, msg: &TranscriptMessage) -> bool {
+        if self.finals_only && msg.msg_type == "transcript" && !msg.is_final {
+            return false;
+        }
+        self.types.is_empty() || self.types.contains(&msg.msg_type)
+    }
+}
+
+/// Text message a WebSocket client sends about its own connection rather
+/// than the engine.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientRequest {
+    Subscribe(Subscription),
+    /// Protocol versions the client understands, and the encoding it
+    /// wants messages in, e.g.
+    /// `{"type": "hello", "versions": [1], "encoding": "msgpack"}`.
+    Hello {
+        versions: Vec<u32>,
+        #[serde(default)]
+        encoding: Encoding,
+    },
+}
+
 /// Shared state for the WebSocket server.
-#[derive(Clone)]
 pub struct ServerState {
     pub tx: broadcast::Sender<TranscriptMessage>,
+    /// Control messages from clients, for the processing loop.
+    pub(super) controls: broadcast::Sender<ControlMessage>,
+    /// Destination of client audio while an [`AudioIngest`] is started.
+    ingest: Arc<Mutex<Option<IngestSink>>>,
+    /// Numbers client connections, to label their audio.
+    next_client: AtomicU64,
+    /// WebSocket and SSE clients connected.
+    clients: Arc<AtomicUsize>,
+    /// Pings sent to WebSocket clients (None = off).
+    heartbeat: Mutex<Option<HeartbeatConfig>>,
+    /// Most partial transcripts per second sent to each WebSocket client,
+    /// unless it subscribes with its own limit (None = no limit).
+    partial_rate: Mutex<Option<f32>>,
+    /// Engine stats of the last `stats` message, for `GET /stats`.
+    stats: Mutex<Option<EngineStats>>,
+    /// Latest final transcripts per session, oldest first, replayed to
+    /// new clients.
+    history: Mutex<HashMap<Option<String>, VecDeque<TranscriptMessage>>>,
+    /// Final transcripts of each session, for its subtitle files.
+    segments: Mutex<HashMap<Option<String>, VecDeque<Segment>>>,
+    /// Broadcast channel of each session with clients connected.
+    rooms: Mutex<HashMap<String, broadcast::Sender<TranscriptMessage>>>,
+    /// Session of each client streaming audio in a room, by its label
+    /// (kept for [`CLIENT_SESSION_GRACE`] after it disconnects, for the
+    /// transcripts of its last audio).
+    client_sessions: Mutex<HashMap<String, ClientSession>>,
+    /// Since when each session with history or subtitles has had no room
+    /// or client; dropped after [`SESSION_RETENTION`].
+    idle_sessions: Mutex<HashMap<String, Instant>>,
+    /// When clients and sessions were last pruned.
+    last_prune: Mutex<Instant>,
+    /// Where `POST /api/transcribe` sends uploads, once a consumer asked
+    /// for them with [`TranscriptServer::file_requests`].
+    uploads: Mutex<Option<mpsc::Sender<TranscribeRequest>>>,
+    /// Cancelled to close client connections and stop the server.
+    pub(super) shutdown: CancellationToken,
+}
+
+impl ServerState {
+    /// Session `msg` belongs to: its own, else that of the client whose
+    /// audio it is about.
+    pub(super) fn session_of(&self, msg: &TranscriptMessage) -> Option<String> {
+        msg.session.clone().or_else(|| {
+            let speaker = msg.speaker.as_ref()?;
+            let clients = self.client_sessions.lock().unwrap();
+            clients.get(speaker).map(|client| client.session.clone())
+        })
+    }
+
+    /// Put the audio of client `label` in `session` while the returned
+    /// guard is alive, and for [`CLIENT_SESSION_GRACE`] after.
+    fn enter_session(self: &Arc<Self>, label: &str, session: &str) -> SessionGuard {
+        self.client_sessions.lock().unwrap().insert(
+            label.to_string(),
+            ClientSession {
+                session: session.to_string(),
+                left: None,
+            },
+        );
+        SessionGuard {
+            state: Arc::clone(self),
+            label: label.to_string(),
+        }
+    }
+
+    /// Forget clients that left more than the grace period ago, and the
+    /// history and subtitles of sessions without a room or client for
+    /// longer than the retention.
+    fn prune(&self) {
+        let now = Instant::now();
+        {
+            let mut last_prune = self.last_prune.lock().unwrap();
+            if now.duration_since(*last_prune) < PRUNE_INTERVAL {
+                return;
+            }
+            *last_prune = now;
+        }
+
+        let mut active: HashSet<String> = self.rooms.lock().unwrap().keys().cloned().collect();
+        {
+            let mut clients = self.client_sessions.lock().unwrap();
+            clients.retain(|_, client| {
+                client
+                    .left
+                    .is_none_or(|left| now.duration_since(left) < CLIENT_SESSION_GRACE)
+            });
+            active.extend(clients.values().map(|client| client.session.clone()));
+        }
+
+        let mut known: HashSet<String> = self
+            .history
+            .lock()
+            .unwrap()
+            .keys()
+            .flatten()
+            .cloned()
+            .collect();
+        known.extend(self.segments.lock().unwrap().keys().flatten().cloned());
+        let mut idle = self.idle_sessions.lock().unwrap();
+        idle.retain(|session, _| known.contains(session) && !active.contains(session));
+        let mut expired = Vec::new();
+        for session in known.into_iter().filter(|s| !active.contains(s)) {
+            let since = *idle.entry(session.clone()).or_insert(now);
+            if now.duration_since(since) >= SESSION_RETENTION {
+                idle.remove(&session);
+                expired.push(Some(session));
+            }
+        }
+        drop(idle);
+        if expired.is_empty() {
+            return;
+        }
+        info!(sessions = ?expired, "Dropping the history of abandoned sessions");
+        let mut history = self.history.lock().unwrap();
+        let mut segments = self.segments.lock().unwrap();
+        for session in &expired {
+            history.remove(session);
+            segments.remove(session);
+        }
+    }
+
+    /// Subscribe to the channel of room `session`, opening it if needed.
+    pub(super) fn join(&self, session: &str) -> broadcast::Receiver<TranscriptMessage> {
+        self.rooms
+            .lock()
+            .unwrap()
+            .entry(session.to_string())
+            .or_insert_with(|| broadcast::channel(256).0)
+            .subscribe()
+    }
+
+    /// Decode `bytes`, an uploaded audio file named `file_name`, and have
+    /// the engine transcribe it.
+    pub(super) async fn transcribe_file(
+        &self,
+        bytes: Vec<u8>,
+        file_name: String,
+    ) -> Result<TranscriptResult, UploadError> {
+        let Some(uploads) = self.uploads.lock().unwrap().clone() else {
+            return Err(UploadError::Unavailable(
+                "File transcription is not available".to_string(),
+            ));
+        };
+        // Decoding and resampling a long file would stall the runtime
+        let audio = tokio::task::spawn_blocking(move || decode_upload(bytes, &file_name))
+            .await
+            .map_err(|e| UploadError::Failed(e.to_string()))?
+            .map_err(|e| UploadError::BadAudio(format!("{:#}", e)))?;
+
+        let (reply, result) = oneshot::channel();
+        let stopped = || UploadError::Unavailable("The transcription loop stopped".to_string());
+        uploads
+            .send(TranscribeRequest { audio, reply })
+            .await
+            .map_err(|_| stopped())?;
+        result
+            .await
+            .map_err(|_| stopped())?
+            .map_err(|e| UploadError::Failed(format!("{:#}", e)))
+    }
+
+    /// Handle a broadcast message: keep engine stats and final transcripts
+    /// for later clients, and pass it on to its session's room.
+    fn dispatch(&self, mut msg: TranscriptMessage, replay: usize) {
+        msg.session = self.session_of(&msg);
+        if msg.stats.is_some() {
+            *self.stats.lock().unwrap() = msg.stats;
+        }
+        if replay > 0 && msg.msg_type == "transcript" && msg.is_final {
+            let mut history = self.history.lock().unwrap();
+            let transcripts = history.entry(msg.session.clone()).or_default();
+            if transcripts.len() == replay {
+                transcripts.pop_front();
+            }
+            transcripts.push_back(msg.clone());
+        }
+        if let Some(segment) = Segment::from_message(&msg) {
+            let mut segments = self.segments.lock().unwrap();
+            let segments = segments.entry(msg.session.clone()).or_default();
+            if segments.len() == MAX_SUBTITLE_SEGMENTS {
+                segments.pop_front();
+            }
+            segments.push_back(segment);
+        }
+
+        let mut rooms = self.rooms.lock().unwrap();
+        rooms.retain(|_, room| room.receiver_count() > 0);
+        match msg.session {
+            Some(ref session) => {
+                if let Some(room) = rooms.get(session) {
+                    let _ = room.send(msg);
+                }
+            }
+            // Engine status and stats concern every session
+            None if matches!(msg.msg_type.as_str(), "status" | "stats") => {
+                for room in rooms.values() {
+                    let _ = room.send(msg.clone());
+                }
+            }
+            None => {}
+        }
+        drop(rooms);
+        self.prune();
+    }
+}
+
+/// Session a client streams audio into.
+struct ClientSession {
+    session: String,
+    /// When the client disconnected (None while it is connected).
+    left: Option<Instant>,
+}
+
+/// Marks a client as gone from its session when dropped (see
+/// [`ServerState::enter_session`]).
+struct SessionGuard {
+    state: Arc<ServerState>,
+    label: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut clients) = self.state.client_sessions.lock() {
+            if let Some(client) = clients.get_mut(&self.label) {
+                client.left = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Counts a client as connected for as long as it is alive.
+struct ClientGuard(Arc<AtomicUsize>);
+
+impl ClientGuard {
+    fn new(state: &ServerState) -> Self {
+        state.clients.fetch_add(1, Ordering::Relaxed);
+        Self(Arc::clone(&state.clients))
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Default time between pings to WebSocket clients.
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+/// Default wait for a client's pong before dropping it.
+pub const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Server-initiated pings to WebSocket clients. A client that doesn't
+/// answer one within `timeout` is disconnected, so dead connections (a
+/// laptop gone to sleep, a dropped network) don't linger as subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// Time between the last pong and the next ping.
+    pub interval: Duration,
+    /// Wait for the pong after a ping.
+    pub timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_PING_INTERVAL,
+            timeout: DEFAULT_PONG_TIMEOUT,
+        }
+    }
+}
+
+/// Ping schedule of one WebSocket connection.
+struct Heartbeat {
+    config: Option<HeartbeatConfig>,
+    /// When to ping next or, while a ping is unanswered, give up.
+    deadline: Instant,
+    awaiting_pong: bool,
+}
+
+impl Heartbeat {
+    fn new(config: Option<HeartbeatConfig>) -> Self {
+        Self {
+            config,
+            deadline: Instant::now() + config.map_or(Duration::ZERO, |c| c.interval),
+            awaiting_pong: false,
+        }
+    }
+
+    /// Wait for the deadline (forever when pings are off).
+    async fn tick(&self) {
+        match self.config {
+            Some(_) => tokio::time::sleep_until(self.deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// At the deadline: whether the client is still alive, in which case a
+    /// ping is due and the wait for its pong starts.
+    fn expire(&mut self) -> bool {
+        let Some(config) = self.config else {
+            return true;
+        };
+        if self.awaiting_pong {
+            return false;
+        }
+        self.awaiting_pong = true;
+        self.deadline = Instant::now() + config.timeout;
+        true
+    }
+
+    /// The client answered: schedule the next ping.
+    fn pong(&mut self) {
+        if let (Some(config), true) = (self.config, self.awaiting_pong) {
+            self.awaiting_pong = false;
+            self.deadline = Instant::now() + config.interval;
+        }
+    }
+}
+
+/// Rate limit on the partial transcripts sent to one WebSocket client.
+/// A partial arriving sooner than `interval` after the last one sent for
+/// its source is held back, replacing any held back before it, and goes
+/// out once the interval is over unless a final supersedes it first.
+struct PartialThrottle {
+    interval: Option<Duration>,
+    /// Per source: when its last partial went out, and the newest one
+    /// held back since.
+    sources: HashMap<Option<String>, (Instant, Option<TranscriptMessage>)>,
+}
+
+impl PartialThrottle {
+    /// Throttle to `rate` partials per second (None or 0 = no limit).
+    fn new(rate: Option<f32>) -> Self {
+        Self {
+            interval: rate
+                .filter(|&r| r > 0.0)
+                .map(|r| Duration::from_secs_f32(1.0 / r)),
+            sources: HashMap::new(),
+        }
+    }
+
+    /// `msg` if it goes out now; held-back partials come from [`due`](Self::due).
+    fn admit(&mut self, msg: TranscriptMessage) -> Option<TranscriptMessage> {
+        let Some(interval) = self.interval else {
+            return Some(msg);
+        };
+        if msg.msg_type != "transcript" {
+            return Some(msg);
+        }
+        let now = Instant::now();
+        if msg.is_final {
+            // The final has everything the held-back partial had
+            if let Some((_, held)) = self.sources.get_mut(&msg.speaker) {
+                *held = None;
+            }
+            return Some(msg);
+        }
+        match self.sources.get_mut(&msg.speaker) {
+            Some((sent, held)) if now < *sent + interval => {
+                *held = Some(msg);
+                None
+            }
+            Some((sent, held)) => {
+                *sent = now;
+                *held = None;
+                Some(msg)
+            }
+            None => {
+                self.sources.insert(msg.speaker.clone(), (now, None));
+                Some(msg)
+            }
+        }
+    }
+
+    /// Wait until a held-back partial may go out (forever when none is).
+    async fn tick(&self) {
+        let next = self.interval.and_then(|interval| {
+            self.sources
+                .values()
+                .filter(|(_, held)| held.is_some())
+                .map(|(sent, _)| *sent + interval)
+                .min()
+        });
+        match next {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Held-back partials whose interval is over, marked as sent.
+    fn due(&mut self) -> Vec<TranscriptMessage> {
+        let Some(interval) = self.interval else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        self.sources
+            .values_mut()
+            .filter(|(sent, held)| held.is_some() && now >= *sent + interval)
+            .filter_map(|(sent, held)| {
+                *sent = now;
+                held.take()
+            })
+            .collect()
+    }
+}
+
+/// A file uploaded to `POST /api/transcribe`, decoded to 16 kHz mono and
+/// waiting for the engine. Send the transcript, or why it failed, to
+/// `reply`.
+pub struct TranscribeRequest {
+    pub audio: AudioBuffer,
+    pub reply: oneshot::Sender<anyhow::Result<TranscriptResult>>,
+}
+
+/// Why an uploaded file wasn't transcribed.
+#[derive(Debug)]
+pub(super) enum UploadError {
+    /// Nothing is serving uploads (any more).
+    Unavailable(String),
+    /// The file isn't audio the decoder reads.
+    BadAudio(String),
+    /// The engine failed on it.
+    Failed(String),
+}
+
+/// Where decoded client audio goes, and in what chunk duration.
+struct IngestSink {
+    sender: mpsc::Sender<AudioChunk>,
+    buffer_ms: u32,
+}
+
+/// Encoding of the binary messages a client streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestFormat {
+    /// One Opus packet per message.
+    #[default]
+    Opus,
+    /// Raw 16-bit little-endian PCM, whole frames per message.
+    S16Le,
+    /// Raw 32-bit little-endian float PCM, whole frames per message.
+    F32Le,
+}
+
+impl fmt::Display for IngestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Opus => write!(f, "opus"),
+            Self::S16Le => write!(f, "s16le"),
+            Self::F32Le => write!(f, "f32le"),
+        }
+    }
+}
+
+/// Stream format of an `/ingest` connection, from its query string, e.g.
+/// `/ingest?format=s16le&sample_rate=48000&channels=2`. PCM is taken at
+/// any rate and downmixed to mono; Opus is always decoded to 16 kHz mono.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IngestParams {
+    /// Session the client's transcripts are scoped to (None = the
+    /// default one, on `/`).
+    pub session: Option<String>,
+    pub format: IngestFormat,
+    pub sample_rate: u32,
+    /// Interleaved channels per frame.
+    pub channels: usize,
+}
+
+impl Default for IngestParams {
+    fn default() -> Self {
+        Self {
+            session: None,
+            format: IngestFormat::Opus,
+            sample_rate: OPUS_DECODE_RATE,
+            channels: 1,
+        }
+    }
 }
 
+/// Final transcripts replayed to new clients by default.
+pub const DEFAULT_REPLAY_TRANSCRIPTS: usize = 50;
+
 /// WebSocket server that broadcasts transcript messages to connected clients.
 pub struct TranscriptServer {
+    host: String,
     port: u16,
+    /// Ports after `port` tried when it is taken.
+    port_fallback: u16,
+    /// Final transcripts kept for clients that connect later.
+    replay: usize,
+    /// Cross-origin access for browser clients (None = same origin only).
+    cors: Option<CorsLayer>,
     state: Arc<ServerState>,
 }
 
@@ -86,10 +1008,103 @@ impl TranscriptServer {
     /// Create a new server on the specified port.
     pub fn new(port: u16) -> Self {
         let (tx, _) = broadcast::channel(256);
+        let (controls, _) = broadcast::channel(16);
         Self {
+            host: "127.0.0.1".to_string(),
             port,
-            state: Arc::new(ServerState { tx }),
+            port_fallback: 0,
+            replay: DEFAULT_REPLAY_TRANSCRIPTS,
+            cors: None,
+            state: Arc::new(ServerState {
+                tx,
+                controls,
+                ingest: Arc::new(Mutex::new(None)),
+                next_client: AtomicU64::new(1),
+                clients: Arc::new(AtomicUsize::new(0)),
+                heartbeat: Mutex::new(Some(HeartbeatConfig::default())),
+                partial_rate: Mutex::new(None),
+                stats: Mutex::new(None),
+                history: Mutex::new(HashMap::new()),
+                segments: Mutex::new(HashMap::new()),
+                rooms: Mutex::new(HashMap::new()),
+                client_sessions: Mutex::new(HashMap::new()),
+                idle_sessions: Mutex::new(HashMap::new()),
+                last_prune: Mutex::new(Instant::now()),
+                uploads: Mutex::new(None),
+                shutdown: CancellationToken::new(),
+            }),
+        }
+    }
+
+    /// Listen on `host` instead of localhost only (e.g. "0.0.0.0" to take
+    /// `/ingest` connections from other machines).
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    /// When the port is taken, listen on the first free one of the next
+    /// `count` ports instead of failing.
+    pub fn with_port_fallback(mut self, count: u16) -> Self {
+        self.port_fallback = count;
+        self
+    }
+
+    /// Let pages from `origins` (e.g. "https://captions.example.com", or
+    /// "*" for any) call the REST and SSE endpoints, for browser clients
+    /// hosted elsewhere. No origins leaves cross-origin requests blocked.
+    pub fn with_cors_origins(mut self, origins: &[String]) -> anyhow::Result<Self> {
+        if origins.is_empty() {
+            self.cors = None;
+            return Ok(self);
         }
+        let allow = if origins.iter().any(|o| o == "*") {
+            AllowOrigin::any()
+        } else {
+            let origins = origins
+                .iter()
+                .map(|o| {
+                    HeaderValue::from_str(o.trim_end_matches('/'))
+                        .map_err(|_| anyhow::anyhow!("Invalid CORS origin '{}'", o))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            AllowOrigin::list(origins)
+        };
+        self.cors = Some(
+            CorsLayer::new()
+                .allow_origin(allow)
+                .allow_methods([Method::GET, Method::POST])
+                .allow_headers([header::CONTENT_TYPE]),
+        );
+        Ok(self)
+    }
+
+    /// Replay the last `count` final transcripts to each WebSocket client
+    /// that connects, so a dashboard reconnecting mid-session keeps its
+    /// context (0 = off).
+    pub fn with_replay(mut self, count: usize) -> Self {
+        self.replay = count;
+        self
+    }
+
+    /// Ping WebSocket clients on `heartbeat`'s schedule and drop those that
+    /// don't answer (None = never ping).
+    pub fn with_heartbeat(self, heartbeat: Option<HeartbeatConfig>) -> Self {
+        *self.state.heartbeat.lock().unwrap() = heartbeat;
+        self
+    }
+
+    /// Send WebSocket clients at most `rate` partial transcripts per
+    /// second and source, keeping the newest of those in between (None =
+    /// every one). Clients can pick their own rate when they subscribe.
+    pub fn with_partial_rate(self, rate: Option<f32>) -> Self {
+        *self.state.partial_rate.lock().unwrap() = rate.filter(|&r| r > 0.0);
+        self
+    }
+
+    /// Gauge of the WebSocket and SSE clients connected, for stats.
+    pub fn connected_clients(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.state.clients)
     }
 
     /// Get a sender to publish transcript messages.
@@ -97,54 +1112,422 @@ impl TranscriptServer {
         self.state.tx.clone()
     }
 
+    /// Subscribe to control messages sent by clients.
+    pub fn controls(&self) -> broadcast::Receiver<ControlMessage> {
+        self.state.controls.subscribe()
+    }
+
+    /// Audio source fed by clients: binary WebSocket messages are decoded
+    /// and transcribed. On `/` each message is one Opus packet; on
+    /// `/ingest` it is Opus or PCM (see [`IngestParams`]), and the
+    /// connection gets back the messages about its own audio. Connect it
+    /// to the audio channel with [`AudioIngest::with_sender`].
+    pub fn audio_ingest(&self) -> AudioIngest {
+        AudioIngest {
+            slot: Arc::clone(&self.state.ingest),
+            sender: None,
+        }
+    }
+
+    /// Files uploaded to `POST /api/transcribe`, for whoever owns the
+    /// engine to transcribe. Until this is called the endpoint answers 503.
+    pub fn file_requests(&self) -> mpsc::Receiver<TranscribeRequest> {
+        let (sender, requests) = mpsc::channel(4);
+        *self.state.uploads.lock().unwrap() = Some(sender);
+        requests
+    }
+
+    /// gRPC server on `port` of the same host, serving this server's
+    /// messages, controls and file transcription with typed contracts.
+    /// It stops with this one.
+    pub fn grpc(&self, port: u16) -> GrpcServer {
+        GrpcServer::new(Arc::clone(&self.state), self.host.clone(), port)
+    }
+
+    /// Token stopping the server once cancelled: clients are sent what is
+    /// already queued for them, WebSocket connections are closed with a
+    /// Close frame and SSE streams ended, then [`run`](Self::run) returns.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.state.shutdown.clone()
+    }
+
     /// Run the server (blocks until shutdown).
     pub async fn run(&self) -> anyhow::Result<()> {
+        let listener = self.bind().await?;
+        self.serve(listener).await
+    }
+
+    /// Listen on the configured host and port, or the first free fallback
+    /// port. The port actually used is the listener's `local_addr()`.
+    pub async fn bind(&self) -> anyhow::Result<TcpListener> {
+        let last = self.port.saturating_add(self.port_fallback);
+        for port in self.port..=last {
+            match TcpListener::bind((self.host.as_str(), port)).await {
+                Ok(listener) => {
+                    if port != self.port {
+                        warn!(
+                            taken = self.port,
+                            port, "WebSocket port taken, listening on the next free one"
+                        );
+                    }
+                    return Ok(listener);
+                }
+                Err(e) if e.kind() == ErrorKind::AddrInUse && port < last => {}
+                Err(e) => return Err(bind_error(&self.host, self.port, last, e)),
+            }
+        }
+        unreachable!("the last port's error is returned")
+    }
+
+    /// Serve clients on `listener` (see [`bind`](Self::bind)) until the
+    /// [shutdown token](Self::shutdown_token) is cancelled.
+    pub async fn serve(&self, listener: TcpListener) -> anyhow::Result<()> {
         let app = Router::new()
             .route("/", get(ws_handler))
+            .route("/ws/:session_id", get(session_handler))
+            .route("/ingest", get(ingest_handler))
+            .route("/events", get(events_handler))
             .route("/health", get(health_handler))
+            .route("/stats", get(stats_handler))
+            .route("/control", post(control_handler))
+            .route("/api/session/:id/subtitles.srt", get(srt_handler))
+            .route("/api/session/:id/subtitles.vtt", get(vtt_handler))
+            .route(
+                "/api/transcribe",
+                post(transcribe_handler).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+            )
             .with_state(self.state.clone());
+        let app = match self.cors {
+            Some(ref cors) => app.layer(cors.clone()),
+            None => app,
+        };
+
+        // Route messages to their sessions, keeping the latest engine stats
+        // for clients that poll and final transcripts for clients that
+        // connect later
+        let state = Arc::clone(&self.state);
+        let replay = self.replay;
+        let mut messages = state.tx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let result = tokio::select! {
+                    biased;
+                    result = messages.recv() => result,
+                    _ = state.shutdown.cancelled() => break,
+                };
+                match result {
+                    Ok(msg) => state.dispatch(msg, replay),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
 
-        let addr = format!("127.0.0.1:{}", self.port);
-        let listener = tokio::net::TcpListener::bind(&addr).await?;
-        info!(addr, "WebSocket server listening");
+        let addr = listener.local_addr()?;
+        info!(%addr, "WebSocket server listening");
 
-        axum::serve(listener, app).await?;
+        axum::serve(listener, app)
+            .with_graceful_shutdown(self.state.shutdown.clone().cancelled_owned())
+            .await?;
+        info!("WebSocket server stopped");
         Ok(())
     }
 }
 
+/// Why listening on `host`, ports `first..=last`, failed, in terms of
+/// what to do about it.
+fn bind_error(host: &str, first: u16, last: u16, e: std::io::Error) -> anyhow::Error {
+    let ports = if first == last {
+        format!("port {}", first)
+    } else {
+        format!("ports {}-{}", first, last)
+    };
+    match e.kind() {
+        ErrorKind::AddrInUse => anyhow::anyhow!(
+            "WebSocket {} already in use on {} (is another VoxVault running?); \
+             pick another with --ws-port",
+            ports,
+            host
+        ),
+        ErrorKind::AddrNotAvailable => anyhow::anyhow!(
+            "Can't listen on {}: not an address of this machine \
+             (use 0.0.0.0 for every interface)",
+            host
+        ),
+        ErrorKind::PermissionDenied => anyhow::anyhow!(
+            "Not allowed to listen on {} {} (ports below 1024 need privileges)",
+            host,
+            ports
+        ),
+        _ => anyhow::Error::new(e).context(format!("Failed to listen on {} {}", host, ports)),
+    }
+}
+
 /// Health check endpoint.
 async fn health_handler() -> &'static str {
     "ok"
 }
 
+/// Body of `GET /stats`.
+#[derive(Serialize)]
+struct StatsResponse {
+    #[serde(flatten)]
+    stats: EngineStats,
+    clients: usize,
+}
+
+/// Latest engine stats (counters, rolling performance, GPU memory) and the
+/// clients connected, or 204 before the first `stats` message.
+async fn stats_handler(State(state): State<Arc<ServerState>>) -> impl IntoResponse {
+    match state.stats.lock().ok().and_then(|stats| *stats) {
+        Some(stats) => Json(StatsResponse {
+            stats,
+            clients: state.clients.load(Ordering::Relaxed),
+        })
+        .into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Control endpoint for clients that don't hold a WebSocket open (e.g. the
+/// desktop app's settings): accepts the same JSON as control messages.
+async fn control_handler(
+    State(state): State<Arc<ServerState>>,
+    Json(control): Json<ControlMessage>,
+) -> StatusCode {
+    info!(?control, "Control request received");
+    let _ = state.controls.send(control);
+    StatusCode::ACCEPTED
+}
+
+/// One-shot transcription of an uploaded audio file (multipart, the first
+/// file field; WAV, FLAC, MP3 or OGG), answering with the whole
+/// [`TranscriptResult`], word confidences and timings included.
+async fn transcribe_handler(
+    State(state): State<Arc<ServerState>>,
+    mut multipart: Multipart,
+) -> Result<Json<TranscriptResult>, (StatusCode, String)> {
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, e);
+
+    let field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.file_name().is_some() => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return Err(bad_request("No file in the upload".to_string())),
+            Err(e) => return Err(bad_request(format!("Invalid upload: {}", e))),
+        }
+    };
+    let file_name = field.file_name().unwrap_or_default().to_string();
+    let bytes = field
+        .bytes()
+        .await
+        .map_err(|e| bad_request(format!("Invalid upload: {}", e)))?;
+    info!(
+        file = file_name,
+        bytes = bytes.len(),
+        "File transcription requested"
+    );
+
+    match state.transcribe_file(bytes.to_vec(), file_name).await {
+        Ok(transcript) => Ok(Json(transcript)),
+        Err(UploadError::Unavailable(e)) => Err((StatusCode::SERVICE_UNAVAILABLE, e)),
+        Err(UploadError::BadAudio(e)) => Err(bad_request(e)),
+        Err(UploadError::Failed(e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+/// Decode an uploaded file to 16 kHz mono, its name's extension hinting
+/// at the format.
+fn decode_upload(bytes: Vec<u8>, file_name: &str) -> anyhow::Result<AudioBuffer> {
+    let extension = std::path::Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str());
+    let decoder = AudioFileDecoder::from_source(Box::new(Cursor::new(bytes)), extension)?;
+    let sample_rate = decoder.sample_rate();
+    let samples = decoder.decode_all()?;
+    let mut resampler = Resampler::new(ResamplerQuality::High, UPLOAD_SAMPLE_RATE);
+    Ok(AudioBuffer::new(
+        resampler.process(&samples, sample_rate),
+        UPLOAD_SAMPLE_RATE,
+    ))
+}
+
+/// Subtitles of session `id` ("default" for the clients of `/`) as SRT.
+async fn srt_handler(
+    Path(id): Path<String>,
+    State(state): State<Arc<ServerState>>,
+) -> impl IntoResponse {
+    subtitles_response(&state, id, SubtitleFormat::Srt)
+}
+
+/// Subtitles of session `id` ("default" for the clients of `/`) as WebVTT.
+async fn vtt_handler(
+    Path(id): Path<String>,
+    State(state): State<Arc<ServerState>>,
+) -> impl IntoResponse {
+    subtitles_response(&state, id, SubtitleFormat::Vtt)
+}
+
+/// Subtitle file of a session's final transcripts so far, as a download,
+/// or 404 if it has none.
+fn subtitles_response(state: &ServerState, id: String, format: SubtitleFormat) -> Response {
+    let session = Some(id.clone()).filter(|id| id != "default");
+    let segments: Vec<Segment> = match state.segments.lock().unwrap().get(&session) {
+        Some(segments) if !segments.is_empty() => segments.iter().cloned().collect(),
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("No transcripts in session '{}'", id),
+            )
+                .into_response()
+        }
+    };
+    let file_name = format!("{}.{}", id, format.extension());
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        subtitles::render(format, &segments),
+    )
+        .into_response()
+}
+
+/// Session an `/events` client follows (None = the default one).
+#[derive(Debug, Deserialize)]
+struct EventsParams {
+    session: Option<String>,
+}
+
+/// The messages WebSocket clients get, as Server-Sent Events named after
+/// their type, for clients that can't hold a WebSocket (e.g.
+/// `curl -N http://localhost:8765/events?session=standup`).
+async fn events_handler(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<EventsParams>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    info!(session = params.session, "SSE client connected");
+    let rx = match params.session {
+        Some(ref session) => state.join(session),
+        None => state.tx.subscribe(),
+    };
+    let scoped = params.session.is_some();
+    let client = ClientGuard::new(&state);
+    let events = stream::unfold(
+        (rx, state, client),
+        move |(mut rx, state, client)| async move {
+            loop {
+                // Queued messages go out before the stream ends
+                let result = tokio::select! {
+                    biased;
+                    result = rx.recv() => result,
+                    _ = state.shutdown.cancelled() => return None,
+                };
+                match result {
+                    // Other sessions' messages are for their rooms
+                    Ok(msg) if !scoped && state.session_of(&msg).is_some() => {}
+                    Ok(msg) => match Event::default().event(&msg.msg_type).json_data(&msg) {
+                        Ok(event) => return Some((Ok(event), (rx, state, client))),
+                        Err(e) => error!("Failed to serialize message: {}", e),
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "SSE client lagging behind, skipped messages");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
 /// WebSocket upgrade handler.
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<ServerState>>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, None))
 }
 
-/// Handle a single WebSocket connection.
-async fn handle_socket(mut socket: WebSocket, state: Arc<ServerState>) {
-    info!("WebSocket client connected");
-    let mut rx = state.tx.subscribe();
+/// WebSocket upgrade handler for the clients of one session.
+async fn session_handler(
+    ws: WebSocketUpgrade,
+    Path(session_id): Path<String>,
+    State(state): State<Arc<ServerState>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, state, Some(session_id)))
+}
+
+/// Handle a single WebSocket connection, to the default session or to
+/// `session`'s room. Audio the client streams is scoped to the same
+/// session.
+async fn handle_socket(mut socket: WebSocket, state: Arc<ServerState>, session: Option<String>) {
+    let client_id = state.next_client.fetch_add(1, Ordering::Relaxed);
+    info!(client_id, session, "WebSocket client connected");
+    let _client = ClientGuard::new(&state);
+    let mut heartbeat = Heartbeat::new(*state.heartbeat.lock().unwrap());
+    let mut rx = match session {
+        Some(ref session) => state.join(session),
+        None => state.tx.subscribe(),
+    };
+    let label = format!("client{}", client_id);
+    let _membership = session
+        .as_deref()
+        .map(|session| state.enter_session(&label, session));
+    let mut audio = ClientAudio::new(label.clone(), IngestParams::default());
+    let mut subscription = Subscription::default();
+    let partial_rate = *state.partial_rate.lock().unwrap();
+    let mut throttle = PartialThrottle::new(partial_rate);
+    let mut encoding = Encoding::Json;
+    if !greet(&mut socket).await {
+        info!("WebSocket client disconnected (send failed)");
+        return;
+    }
+
+    // Catch up on the session so far. Transcripts broadcast since
+    // subscribing may already be in the history; don't send them twice.
+    let replayed: Vec<TranscriptMessage> = state
+        .history
+        .lock()
+        .unwrap()
+        .get(&session)
+        .map_or_else(Vec::new, |transcripts| {
+            transcripts.iter().cloned().collect()
+        });
+    let replayed_until = replayed.last().map(|msg| msg.timestamp);
+    for mut msg in replayed {
+        msg.replayed = true;
+        let Ok(frame) = encoding.frame(&msg) else {
+            continue;
+        };
+        if socket.send(frame).await.is_err() {
+            info!("WebSocket client disconnected (send failed)");
+            return;
+        }
+    }
 
     loop {
         tokio::select! {
+            // Messages already queued go out before the server closes
+            biased;
             // Forward broadcast messages to this client
             result = rx.recv() => {
                 match result {
+                    Ok(msg)
+                        if msg.msg_type == "transcript"
+                            && msg.is_final
+                            && replayed_until.is_some_and(|until| msg.timestamp <= until) => {}
+                    // Other sessions' messages are for their rooms
+                    Ok(msg) if session.is_none() && state.session_of(&msg).is_some() => {}
+                    Ok(msg) if !subscription.wants(&msg) => {}
                     Ok(msg) => {
-                        let json = match serde_json::to_string(&msg) {
-                            Ok(j) => j,
-                            Err(e) => {
-                                error!("Failed to serialize message: {}", e);
-                                continue;
-                            }
+                        let Some(msg) = throttle.admit(msg) else {
+                            continue;
                         };
-                        if socket.send(Message::Text(json.into())).await.is_err() {
+                        if !send_message(&mut socket, encoding, &msg).await {
                             info!("WebSocket client disconnected (send failed)");
                             break;
                         }
@@ -158,6 +1541,33 @@ async fn handle_socket(mut socket: WebSocket, state: Arc<ServerState>) {
                     }
                 }
             }
+            _ = state.shutdown.cancelled() => {
+                info!(client_id, "Closing WebSocket client (server shutting down)");
+                close_going_away(&mut socket).await;
+                break;
+            }
+            // Partials held back by the throttle, once their turn comes
+            _ = throttle.tick() => {
+                let mut sent = true;
+                for msg in throttle.due() {
+                    sent = sent && send_message(&mut socket, encoding, &msg).await;
+                }
+                if !sent {
+                    info!("WebSocket client disconnected (send failed)");
+                    break;
+                }
+            }
+            // Ping the client, or drop it if it missed the last ping
+            _ = heartbeat.tick() => {
+                if !heartbeat.expire() {
+                    info!(client_id, "WebSocket client disconnected (no pong)");
+                    break;
+                }
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    info!(client_id, "WebSocket client disconnected (send failed)");
+                    break;
+                }
+            }
             // Handle incoming messages from client (ping/pong, close)
             result = socket.recv() => {
                 match result {
@@ -170,8 +1580,47 @@ async fn handle_socket(mut socket: WebSocket, state: Arc<ServerState>) {
                             break;
                         }
                     }
-                    Some(Ok(_)) => {
-                        // Ignore other messages from client
+                    Some(Ok(Message::Pong(_))) => heartbeat.pong(),
+                    Some(Ok(Message::Binary(packet))) => {
+                        audio.ingest(&state, &packet).await;
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<ClientRequest>(&text) {
+                            Ok(ClientRequest::Subscribe(subscribed)) => {
+                                info!(client_id, ?subscribed, "Client subscribed");
+                                throttle = PartialThrottle::new(
+                                    subscribed.max_partial_rate.or(partial_rate),
+                                );
+                                subscription = subscribed;
+                                continue;
+                            }
+                            Ok(ClientRequest::Hello { versions, encoding: wanted }) => {
+                                match answer_hello(&mut socket, client_id, &versions, wanted).await {
+                                    Some(agreed) => encoding = agreed,
+                                    None => break,
+                                }
+                                continue;
+                            }
+                            Err(_) => {}
+                        }
+                        match serde_json::from_str::<ControlMessage>(&text) {
+                            Ok(control) => {
+                                info!(client_id, ?control, "Control message received");
+                                let _ = state.controls.send(control);
+                            }
+                            Err(e) => {
+                                warn!(client_id, "Invalid control message: {}", e);
+                                let msg = TranscriptMessage::error(
+                                    ErrorCode::InvalidControl,
+                                    format!("Invalid control message: {}", e),
+                                );
+                                if let Ok(frame) = encoding.frame(&msg) {
+                                    if socket.send(frame).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
                     }
                     Some(Err(e)) => {
                         warn!("WebSocket error: {}", e);
@@ -182,3 +1631,365 @@ async fn handle_socket(mut socket: WebSocket, state: Arc<ServerState>) {
         }
     }
 }
+
+/// Ingest WebSocket upgrade handler.
+async fn ingest_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<IngestParams>,
+    State(state): State<Arc<ServerState>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_ingest(socket, state, params))
+}
+
+/// Handle a connection streaming audio in: its frames are transcribed, and
+/// the messages attributed to it (transcripts, translations, VAD) are sent
+/// back, while other sources' are not.
+async fn handle_ingest(mut socket: WebSocket, state: Arc<ServerState>, params: IngestParams) {
+    let client_id = state.next_client.fetch_add(1, Ordering::Relaxed);
+    let label = format!("client{}", client_id);
+    info!(
+        client_id,
+        session = params.session,
+        format = %params.format,
+        sample_rate = params.sample_rate,
+        channels = params.channels,
+        "Ingest client connected"
+    );
+
+    let refusal = if state.ingest.lock().unwrap().is_none() {
+        Some("Audio ingest not enabled (start the CLI with --ws-audio)")
+    } else if params.sample_rate == 0 || params.channels == 0 {
+        Some("Ingest needs a non-zero sample rate and channel count")
+    } else {
+        None
+    };
+    if let Some(refusal) = refusal {
+        warn!(client_id, "Refusing ingest client: {}", refusal);
+        let msg = TranscriptMessage::error(ErrorCode::IngestRefused, refusal.to_string());
+        if let Ok(json) = serde_json::to_string(&msg) {
+            let _ = socket.send(Message::Text(json)).await;
+        }
+        let _ = socket.close().await;
+        return;
+    }
+
+    let mut rx = state.tx.subscribe();
+    let _membership = params
+        .session
+        .as_deref()
+        .map(|session| state.enter_session(&label, session));
+    let mut audio = ClientAudio::new(label.clone(), params);
+    let _client = ClientGuard::new(&state);
+    let mut heartbeat = Heartbeat::new(*state.heartbeat.lock().unwrap());
+    let mut throttle = PartialThrottle::new(*state.partial_rate.lock().unwrap());
+    let mut encoding = Encoding::Json;
+    if !greet(&mut socket).await {
+        info!(client_id, "Ingest client disconnected (send failed)");
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            biased;
+            // Echo this client's messages back
+            result = rx.recv() => {
+                match result {
+                    Ok(msg) if msg.speaker.as_deref() == Some(label.as_str()) => {
+                        let Some(msg) = throttle.admit(msg) else {
+                            continue;
+                        };
+                        if !send_message(&mut socket, encoding, &msg).await {
+                            info!(client_id, "Ingest client disconnected (send failed)");
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "Ingest client lagging behind, skipped messages");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            _ = state.shutdown.cancelled() => {
+                info!(client_id, "Closing ingest client (server shutting down)");
+                close_going_away(&mut socket).await;
+                break;
+            }
+            _ = throttle.tick() => {
+                let mut sent = true;
+                for msg in throttle.due() {
+                    sent = sent && send_message(&mut socket, encoding, &msg).await;
+                }
+                if !sent {
+                    info!(client_id, "Ingest client disconnected (send failed)");
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if !heartbeat.expire() {
+                    info!(client_id, "Ingest client disconnected (no pong)");
+                    break;
+                }
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    info!(client_id, "Ingest client disconnected (send failed)");
+                    break;
+                }
+            }
+            result = socket.recv() => {
+                match result {
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!(client_id, "Ingest client disconnected");
+                        break;
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        if socket.send(Message::Pong(data)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Pong(_))) => heartbeat.pong(),
+                    Some(Ok(Message::Binary(frame))) => {
+                        audio.ingest(&state, &frame).await;
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        // Controls go to `/` or `POST /control`
+                        if let Ok(ClientRequest::Hello { versions, encoding: wanted }) =
+                            serde_json::from_str::<ClientRequest>(&text)
+                        {
+                            match answer_hello(&mut socket, client_id, &versions, wanted).await {
+                                Some(agreed) => encoding = agreed,
+                                None => break,
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        warn!("WebSocket error: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Send `msg` to a client in `encoding`. Returns false if the connection
+/// is gone.
+async fn send_message(socket: &mut WebSocket, encoding: Encoding, msg: &TranscriptMessage) -> bool {
+    match encoding.frame(msg) {
+        Ok(frame) => socket.send(frame).await.is_ok(),
+        Err(e) => {
+            error!("Failed to serialize message: {}", e);
+            true
+        }
+    }
+}
+
+/// Send a client the `hello` listing the protocol versions and encodings
+/// the server speaks. Returns false if the connection is gone.
+async fn greet(socket: &mut WebSocket) -> bool {
+    let hello = TranscriptMessage::hello(PROTOCOL_VERSION, Encoding::Json);
+    let Ok(json) = serde_json::to_string(&hello) else {
+        return true;
+    };
+    socket.send(Message::Text(json)).await.is_ok()
+}
+
+/// Answer a client's `hello` with the newest protocol version both speak
+/// and the `encoding` it asked for, or an error and a Close frame if there
+/// is no such version. The answer itself is JSON. Returns the encoding of
+/// the messages that follow, or None if the connection is closed.
+async fn answer_hello(
+    socket: &mut WebSocket,
+    client_id: u64,
+    versions: &[u32],
+    encoding: Encoding,
+) -> Option<Encoding> {
+    let (msg, version) = match negotiate_version(versions) {
+        Some(version) => (TranscriptMessage::hello(version, encoding), Some(version)),
+        None => (
+            TranscriptMessage::error(
+                ErrorCode::UnsupportedProtocol,
+                format!(
+                    "No supported protocol version in {:?}: this server speaks {:?}",
+                    versions, SUPPORTED_VERSIONS
+                ),
+            ),
+            None,
+        ),
+    };
+    let Ok(json) = serde_json::to_string(&msg) else {
+        return Some(Encoding::Json);
+    };
+    if socket.send(Message::Text(json)).await.is_err() {
+        return None;
+    }
+    match version {
+        Some(version) => {
+            info!(
+                client_id,
+                version,
+                ?encoding,
+                "Client protocol version agreed"
+            );
+            Some(encoding)
+        }
+        None => {
+            warn!(
+                client_id,
+                ?versions,
+                "Client speaks no supported protocol version"
+            );
+            let frame = CloseFrame {
+                code: close_code::PROTOCOL,
+                reason: "Unsupported protocol version".into(),
+            };
+            let _ = socket.send(Message::Close(Some(frame))).await;
+            None
+        }
+    }
+}
+
+/// Close a client's connection because the server is shutting down.
+async fn close_going_away(socket: &mut WebSocket) {
+    let frame = CloseFrame {
+        code: close_code::AWAY,
+        reason: "Server shutting down".into(),
+    };
+    let _ = socket.send(Message::Close(Some(frame))).await;
+}
+
+/// An [`AudioSource`] producing the audio WebSocket clients stream in.
+///
+/// Clients are accepted whether or not the ingest is started; their audio
+/// is discarded until `start()` and after `stop()`. Each connection gets
+/// its own label ("client1", "client2", ...) so it is transcribed
+/// separately.
+pub struct AudioIngest {
+    slot: Arc<Mutex<Option<IngestSink>>>,
+    sender: Option<mpsc::Sender<AudioChunk>>,
+}
+
+impl AudioIngest {
+    /// Send decoded client audio to this channel.
+    pub fn with_sender(mut self, sender: mpsc::Sender<AudioChunk>) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+}
+
+impl AudioSource for AudioIngest {
+    fn start(&mut self, buffer_duration_ms: u32) -> anyhow::Result<()> {
+        let Some(sender) = self.sender.take() else {
+            anyhow::bail!("WebSocket audio ingest has no output channel or was already started");
+        };
+        *self.slot.lock().unwrap() = Some(IngestSink {
+            sender,
+            buffer_ms: buffer_duration_ms.max(1),
+        });
+        info!("Accepting audio from WebSocket clients");
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        // Drops the sender; connections only hold it while sending
+        self.slot.lock().unwrap().take();
+    }
+}
+
+/// Per-connection decoding state for client audio.
+struct ClientAudio {
+    label: String,
+    params: IngestParams,
+    /// Created on the first Opus packet, so listen-only clients cost nothing.
+    decoder: Option<OpusDecoder>,
+    pending: Vec<f32>,
+    /// Whether a packet was already dropped (logged once per connection).
+    warned: bool,
+}
+
+impl ClientAudio {
+    fn new(label: String, params: IngestParams) -> Self {
+        Self {
+            label,
+            params,
+            decoder: None,
+            pending: Vec::new(),
+            warned: false,
+        }
+    }
+
+    /// Decode one message of audio and forward every complete chunk.
+    async fn ingest(&mut self, state: &ServerState, packet: &[u8]) {
+        let Some((sender, buffer_ms)) = state
+            .ingest
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|sink| (sink.sender.clone(), sink.buffer_ms))
+        else {
+            if !self.warned {
+                warn!(
+                    client = self.label,
+                    "Ignoring client audio: audio ingest not enabled"
+                );
+                self.warned = true;
+            }
+            return;
+        };
+
+        let samples = match self.decode(packet) {
+            Ok(s) => s,
+            Err(e) => {
+                if !self.warned {
+                    warn!(client = self.label, "Dropping client audio: {:#}", e);
+                    self.warned = true;
+                }
+                return;
+            }
+        };
+        self.pending.extend_from_slice(&samples);
+
+        let sample_rate = self.sample_rate();
+        let chunk_samples = ((sample_rate as usize * buffer_ms as usize) / 1000).max(1);
+        while self.pending.len() >= chunk_samples {
+            let chunk = AudioChunk {
+                samples: self.pending.drain(..chunk_samples).collect(),
+                sample_rate,
+                label: Some(self.label.clone()),
+            };
+            if sender.send(chunk).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Rate of the decoded audio.
+    fn sample_rate(&self) -> u32 {
+        match self.params.format {
+            IngestFormat::Opus => OPUS_DECODE_RATE,
+            IngestFormat::S16Le | IngestFormat::F32Le => self.params.sample_rate,
+        }
+    }
+
+    /// Decode one message to mono samples.
+    fn decode(&mut self, packet: &[u8]) -> anyhow::Result<Vec<f32>> {
+        let interleaved = match self.params.format {
+            IngestFormat::Opus => {
+                let decoder = match self.decoder {
+                    Some(ref mut d) => d,
+                    None => self.decoder.insert(OpusDecoder::new()?),
+                };
+                return decoder.decode(packet);
+            }
+            IngestFormat::S16Le => s16le_samples(packet),
+            IngestFormat::F32Le => f32le_samples(packet),
+        };
+        let channels = self.params.channels;
+        if channels == 1 {
+            return Ok(interleaved);
+        }
+        Ok(interleaved
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect())
+    }
+}
@@ -0,0 +1,158 @@
+//! Context across the chunks a long buffer is decoded in.
+//!
+//! Decoded on its own, each chunk starts cold: a word cut by the chunk
+//! edge comes out as two halves, and speech in the chunks' overlap is
+//! transcribed twice. So every chunk after the first is decoded with the
+//! last [`CONTEXT_MS`] of the previous chunk ahead of it, and the two hand
+//! off in the middle of the audio they share. The earlier chunk keeps its
+//! text up to the first word starting past the handoff, the later one
+//! from that word on.
+//!
+//! Decoder positions map to audio time, which lines up the two decodes;
+//! moving the cut to a word start keeps the word straddling the handoff
+//! in one piece, and in one chunk only.
+
+use voxtral_mini_realtime::audio::AudioChunk;
+use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
+
+/// Audio of the previous chunk decoded ahead of each later chunk.
+pub const CONTEXT_MS: usize = 1000;
+/// Audio covered by one decoder position.
+pub const POSITION_MS: usize = 80;
+
+/// The audio a chunk is decoded from, and the part of it whose text the
+/// chunk keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkWindow {
+    /// First sample decoded, ahead of the chunk's own for later chunks.
+    pub start_sample: usize,
+    /// Sample after the last one decoded.
+    pub end_sample: usize,
+    /// Samples into the window where the chunk's text starts.
+    pub keep_from: usize,
+    /// Samples into the window where the next chunk's text starts (none
+    /// for the last chunk).
+    pub keep_until: Option<usize>,
+}
+
+/// Windows of `chunks`, consecutive chunks of audio at `sample_rate`.
+pub fn windows(chunks: &[AudioChunk], sample_rate: u32) -> Vec<ChunkWindow> {
+    let context = sample_rate as usize * CONTEXT_MS / 1000;
+    // Chunk `i` hands off to chunk `i + 1` half the context before its end
+    let handoffs: Vec<usize> = chunks
+        .windows(2)
+        .map(|pair| pair[0].end_sample.saturating_sub(context / 2))
+        .collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let start_sample = match i.checked_sub(1) {
+                Some(previous) => chunk
+                    .start_sample
+                    .min(chunks[previous].end_sample.saturating_sub(context)),
+                None => chunk.start_sample,
+            };
+            ChunkWindow {
+                start_sample,
+                end_sample: chunk.end_sample,
+                keep_from: i.checked_sub(1).map_or(0, |p| handoffs[p] - start_sample),
+                keep_until: handoffs.get(i).map(|&h| h - start_sample),
+            }
+        })
+        .collect()
+}
+
+/// Decoder positions covering `samples` of audio at `sample_rate`.
+pub fn positions(samples: usize, sample_rate: u32) -> usize {
+    samples / (sample_rate as usize * POSITION_MS / 1000)
+}
+
+/// What becomes of a text token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Before the kept range: another chunk's text.
+    Skip,
+    Keep,
+    /// Past the kept range: this and all later tokens are the next chunk's.
+    Stop,
+}
+
+/// Picks the text tokens of a decode that fall in a range of positions,
+/// with both ends moved to the next word start.
+#[derive(Debug, Clone)]
+pub struct TokenFilter {
+    from: usize,
+    until: Option<usize>,
+    /// Text tokens skipped so far, to tell where the next word starts.
+    skipped: Vec<u32>,
+    keeping: bool,
+}
+
+impl TokenFilter {
+    /// Filter keeping the text generated from position `from` (prefix
+    /// included) up to position `until`, if any.
+    pub fn new(from: usize, until: Option<usize>) -> Self {
+        Self {
+            from,
+            until,
+            skipped: Vec::new(),
+            keeping: false,
+        }
+    }
+
+    /// Verdict on text token `id` generated at position `pos`, after the
+    /// tokens in `kept`.
+    pub fn check(
+        &mut self,
+        tokenizer: &VoxtralTokenizer,
+        kept: &[u32],
+        id: u32,
+        pos: usize,
+    ) -> Verdict {
+        if !self.keeping {
+            if pos < self.from || !starts_word(tokenizer, &self.skipped, id) {
+                self.skipped.push(id);
+                return Verdict::Skip;
+            }
+            self.keeping = true;
+        } else if self.until.is_some_and(|until| pos >= until) && starts_word(tokenizer, kept, id) {
+            return Verdict::Stop;
+        }
+        Verdict::Keep
+    }
+
+    /// The text tokens of `generated`, one token per decoder position
+    /// (prefix included), that the filter keeps.
+    pub fn apply(mut self, tokenizer: &VoxtralTokenizer, generated: &[i32]) -> Vec<u32> {
+        let mut kept = Vec::new();
+        for (pos, &token) in generated.iter().enumerate() {
+            // Control tokens are below 1000
+            if token < 1000 {
+                continue;
+            }
+            match self.check(tokenizer, &kept, token as u32, pos) {
+                Verdict::Skip => {}
+                Verdict::Keep => kept.push(token as u32),
+                Verdict::Stop => break,
+            }
+        }
+        kept
+    }
+}
+
+/// Whether token `id` starts a new word after the tokens in `before`.
+fn starts_word(tokenizer: &VoxtralTokenizer, before: &[u32], id: u32) -> bool {
+    if before.is_empty() {
+        return true;
+    }
+    let (Ok(text), Ok(extended)) = (
+        tokenizer.decode(before),
+        tokenizer.decode(&[before, &[id]].concat()),
+    ) else {
+        return true;
+    };
+    extended
+        .get(text.len()..)
+        .is_some_and(|added| added.starts_with(char::is_whitespace))
+}
@@ -2,29 +2,325 @@ use anyhow::{bail, Context, Result};
 use burn::backend::wgpu::WgpuDevice;
 use burn::backend::Wgpu;
 use burn::tensor::{Tensor, TensorData};
-use std::path::PathBuf;
-use std::time::Instant;
-use tracing::info;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
 use voxtral_mini_realtime::audio::{
     chunk::{chunk_audio, needs_chunking, ChunkConfig},
     mel::{MelConfig, MelSpectrogram},
     pad::{pad_audio, PadConfig},
-    AudioBuffer,
+    AudioBuffer, AudioChunk,
 };
 use voxtral_mini_realtime::gguf::loader::Q4ModelLoader;
 use voxtral_mini_realtime::gguf::model::Q4VoxtralModel;
 use voxtral_mini_realtime::models::time_embedding::TimeEmbedding;
 use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
 
-use super::types::TranscriptResult;
+use super::boundary::{self, ChunkWindow, TokenFilter};
+use super::cancel::{CancelToken, Cancelled};
+use super::gpu::{self, GpuMemory};
+use super::hallucination::{self, HallucinationAction};
+use super::hotwords::Hotwords;
+use super::integrity;
+use super::language::{reported_language, LanguageBias, LanguageHint};
+use super::prompt::PromptEncoder;
+use super::stats::RollingStats;
+use super::transcriber::TranscriptionEngine;
+use super::types::{FileTranscript, TranscriptResult};
+use crate::audio::file::decode_file;
+use crate::audio::resample::{Resampler, ResamplerQuality};
 
 type Backend = Wgpu;
 
+/// Sample rate the model takes.
+const SAMPLE_RATE: u32 = 16000;
+
+/// One second of 16kHz audio, fed through the model by the warm-up pass.
+const WARM_UP_SAMPLES: usize = 16000;
+
+/// Decoder delay the model was trained around (480 ms), and the one its
+/// own decode loop assumes.
+pub const DEFAULT_DELAY: usize = 6;
+/// Longest decoder delay the model supports (2.4 s).
+pub const MAX_DELAY: usize = 30;
+
+/// Mel frames (10 ms each) per chunk of a long buffer by default (12 s).
+pub const DEFAULT_MAX_MEL_FRAMES: usize = 1200;
+/// Shortest chunk allowed (2 s); shorter ones cut most sentences.
+pub const MIN_MEL_FRAMES: usize = 200;
+/// Longest chunk allowed (30 s), a segment's maximum duration.
+pub const MAX_MEL_FRAMES: usize = 3000;
+
+/// Named latency/accuracy trade-offs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LatencyPreset {
+    /// 240 ms delay: text sooner, more errors on fast or unclear speech.
+    LowLatency,
+    /// 480 ms delay, the model's default.
+    #[default]
+    Balanced,
+    /// 960 ms delay: more audio heard before each token.
+    Accurate,
+}
+
+impl LatencyPreset {
+    /// Decoder delay in tokens (1 token = 80 ms).
+    pub fn delay(self) -> usize {
+        match self {
+            Self::LowLatency => 3,
+            Self::Balanced => DEFAULT_DELAY,
+            Self::Accurate => 12,
+        }
+    }
+}
+
+impl FromStr for LatencyPreset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low-latency" | "low" => Ok(Self::LowLatency),
+            "balanced" => Ok(Self::Balanced),
+            "accurate" => Ok(Self::Accurate),
+            other => bail!(
+                "Unknown latency preset '{}': expected 'low-latency', 'balanced' or 'accurate'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for LatencyPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LowLatency => write!(f, "low-latency"),
+            Self::Balanced => write!(f, "balanced"),
+            Self::Accurate => write!(f, "accurate"),
+        }
+    }
+}
+
+/// Decoding settings of a [`VoxtralEngine`].
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    /// Divide the logits of text tokens already generated in the chunk by
+    /// this (1.0 = off). Values around 1.1–1.3 break phrase loops.
+    pub repetition_penalty: f32,
+    /// Never generate a token that repeats an n-gram of this many text
+    /// tokens already in the chunk (0 = off).
+    pub no_repeat_ngram_size: usize,
+    /// What to do with transcripts that look hallucinated (text heard in
+    /// silence or noise).
+    pub hallucinations: HallucinationAction,
+    /// Fall back to wgpu's CPU adapter when the configured device has no
+    /// usable GPU adapter, instead of failing to load.
+    pub cpu_fallback: bool,
+    /// Transcribe a second of silence at the end of `load()`, so shader
+    /// compilation doesn't delay the first real segment.
+    pub warm_up: bool,
+    /// Unload the model after this long without transcriptions, freeing
+    /// GPU memory until the next request (None = keep it loaded).
+    pub idle_unload_after: Option<Duration>,
+    /// Chunks of a long buffer decoded concurrently (1 = one at a time).
+    /// Each chunk in flight holds its own activations and KV cache, so
+    /// GPU memory bounds this; 2–3 overlap one chunk's mel extraction
+    /// and token decoding with another's GPU work. In streaming decoding
+    /// the chunks of a wave all follow on from the text before the wave
+    /// (not from each other), and report their text once the wave is done
+    /// instead of token by token.
+    pub parallel_chunks: usize,
+    /// Tokens (80 ms each) the decoder lags the audio by, 1 to
+    /// [`MAX_DELAY`]. Longer delays let each token hear more of what
+    /// follows, for more accurate text that appears later; see
+    /// [`LatencyPreset`].
+    pub delay: usize,
+    /// Mel frames per chunk a long buffer is split into, [`MIN_MEL_FRAMES`]
+    /// to [`MAX_MEL_FRAMES`]. Encoder activations and the KV cache grow
+    /// with the chunk: longer chunks cut fewer sentences but need more GPU
+    /// memory (see `peak_inference_bytes` in [`EngineStats`]), so lower it
+    /// on GPUs that run out.
+    pub max_mel_frames: usize,
+    /// Mel frames consecutive chunks share, so a word at a cut is heard
+    /// whole in one of them (None = the model's default). Less than half
+    /// of `max_mel_frames`. Each later chunk is decoded with at least the
+    /// last second of the one before anyway, and the text the two share
+    /// is kept once (see [`boundary`](super::boundary)).
+    pub chunk_overlap_frames: Option<usize>,
+    /// Seed the backend's random number generator with this at load, for
+    /// runs that must repeat exactly (regression tests). Decoding is
+    /// greedy, so what varies between runs otherwise is GPU scheduling;
+    /// a seed therefore also requires `parallel_chunks` = 1. Results are
+    /// reproducible on the same machine, not across GPUs or drivers.
+    pub seed: Option<u64>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            repetition_penalty: 1.0,
+            no_repeat_ngram_size: 0,
+            hallucinations: HallucinationAction::default(),
+            cpu_fallback: true,
+            warm_up: true,
+            idle_unload_after: None,
+            parallel_chunks: 1,
+            delay: DEFAULT_DELAY,
+            max_mel_frames: DEFAULT_MAX_MEL_FRAMES,
+            chunk_overlap_frames: None,
+            seed: None,
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Check the settings are in range.
+    pub fn validate(&self) -> Result<()> {
+        if !self.repetition_penalty.is_finite() || self.repetition_penalty < 1.0 {
+            bail!("repetition_penalty must be at least 1.0");
+        }
+        if self.no_repeat_ngram_size == 1 {
+            bail!("no_repeat_ngram_size must be 0 (off) or at least 2");
+        }
+        if self.parallel_chunks == 0 {
+            bail!("parallel_chunks must be at least 1");
+        }
+        if !(1..=MAX_DELAY).contains(&self.delay) {
+            bail!("delay must be between 1 and {} tokens", MAX_DELAY);
+        }
+        if !(MIN_MEL_FRAMES..=MAX_MEL_FRAMES).contains(&self.max_mel_frames) {
+            bail!(
+                "max_mel_frames must be between {} and {}",
+                MIN_MEL_FRAMES,
+                MAX_MEL_FRAMES
+            );
+        }
+        if self
+            .chunk_overlap_frames
+            .is_some_and(|overlap| overlap * 2 >= self.max_mel_frames)
+        {
+            bail!("chunk_overlap_frames must be less than half of max_mel_frames");
+        }
+        if self.seed.is_some() && self.parallel_chunks > 1 {
+            bail!("A decoding seed requires parallel_chunks = 1");
+        }
+        Ok(())
+    }
+
+    /// How long buffers are split into chunks.
+    pub fn chunk_config(&self) -> ChunkConfig {
+        let config = ChunkConfig::voxtral().with_max_frames(self.max_mel_frames);
+        match self.chunk_overlap_frames {
+            Some(overlap) => config.with_overlap(overlap),
+            None => config,
+        }
+    }
+
+    /// Whether the decode loop has to adjust logits for repetition.
+    pub fn suppresses_repetition(&self) -> bool {
+        self.repetition_penalty != 1.0 || self.no_repeat_ngram_size > 0
+    }
+}
+
+/// Per-call options of a transcription.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TranscribeOptions<'a> {
+    /// Text assumed to precede the audio, such as the previous segment's
+    /// transcript or a user-provided context (names, topic), which the
+    /// decoder is conditioned on.
+    pub prompt: Option<&'a str>,
+    /// Abort the decode (with [`Cancelled`](super::cancel::Cancelled)) once
+    /// this is cancelled.
+    pub cancel: Option<&'a CancelToken>,
+    /// Stop decoding after this much wall time and return the text so far,
+    /// with `timed_out` set.
+    pub timeout: Option<Duration>,
+    /// Audio heard just before this buffer (the tail of the previous
+    /// segment, at the same sample rate). It is decoded ahead of the buffer
+    /// so the decoder carries its state across the cut, and the text it
+    /// produces is dropped. Voxtral only; other engines ignore it.
+    pub context_audio: Option<&'a [f32]>,
+}
+
+/// Name the model an engine is created with is registered under.
+pub const DEFAULT_MODEL: &str = "default";
+
+/// Files of a model variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelFiles {
+    /// Q4 GGUF weights.
+    pub model: PathBuf,
+    /// tekken.json tokenizer.
+    pub tokenizer: PathBuf,
+}
+
+/// A model variant given as `NAME=PATH` on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedModel {
+    /// Name to switch to it by.
+    pub name: String,
+    /// Q4 GGUF weights.
+    pub path: PathBuf,
+}
+
+impl FromStr for NamedModel {
+    type Err = anyhow::Error;
+
+    /// Parse `NAME=PATH` (e.g. `"accurate=../../models/voxtral-accurate.gguf"`).
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((name, path)) = s.split_once('=') else {
+            bail!("Expected NAME=PATH, got '{}'", s);
+        };
+        let (name, path) = (name.trim(), path.trim());
+        if name.is_empty() || path.is_empty() {
+            bail!("Expected NAME=PATH, got '{}'", s);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Transcription counters of an engine, and its performance over its
+/// recent transcriptions.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct EngineStats {
+    /// Audio buffers transcribed.
+    pub transcribed: u64,
+    /// Transcripts dropped as hallucinations.
+    pub suppressed: u64,
+    /// Transcripts kept but flagged as likely hallucinations.
+    pub flagged: u64,
+    /// Rolling Real-Time Factor (processing time / audio duration).
+    pub rtf: Option<f64>,
+    /// Rolling text tokens decoded per second of processing.
+    pub tokens_per_sec: Option<f64>,
+    /// Rolling mean time from the start of a streaming transcription to
+    /// its first partial, in ms.
+    pub first_partial_ms: Option<f64>,
+    /// Device memory the loaded model's weights take, in bytes.
+    pub model_memory_bytes: Option<u64>,
+    /// Device memory the allocator holds now (None while unloaded).
+    pub memory: Option<GpuMemory>,
+    /// Most device memory a transcription needed on top of the weights,
+    /// in bytes. If this plus the weights nears the GPU's memory, lower
+    /// [`EngineConfig::max_mel_frames`].
+    pub peak_inference_bytes: Option<u64>,
+}
+
 /// Voxtral inference engine with lazy loading support (ADR-007).
 ///
 /// The model is loaded into GPU memory only when `load()` is called
 /// and freed when `unload()` is called, minimizing idle memory usage.
+/// With [`EngineConfig::idle_unload_after`] set, `unload_if_idle()` frees
+/// it after a quiet period and `ensure_loaded()` brings it back.
 pub struct VoxtralEngine {
     model_path: PathBuf,
     tokenizer_path: PathBuf,
@@ -34,15 +330,37 @@ pub struct VoxtralEngine {
     tokenizer: Option<VoxtralTokenizer>,
     mel_extractor: Option<MelSpectrogram>,
     t_embed: Option<Tensor<Backend, 3>>,
-    /// Delay in tokens (1 token = 80ms). Default 6 = 480ms latency.
-    delay: usize,
-    /// Max mel frames per chunk (for GPU memory limits).
-    max_mel_frames: usize,
+    /// Language decoding is biased towards (None = auto).
+    language: Option<LanguageBias>,
+    /// Custom vocabulary boosted during decoding.
+    hotwords: Option<Hotwords>,
+    prompt_encoder: PromptEncoder,
+    config: EngineConfig,
+    /// Registered model variants by name, including the active one.
+    models: BTreeMap<String, ModelFiles>,
+    active_model: String,
+    /// Model files already checked against their manifest (once per
+    /// engine; hashing 2.5 GB on every reload would dominate load time).
+    verified: HashSet<PathBuf>,
+    /// When the model was last loaded or used.
+    last_used: Mutex<Instant>,
+    /// Device memory the loaded weights take.
+    model_bytes: Option<u64>,
+    /// Most device memory a transcription needed on top of the weights.
+    peak_inference_bytes: AtomicU64,
+    transcribed: AtomicU64,
+    suppressed: AtomicU64,
+    flagged: AtomicU64,
+    rolling: RollingStats,
 }
 
 impl VoxtralEngine {
     /// Create a new engine (does NOT load the model yet).
     pub fn new(model_path: PathBuf, tokenizer_path: PathBuf) -> Self {
+        let files = ModelFiles {
+            model: model_path.clone(),
+            tokenizer: tokenizer_path.clone(),
+        };
         Self {
             model_path,
             tokenizer_path,
@@ -51,9 +369,187 @@ impl VoxtralEngine {
             tokenizer: None,
             mel_extractor: None,
             t_embed: None,
-            delay: 6,
-            max_mel_frames: 1200,
+            language: None,
+            hotwords: None,
+            prompt_encoder: PromptEncoder::default(),
+            config: EngineConfig::default(),
+            models: BTreeMap::from([(DEFAULT_MODEL.to_string(), files)]),
+            active_model: DEFAULT_MODEL.to_string(),
+            verified: HashSet::new(),
+            last_used: Mutex::new(Instant::now()),
+            model_bytes: None,
+            peak_inference_bytes: AtomicU64::new(0),
+            transcribed: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+            flagged: AtomicU64::new(0),
+            rolling: RollingStats::default(),
+        }
+    }
+
+    /// Boost `hotwords` (names, jargon) during decoding.
+    pub fn with_hotwords(mut self, hotwords: Option<Hotwords>) -> Self {
+        self.set_hotwords(hotwords);
+        self
+    }
+
+    /// Replace the boosted vocabulary (None or empty = none).
+    pub fn set_hotwords(&mut self, hotwords: Option<Hotwords>) {
+        self.hotwords = hotwords.filter(|h| !h.is_empty());
+    }
+
+    /// Currently boosted vocabulary.
+    pub fn hotwords(&self) -> &[String] {
+        self.hotwords.as_ref().map_or(&[], Hotwords::words)
+    }
+
+    /// Run the model on `device` instead of the default GPU.
+    pub fn with_device(mut self, device: WgpuDevice) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Device the model runs (or will run) on.
+    pub fn device(&self) -> &WgpuDevice {
+        &self.device
+    }
+
+    /// Whether the model runs on the CPU adapter, which is usable but
+    /// several times slower than realtime on most machines.
+    pub fn on_cpu(&self) -> bool {
+        matches!(self.device, WgpuDevice::Cpu)
+    }
+
+    /// Use `config` for decoding.
+    pub fn with_config(mut self, config: EngineConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Current decoding settings.
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Change the decoder delay (tokens of 80 ms), taking effect from the
+    /// next transcription.
+    pub fn set_delay(&mut self, delay: usize) -> Result<()> {
+        let config = EngineConfig {
+            delay,
+            ..self.config.clone()
+        };
+        config.validate()?;
+        self.config = config;
+        if self.is_loaded() {
+            self.t_embed = Some(self.time_embedding());
+        }
+        info!(delay, delay_ms = delay * 80, "Decoder delay changed");
+        Ok(())
+    }
+
+    /// Time embedding conditioning the decoder on the configured delay.
+    fn time_embedding(&self) -> Tensor<Backend, 3> {
+        TimeEmbedding::new(3072).embed::<Backend>(self.config.delay as f32, &self.device)
+    }
+
+    /// Bias decoding towards `language` and report it in results.
+    pub fn with_language(mut self, language: Option<LanguageHint>) -> Self {
+        self.set_language(language);
+        self
+    }
+
+    /// Change the language hint (None = auto).
+    pub fn set_language(&mut self, language: Option<LanguageHint>) {
+        self.language = language.map(LanguageBias::new);
+    }
+
+    /// Current language hint, if any.
+    pub fn language(&self) -> Option<&LanguageHint> {
+        self.language.as_ref().map(LanguageBias::hint)
+    }
+
+    /// Transcription counters since the engine was created, and rolling
+    /// performance over the recent transcriptions.
+    pub fn stats(&self) -> EngineStats {
+        let mut stats = EngineStats {
+            transcribed: self.transcribed.load(Ordering::Relaxed),
+            suppressed: self.suppressed.load(Ordering::Relaxed),
+            flagged: self.flagged.load(Ordering::Relaxed),
+            model_memory_bytes: self.model_bytes,
+            memory: self.is_loaded().then(|| gpu::memory_usage(&self.device)),
+            peak_inference_bytes: Some(self.peak_inference_bytes.load(Ordering::Relaxed))
+                .filter(|&bytes| bytes > 0),
+            ..EngineStats::default()
+        };
+        self.rolling.fill(&mut stats);
+        stats
+    }
+
+    /// Register another model variant under `name` (replacing one of the
+    /// same name), to switch to later with [`switch_model`](Self::switch_model).
+    pub fn with_model(mut self, name: &str, files: ModelFiles) -> Self {
+        self.models.insert(name.to_string(), files);
+        self
+    }
+
+    /// Names of the registered model variants.
+    pub fn models(&self) -> impl Iterator<Item = &str> {
+        self.models.keys().map(String::as_str)
+    }
+
+    /// Name of the model variant in use.
+    pub fn active_model(&self) -> &str {
+        &self.active_model
+    }
+
+    /// Switch to the registered model `name`. A loaded engine unloads the
+    /// current model first (both rarely fit in GPU memory at once) and
+    /// loads the new one, returning its load time in milliseconds; if that
+    /// fails, the previous model is reloaded and the error returned.
+    pub fn switch_model(&mut self, name: &str) -> Result<u64> {
+        let Some(files) = self.models.get(name).cloned() else {
+            bail!(
+                "Unknown model '{}' (registered: {})",
+                name,
+                self.models().collect::<Vec<_>>().join(", ")
+            );
+        };
+        if name == self.active_model {
+            return Ok(0);
+        }
+
+        let was_loaded = self.is_loaded();
+        let previous = self.active_model.clone();
+        self.unload();
+        self.use_files(name, files);
+        if !was_loaded {
+            return Ok(0);
         }
+
+        match self.load() {
+            Ok(load_ms) => {
+                info!(model = name, load_ms, "Switched model");
+                Ok(load_ms)
+            }
+            Err(e) => {
+                warn!(model = name, %previous, "Model switch failed; restoring previous model");
+                let files = self.models[&previous].clone();
+                self.use_files(&previous, files);
+                self.load().context("Failed to reload the previous model")?;
+                Err(e.context(format!("Failed to load model '{}'", name)))
+            }
+        }
+    }
+
+    fn use_files(&mut self, name: &str, files: ModelFiles) {
+        if files.tokenizer != self.tokenizer_path {
+            // Token tables built from the old vocabulary
+            self.prompt_encoder = PromptEncoder::default();
+            let words = self.hotwords().to_vec();
+            self.set_hotwords(Some(Hotwords::new(words)));
+        }
+        self.model_path = files.model;
+        self.tokenizer_path = files.tokenizer;
+        self.active_model = name.to_string();
     }
 
     /// Check if the model is currently loaded.
@@ -61,6 +557,45 @@ impl VoxtralEngine {
         self.model.is_some()
     }
 
+    /// Load the model if it isn't loaded, e.g. after an idle unload.
+    /// Returns the load time in milliseconds if it had to load.
+    pub fn ensure_loaded(&mut self) -> Result<Option<u64>> {
+        if self.is_loaded() {
+            return Ok(None);
+        }
+        let load_ms = self.load()?;
+        info!(load_ms, "Model reloaded");
+        Ok(Some(load_ms))
+    }
+
+    /// Unload the model if it has gone unused for the configured idle
+    /// timeout. Returns whether it was unloaded.
+    pub fn unload_if_idle(&mut self) -> bool {
+        let Some(timeout) = self.config.idle_unload_after else {
+            return false;
+        };
+        let idle = self.idle_for();
+        if !self.is_loaded() || idle < timeout {
+            return false;
+        }
+        info!(idle_secs = idle.as_secs(), "Unloading idle model");
+        self.unload();
+        true
+    }
+
+    /// Time since the model was last loaded or used.
+    pub fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .map_or(Duration::ZERO, |t| t.elapsed())
+    }
+
+    fn touch(&self) {
+        if let Ok(mut last_used) = self.last_used.lock() {
+            *last_used = Instant::now();
+        }
+    }
+
     /// Load the model into GPU memory. Returns load time in milliseconds.
     pub fn load(&mut self) -> Result<u64> {
         if self.is_loaded() {
@@ -69,6 +604,38 @@ impl VoxtralEngine {
 
         let start = Instant::now();
 
+        // Pick the device before anything is allocated on it
+        if !gpu::adapter_available(&self.device) {
+            if !self.config.cpu_fallback || self.on_cpu() {
+                bail!("No wgpu adapter for {:?}", self.device);
+            }
+            warn!(device = ?self.device, "No GPU adapter; falling back to CPU (slow)");
+            self.device = WgpuDevice::Cpu;
+            if !gpu::adapter_available(&self.device) {
+                bail!(
+                    "No wgpu adapter, not even a CPU one: install a software \
+                     renderer (llvmpipe/lavapipe on Linux, WARP on Windows)"
+                );
+            }
+        }
+
+        match gpu::adapter_for(&self.device) {
+            Some(adapter) => info!(%adapter, "Using GPU adapter"),
+            None => info!(device = ?self.device, "Using wgpu's default adapter"),
+        }
+        if let Some(seed) = self.config.seed {
+            info!(seed, "Seeding the backend for deterministic decoding");
+            <Backend as burn::tensor::backend::Backend>::seed(&self.device, seed);
+        }
+
+        // Catch truncated or corrupted files before the loaders choke on them
+        for path in [&self.tokenizer_path, &self.model_path] {
+            if !self.verified.contains(path) {
+                integrity::verify(path)?;
+                self.verified.insert(path.clone());
+            }
+        }
+
         // Load tokenizer
         info!(path = %self.tokenizer_path.display(), "Loading tokenizer");
         let tokenizer = VoxtralTokenizer::from_file(&self.tokenizer_path)
@@ -76,39 +643,108 @@ impl VoxtralEngine {
 
         // Load Q4 GGUF model
         info!(path = %self.model_path.display(), "Loading Q4 GGUF model");
+        let before = gpu::memory_usage(&self.device);
         let mut loader = Q4ModelLoader::from_file(&self.model_path)
             .context("Failed to open GGUF file")?;
         let model = loader
             .load(&self.device)
             .context("Failed to load Q4 model")?;
+        let model_bytes = gpu::memory_usage(&self.device)
+            .in_use
+            .saturating_sub(before.in_use);
+        info!(
+            model_mb = model_bytes / (1 << 20),
+            "Model weights uploaded to the device"
+        );
 
         // Initialize mel extractor and time embedding
         let mel_extractor = MelSpectrogram::new(MelConfig::voxtral());
-        let time_embed = TimeEmbedding::new(3072);
-        let t_embed = time_embed.embed::<Backend>(self.delay as f32, &self.device);
-
-        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let t_embed = self.time_embedding();
 
         self.model = Some(model);
         self.tokenizer = Some(tokenizer);
         self.mel_extractor = Some(mel_extractor);
         self.t_embed = Some(t_embed);
+        self.model_bytes = Some(model_bytes);
 
+        if self.config.warm_up {
+            self.warm_up();
+        }
+
+        self.touch();
+        let elapsed_ms = start.elapsed().as_millis() as u64;
         info!(elapsed_ms, "VoxtralEngine loaded");
         Ok(elapsed_ms)
     }
 
+    /// Run a second of silence through the decode path transcriptions will
+    /// take, compiling its shaders and pipelines now.
+    fn warm_up(&self) {
+        let start = Instant::now();
+        let stats = self.stats();
+        let silence = AudioBuffer::new(vec![0.0; WARM_UP_SAMPLES], SAMPLE_RATE);
+        // The streaming decoder: live transcription (CLI, app) goes through it
+        if let Err(e) =
+            self.transcribe_streaming_with(silence, TranscribeOptions::default(), |_| {})
+        {
+            warn!(error = %e, "Warm-up transcription failed");
+        }
+        // Not a real transcription: keep it out of the counters
+        if self.transcribed.load(Ordering::Relaxed) > stats.transcribed {
+            self.rolling.discard_last();
+        }
+        self.transcribed.store(stats.transcribed, Ordering::Relaxed);
+        self.suppressed.store(stats.suppressed, Ordering::Relaxed);
+        self.flagged.store(stats.flagged, Ordering::Relaxed);
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        info!(elapsed_ms, "Warm-up done");
+    }
+
     /// Unload the model from GPU memory.
     pub fn unload(&mut self) {
         self.model = None;
         self.tokenizer = None;
         self.mel_extractor = None;
         self.t_embed = None;
+        self.model_bytes = None;
+        // Drop the cached language bias tensor with the rest of GPU state
+        let language = self.language.take();
+        self.set_language(language.map(|l| l.hint().clone()));
         info!("VoxtralEngine unloaded");
     }
 
     /// Transcribe an audio buffer. The model must be loaded first.
     pub fn transcribe(&self, audio: AudioBuffer) -> Result<TranscriptResult> {
+        self.transcribe_with(audio, TranscribeOptions::default())
+    }
+
+    /// Transcribe an audio buffer with per-call options.
+    ///
+    /// Without a prompt, context audio, cancel token, timeout, language
+    /// hint, hotwords, repetition constraints or a non-default delay this
+    /// runs the model's own decode loop. With a cancel token it runs ours,
+    /// which checks it at every token, so a cancelled job frees the GPU
+    /// right away.
+    pub fn transcribe_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+    ) -> Result<TranscriptResult> {
+        // The model's own decode loop can't be interrupted, apply a
+        // language hint, hotwords or repetition constraints, and assumes the
+        // default delay; ours can
+        if options.prompt.is_some()
+            || options.cancel.is_some()
+            || options.timeout.is_some()
+            || options.context_audio.is_some()
+            || self.language.is_some()
+            || self.hotwords.is_some()
+            || self.config.suppresses_repetition()
+            || self.config.delay != DEFAULT_DELAY
+        {
+            return self.transcribe_streaming_with(audio, options, |_| {});
+        }
+
         let model = self.model.as_ref().context("Model not loaded")?;
         let tokenizer = self.tokenizer.as_ref().context("Tokenizer not loaded")?;
         let mel_extractor = self.mel_extractor.as_ref().context("Mel extractor not loaded")?;
@@ -118,7 +754,7 @@ impl VoxtralEngine {
         let audio_duration_secs = audio.samples.len() as f64 / audio.sample_rate as f64;
 
         let pad_config = PadConfig::voxtral();
-        let chunk_config = ChunkConfig::voxtral().with_max_frames(self.max_mel_frames);
+        let chunk_config = self.config.chunk_config();
 
         let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
 
@@ -131,7 +767,7 @@ impl VoxtralEngine {
             );
             chunks
         } else {
-            vec![voxtral_mini_realtime::audio::AudioChunk {
+            vec![AudioChunk {
                 samples: audio.samples.clone(),
                 start_sample: 0,
                 end_sample: audio.samples.len(),
@@ -140,23 +776,68 @@ impl VoxtralEngine {
             }]
         };
 
-        let mut texts = Vec::new();
+        // Later chunks start with the last second of the chunk before them
+        let windows = boundary::windows(&chunks, audio.sample_rate);
+        let prefix_len = super::streaming::prefix_len(DEFAULT_DELAY);
 
-        for chunk in &chunks {
-            let chunk_audio = AudioBuffer::new(chunk.samples.clone(), audio.sample_rate);
+        // Text of each chunk, and the text tokens it was decoded from
+        let decode_chunk = |window: &ChunkWindow| -> Result<(String, usize)> {
+            let samples = audio.samples[window.start_sample..window.end_sample].to_vec();
+            let chunk_audio = AudioBuffer::new(samples, audio.sample_rate);
             let mel_tensor = self.compute_mel(&chunk_audio, mel_extractor, &pad_config)?;
 
             // Run Q4 streaming inference
             let generated = model.transcribe_streaming(mel_tensor, t_embed.clone());
 
-            // Decode tokens, filtering control tokens (< 1000)
-            let text = self.decode_tokens(tokenizer, &generated)?;
-            if !text.trim().is_empty() {
-                texts.push(text.trim().to_string());
+            // Keep the chunk's own text tokens, dropping control tokens
+            let position_of =
+                |samples: usize| prefix_len + boundary::positions(samples, audio.sample_rate);
+            let text_tokens = TokenFilter::new(
+                position_of(window.keep_from),
+                window.keep_until.map(position_of),
+            )
+            .apply(tokenizer, &generated);
+            let text = tokenizer
+                .decode(&text_tokens)
+                .context("Failed to decode tokens")?;
+            Ok((text, text_tokens.len()))
+        };
+
+        let texts = if self.config.parallel_chunks > 1 && chunks.len() > 1 {
+            // Waves of concurrent chunks, kept in order
+            let mut texts = Vec::with_capacity(chunks.len());
+            for wave in windows.chunks(self.config.parallel_chunks) {
+                let decoded = std::thread::scope(|scope| {
+                    let handles: Vec<_> = wave
+                        .iter()
+                        .map(|window| scope.spawn(|| decode_chunk(window)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| {
+                            handle
+                                .join()
+                                .unwrap_or_else(|_| bail!("Chunk transcription panicked"))
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })?;
+                texts.extend(decoded);
             }
-        }
+            texts
+        } else {
+            windows
+                .iter()
+                .map(decode_chunk)
+                .collect::<Result<Vec<_>>>()?
+        };
 
-        let full_text = texts.join(" ");
+        let tokens = texts.iter().map(|&(_, tokens)| tokens).sum();
+        let full_text = texts
+            .iter()
+            .map(|(text, _)| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
 
         let elapsed_secs = start_time.elapsed().as_secs_f64();
         let rtf = if audio_duration_secs > 0.0 {
@@ -165,13 +846,24 @@ impl VoxtralEngine {
             None
         };
 
-        Ok(TranscriptResult {
+        let language = reported_language(&full_text, self.language());
+
+        let result = TranscriptResult {
             text: full_text,
-            language: "auto".to_string(), // Voxtral auto-detects language
+            language,
             timestamp_ms,
             is_final: true,
             rtf,
-        })
+            start_ms: None,
+            end_ms: None,
+            confidence: None,
+            words: Vec::new(),
+            avg_logprob: None,
+            hallucination: None,
+            timed_out: false,
+            tokens,
+        };
+        Ok(self.screen(result, audio_duration_secs, None))
     }
 
     /// Compute mel spectrogram tensor from audio buffer.
@@ -205,18 +897,6 @@ impl VoxtralEngine {
         ))
     }
 
-    /// Filter control tokens and decode to text.
-    fn decode_tokens(&self, tokenizer: &VoxtralTokenizer, generated: &[i32]) -> Result<String> {
-        let text_tokens: Vec<u32> = generated
-            .iter()
-            .filter(|&&t| t >= 1000)
-            .map(|&t| t as u32)
-            .collect();
-        tokenizer
-            .decode(&text_tokens)
-            .context("Failed to decode tokens")
-    }
-
     /// Transcribe an audio buffer with per-token streaming callback.
     ///
     /// Calls `on_partial(text_so_far)` each time a new text token is decoded,
@@ -226,6 +906,47 @@ impl VoxtralEngine {
         &self,
         audio: AudioBuffer,
         on_partial: F,
+    ) -> Result<TranscriptResult> {
+        self.transcribe_streaming_with(audio, TranscribeOptions::default(), on_partial)
+    }
+
+    /// Like `transcribe_streaming`, with per-call options (prompt,
+    /// context audio, cancellation, timeout).
+    pub fn transcribe_streaming_with<F: FnMut(&str)>(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        mut on_partial: F,
+    ) -> Result<TranscriptResult> {
+        let audio_secs = audio.samples.len() as f64 / audio.sample_rate as f64;
+        let start = Instant::now();
+        let mut first_partial = None;
+        let result = self.decode_streaming(audio, options, |text| {
+            first_partial.get_or_insert_with(|| start.elapsed());
+            on_partial(text)
+        })?;
+        Ok(self.screen(result, audio_secs, first_partial))
+    }
+
+    /// Like `transcribe_streaming_with`, for a preview of audio that is
+    /// transcribed again later (a segment still being spoken): it isn't
+    /// counted in the stats or screened for hallucinations.
+    pub fn preview_streaming_with<F: FnMut(&str)>(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: F,
+    ) -> Result<TranscriptResult> {
+        self.touch();
+        self.decode_streaming(audio, options, on_partial)
+    }
+
+    /// Run the streaming decoder over `audio`.
+    fn decode_streaming<F: FnMut(&str)>(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: F,
     ) -> Result<TranscriptResult> {
         let model = self.model.as_ref().context("Model not loaded")?;
         let tokenizer = self.tokenizer.as_ref().context("Tokenizer not loaded")?;
@@ -238,9 +959,218 @@ impl VoxtralEngine {
             mel_extractor,
             t_embed,
             &self.device,
-            self.max_mel_frames,
+            &self.config,
+        )
+        .with_language(self.language.as_ref())
+        .with_hotwords(self.hotwords.as_ref())
+        .with_prompt(options.prompt, &self.prompt_encoder)
+        .with_context_audio(options.context_audio)
+        .with_cancel(options.cancel)
+        .with_timeout(options.timeout);
+        streamer.transcribe(audio, on_partial)
+    }
+
+    /// Transcribe audio files (WAV, FLAC, MP3, Ogg/Vorbis) one after
+    /// another, each decoded to mono, resampled to 16kHz and padded and
+    /// chunked like any other buffer. The model must be loaded first.
+    ///
+    /// A file that fails doesn't stop the batch; its error is in its
+    /// result. Cancelling `options.cancel` stops the batch, returning the
+    /// files done so far.
+    pub fn transcribe_files<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        options: TranscribeOptions,
+    ) -> Vec<FileTranscript> {
+        let mut transcripts = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = path.as_ref();
+            if options.cancel.is_some_and(CancelToken::is_cancelled) {
+                info!(
+                    done = transcripts.len(),
+                    total = paths.len(),
+                    "Batch transcription cancelled"
+                );
+                break;
+            }
+
+            let start = Instant::now();
+            let decoded = decode_file(path).map(|(samples, sample_rate)| {
+                let mut resampler = Resampler::new(ResamplerQuality::High, SAMPLE_RATE);
+                resampler.process(&samples, sample_rate)
+            });
+            let decode_ms = start.elapsed().as_millis() as u64;
+            let samples = match decoded {
+                Ok(samples) => samples,
+                Err(e) => {
+                    warn!(path = %path.display(), "Failed to decode audio file: {:#}", e);
+                    transcripts.push(FileTranscript {
+                        path: path.to_path_buf(),
+                        audio_secs: None,
+                        decode_ms,
+                        transcribe_ms: 0,
+                        result: Err(e),
+                    });
+                    continue;
+                }
+            };
+
+            let audio_secs = samples.len() as f64 / SAMPLE_RATE as f64;
+            let start = Instant::now();
+            let result = self.transcribe_with(AudioBuffer::new(samples, SAMPLE_RATE), options);
+            let transcribe_ms = start.elapsed().as_millis() as u64;
+            if let Err(ref e) = result {
+                if e.is::<Cancelled>() {
+                    break;
+                }
+                warn!(path = %path.display(), "Failed to transcribe audio file: {:#}", e);
+            }
+            info!(path = %path.display(), audio_secs, decode_ms, transcribe_ms, "Transcribed file");
+            transcripts.push(FileTranscript {
+                path: path.to_path_buf(),
+                audio_secs: Some(audio_secs),
+                decode_ms,
+                transcribe_ms,
+                result,
+            });
+        }
+        transcripts
+    }
+
+    /// Track the memory transcriptions need beyond the weights. The
+    /// allocator keeps freed buffers pooled, so what it holds after a
+    /// transcription covers the most that transcription had live.
+    fn record_memory(&self) {
+        let Some(model_bytes) = self.model_bytes else {
+            return;
+        };
+        let inference = gpu::memory_usage(&self.device)
+            .reserved
+            .saturating_sub(model_bytes);
+        let previous = self
+            .peak_inference_bytes
+            .fetch_max(inference, Ordering::Relaxed);
+        if inference > previous {
+            debug!(
+                inference_mb = inference / (1 << 20),
+                "New peak inference memory"
+            );
+        }
+    }
+
+    /// Count a transcript of `audio_secs` of audio (with its first partial
+    /// after `first_partial`, if streamed) and apply the hallucination
+    /// action to it: drop its text or flag it.
+    fn screen(
+        &self,
+        mut result: TranscriptResult,
+        audio_secs: f64,
+        first_partial: Option<Duration>,
+    ) -> TranscriptResult {
+        self.touch();
+        self.transcribed.fetch_add(1, Ordering::Relaxed);
+        let elapsed_secs = result.rtf.map_or(0.0, |rtf| rtf * audio_secs);
+        self.rolling
+            .record(audio_secs, elapsed_secs, result.tokens, first_partial);
+        self.record_memory();
+        if self.config.hallucinations == HallucinationAction::Keep {
+            return result;
+        }
+        let Some(reason) = hallucination::detect(&result.text, audio_secs, result.avg_logprob)
+        else {
+            return result;
+        };
+
+        warn!(
+            reason,
+            text = %result.text,
+            action = %self.config.hallucinations,
+            "Likely hallucinated transcript"
         );
+        match self.config.hallucinations {
+            HallucinationAction::Drop => {
+                self.suppressed.fetch_add(1, Ordering::Relaxed);
+                result.text.clear();
+                result.words.clear();
+            }
+            _ => {
+                self.flagged.fetch_add(1, Ordering::Relaxed);
+                result.hallucination = Some(reason.to_string());
+            }
+        }
+        result
+    }
+}
 
-        streamer.transcribe(audio, on_partial)
+impl TranscriptionEngine for VoxtralEngine {
+    fn name(&self) -> &'static str {
+        "voxtral"
+    }
+
+    fn is_loaded(&self) -> bool {
+        VoxtralEngine::is_loaded(self)
+    }
+
+    fn load(&mut self) -> Result<u64> {
+        VoxtralEngine::load(self)
+    }
+
+    fn unload(&mut self) {
+        VoxtralEngine::unload(self)
+    }
+
+    fn transcribe_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+    ) -> Result<TranscriptResult> {
+        VoxtralEngine::transcribe_with(self, audio, options)
+    }
+
+    fn transcribe_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        VoxtralEngine::transcribe_streaming_with(self, audio, options, on_partial)
+    }
+
+    fn preview_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        VoxtralEngine::preview_streaming_with(self, audio, options, on_partial)
+    }
+
+    fn ensure_loaded(&mut self) -> Result<Option<u64>> {
+        VoxtralEngine::ensure_loaded(self)
+    }
+
+    fn unload_if_idle(&mut self) -> bool {
+        VoxtralEngine::unload_if_idle(self)
+    }
+
+    fn language(&self) -> Option<&LanguageHint> {
+        VoxtralEngine::language(self)
+    }
+
+    fn set_hotwords(&mut self, hotwords: Option<Hotwords>) -> Result<()> {
+        VoxtralEngine::set_hotwords(self, hotwords);
+        Ok(())
+    }
+
+    fn switch_model(&mut self, name: &str) -> Result<u64> {
+        VoxtralEngine::switch_model(self, name)
+    }
+
+    fn set_delay(&mut self, delay: usize) -> Result<()> {
+        VoxtralEngine::set_delay(self, delay)
+    }
+
+    fn stats(&self) -> EngineStats {
+        VoxtralEngine::stats(self)
     }
 }
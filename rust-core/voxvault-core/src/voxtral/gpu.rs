@@ -0,0 +1,190 @@
+//! GPU adapter enumeration and selection.
+//!
+//! `WgpuDevice::default()` lets wgpu pick a "high performance" adapter,
+//! which on iGPU + dGPU or eGPU machines is sometimes the weak one. Burn
+//! addresses adapters by type and index within that type
+//! (`DiscreteGpu(1)` = second discrete GPU), which users can't know, so
+//! this module lists the adapters the model's graphics API sees, with
+//! their names, and maps a choice back to the matching `WgpuDevice`. It
+//! also reports how much device memory burn's allocator holds.
+
+use anyhow::{bail, Result};
+use burn::backend::wgpu::graphics::{AutoGraphicsApi, GraphicsApi};
+use burn::backend::wgpu::{WgpuDevice, WgpuRuntime};
+use cubecl_runtime::runtime::Runtime;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// A GPU (or CPU) adapter the model can run on.
+#[derive(Debug, Clone)]
+pub struct GpuAdapter {
+    /// Position in [`list_adapters`]'s output.
+    pub index: usize,
+    /// Adapter name reported by the driver.
+    pub name: String,
+    /// Adapter type: discrete, integrated, virtual or cpu.
+    pub kind: &'static str,
+    /// Graphics API it is driven through (vulkan, metal, dx12, ...).
+    pub api: String,
+    /// Device to pass to the engine to use this adapter.
+    pub device: WgpuDevice,
+}
+
+impl fmt::Display for GpuAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} ({}, {})",
+            self.index, self.name, self.kind, self.api
+        )
+    }
+}
+
+/// Device memory held by burn's allocator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct GpuMemory {
+    /// Bytes of live buffers.
+    pub in_use: u64,
+    /// Bytes allocated from the device, including freed buffers the
+    /// allocator keeps pooled for reuse.
+    pub reserved: u64,
+}
+
+/// Memory burn's allocator currently holds on `device`, as seen from the
+/// calling thread's stream.
+pub fn memory_usage(device: &WgpuDevice) -> GpuMemory {
+    let usage = WgpuRuntime::client(device).memory_usage();
+    GpuMemory {
+        in_use: usage.bytes_in_use,
+        reserved: usage.bytes_reserved,
+    }
+}
+
+/// List the adapters available to the model, in wgpu's enumeration order.
+pub fn list_adapters() -> Vec<GpuAdapter> {
+    let backend = AutoGraphicsApi::backend();
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: backend.into(),
+        ..Default::default()
+    });
+
+    // Burn numbers adapters within their type, in this same order
+    let mut per_kind = [0usize; 4];
+    let mut adapters = Vec::new();
+    for adapter in instance.enumerate_adapters(backend.into()) {
+        let info = adapter.get_info();
+        let (slot, kind) = match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => (0, "discrete"),
+            wgpu::DeviceType::IntegratedGpu => (1, "integrated"),
+            wgpu::DeviceType::VirtualGpu => (2, "virtual"),
+            wgpu::DeviceType::Cpu => (3, "cpu"),
+            // Burn can't address these by type
+            wgpu::DeviceType::Other => continue,
+        };
+        let n = per_kind[slot];
+        per_kind[slot] += 1;
+        let device = match slot {
+            0 => WgpuDevice::DiscreteGpu(n),
+            1 => WgpuDevice::IntegratedGpu(n),
+            2 => WgpuDevice::VirtualGpu(n),
+            _ => WgpuDevice::Cpu,
+        };
+        // Burn only reaches the first CPU adapter
+        if slot == 3 && n > 0 {
+            continue;
+        }
+        adapters.push(GpuAdapter {
+            index: adapters.len(),
+            name: info.name,
+            kind,
+            api: info.backend.to_string(),
+            device,
+        });
+    }
+    adapters
+}
+
+/// Whether `device` has an adapter to run on, checked through wgpu's
+/// enumeration: burn's runtime panics when it finds none, and unwinding
+/// out of its initialization would leave it half-built.
+pub fn adapter_available(device: &WgpuDevice) -> bool {
+    #[allow(deprecated)]
+    match device {
+        WgpuDevice::DefaultDevice | WgpuDevice::BestAvailable => !list_adapters().is_empty(),
+        // Registered by the caller, so it exists
+        WgpuDevice::Existing(_) => true,
+        device => list_adapters().iter().any(|a| a.device == *device),
+    }
+}
+
+/// The listed adapter `device` refers to, if it names one explicitly.
+pub fn adapter_for(device: &WgpuDevice) -> Option<GpuAdapter> {
+    list_adapters().into_iter().find(|a| a.device == *device)
+}
+
+/// How the user picks an adapter: its index in [`list_adapters`] or part
+/// of its name (case-insensitive).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// Index shown by [`list_adapters`].
+    Index(usize),
+    /// Substring of the adapter name.
+    Name(String),
+}
+
+impl AdapterSelector {
+    /// Find the selected adapter.
+    pub fn select(&self) -> Result<GpuAdapter> {
+        let adapters = list_adapters();
+        let found = match self {
+            Self::Index(i) => adapters.iter().find(|a| a.index == *i),
+            Self::Name(name) => {
+                let name = name.to_lowercase();
+                adapters
+                    .iter()
+                    .find(|a| a.name.to_lowercase().contains(&name))
+            }
+        };
+        match found {
+            Some(adapter) => Ok(adapter.clone()),
+            None => bail!(
+                "No GPU adapter matches '{}'. Available: {}",
+                self,
+                if adapters.is_empty() {
+                    "none".to_string()
+                } else {
+                    adapters
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+            ),
+        }
+    }
+}
+
+impl FromStr for AdapterSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            bail!("GPU selector must be an adapter index or name");
+        }
+        Ok(match s.parse() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(s.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for AdapterSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(i) => write!(f, "{}", i),
+            Self::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Detection of hallucinated transcripts.
+//!
+//! Fed silence or noise that slipped past the VAD, the decoder tends to
+//! produce fluent text that was never said: subtitle credits and sign-offs
+//! memorized from training data, or long runs of words for a second of
+//! audio. Three heuristics catch most of it:
+//!
+//! - low average log-probability of the generated tokens;
+//! - more words than the audio could hold;
+//! - the whole transcript being a known hallucination phrase.
+
+use anyhow::bail;
+use std::fmt;
+use std::str::FromStr;
+
+/// Transcripts whose tokens average a lower log-probability are suspect
+/// (geometric mean probability below ~0.37).
+const MIN_AVG_LOGPROB: f32 = -1.0;
+/// Faster than any speaker sustains; more words per second of audio means
+/// the text wasn't heard.
+const MAX_WORDS_PER_SEC: f32 = 6.0;
+/// Below this many words the rate check is too noisy to trust.
+const MIN_WORDS_FOR_RATE: usize = 4;
+
+/// Phrases transcribed from silence, normalized (lowercase, without
+/// punctuation).
+const KNOWN_PHRASES: &[&str] = &[
+    "thank you for watching",
+    "thanks for watching",
+    "please subscribe",
+    "subtitles by the amaraorg community",
+    "obrigado por assistir",
+    "obrigada por assistir",
+    "legendas pela comunidade amaraorg",
+    "inscrevase no canal",
+    "gracias por ver",
+    "subtítulos realizados por la comunidad de amaraorg",
+    "merci davoir regardé",
+    "soustitres réalisés par la communauté damaraorg",
+    "untertitel im auftrag des zdf",
+    "grazie per la visione",
+];
+
+/// What to do with a transcript judged hallucinated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HallucinationAction {
+    /// Keep it unmarked (detection off).
+    Keep,
+    /// Keep it, marked as suspect.
+    Flag,
+    /// Drop the text before it is broadcast.
+    #[default]
+    Drop,
+}
+
+impl FromStr for HallucinationAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "keep" | "off" => Ok(Self::Keep),
+            "flag" => Ok(Self::Flag),
+            "drop" => Ok(Self::Drop),
+            other => bail!(
+                "Unknown hallucination action '{}': expected 'keep', 'flag' or 'drop'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HallucinationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keep => write!(f, "keep"),
+            Self::Flag => write!(f, "flag"),
+            Self::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+/// Why `text`, transcribed from `audio_secs` of audio, looks hallucinated,
+/// or None if it doesn't. `avg_logprob` is the mean log-probability of its
+/// tokens, when known.
+pub fn detect(text: &str, audio_secs: f64, avg_logprob: Option<f32>) -> Option<&'static str> {
+    let words = text.split_whitespace().count();
+    if words == 0 {
+        return None;
+    }
+    if avg_logprob.is_some_and(|lp| lp < MIN_AVG_LOGPROB) {
+        return Some("low average log-probability");
+    }
+    if words >= MIN_WORDS_FOR_RATE && words as f64 > audio_secs * MAX_WORDS_PER_SEC as f64 {
+        return Some("too many words for the audio duration");
+    }
+    if KNOWN_PHRASES.contains(&normalize(text).as_str()) {
+        return Some("known hallucination phrase");
+    }
+    None
+}
+
+/// Lowercase `text`, drop punctuation and collapse whitespace.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
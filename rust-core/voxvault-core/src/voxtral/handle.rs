@@ -0,0 +1,139 @@
+//! Shareable handle to an engine running on its own inference thread.
+//!
+//! An engine holds GPU state and is driven through `&mut self`, so it
+//! can't be shared between tasks. [`VoxtralHandle`] moves it onto a
+//! dedicated worker thread and feeds it jobs through a queue; clones of
+//! the handle (Tauri commands, the WebSocket control plane, the capture
+//! loop) submit work and await the results. Jobs run one at a time in the
+//! order they were submitted.
+
+use anyhow::{anyhow, Context, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+use voxtral_mini_realtime::audio::AudioBuffer;
+
+use super::cancel::CancelToken;
+use super::engine::{EngineStats, TranscribeOptions};
+use super::transcriber::TranscriptionEngine;
+use super::types::TranscriptResult;
+
+/// Work for the inference thread.
+type Job = Box<dyn FnOnce(&mut dyn TranscriptionEngine) + Send>;
+
+/// Owned counterpart of [`TranscribeOptions`], for jobs that outlive the
+/// caller's borrows.
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    /// See [`TranscribeOptions::prompt`].
+    pub prompt: Option<String>,
+    /// See [`TranscribeOptions::cancel`].
+    pub cancel: Option<CancelToken>,
+    /// See [`TranscribeOptions::timeout`].
+    pub timeout: Option<Duration>,
+    /// See [`TranscribeOptions::context_audio`].
+    pub context_audio: Option<Vec<f32>>,
+}
+
+impl JobOptions {
+    /// Borrow as the engine's per-call options.
+    pub fn as_options(&self) -> TranscribeOptions<'_> {
+        TranscribeOptions {
+            prompt: self.prompt.as_deref(),
+            cancel: self.cancel.as_ref(),
+            timeout: self.timeout,
+            context_audio: self.context_audio.as_deref(),
+        }
+    }
+}
+
+/// Cloneable, thread-safe handle to an engine on a worker thread.
+///
+/// The worker exits once every clone of the handle is dropped and the
+/// queued jobs are done, dropping the engine with it.
+#[derive(Clone)]
+pub struct VoxtralHandle {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl VoxtralHandle {
+    /// Move `engine` onto a new inference thread.
+    pub fn spawn(engine: impl TranscriptionEngine + 'static) -> Result<Self> {
+        let mut engine: Box<dyn TranscriptionEngine> = Box::new(engine);
+        let (jobs, mut queue) = mpsc::unbounded_channel::<Job>();
+        std::thread::Builder::new()
+            .name("inference".into())
+            .spawn(move || {
+                while let Some(job) = queue.blocking_recv() {
+                    // A job that panics drops its reply; the engine takes
+                    // the next one
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(engine.as_mut()))).is_err() {
+                        error!(engine = engine.name(), "Inference job panicked");
+                    }
+                }
+                info!(engine = engine.name(), "Inference worker stopped");
+            })
+            .context("Failed to spawn inference thread")?;
+        Ok(Self { jobs })
+    }
+
+    /// Run `f` on the inference thread with exclusive access to the
+    /// engine, after the jobs queued before it.
+    pub async fn run<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn TranscriptionEngine) -> R + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |engine| {
+                let _ = reply.send(f(engine));
+            }))
+            .map_err(|_| anyhow!("Inference worker stopped"))?;
+        // A job that panicked drops its reply
+        result.await.context("Inference job failed")
+    }
+
+    /// Load the model if it isn't loaded. Returns the load time in
+    /// milliseconds if it had to load.
+    pub async fn ensure_loaded(&self) -> Result<Option<u64>> {
+        self.run(|engine| engine.ensure_loaded()).await?
+    }
+
+    /// Free the model.
+    pub async fn unload(&self) -> Result<()> {
+        self.run(|engine| engine.unload()).await
+    }
+
+    /// Transcribe `audio`. The model must be loaded first.
+    pub async fn transcribe(
+        &self,
+        audio: AudioBuffer,
+        options: JobOptions,
+    ) -> Result<TranscriptResult> {
+        self.run(move |engine| engine.transcribe_with(audio, options.as_options()))
+            .await?
+    }
+
+    /// Transcribe `audio`, sending the text decoded so far to `partials`
+    /// as it grows.
+    pub async fn transcribe_streaming(
+        &self,
+        audio: AudioBuffer,
+        options: JobOptions,
+        partials: mpsc::UnboundedSender<String>,
+    ) -> Result<TranscriptResult> {
+        self.run(move |engine| {
+            engine.transcribe_streaming_with(audio, options.as_options(), &mut |text| {
+                let _ = partials.send(text.to_string());
+            })
+        })
+        .await?
+    }
+
+    /// Transcription counters of the engine.
+    pub async fn stats(&self) -> Result<EngineStats> {
+        self.run(|engine| engine.stats()).await
+    }
+}
@@ -0,0 +1,151 @@
+//! Custom vocabulary boosting.
+//!
+//! Product names, people and jargon the model rarely saw get split into
+//! plausible-sounding common words. Given a list of such hotwords, the
+//! decode loop raises the logits of tokens that spell one of them:
+//!
+//! - a small boost for tokens that start a hotword (after a space), so
+//!   the model considers it without inserting it everywhere;
+//! - a larger boost for tokens that continue a hotword the text already
+//!   ends with a prefix of, so a started term is finished correctly.
+//!
+//! Matching works on decoded token text, so it needs no encoder: every
+//! vocabulary entry is decoded once, on first use, and matched against the
+//! hotwords.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use tracing::info;
+use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
+
+/// First text token id; lower ids are control tokens.
+const TEXT_TOKEN_OFFSET: usize = 1000;
+/// Logit boost for tokens starting a hotword.
+const START_BOOST: f32 = 1.5;
+/// Logit boost for tokens continuing a hotword already started.
+const CONTINUE_BOOST: f32 = 4.0;
+
+/// A hotword list and the token tables it translates to.
+pub struct Hotwords {
+    words: Vec<String>,
+    table: OnceLock<HotwordTable>,
+}
+
+/// Per hotword, the tokens that continue it from each byte offset
+/// (offset 0 = tokens starting it after a space).
+struct HotwordTable {
+    continuations: Vec<HashMap<usize, Vec<u32>>>,
+}
+
+impl Hotwords {
+    /// Boost `words` (blank entries are ignored).
+    pub fn new(words: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            words: words
+                .into_iter()
+                .map(|w| w.trim().to_string())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            table: OnceLock::new(),
+        }
+    }
+
+    /// Read a hotword list from a file: one term per line, `#` comments.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read hotwords file {}", path.display()))?;
+        Ok(Self::new(
+            content
+                .lines()
+                .filter(|l| !l.trim_start().starts_with('#'))
+                .map(str::to_string),
+        ))
+    }
+
+    /// The boosted terms.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Whether there is nothing to boost.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Boost the tokens in `logits` that start a hotword or continue one
+    /// `text` (decoded so far) ends partway through.
+    pub fn apply(&self, logits: &mut [f32], text: &str, tokenizer: &VoxtralTokenizer) {
+        let table = self
+            .table
+            .get_or_init(|| HotwordTable::build(&self.words, logits.len(), tokenizer));
+        let text = text.to_lowercase();
+
+        for (word, continuations) in self.words.iter().zip(&table.continuations) {
+            let mut boost = |offset: usize, amount: f32| {
+                for &id in continuations.get(&offset).into_iter().flatten() {
+                    if let Some(logit) = logits.get_mut(id as usize) {
+                        *logit += amount;
+                    }
+                }
+            };
+            boost(0, START_BOOST);
+            if let Some(offset) = matched_prefix(&text, &word.to_lowercase()) {
+                boost(offset, CONTINUE_BOOST);
+            }
+        }
+    }
+}
+
+impl HotwordTable {
+    fn build(words: &[String], vocab: usize, tokenizer: &VoxtralTokenizer) -> Self {
+        let mut continuations = vec![HashMap::<usize, Vec<u32>>::new(); words.len()];
+        for id in TEXT_TOKEN_OFFSET..vocab {
+            let Ok(piece) = tokenizer.decode(&[id as u32]) else {
+                continue;
+            };
+            if piece.trim().is_empty() {
+                continue;
+            }
+            for (word, table) in words.iter().zip(continuations.iter_mut()) {
+                // Starting the word: a space, then a prefix of it (single
+                // letters start too many words to be worth boosting)
+                if let Some(start) = piece.strip_prefix(' ') {
+                    if word.starts_with(start) && (start.len() > 1 || start == word) {
+                        table.entry(0).or_default().push(id as u32);
+                    }
+                }
+                // Continuing it from inside
+                for (offset, _) in word.char_indices().skip(1) {
+                    let rest = &word[offset..];
+                    let finishes = piece
+                        .strip_prefix(rest)
+                        .is_some_and(|after| after.starts_with(|c: char| !c.is_alphanumeric()));
+                    if rest.starts_with(&piece) || finishes {
+                        table.entry(offset).or_default().push(id as u32);
+                    }
+                }
+            }
+        }
+        info!(words = words.len(), "Built hotword table");
+        Self { continuations }
+    }
+}
+
+/// Length of the longest proper prefix of `word` that `text` ends with,
+/// starting at a word boundary (both lowercase).
+fn matched_prefix(text: &str, word: &str) -> Option<usize> {
+    word.char_indices()
+        .skip(1)
+        .map(|(offset, _)| offset)
+        .filter(|&offset| {
+            text.ends_with(&word[..offset])
+                && text[..text.len() - offset]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+        })
+        .last()
+}
@@ -0,0 +1,104 @@
+//! Model file checksums.
+//!
+//! A truncated or corrupted GGUF fails deep inside the loader with errors
+//! that don't point at the file. Model directories can carry a
+//! `SHA256SUMS` manifest (`sha256sum` format: `<hex>  <file name>` per
+//! line); files listed there are hashed at load time and rejected with an
+//! actionable message when they don't match. The downloader writes the
+//! manifest, so downloaded models are covered automatically.
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::{debug, info};
+
+/// Manifest file name, looked up next to the model files.
+pub const MANIFEST_FILE: &str = "SHA256SUMS";
+
+/// Hex SHA-256 of the file at `path`.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Whether `s` looks like a hex SHA-256.
+pub fn is_sha256(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Check the file at `path` against the manifest in its directory. Files
+/// without a manifest entry pass unchecked.
+pub fn verify(path: &Path) -> Result<()> {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return Ok(());
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let Some(expected) = read_manifest(dir)?.remove(name) else {
+        debug!(path = %path.display(), "No checksum to verify against");
+        return Ok(());
+    };
+
+    info!(path = %path.display(), "Verifying checksum");
+    let actual = sha256_file(path)?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        bail!(
+            "{} is corrupted or incomplete (SHA-256 {}, expected {} per {}). \
+             Delete it and download it again with `voxvault-cli models download`.",
+            path.display(),
+            actual,
+            expected,
+            dir.join(MANIFEST_FILE).display()
+        );
+    }
+    Ok(())
+}
+
+/// Record `sha256` as the checksum of `file` in `dir`'s manifest.
+pub fn record(dir: &Path, file: &str, sha256: &str) -> Result<()> {
+    let mut manifest = read_manifest(dir)?;
+    manifest.insert(file.to_string(), sha256.to_ascii_lowercase());
+    let content: String = manifest
+        .iter()
+        .map(|(name, hash)| format!("{}  {}\n", hash, name))
+        .collect();
+    let path = dir.join(MANIFEST_FILE);
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// File name → checksum entries of `dir`'s manifest (empty if none).
+fn read_manifest(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let path = dir.join(MANIFEST_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+            // sha256sum marks binary mode with a leading '*'
+            let name = name.trim_start().trim_start_matches('*');
+            is_sha256(hash).then(|| (name.to_string(), hash.to_string()))
+        })
+        .collect())
+}
@@ -0,0 +1,587 @@
+//! Inverse text normalization (ITN) of final transcripts.
+//!
+//! The decoder spells out what it hears: "twenty five dollars", "trinta
+//! por cento", "march fifth twenty twenty four". ITN rewrites those spans
+//! the way they are written: "$25", "30%", "March 5, 2024". Rules exist
+//! for English and Portuguese; text in other languages is left as is.
+//!
+//! Only unambiguous spans are rewritten. A lone number word below ten
+//! ("one of them", "um carro") stays a word unless a currency, a percent
+//! or a date makes it a quantity, and month names that are also common
+//! words ("may", "march") only start a date that ends the phrase.
+
+/// Number, currency and date vocabulary of one language.
+struct Rules {
+    code: &'static str,
+    /// Number words adding their value: units, teens, tens (and the
+    /// hundreds in languages with a word per hundred).
+    numbers: &'static [(&'static str, u64)],
+    /// Word multiplying the number before it by 100 ("hundred").
+    hundred: Option<&'static str>,
+    /// Words multiplying everything before them: thousand, million, ...
+    scales: &'static [(&'static str, u64)],
+    /// Word joining the parts of a number ("and", "e").
+    joiner: &'static str,
+    /// Day ordinals, for dates.
+    ordinals: &'static [(&'static str, u64)],
+    /// Month names, lowercase, January first.
+    months: &'static [&'static str],
+    /// Word between a quantity and its currency ("um milhão de reais").
+    of: Option<&'static str>,
+    /// Currency names and their symbols.
+    currencies: &'static [(&'static str, &'static str)],
+    /// Names of the hundredth part of a currency unit.
+    cents: &'static [&'static str],
+    /// Phrases meaning percent.
+    percent: &'static [&'static [&'static str]],
+    /// Space between a currency symbol and the amount ("R$ 25").
+    symbol_space: bool,
+    decimal_separator: char,
+    thousands_separator: char,
+}
+
+const ENGLISH: Rules = Rules {
+    code: "en",
+    numbers: &[
+        ("zero", 0),
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+        ("ten", 10),
+        ("eleven", 11),
+        ("twelve", 12),
+        ("thirteen", 13),
+        ("fourteen", 14),
+        ("fifteen", 15),
+        ("sixteen", 16),
+        ("seventeen", 17),
+        ("eighteen", 18),
+        ("nineteen", 19),
+        ("twenty", 20),
+        ("thirty", 30),
+        ("forty", 40),
+        ("fifty", 50),
+        ("sixty", 60),
+        ("seventy", 70),
+        ("eighty", 80),
+        ("ninety", 90),
+    ],
+    hundred: Some("hundred"),
+    scales: &[
+        ("thousand", 1_000),
+        ("million", 1_000_000),
+        ("billion", 1_000_000_000),
+    ],
+    joiner: "and",
+    ordinals: &[
+        ("first", 1),
+        ("second", 2),
+        ("third", 3),
+        ("fourth", 4),
+        ("fifth", 5),
+        ("sixth", 6),
+        ("seventh", 7),
+        ("eighth", 8),
+        ("ninth", 9),
+        ("tenth", 10),
+        ("eleventh", 11),
+        ("twelfth", 12),
+        ("thirteenth", 13),
+        ("fourteenth", 14),
+        ("fifteenth", 15),
+        ("sixteenth", 16),
+        ("seventeenth", 17),
+        ("eighteenth", 18),
+        ("nineteenth", 19),
+        ("twentieth", 20),
+        ("thirtieth", 30),
+    ],
+    months: &[
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ],
+    of: None,
+    currencies: &[
+        ("dollar", "$"),
+        ("dollars", "$"),
+        ("euro", "€"),
+        ("euros", "€"),
+        ("pound", "£"),
+        ("pounds", "£"),
+    ],
+    cents: &["cent", "cents"],
+    percent: &[&["percent"], &["per", "cent"]],
+    symbol_space: false,
+    decimal_separator: '.',
+    thousands_separator: ',',
+};
+
+const PORTUGUESE: Rules = Rules {
+    code: "pt",
+    numbers: &[
+        ("zero", 0),
+        ("um", 1),
+        ("uma", 1),
+        ("dois", 2),
+        ("duas", 2),
+        ("três", 3),
+        ("quatro", 4),
+        ("cinco", 5),
+        ("seis", 6),
+        ("sete", 7),
+        ("oito", 8),
+        ("nove", 9),
+        ("dez", 10),
+        ("onze", 11),
+        ("doze", 12),
+        ("treze", 13),
+        ("catorze", 14),
+        ("quatorze", 14),
+        ("quinze", 15),
+        ("dezesseis", 16),
+        ("dezessete", 17),
+        ("dezoito", 18),
+        ("dezenove", 19),
+        ("vinte", 20),
+        ("trinta", 30),
+        ("quarenta", 40),
+        ("cinquenta", 50),
+        ("sessenta", 60),
+        ("setenta", 70),
+        ("oitenta", 80),
+        ("noventa", 90),
+        ("cem", 100),
+        ("cento", 100),
+        ("duzentos", 200),
+        ("duzentas", 200),
+        ("trezentos", 300),
+        ("trezentas", 300),
+        ("quatrocentos", 400),
+        ("quatrocentas", 400),
+        ("quinhentos", 500),
+        ("quinhentas", 500),
+        ("seiscentos", 600),
+        ("seiscentas", 600),
+        ("setecentos", 700),
+        ("setecentas", 700),
+        ("oitocentos", 800),
+        ("oitocentas", 800),
+        ("novecentos", 900),
+        ("novecentas", 900),
+    ],
+    hundred: None,
+    scales: &[
+        ("mil", 1_000),
+        ("milhão", 1_000_000),
+        ("milhões", 1_000_000),
+        ("bilhão", 1_000_000_000),
+        ("bilhões", 1_000_000_000),
+    ],
+    joiner: "e",
+    // Other days are cardinals: "cinco de março"
+    ordinals: &[("primeiro", 1)],
+    months: &[
+        "janeiro",
+        "fevereiro",
+        "março",
+        "abril",
+        "maio",
+        "junho",
+        "julho",
+        "agosto",
+        "setembro",
+        "outubro",
+        "novembro",
+        "dezembro",
+    ],
+    of: Some("de"),
+    currencies: &[
+        ("real", "R$"),
+        ("reais", "R$"),
+        ("dólar", "US$"),
+        ("dólares", "US$"),
+        ("euro", "€"),
+        ("euros", "€"),
+    ],
+    cents: &["centavo", "centavos"],
+    percent: &[&["por", "cento"]],
+    symbol_space: true,
+    decimal_separator: ',',
+    thousands_separator: '.',
+};
+
+/// Languages with ITN rules.
+const LANGUAGES: &[&Rules] = &[&ENGLISH, &PORTUGUESE];
+
+/// A word of the transcript, split from the punctuation that follows it.
+struct Word<'a> {
+    text: &'a str,
+    /// Lowercase, without the trailing punctuation.
+    key: String,
+    /// Punctuation after the word; a span can't continue past it.
+    trail: &'a str,
+}
+
+/// `text` in `language` (ISO 639-1 code) with spelled-out numbers,
+/// amounts, percentages and dates written as digits and symbols. Text in
+/// a language without rules is returned unchanged.
+pub fn normalize(text: &str, language: &str) -> String {
+    let Some(rules) = LANGUAGES.iter().find(|rules| rules.code == language) else {
+        return text.to_string();
+    };
+    let words = split(text, rules);
+    let mut normalized: Vec<String> = Vec::with_capacity(words.len());
+    let mut index = 0;
+    while index < words.len() {
+        let rest = &words[index..];
+        match date(rules, rest).or_else(|| quantity(rules, rest)) {
+            Some((len, written)) => {
+                normalized.push(format!("{}{}", written, rest[len - 1].trail));
+                index += len;
+            }
+            None => {
+                normalized.push(rest[0].text.to_string());
+                index += 1;
+            }
+        }
+    }
+    normalized.join(" ")
+}
+
+/// Split `text` into words, breaking hyphenated numbers ("twenty-five")
+/// into their parts.
+fn split<'a>(text: &'a str, rules: &Rules) -> Vec<Word<'a>> {
+    let mut words = Vec::new();
+    for token in text.split_whitespace() {
+        let body = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+        let trail = &token[body.len()..];
+        let parts: Vec<&str> = body.split('-').collect();
+        let numeric = parts.len() > 1
+            && parts.iter().all(|part| {
+                let key = part.to_lowercase();
+                number_word(rules, &key).is_some() || ordinal(rules, &key).is_some()
+            });
+        if numeric {
+            let last = parts.len() - 1;
+            for (i, part) in parts.into_iter().enumerate() {
+                words.push(Word {
+                    // The last part keeps the punctuation, in case the
+                    // number isn't rewritten
+                    text: if i == last {
+                        &token[body.len() - part.len()..]
+                    } else {
+                        part
+                    },
+                    key: part.to_lowercase(),
+                    trail: if i == last { trail } else { "" },
+                });
+            }
+        } else {
+            words.push(Word {
+                text: token,
+                key: body.to_lowercase(),
+                trail,
+            });
+        }
+    }
+    words
+}
+
+fn number_word(rules: &Rules, key: &str) -> Option<u64> {
+    rules
+        .numbers
+        .iter()
+        .find(|(word, _)| *word == key)
+        .map(|&(_, value)| value)
+}
+
+fn ordinal(rules: &Rules, key: &str) -> Option<u64> {
+    rules
+        .ordinals
+        .iter()
+        .find(|(word, _)| *word == key)
+        .map(|&(_, value)| value)
+}
+
+fn month(rules: &Rules, key: &str) -> Option<usize> {
+    rules.months.iter().position(|month| *month == key)
+}
+
+/// Length of `phrase` at the start of `words`, if it is there without
+/// punctuation inside.
+fn phrase(words: &[Word], phrase: &[&str]) -> Option<usize> {
+    let matched = words.len() >= phrase.len()
+        && words
+            .iter()
+            .zip(phrase)
+            .enumerate()
+            .all(|(i, (word, key))| {
+                word.key == *key && (i + 1 == phrase.len() || word.trail.is_empty())
+            });
+    matched.then_some(phrase.len())
+}
+
+/// The words after `words[..len]`, if the span may continue into them.
+fn after<'a, 'b>(words: &'a [Word<'b>], len: usize) -> Option<&'a [Word<'b>]> {
+    (len > 0 && words[len - 1].trail.is_empty()).then(|| &words[len..])
+}
+
+/// Spelled-out number at the start of `words`: its value and the number
+/// of words it spans.
+fn number(rules: &Rules, words: &[Word]) -> Option<(u64, usize)> {
+    let (mut total, mut current) = (0u64, 0u64);
+    let mut last_scale = u64::MAX;
+    let mut len = 0;
+    let mut index = 0;
+    while let Some(word) = words.get(index) {
+        let mut next = index;
+        // A joiner only belongs to the number if a number word follows it
+        if len > 0 && word.key == rules.joiner && word.trail.is_empty() {
+            next += 1;
+        }
+        let Some(word) = words.get(next) else {
+            break;
+        };
+        if let Some(value) = number_word(rules, &word.key) {
+            let fits = match current {
+                0 => true,
+                c if c % 100 == 0 => value < 100,
+                c if c % 10 == 0 => value < 10,
+                _ => false,
+            };
+            if !fits {
+                break;
+            }
+            current += value;
+        } else if rules.hundred == Some(word.key.as_str()) && current < 10 {
+            current = current.max(1) * 100;
+        } else if let Some(&(_, scale)) = rules.scales.iter().find(|(w, _)| *w == word.key) {
+            if scale >= last_scale {
+                break;
+            }
+            total += current.max(1) * scale;
+            current = 0;
+            last_scale = scale;
+        } else {
+            break;
+        }
+        index = next + 1;
+        len = index;
+        if !word.trail.is_empty() {
+            break;
+        }
+    }
+    (len > 0).then_some((total + current, len))
+}
+
+/// [`number`], also reading English years said in pairs ("nineteen
+/// ninety", "twenty twenty four").
+fn cardinal(rules: &Rules, words: &[Word]) -> Option<(u64, usize)> {
+    let (value, len) = number(rules, words)?;
+    if rules.code == "en" && (11..=20).contains(&value) && len == 1 {
+        if let Some((low, low_len)) = after(words, len).and_then(|rest| number(rules, rest)) {
+            if (10..=99).contains(&low) {
+                return Some((value * 100 + low, len + low_len));
+            }
+        }
+    }
+    Some((value, len))
+}
+
+/// Day ordinal at the start of `words` ("fifth", "twenty first").
+fn day_ordinal(rules: &Rules, words: &[Word]) -> Option<(u64, usize)> {
+    if let Some(day) = words.first().and_then(|w| ordinal(rules, &w.key)) {
+        return Some((day, 1));
+    }
+    let tens = number_word(rules, &words.first()?.key)?;
+    let units = after(words, 1)?
+        .first()
+        .and_then(|w| ordinal(rules, &w.key))?;
+    ((tens == 20 || tens == 30) && units < 10).then_some((tens + units, 2))
+}
+
+/// Year following a date ("twenty twenty four", "de dois mil e dez").
+fn year(rules: &Rules, words: &[Word]) -> Option<(u64, usize)> {
+    let skip = match rules.of {
+        Some(of) => phrase(words, &[of])?,
+        None => 0,
+    };
+    let (value, len) = cardinal(rules, &words[skip..])?;
+    (1000..=2999)
+        .contains(&value)
+        .then_some((value, skip + len))
+}
+
+/// Date at the start of `words`: its length and written form.
+fn date(rules: &Rules, words: &[Word]) -> Option<(usize, String)> {
+    if rules.code == "en" {
+        english_date(rules, words)
+    } else {
+        portuguese_date(rules, words)
+    }
+}
+
+/// "march fifth [twenty twenty four]" or "fifth of march".
+fn english_date(rules: &Rules, words: &[Word]) -> Option<(usize, String)> {
+    if let Some(m) = month(rules, &words.first()?.key) {
+        let (day, day_len) = after(words, 1).and_then(|rest| day_ordinal(rules, rest))?;
+        let mut len = 1 + day_len;
+        let mut written = format!("{} {}", capitalize(rules.months[m]), day);
+        if let Some((y, y_len)) = after(words, len).and_then(|rest| year(rules, rest)) {
+            written = format!("{}, {}", written, y);
+            len += y_len;
+        }
+        // "you may first check": only take these as a date ending the phrase
+        let ambiguous = matches!(rules.months[m], "may" | "march");
+        let ends = !words[len - 1].trail.is_empty() || len == words.len();
+        return (!ambiguous || ends).then_some((len, written));
+    }
+    let (day, day_len) = day_ordinal(rules, words)?;
+    let rest = after(words, day_len)?;
+    let of = phrase(rest, &["of"])?;
+    let m = month(rules, &after(rest, of)?.first()?.key)?;
+    let mut len = day_len + of + 1;
+    let mut written = format!("{}{} of {}", day, suffix(day), capitalize(rules.months[m]));
+    if let Some((y, y_len)) = after(words, len).and_then(|rest| year(rules, rest)) {
+        written = format!("{} {}", written, y);
+        len += y_len;
+    }
+    Some((len, written))
+}
+
+/// "cinco de março [de dois mil e vinte e quatro]", "primeiro de maio".
+fn portuguese_date(rules: &Rules, words: &[Word]) -> Option<(usize, String)> {
+    let (day, day_len, first) = match words.first().and_then(|w| ordinal(rules, &w.key)) {
+        Some(day) => (day, 1, true),
+        None => {
+            let (day, len) = number(rules, words)?;
+            (day, len, false)
+        }
+    };
+    if !(1..=31).contains(&day) {
+        return None;
+    }
+    let rest = after(words, day_len)?;
+    let of = phrase(rest, &[rules.of?])?;
+    let m = month(rules, &after(rest, of)?.first()?.key)?;
+    let mut len = day_len + of + 1;
+    let mut written = format!(
+        "{}{} de {}",
+        day,
+        if first { "º" } else { "" },
+        rules.months[m]
+    );
+    if let Some((y, y_len)) = after(words, len).and_then(|rest| year(rules, rest)) {
+        written = format!("{} de {}", written, y);
+        len += y_len;
+    }
+    Some((len, written))
+}
+
+/// Number at the start of `words`, with the currency or percent that
+/// makes it an amount: its length and written form.
+fn quantity(rules: &Rules, words: &[Word]) -> Option<(usize, String)> {
+    let (value, len) = cardinal(rules, words)?;
+    if let Some(rest) = after(words, len) {
+        if let Some(percent) = rules.percent.iter().find_map(|p| phrase(rest, p)) {
+            return Some((len + percent, format!("{}%", group(rules, value))));
+        }
+        if let Some((money_len, written)) = money(rules, value, rest) {
+            return Some((len + money_len, written));
+        }
+    }
+    (len > 1 || value >= 10).then(|| (len, group(rules, value)))
+}
+
+/// Currency after an amount of `units`, with any cents after it ("dollars
+/// and fifty cents"): its length and the written amount.
+fn money(rules: &Rules, units: u64, words: &[Word]) -> Option<(usize, String)> {
+    // "um milhão de reais"
+    let skip = rules
+        .of
+        .and_then(|of| phrase(words, &[of]))
+        .filter(|&skip| {
+            after(words, skip)
+                .and_then(|rest| rest.first())
+                .is_some_and(|w| rules.currencies.iter().any(|(c, _)| *c == w.key))
+        })
+        .unwrap_or(0);
+    let currency = words.get(skip)?;
+    let &(_, symbol) = rules.currencies.iter().find(|(c, _)| *c == currency.key)?;
+    let mut len = skip + 1;
+
+    let mut cents = None;
+    let joined = after(words, len).and_then(|rest| {
+        let joined = phrase(rest, &[rules.joiner])?;
+        after(rest, joined).map(|rest| (joined, rest))
+    });
+    if let Some((joined, rest)) = joined {
+        if let Some((value, value_len)) = number(rules, rest).filter(|&(v, _)| v < 100) {
+            let unit = after(rest, value_len)
+                .and_then(|r| r.first())
+                .filter(|w| rules.cents.contains(&w.key.as_str()));
+            if unit.is_some() {
+                cents = Some(value);
+                len += joined + value_len + 1;
+            }
+        }
+    }
+
+    let space = if rules.symbol_space { " " } else { "" };
+    let mut written = format!("{}{}{}", symbol, space, group(rules, units));
+    if let Some(cents) = cents {
+        written = format!("{}{}{:02}", written, rules.decimal_separator, cents);
+    }
+    Some((len, written))
+}
+
+/// `value` in digits, grouped by thousands from five digits up.
+fn group(rules: &Rules, value: u64) -> String {
+    let digits = value.to_string();
+    if digits.len() < 5 {
+        return digits;
+    }
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(rules.thousands_separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// English ordinal suffix of `day`.
+fn suffix(day: u64) -> &'static str {
+    match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,365 @@
+//! Language hints for decoding.
+//!
+//! The realtime Voxtral prompt has no language token, so a hint can't be
+//! passed to the model directly. Instead the decode loop biases the logits
+//! against text tokens written in scripts the language doesn't use (e.g.
+//! Cyrillic or CJK fragments while transcribing Portuguese). A forced hint
+//! rules those tokens out entirely. Languages sharing a script (pt/es/fr…)
+//! are not told apart by the bias; the hint still decides what is reported.
+//!
+//! Without a hint, the language of each transcript is detected from its
+//! text: the dominant script settles most languages, and stopword counts
+//! tell the Latin-script ones apart.
+
+use anyhow::{bail, Result};
+use burn::backend::wgpu::WgpuDevice;
+use burn::backend::Wgpu;
+use burn::tensor::{Tensor, TensorData};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::info;
+
+use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
+
+type Backend = Wgpu;
+
+/// First text token id; lower ids are control tokens.
+const TEXT_TOKEN_OFFSET: usize = 1000;
+/// Logit penalty for off-script tokens under a (non-forced) hint.
+const HINT_PENALTY: f32 = 4.0;
+/// Logit penalty for off-script tokens under a forced hint (effectively
+/// never picked, while keeping softmax finite).
+const FORCED_PENALTY: f32 = 1e4;
+
+/// Writing systems the supported languages are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Script {
+    Latin,
+    Cyrillic,
+    Arabic,
+    Devanagari,
+    Han,
+    Kana,
+    Hangul,
+}
+
+impl Script {
+    /// Script `c` belongs to, if it is a letter of one of the supported
+    /// scripts.
+    pub(crate) fn of(c: char) -> Option<Self> {
+        match c {
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => {
+                Some(Self::Latin)
+            }
+            '\u{0400}'..='\u{052F}' => Some(Self::Cyrillic),
+            '\u{0600}'..='\u{06FF}'
+            | '\u{0750}'..='\u{077F}'
+            | '\u{FB50}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}' => Some(Self::Arabic),
+            '\u{0900}'..='\u{097F}' => Some(Self::Devanagari),
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => {
+                Some(Self::Han)
+            }
+            '\u{3040}'..='\u{30FF}' => Some(Self::Kana),
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Some(Self::Hangul)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Languages Voxtral transcribes, with the scripts each is written in.
+pub(crate) const LANGUAGES: &[(&str, &[Script])] = &[
+    ("en", &[Script::Latin]),
+    ("es", &[Script::Latin]),
+    ("fr", &[Script::Latin]),
+    ("pt", &[Script::Latin]),
+    ("de", &[Script::Latin]),
+    ("it", &[Script::Latin]),
+    ("nl", &[Script::Latin]),
+    ("ru", &[Script::Cyrillic]),
+    ("ar", &[Script::Arabic]),
+    ("hi", &[Script::Devanagari]),
+    ("zh", &[Script::Han]),
+    ("ja", &[Script::Han, Script::Kana]),
+    ("ko", &[Script::Hangul]),
+];
+
+/// Common short words of the Latin-script languages, for telling them
+/// apart. Accents are kept; Voxtral transcribes them.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "to", "of", "it", "that", "was", "for", "with", "this", "are",
+            "not", "you",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "de", "que", "não", "uma", "com", "para", "por", "mais", "foi", "como", "está", "você",
+            "dos", "das", "isso",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "que", "el", "en", "los", "del", "las", "por", "con", "una", "para", "está", "como",
+            "pero", "muy", "esto",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "les", "des", "est", "une", "dans", "pour", "pas", "sur", "avec", "sont", "mais",
+            "vous", "nous", "cette", "c'est",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "ist", "und", "ein", "den", "von", "nicht", "mit", "ich", "sich",
+            "auf", "auch", "wir",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "che", "non", "una", "per", "sono", "del", "con", "della", "questo", "anche", "gli",
+            "molto", "è", "lo",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "niet", "dat", "met", "zijn", "voor", "ook",
+            "maar", "wij",
+        ],
+    ),
+];
+/// Share of the letters a script needs to be the text's script.
+const DOMINANT_SCRIPT: f32 = 0.5;
+
+/// Language to bias (or force) decoding towards, by ISO 639-1 code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageHint {
+    code: &'static str,
+    scripts: &'static [Script],
+    forced: bool,
+}
+
+impl LanguageHint {
+    /// Hint for the language with ISO 639-1 `code` (e.g. "pt").
+    pub fn new(code: &str) -> Result<Self> {
+        let code = code.trim().to_lowercase();
+        match LANGUAGES.iter().find(|(c, _)| *c == code) {
+            Some(&(code, scripts)) => Ok(Self {
+                code,
+                scripts,
+                forced: false,
+            }),
+            None => bail!(
+                "Unsupported language '{}' (expected one of: {})",
+                code,
+                LANGUAGES
+                    .iter()
+                    .map(|(c, _)| *c)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// Rule out tokens in other scripts instead of only penalizing them.
+    pub fn forced(mut self) -> Self {
+        self.forced = true;
+        self
+    }
+
+    /// ISO 639-1 code of the language.
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Whether off-script tokens are ruled out rather than penalized.
+    pub fn is_forced(&self) -> bool {
+        self.forced
+    }
+
+    /// Whether the language with ISO 639-1 `code` is written in scripts
+    /// this one uses.
+    fn shares_script(&self, code: &str) -> bool {
+        LANGUAGES
+            .iter()
+            .any(|&(c, scripts)| c == code && scripts.iter().all(|s| self.scripts.contains(s)))
+    }
+
+    /// Whether decoded token text fits the language: every letter in it is
+    /// in one of the language's scripts. Digits, punctuation and
+    /// whitespace always fit.
+    fn accepts(&self, text: &str) -> bool {
+        text.chars()
+            .filter_map(Script::of)
+            .all(|s| self.scripts.contains(&s))
+    }
+}
+
+/// Detect the language of transcribed `text` as an ISO 639-1 code, or
+/// None when the text is too short or ambiguous to tell.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let letters = text.chars().filter_map(Script::of).count();
+    if letters == 0 {
+        return None;
+    }
+    let count = |script| {
+        text.chars()
+            .filter(|&c| Script::of(c) == Some(script))
+            .count()
+    };
+    let dominant = |n: usize| n as f32 / letters as f32 >= DOMINANT_SCRIPT;
+
+    // Kana only appears in Japanese, usually mixed with Han
+    if count(Script::Kana) > 0 && dominant(count(Script::Han) + count(Script::Kana)) {
+        return Some("ja");
+    }
+    let by_script = [
+        (Script::Cyrillic, "ru"),
+        (Script::Arabic, "ar"),
+        (Script::Devanagari, "hi"),
+        (Script::Han, "zh"),
+        (Script::Hangul, "ko"),
+    ];
+    if let Some(&(_, code)) = by_script.iter().find(|(s, _)| dominant(count(*s))) {
+        return Some(code);
+    }
+    if !dominant(count(Script::Latin)) {
+        return None;
+    }
+
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase()
+        })
+        .collect();
+    let scores: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|&(code, stopwords)| {
+            let hits = words
+                .iter()
+                .filter(|w| stopwords.contains(&w.as_str()))
+                .count();
+            (code, hits)
+        })
+        .collect();
+    let best = scores.iter().map(|&(_, hits)| hits).max().unwrap_or(0);
+    let mut leaders = scores.iter().filter(|&&(_, hits)| hits == best);
+    match (leaders.next(), leaders.next()) {
+        (Some(&(code, hits)), None) if hits > 0 => Some(code),
+        _ => None,
+    }
+}
+
+/// Language to report for `text`: the hint's if set, unless the text is
+/// clearly in a script the hinted language doesn't use; otherwise the
+/// detected language, or "auto" when it can't be told.
+pub fn reported_language(text: &str, hint: Option<&LanguageHint>) -> String {
+    let detected = detect_language(text);
+    let code = match (hint, detected) {
+        (Some(hint), Some(code)) if !hint.shares_script(code) => code,
+        (Some(hint), _) => hint.code,
+        (None, Some(code)) => code,
+        (None, None) => "auto",
+    };
+    code.to_string()
+}
+
+impl FromStr for LanguageHint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for LanguageHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code)
+    }
+}
+
+/// A language hint and the logit bias it translates to.
+///
+/// The bias needs the vocabulary size and a decode of every token, so it
+/// is built on first use and cached.
+pub struct LanguageBias {
+    hint: LanguageHint,
+    bias: OnceLock<Tensor<Backend, 3>>,
+}
+
+impl LanguageBias {
+    /// Bias for `hint`; the tensor itself is built lazily.
+    pub fn new(hint: LanguageHint) -> Self {
+        Self {
+            hint,
+            bias: OnceLock::new(),
+        }
+    }
+
+    /// The hint this bias was built from.
+    pub fn hint(&self) -> &LanguageHint {
+        &self.hint
+    }
+
+    /// Apply the bias to logits of shape `[1, 1, vocab]`.
+    pub fn apply(
+        &self,
+        logits: Tensor<Backend, 3>,
+        tokenizer: &VoxtralTokenizer,
+        device: &WgpuDevice,
+    ) -> Tensor<Backend, 3> {
+        let vocab = logits.dims()[2];
+        let bias = self
+            .bias
+            .get_or_init(|| self.build(vocab, tokenizer, device));
+        logits + bias.clone()
+    }
+
+    fn build(
+        &self,
+        vocab: usize,
+        tokenizer: &VoxtralTokenizer,
+        device: &WgpuDevice,
+    ) -> Tensor<Backend, 3> {
+        let penalty = if self.hint.forced {
+            FORCED_PENALTY
+        } else {
+            HINT_PENALTY
+        };
+        let bias: Vec<f32> = (0..vocab)
+            .map(|id| {
+                let fits = id < TEXT_TOKEN_OFFSET
+                    || tokenizer
+                        .decode(&[id as u32])
+                        .map_or(true, |text| self.hint.accepts(&text));
+                if fits {
+                    0.0
+                } else {
+                    -penalty
+                }
+            })
+            .collect();
+        let penalized = bias.iter().filter(|&&b| b < 0.0).count();
+        info!(
+            language = self.hint.code,
+            forced = self.hint.forced,
+            penalized,
+            "Built language bias"
+        );
+        Tensor::from_data(TensorData::new(bias, [1, 1, vocab]), device)
+    }
+}
@@ -1,3 +1,23 @@
+pub mod boundary;
+pub mod cancel;
 pub mod engine;
+pub mod gpu;
+pub mod hallucination;
+pub mod handle;
+pub mod hotwords;
+pub mod integrity;
+pub mod itn;
+pub mod language;
+pub mod models;
+pub mod pool;
+pub mod profanity;
+pub mod prompt;
+pub mod punctuation;
+pub mod repetition;
+pub mod stats;
+pub mod stitch;
 pub mod streaming;
+pub mod transcriber;
+pub mod translate;
 pub mod types;
+pub mod whisper;
@@ -0,0 +1,233 @@
+//! Model file downloads.
+//!
+//! Fetches the Voxtral GGUF and the tekken.json tokenizer from a Hugging
+//! Face repository (or any base URL serving both files). Downloads go to a
+//! `.part` file next to the destination and resume from it with an HTTP
+//! range request, so an interrupted 2.5 GB download doesn't start over.
+//! Completed files are checked against the checksum the server publishes,
+//! when it does, and recorded in the directory's `SHA256SUMS` manifest.
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use super::integrity;
+
+/// Hugging Face repository the models are published in.
+pub const DEFAULT_REPO: &str = "TrevorJS/voxtral-mini-realtime-gguf";
+/// Quantized model file name.
+pub const MODEL_FILE: &str = "voxtral-q4.gguf";
+/// Tokenizer file name.
+pub const TOKENIZER_FILE: &str = "tekken.json";
+
+/// Where model files are downloaded from.
+#[derive(Debug, Clone)]
+pub struct ModelSource {
+    base_url: String,
+}
+
+impl ModelSource {
+    /// Files from the main branch of Hugging Face repository `repo`.
+    pub fn hugging_face(repo: &str) -> Self {
+        Self::url(&format!("https://huggingface.co/{}/resolve/main", repo))
+    }
+
+    /// Files served under `base_url` (`<base_url>/<file name>`).
+    pub fn url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// URL of `file`.
+    pub fn file_url(&self, file: &str) -> String {
+        format!("{}/{}", self.base_url, file)
+    }
+}
+
+impl Default for ModelSource {
+    fn default() -> Self {
+        Self::hugging_face(DEFAULT_REPO)
+    }
+}
+
+/// Progress of a model file download.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    /// File being downloaded.
+    pub file: String,
+    /// Bytes on disk so far, including a resumed part.
+    pub downloaded: u64,
+    /// Full size, when the server reports it.
+    pub total: Option<u64>,
+}
+
+/// Download the model and tokenizer from `source` into `dir`, skipping
+/// files already there. Returns the model and tokenizer paths.
+pub async fn download_models(
+    source: &ModelSource,
+    dir: &Path,
+    mut on_progress: impl FnMut(&DownloadProgress),
+) -> Result<(PathBuf, PathBuf)> {
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let client = reqwest::Client::new();
+    let model = dir.join(MODEL_FILE);
+    let tokenizer = dir.join(TOKENIZER_FILE);
+    for (file, dest) in [(MODEL_FILE, &model), (TOKENIZER_FILE, &tokenizer)] {
+        if fs::try_exists(dest).await? {
+            info!(path = %dest.display(), "Model file already present");
+            continue;
+        }
+        download_file(&client, &source.file_url(file), dest, &mut on_progress).await?;
+    }
+    Ok((model, tokenizer))
+}
+
+/// Download `url` to `dest`, resuming from `dest.part` if present.
+pub async fn download_file(
+    client: &reqwest::Client,
+    url: &str,
+    dest: &Path,
+    on_progress: &mut impl FnMut(&DownloadProgress),
+) -> Result<()> {
+    let mut part = dest.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let resume_from = match fs::metadata(&part).await {
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    };
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={}-", resume_from));
+    }
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to request {}", url))?;
+
+    let status = response.status();
+    let (mut downloaded, append) = match status {
+        StatusCode::PARTIAL_CONTENT => (resume_from, true),
+        // Nothing left past the part: it is already complete
+        StatusCode::RANGE_NOT_SATISFIABLE if resume_from > 0 => {
+            return finish(&part, dest, None).await;
+        }
+        // Server ignored the range: start over
+        s if s.is_success() => (0, false),
+        s => bail!("Download of {} failed: HTTP {}", url, s),
+    };
+    let total = total_size(&response, downloaded);
+    let expected = published_sha256(&response);
+    info!(
+        url,
+        resume_from = downloaded,
+        ?total,
+        "Downloading model file"
+    );
+
+    let mut out = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&part)
+        .await
+        .with_context(|| format!("Failed to open {}", part.display()))?;
+
+    let file = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut progress = DownloadProgress {
+        file,
+        downloaded,
+        total,
+    };
+    on_progress(&progress);
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .with_context(|| format!("Download of {} interrupted", url))?
+    {
+        out.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+        progress.downloaded = downloaded;
+        on_progress(&progress);
+    }
+    out.flush().await?;
+    drop(out);
+
+    if let Some(total) = total.filter(|&t| downloaded < t) {
+        bail!(
+            "Download of {} ended early ({} of {} bytes); run it again to resume",
+            url,
+            downloaded,
+            total
+        );
+    }
+    finish(&part, dest, expected).await?;
+    info!(path = %dest.display(), bytes = downloaded, "Downloaded model file");
+    Ok(())
+}
+
+/// Check a completed `part` against the `expected` checksum, move it to
+/// `dest` and record its checksum in the directory's manifest.
+async fn finish(part: &Path, dest: &Path, expected: Option<String>) -> Result<()> {
+    let hashed = part.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || integrity::sha256_file(&hashed)).await??;
+    if let Some(expected) = expected.filter(|e| !e.eq_ignore_ascii_case(&actual)) {
+        fs::remove_file(part).await?;
+        bail!(
+            "Downloaded {} is corrupted (SHA-256 {}, expected {}); removed it, run the download again",
+            dest.display(),
+            actual,
+            expected
+        );
+    }
+    fs::rename(part, dest)
+        .await
+        .with_context(|| format!("Failed to move download to {}", dest.display()))?;
+
+    if let (Some(dir), Some(name)) = (dest.parent(), dest.file_name().and_then(|n| n.to_str())) {
+        integrity::record(dir, name, &actual)?;
+    }
+    Ok(())
+}
+
+/// SHA-256 the server publishes for the file. Hugging Face sends it as the
+/// ETag of files stored in LFS.
+fn published_sha256(response: &reqwest::Response) -> Option<String> {
+    ["x-linked-etag", "etag"]
+        .into_iter()
+        .filter_map(|name| response.headers().get(name)?.to_str().ok())
+        .map(|v| v.trim_start_matches("W/").trim_matches('"').to_string())
+        .find(|v| integrity::is_sha256(v))
+}
+
+/// Full file size from a response starting at byte `offset`.
+fn total_size(response: &reqwest::Response, offset: u64) -> Option<u64> {
+    // "bytes 100-199/200" on a range response
+    let from_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit('/').next())
+        .and_then(|v| v.parse().ok());
+    from_range.or_else(|| {
+        response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|len| len + offset)
+    })
+}
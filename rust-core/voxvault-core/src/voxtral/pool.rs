@@ -0,0 +1,213 @@
+//! Pool of engines shared by concurrent sessions.
+//!
+//! For a server transcribing several audio sources at once. Each engine
+//! runs on its own worker thread; jobs are queued per session and workers
+//! take them round-robin across sessions, so a session with a long
+//! backlog can't starve the others. With a single engine this is one
+//! loaded model serving every session in turn.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use tokio::sync::oneshot;
+use tracing::{error, info};
+use voxtral_mini_realtime::audio::AudioBuffer;
+
+use super::handle::JobOptions;
+use super::transcriber::TranscriptionEngine;
+use super::types::TranscriptResult;
+
+/// Work for a pool worker.
+type Job = Box<dyn FnOnce(&mut dyn TranscriptionEngine) + Send>;
+
+/// Queue state of a pool, for dashboards and load shedding.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PoolMetrics {
+    /// Engines in the pool.
+    pub engines: usize,
+    /// Engines running a job right now.
+    pub busy: usize,
+    /// Jobs waiting, over all sessions.
+    pub queued: usize,
+    /// Jobs waiting per session with any queued.
+    pub queued_by_session: BTreeMap<String, usize>,
+    /// Jobs finished since the pool started.
+    pub completed: u64,
+}
+
+#[derive(Default)]
+struct Queues {
+    /// Waiting jobs per session.
+    sessions: BTreeMap<String, VecDeque<Job>>,
+    /// Sessions with waiting jobs, in the order they get a worker.
+    turns: VecDeque<String>,
+    busy: usize,
+    completed: u64,
+    closed: bool,
+}
+
+impl Queues {
+    /// Next job, taken from the session whose turn it is.
+    fn next(&mut self) -> Option<Job> {
+        let session = self.turns.pop_front()?;
+        let queue = self.sessions.get_mut(&session)?;
+        let job = queue.pop_front();
+        if queue.is_empty() {
+            self.sessions.remove(&session);
+        } else {
+            // Back of the line until every other session had a turn
+            self.turns.push_back(session);
+        }
+        job
+    }
+}
+
+struct Shared {
+    queues: Mutex<Queues>,
+    ready: Condvar,
+    engines: usize,
+}
+
+/// Engines serving jobs from several sessions with per-session fairness.
+///
+/// Clones share the pool. The workers exit once the last clone is
+/// dropped and the queued jobs are done.
+#[derive(Clone)]
+pub struct EnginePool {
+    shared: Arc<Shared>,
+    _closer: Arc<Closer>,
+}
+
+/// Closes the queues when the last pool clone is dropped.
+struct Closer(Arc<Shared>);
+
+impl Drop for Closer {
+    fn drop(&mut self) {
+        if let Ok(mut queues) = self.0.queues.lock() {
+            queues.closed = true;
+        }
+        self.0.ready.notify_all();
+    }
+}
+
+impl EnginePool {
+    /// Start a worker thread for each of `engines`. Load them first (or
+    /// through [`run`](Self::run)): a job on an unloaded engine fails.
+    pub fn new(engines: Vec<Box<dyn TranscriptionEngine>>) -> Result<Self> {
+        if engines.is_empty() {
+            bail!("An engine pool needs at least one engine");
+        }
+        let shared = Arc::new(Shared {
+            queues: Mutex::new(Queues::default()),
+            ready: Condvar::new(),
+            engines: engines.len(),
+        });
+        for (index, mut engine) in engines.into_iter().enumerate() {
+            let shared = Arc::clone(&shared);
+            std::thread::Builder::new()
+                .name(format!("inference-{}", index))
+                .spawn(move || worker(&shared, engine.as_mut()))
+                .context("Failed to spawn inference thread")?;
+        }
+        info!(engines = shared.engines, "Engine pool started");
+        Ok(Self {
+            _closer: Arc::new(Closer(Arc::clone(&shared))),
+            shared,
+        })
+    }
+
+    /// Run `f` with exclusive access to one of the engines, once
+    /// `session`'s earlier jobs and its turn come up.
+    pub async fn run<R, F>(&self, session: &str, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn TranscriptionEngine) -> R + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        let job: Job = Box::new(move |engine| {
+            let _ = reply.send(f(engine));
+        });
+        {
+            let mut queues = self
+                .shared
+                .queues
+                .lock()
+                .map_err(|_| anyhow!("Engine pool poisoned"))?;
+            let queue = queues.sessions.entry(session.to_string()).or_default();
+            queue.push_back(job);
+            if queue.len() == 1 {
+                queues.turns.push_back(session.to_string());
+            }
+        }
+        self.shared.ready.notify_one();
+        // A job that panicked drops its reply
+        result.await.context("Inference job failed")
+    }
+
+    /// Transcribe `audio` for `session`.
+    pub async fn transcribe(
+        &self,
+        session: &str,
+        audio: AudioBuffer,
+        options: JobOptions,
+    ) -> Result<TranscriptResult> {
+        self.run(session, move |engine| {
+            engine.transcribe_with(audio, options.as_options())
+        })
+        .await?
+    }
+
+    /// Current queue depths and worker use.
+    pub fn metrics(&self) -> PoolMetrics {
+        let Ok(queues) = self.shared.queues.lock() else {
+            return PoolMetrics::default();
+        };
+        let queued_by_session: BTreeMap<String, usize> = queues
+            .sessions
+            .iter()
+            .map(|(session, jobs)| (session.clone(), jobs.len()))
+            .collect();
+        PoolMetrics {
+            engines: self.shared.engines,
+            busy: queues.busy,
+            queued: queued_by_session.values().sum(),
+            queued_by_session,
+            completed: queues.completed,
+        }
+    }
+}
+
+/// Run jobs on `engine` until the pool is closed and drained.
+fn worker(shared: &Shared, engine: &mut dyn TranscriptionEngine) {
+    loop {
+        let job = {
+            let Ok(mut queues) = shared.queues.lock() else {
+                return;
+            };
+            loop {
+                if let Some(job) = queues.next() {
+                    queues.busy += 1;
+                    break job;
+                }
+                if queues.closed {
+                    info!(engine = engine.name(), "Inference worker stopped");
+                    return;
+                }
+                queues = match shared.ready.wait(queues) {
+                    Ok(queues) => queues,
+                    Err(_) => return,
+                };
+            }
+        };
+        // A job that panics drops its reply; the engine takes the next one
+        if panic::catch_unwind(AssertUnwindSafe(|| job(&mut *engine))).is_err() {
+            error!(engine = engine.name(), "Inference job panicked");
+        }
+        if let Ok(mut queues) = shared.queues.lock() {
+            queues.busy -= 1;
+            queues.completed += 1;
+        }
+    }
+}
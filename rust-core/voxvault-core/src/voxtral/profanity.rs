@@ -0,0 +1,205 @@
+//! Profanity filtering of final transcripts.
+//!
+//! For captions broadcast publicly. Words on the built-in list of the
+//! transcript's language, or on a custom list, are masked ("f***") or
+//! left alone with the transcript tagged, so clients can decide what to
+//! show. Matching is on whole words, case-insensitively.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Built-in lists per ISO 639-1 code, lowercase.
+const WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "fuck",
+            "fucks",
+            "fucked",
+            "fucking",
+            "fucker",
+            "motherfucker",
+            "shit",
+            "shitty",
+            "bullshit",
+            "bitch",
+            "bitches",
+            "asshole",
+            "assholes",
+            "bastard",
+            "cunt",
+            "dick",
+            "dickhead",
+            "piss",
+            "pissed",
+            "wanker",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "porra",
+            "caralho",
+            "merda",
+            "puta",
+            "puto",
+            "putaria",
+            "foda",
+            "foda-se",
+            "fodase",
+            "foder",
+            "fodido",
+            "cacete",
+            "buceta",
+            "arrombado",
+            "desgraçado",
+            "bosta",
+            "cu",
+            "viado",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "mierda",
+            "joder",
+            "jodido",
+            "puta",
+            "puto",
+            "coño",
+            "cabrón",
+            "gilipollas",
+            "pendejo",
+            "chingada",
+            "verga",
+        ],
+    ),
+];
+
+/// What to do with profanity in a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProfanityAction {
+    /// Leave it (filter off).
+    #[default]
+    Keep,
+    /// Replace all but the first letter of each word with `*`.
+    Mask,
+    /// Leave the text, tagging the transcript as profane.
+    Tag,
+}
+
+impl FromStr for ProfanityAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "keep" | "off" => Ok(Self::Keep),
+            "mask" => Ok(Self::Mask),
+            "tag" => Ok(Self::Tag),
+            other => bail!(
+                "Unknown profanity action '{}': expected 'keep', 'mask' or 'tag'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ProfanityAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keep => write!(f, "keep"),
+            Self::Mask => write!(f, "mask"),
+            Self::Tag => write!(f, "tag"),
+        }
+    }
+}
+
+/// Profanity filter: an action and the words it applies to besides the
+/// built-in lists.
+#[derive(Debug, Clone, Default)]
+pub struct ProfanityFilter {
+    action: ProfanityAction,
+    custom: HashSet<String>,
+}
+
+impl ProfanityFilter {
+    /// Filter with `action` and the built-in lists.
+    pub fn new(action: ProfanityAction) -> Self {
+        Self {
+            action,
+            custom: HashSet::new(),
+        }
+    }
+
+    /// Also filter `words`, in any language (blank entries are ignored).
+    pub fn with_words(mut self, words: impl IntoIterator<Item = String>) -> Self {
+        self.custom.extend(
+            words
+                .into_iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty()),
+        );
+        self
+    }
+
+    /// Also filter the words of a file: one per line, `#` comments.
+    pub fn with_words_file(self, path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profanity file {}", path.display()))?;
+        Ok(self.with_words(
+            content
+                .lines()
+                .filter(|l| !l.trim_start().starts_with('#'))
+                .map(str::to_string),
+        ))
+    }
+
+    /// What the filter does with profanity.
+    pub fn action(&self) -> ProfanityAction {
+        self.action
+    }
+
+    /// Filter `text` in `language` (ISO 639-1 code; the lists of every
+    /// language apply when it is unknown). Returns the text, masked if
+    /// the action is [`Mask`](ProfanityAction::Mask), and whether it
+    /// contains profanity (always false when the filter is off).
+    pub fn apply(&self, text: &str, language: &str) -> (String, bool) {
+        if self.action == ProfanityAction::Keep {
+            return (text.to_string(), false);
+        }
+        let lists: Vec<&[&str]> = match WORDS.iter().find(|(code, _)| *code == language) {
+            Some((_, words)) => vec![words],
+            None => WORDS.iter().map(|(_, words)| *words).collect(),
+        };
+        let mut found = false;
+        let filtered: Vec<String> = text
+            .split_whitespace()
+            .map(|token| {
+                let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+                let key = word.to_lowercase();
+                let profane = !key.is_empty()
+                    && (self.custom.contains(&key) || lists.iter().any(|l| l.contains(&&*key)));
+                if !profane {
+                    return token.to_string();
+                }
+                found = true;
+                match self.action {
+                    ProfanityAction::Mask => token.replacen(word, &mask(word), 1),
+                    _ => token.to_string(),
+                }
+            })
+            .collect();
+        (filtered.join(" "), found)
+    }
+}
+
+/// `word` with every letter after the first replaced by `*`.
+fn mask(word: &str) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(i, c)| if i > 0 && c.is_alphanumeric() { '*' } else { c })
+        .collect()
+}
@@ -0,0 +1,82 @@
+//! Decoder conditioning on preceding text.
+//!
+//! The realtime decoder starts every chunk from a prefix of `BOS` and
+//! padding tokens laid over the left-padding silence. Filling the end of
+//! that prefix with the tail of the previous transcript (or a user prompt)
+//! makes the model continue as if it had just written that text, which
+//! helps casing, names and sentences running across segments.
+//!
+//! The tokenizer only decodes, so prompts are encoded by greedy longest
+//! match against the decoded vocabulary, built once on first use. This is
+//! not the canonical BPE encoding, but yields valid tokens spelling the
+//! same text, which is what conditioning needs.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use tracing::info;
+use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
+
+/// First text token id; lower ids are control tokens.
+const TEXT_TOKEN_OFFSET: u32 = 1000;
+/// Size of the Tekken vocabulary Voxtral uses.
+const VOCAB_SIZE: u32 = 131_072;
+/// Longest vocabulary entry considered when matching, in bytes.
+const MAX_PIECE_BYTES: usize = 32;
+
+/// Encodes prompt text into text tokens.
+#[derive(Default)]
+pub struct PromptEncoder {
+    pieces: OnceLock<HashMap<String, u32>>,
+}
+
+impl PromptEncoder {
+    /// Encode `text` into at most `max_tokens` tokens, keeping its end.
+    pub fn encode_tail(
+        &self,
+        text: &str,
+        max_tokens: usize,
+        tokenizer: &VoxtralTokenizer,
+    ) -> Vec<u32> {
+        let pieces = self.pieces.get_or_init(|| build_pieces(tokenizer));
+        // Leading space: pieces start words with one, as in running text
+        let text = format!(" {}", text.split_whitespace().collect::<Vec<_>>().join(" "));
+
+        let mut tokens = Vec::new();
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let matched = (1..=MAX_PIECE_BYTES.min(rest.len()))
+                .rev()
+                .filter(|&len| rest.is_char_boundary(len))
+                .find_map(|len| pieces.get(&rest[..len]).map(|&id| (len, id)));
+            match matched {
+                Some((len, id)) => {
+                    tokens.push(id);
+                    rest = &rest[len..];
+                }
+                // No piece spells this character: skip it
+                None => {
+                    let skip = rest.chars().next().map_or(1, char::len_utf8);
+                    rest = &rest[skip..];
+                }
+            }
+        }
+
+        let start = tokens.len().saturating_sub(max_tokens);
+        tokens.split_off(start)
+    }
+}
+
+/// Map each decoded vocabulary entry to its (first) token id.
+fn build_pieces(tokenizer: &VoxtralTokenizer) -> HashMap<String, u32> {
+    let mut pieces = HashMap::new();
+    for id in TEXT_TOKEN_OFFSET..VOCAB_SIZE {
+        if let Ok(piece) = tokenizer.decode(&[id]) {
+            if !piece.is_empty() && piece.len() <= MAX_PIECE_BYTES && !piece.contains('\u{FFFD}') {
+                pieces.entry(piece).or_insert(id);
+            }
+        }
+    }
+    info!(pieces = pieces.len(), "Built prompt vocabulary");
+    pieces
+}
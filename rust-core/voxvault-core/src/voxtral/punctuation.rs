@@ -0,0 +1,116 @@
+//! Restoration of casing and punctuation on final transcripts.
+//!
+//! Some decodes come back as bare lowercase words: short segments, the
+//! smaller Whisper models, prompts without punctuation to imitate. For
+//! display and for consumers that split on sentences, a rule-based pass
+//! restores the basics:
+//!
+//! - the first letter is capitalized, and the English pronoun "I";
+//! - the text gets a closing period, or a question mark when it opens
+//!   with an interrogative word of its language.
+//!
+//! Text with any casing or punctuation of its own is left alone: the
+//! decoder knows better than these rules.
+
+/// Words opening a question, per ISO 639-1 code.
+const QUESTION_WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "who", "what", "when", "where", "why", "how", "which", "whose", "is", "are", "does",
+            "did", "can", "could",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "quem", "quando", "onde", "como", "qual", "quais", "quanto", "quantos", "quanta",
+            "quantas", "cadê",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "quién", "quiénes", "qué", "cuándo", "dónde", "cómo", "cuál", "cuáles", "cuánto",
+            "cuántos",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "qui", "quoi", "quand", "où", "pourquoi", "comment", "quel", "quelle", "quels",
+            "quelles", "est-ce",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "wer", "was", "wann", "wo", "warum", "wie", "welche", "welcher", "welches", "wieso",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "chi", "cosa", "quando", "dove", "perché", "come", "quale", "quali", "quanto",
+        ],
+    ),
+];
+
+/// Whether `text` lacks casing and punctuation altogether.
+pub fn needs_restoring(text: &str) -> bool {
+    !text
+        .chars()
+        .any(|c| c.is_uppercase() || matches!(c, '.' | ',' | '?' | '!' | ';' | ':'))
+}
+
+/// `text` in `language` (ISO 639-1 code, or "auto") with casing and
+/// closing punctuation restored, if it [needs restoring](needs_restoring).
+pub fn restore(text: &str, language: &str) -> String {
+    let text = text.trim();
+    if text.is_empty() || !needs_restoring(text) {
+        return text.to_string();
+    }
+    let english = language == "en";
+    let mut restored = String::with_capacity(text.len() + 1);
+    for (index, word) in text.split_whitespace().enumerate() {
+        if index > 0 {
+            restored.push(' ');
+        }
+        if index == 0 || (english && is_pronoun_i(word)) {
+            restored.push_str(&capitalize(word));
+        } else {
+            restored.push_str(word);
+        }
+    }
+    restored.push(if is_question(text, language) {
+        '?'
+    } else {
+        '.'
+    });
+    restored
+}
+
+/// Whether `text` opens with an interrogative word of `language`.
+fn is_question(text: &str, language: &str) -> bool {
+    let Some(first) = text.split_whitespace().next() else {
+        return false;
+    };
+    QUESTION_WORDS
+        .iter()
+        .find(|(code, _)| *code == language)
+        .is_some_and(|(_, words)| words.contains(&first))
+}
+
+/// "i" and its contractions ("i'm", "i'll", ...).
+fn is_pronoun_i(word: &str) -> bool {
+    word == "i" || word.starts_with("i'") || word.starts_with("i’")
+}
+
+/// `word` with its first letter uppercased.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,60 @@
+//! Suppression of degenerate repetition during greedy decoding.
+//!
+//! On long segments the decoder can fall into a loop, emitting the same
+//! phrase until the audio runs out. Two standard constraints break it:
+//!
+//! - Repetition penalty (CTRL-style): logits of tokens already generated
+//!   are divided by the penalty (multiplied, if negative).
+//! - No-repeat n-gram: a token that would complete an n-gram already
+//!   present in the output is ruled out.
+//!
+//! Both operate on the text tokens generated so far for the current chunk
+//! and on logits copied to the CPU.
+
+use std::collections::HashSet;
+
+/// Apply the repetition penalty to `logits` for every token in `history`.
+pub fn apply_repetition_penalty(logits: &mut [f32], history: &[u32], penalty: f32) {
+    if penalty == 1.0 {
+        return;
+    }
+    let seen: HashSet<u32> = history.iter().copied().collect();
+    for id in seen {
+        if let Some(logit) = logits.get_mut(id as usize) {
+            *logit = if *logit > 0.0 {
+                *logit / penalty
+            } else {
+                *logit * penalty
+            };
+        }
+    }
+}
+
+/// Rule out tokens that would repeat an `n`-gram of `history`.
+pub fn ban_repeated_ngrams(logits: &mut [f32], history: &[u32], n: usize) {
+    if n == 0 || history.len() < n {
+        return;
+    }
+    // The last n-1 tokens, which the next token would extend to an n-gram
+    let prefix = &history[history.len() + 1 - n..];
+    for window in history.windows(n) {
+        if window[..n - 1] == *prefix {
+            if let Some(logit) = logits.get_mut(window[n - 1] as usize) {
+                *logit = f32::NEG_INFINITY;
+            }
+        }
+    }
+}
+
+/// Greedy choice over CPU logits: the argmax token and its softmax
+/// probability.
+pub fn pick_token(logits: &[f32]) -> (i32, f32) {
+    let (index, max) = logits
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0));
+    let sum: f32 = logits.iter().map(|l| (l - max).exp()).sum();
+    (index as i32, 1.0 / sum)
+}
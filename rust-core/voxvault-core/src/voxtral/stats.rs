@@ -0,0 +1,82 @@
+//! Rolling performance figures of an engine.
+//!
+//! Averages over the most recent transcriptions rather than the whole
+//! session, so a dashboard sees the current speed (after a model switch,
+//! under GPU contention) instead of a slowly moving lifetime mean.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::engine::EngineStats;
+
+/// Transcriptions the rolling figures are averaged over.
+const WINDOW: usize = 50;
+
+/// Cost of one transcription.
+struct Sample {
+    audio_secs: f64,
+    elapsed_secs: f64,
+    tokens: usize,
+    first_partial: Option<Duration>,
+}
+
+/// The last [`WINDOW`] transcriptions of an engine.
+#[derive(Default)]
+pub struct RollingStats {
+    samples: Mutex<VecDeque<Sample>>,
+}
+
+impl RollingStats {
+    /// Record a transcription of `audio_secs` of audio that took
+    /// `elapsed_secs` and decoded `tokens` text tokens, its first partial
+    /// arriving after `first_partial` (None without partials).
+    pub fn record(
+        &self,
+        audio_secs: f64,
+        elapsed_secs: f64,
+        tokens: usize,
+        first_partial: Option<Duration>,
+    ) {
+        let Ok(mut samples) = self.samples.lock() else {
+            return;
+        };
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(Sample {
+            audio_secs,
+            elapsed_secs,
+            tokens,
+            first_partial,
+        });
+    }
+
+    /// Forget the most recent transcription (e.g. a warm-up pass).
+    pub fn discard_last(&self) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.pop_back();
+        }
+    }
+
+    /// Fill the rolling figures of `stats`: RTF, tokens per second and mean
+    /// first-partial latency (None until there is something to average).
+    pub fn fill(&self, stats: &mut EngineStats) {
+        let Ok(samples) = self.samples.lock() else {
+            return;
+        };
+        let audio_secs: f64 = samples.iter().map(|s| s.audio_secs).sum();
+        let elapsed_secs: f64 = samples.iter().map(|s| s.elapsed_secs).sum();
+        let tokens: usize = samples.iter().map(|s| s.tokens).sum();
+        let latencies: Vec<f64> = samples
+            .iter()
+            .filter_map(|s| s.first_partial)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .collect();
+
+        stats.rtf = (audio_secs > 0.0).then(|| elapsed_secs / audio_secs);
+        stats.tokens_per_sec = (elapsed_secs > 0.0).then(|| tokens as f64 / elapsed_secs);
+        stats.first_partial_ms =
+            (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64);
+    }
+}
@@ -0,0 +1,45 @@
+//! De-duplication of text transcribed twice across a segment cut.
+//!
+//! When the processor cuts a segment at its maximum duration, the tail of
+//! the audio is repeated at the start of the next segment so a word split
+//! by the cut is transcribed whole. The words heard in that overlap then
+//! appear at the end of one transcript and the start of the next.
+
+/// Longest run of repeated words looked for (about 3s of speech).
+const MAX_OVERLAP_WORDS: usize = 12;
+
+/// Remove the leading words of `next` that repeat the end of `previous`.
+///
+/// Words are compared ignoring case and surrounding punctuation, and the
+/// longest repeated run wins. The last word of `previous` may be a clipped
+/// fragment of the first repeated word (e.g. "transcri" / "transcription"),
+/// in which case the whole word is still dropped from `next`.
+pub fn stitch_overlap(previous: &str, next: &str) -> String {
+    let prev: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let words: Vec<&str> = next.split_whitespace().collect();
+    let norm: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let max = MAX_OVERLAP_WORDS.min(prev.len()).min(norm.len());
+    let repeated = (1..=max)
+        .rev()
+        .find(|&n| {
+            let tail = &prev[prev.len() - n..];
+            let head = &norm[..n];
+            tail[..n - 1] == head[..n - 1] && is_same_word(&tail[n - 1], &head[n - 1])
+        })
+        .unwrap_or(0);
+
+    words[repeated..].join(" ")
+}
+
+/// A word as compared for overlap: lowercase, without punctuation.
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Whether `tail` (end of the previous transcript) and `head` are the
+/// same word, allowing `tail` to be clipped by the cut.
+fn is_same_word(tail: &str, head: &str) -> bool {
+    !tail.is_empty() && (tail == head || (tail.len() >= 3 && head.starts_with(tail)))
+}
@@ -8,11 +8,13 @@ use anyhow::{bail, Context, Result};
 use burn::backend::wgpu::WgpuDevice;
 use burn::backend::Wgpu;
 use burn::prelude::ElementConversion;
+use burn::tensor::activation::softmax;
 use burn::tensor::{Tensor, TensorData};
-use tracing::info;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
 use voxtral_mini_realtime::audio::{
-    chunk::{chunk_audio, needs_chunking, ChunkConfig},
+    chunk::{chunk_audio, needs_chunking},
     mel::MelSpectrogram,
     pad::{pad_audio, PadConfig},
     AudioBuffer,
@@ -20,15 +22,49 @@ use voxtral_mini_realtime::audio::{
 use voxtral_mini_realtime::gguf::model::Q4VoxtralModel;
 use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
 
-use super::types::TranscriptResult;
+use super::boundary::{self, ChunkWindow, TokenFilter, Verdict, POSITION_MS};
+use super::cancel::CancelToken;
+use super::engine::{EngineConfig, DEFAULT_DELAY};
+use super::hotwords::Hotwords;
+use super::language::{reported_language, LanguageBias};
+use super::prompt::PromptEncoder;
+use super::repetition;
+use super::types::{TranscriptResult, WordConfidence};
 
 type Backend = Wgpu;
 
-const PREFIX_LEN: usize = 38;
+/// Positions of left-padding silence ahead of the audio; the prefix
+/// covers them plus the decoder delay (38 positions at the default delay).
+const LEFT_PAD_POSITIONS: usize = 32;
 const BOS_TOKEN: i32 = 1;
 const STREAMING_PAD: i32 = 32;
 const TEXT_TOKEN_OFFSET: i32 = 1000;
 
+/// Length of the decode prefix at `delay`.
+pub(super) fn prefix_len(delay: usize) -> usize {
+    LEFT_PAD_POSITIONS + delay
+}
+
+/// Text decoded from one audio chunk.
+#[derive(Default)]
+struct DecodedChunk {
+    text: String,
+    /// Per text token: decoded text length once it was added, and its
+    /// probability.
+    tokens: Vec<(usize, f32)>,
+    /// Per text token, the decoder position it was generated at.
+    positions: Vec<usize>,
+    /// Decoding stopped at the deadline before the audio ran out.
+    timed_out: bool,
+}
+
+/// Greedy choice from logits of shape `[1, 1, vocab]`: the argmax token
+/// and its softmax probability.
+fn pick_token(logits: Tensor<Backend, 3>) -> (i32, f32) {
+    let (prob, index) = softmax(logits, 2).max_dim_with_indices(2);
+    (index.into_scalar().elem(), prob.into_scalar().elem())
+}
+
 /// Streaming transcriber that yields tokens one-by-one via callback.
 ///
 /// Uses the Q4 model's public decoder API to run the autoregressive decode loop,
@@ -40,7 +76,15 @@ pub struct StreamingTranscriber<'a> {
     mel_extractor: &'a MelSpectrogram,
     t_embed: &'a Tensor<Backend, 3>,
     device: &'a WgpuDevice,
-    max_mel_frames: usize,
+    language: Option<&'a LanguageBias>,
+    hotwords: Option<&'a Hotwords>,
+    /// Text to condition the first chunk on, and its encoder.
+    prompt: Option<(&'a str, &'a PromptEncoder)>,
+    /// Audio decoded ahead of the first chunk, whose text is dropped.
+    context_audio: Option<&'a [f32]>,
+    cancel: Option<&'a CancelToken>,
+    timeout: Option<Duration>,
+    config: &'a EngineConfig,
 }
 
 impl<'a> StreamingTranscriber<'a> {
@@ -51,7 +95,7 @@ impl<'a> StreamingTranscriber<'a> {
         mel_extractor: &'a MelSpectrogram,
         t_embed: &'a Tensor<Backend, 3>,
         device: &'a WgpuDevice,
-        max_mel_frames: usize,
+        config: &'a EngineConfig,
     ) -> Self {
         Self {
             model,
@@ -59,15 +103,71 @@ impl<'a> StreamingTranscriber<'a> {
             mel_extractor,
             t_embed,
             device,
-            max_mel_frames,
+            language: None,
+            hotwords: None,
+            prompt: None,
+            context_audio: None,
+            cancel: None,
+            timeout: None,
+            config,
         }
     }
 
+    /// Bias decoding with a language hint.
+    pub fn with_language(mut self, language: Option<&'a LanguageBias>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Condition decoding on `prompt` (e.g. the previous transcript), as
+    /// if the model had just written it. Later chunks of a long segment are
+    /// conditioned on the chunk before them.
+    pub fn with_prompt(mut self, prompt: Option<&'a str>, encoder: &'a PromptEncoder) -> Self {
+        self.prompt = prompt
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| (p, encoder));
+        self
+    }
+
+    /// Decode `context_audio` (audio heard just before, e.g. the previous
+    /// segment's tail) ahead of the first chunk, so a sentence continuing
+    /// across a segment cut is decoded as a continuation.
+    ///
+    /// The model's KV cache can't be carried from one decode to the next,
+    /// so the context window is recomputed instead: its positions are
+    /// decoded like any other, and the text tokens generated while the
+    /// decoder is still within them are kept out of the result.
+    pub fn with_context_audio(mut self, context_audio: Option<&'a [f32]>) -> Self {
+        self.context_audio = context_audio.filter(|a| !a.is_empty());
+        self
+    }
+
+    /// Stop decoding with [`Cancelled`](super::cancel::Cancelled) once
+    /// `cancel` is cancelled.
+    pub fn with_cancel(mut self, cancel: Option<&'a CancelToken>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Stop decoding after `timeout` of wall time, keeping the text so far.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Boost a custom vocabulary.
+    pub fn with_hotwords(mut self, hotwords: Option<&'a Hotwords>) -> Self {
+        self.hotwords = hotwords.filter(|h| !h.is_empty());
+        self
+    }
+
     /// Transcribe audio with per-token streaming callback.
     ///
     /// Calls `on_partial(text_so_far)` each time a new text token is decoded,
     /// providing the accumulated transcription. Control tokens (< 1000) are
     /// filtered; the callback fires only when decoded text actually grows.
+    /// Chunks of a long buffer decoded concurrently (`parallel_chunks`)
+    /// report their text once per chunk instead.
     ///
     /// Returns the final `TranscriptResult` with `is_final: true`.
     pub fn transcribe<F: FnMut(&str)>(
@@ -75,11 +175,11 @@ impl<'a> StreamingTranscriber<'a> {
         audio: AudioBuffer,
         mut on_partial: F,
     ) -> Result<TranscriptResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = Instant::now();
+        let deadline = self.timeout.map(|t| start_time + t);
         let audio_duration_secs = audio.samples.len() as f64 / audio.sample_rate as f64;
 
-        let pad_config = PadConfig::voxtral();
-        let chunk_config = ChunkConfig::voxtral().with_max_frames(self.max_mel_frames);
+        let chunk_config = self.config.chunk_config();
         let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
 
         let chunks = if needs_chunking(audio.samples.len(), &chunk_config) {
@@ -100,18 +200,100 @@ impl<'a> StreamingTranscriber<'a> {
         };
 
         let mut texts = Vec::new();
+        let mut words = Vec::new();
+        let mut token_probs = Vec::new();
 
-        for chunk in &chunks {
-            let chunk_audio = AudioBuffer::new(chunk.samples.clone(), audio.sample_rate);
-            let mel_tensor = self.compute_mel(&chunk_audio, &pad_config)?;
+        let mut context = self.prompt.map(|(prompt, _)| prompt.to_string());
+        let mut timed_out = false;
+        let windows = boundary::windows(&chunks, audio.sample_rate);
+        let wave_size = self.config.parallel_chunks.max(1);
 
-            let text = self.decode_streaming(mel_tensor, &mut on_partial)?;
-            if !text.trim().is_empty() {
-                texts.push(text.trim().to_string());
+        for (wave_index, wave) in windows.chunks(wave_size).enumerate() {
+            if let Some(cancel) = self.cancel {
+                cancel.check()?;
+            }
+            if timed_out {
+                break;
+            }
+            let first_index = wave_index * wave_size;
+            let decoded = if let [window] = wave {
+                vec![self.decode_window(
+                    &audio,
+                    window,
+                    first_index == 0,
+                    context.as_deref(),
+                    deadline,
+                    &mut on_partial,
+                )?]
+            } else {
+                // Chunks of a wave decode concurrently, each following on
+                // from the text before the wave; their text is reported as
+                // each chunk's partial once the wave is done
+                let context = context.as_deref();
+                let audio = &audio;
+                let decoded = std::thread::scope(|scope| {
+                    let handles: Vec<_> = wave
+                        .iter()
+                        .enumerate()
+                        .map(|(i, window)| {
+                            let first = first_index + i == 0;
+                            scope.spawn(move || {
+                                self.decode_window(
+                                    audio,
+                                    window,
+                                    first,
+                                    context,
+                                    deadline,
+                                    &mut |_| {},
+                                )
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| {
+                            handle
+                                .join()
+                                .unwrap_or_else(|_| bail!("Chunk transcription panicked"))
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })?;
+                for (chunk, _) in &decoded {
+                    let text = chunk.text.trim();
+                    if !text.is_empty() {
+                        on_partial(text);
+                    }
+                }
+                decoded
+            };
+
+            for (decoded, times) in decoded {
+                timed_out |= decoded.timed_out;
+                if !decoded.text.trim().is_empty() {
+                    context = Some(decoded.text.clone());
+                }
+                words.extend(WordConfidence::from_timed_tokens(
+                    &decoded.text,
+                    &decoded.tokens,
+                    &times,
+                ));
+                token_probs.extend(decoded.tokens.iter().map(|&(_, prob)| prob));
+                if !decoded.text.trim().is_empty() {
+                    texts.push(decoded.text.trim().to_string());
+                }
             }
         }
 
         let full_text = texts.join(" ");
+        let confidence = (!token_probs.is_empty())
+            .then(|| token_probs.iter().sum::<f32>() / token_probs.len() as f32);
+        let avg_logprob = (!token_probs.is_empty()).then(|| {
+            token_probs
+                .iter()
+                .map(|p| p.max(f32::MIN_POSITIVE).ln())
+                .sum::<f32>()
+                / token_probs.len() as f32
+        });
 
         let elapsed_secs = start_time.elapsed().as_secs_f64();
         let rtf = if audio_duration_secs > 0.0 {
@@ -120,42 +302,126 @@ impl<'a> StreamingTranscriber<'a> {
             None
         };
 
+        let language = reported_language(&full_text, self.language.map(LanguageBias::hint));
+
         Ok(TranscriptResult {
             text: full_text,
-            language: "auto".to_string(),
+            language,
             timestamp_ms,
             is_final: true,
             rtf,
+            start_ms: None,
+            end_ms: None,
+            confidence,
+            words,
+            avg_logprob,
+            hallucination: None,
+            timed_out,
+            tokens: token_probs.len(),
         })
     }
 
+    /// Decode the chunk at `window` of `audio`, following on from
+    /// `context` (the text before it), with the start and end time in the
+    /// segment of each kept token. The `first` chunk is decoded after the
+    /// context audio.
+    fn decode_window<F: FnMut(&str)>(
+        &self,
+        audio: &AudioBuffer,
+        window: &ChunkWindow,
+        first: bool,
+        context: Option<&str>,
+        deadline: Option<Instant>,
+        on_partial: &mut F,
+    ) -> Result<(DecodedChunk, Vec<(u64, u64)>)> {
+        // Later chunks follow on from the chunk before them, and their
+        // window starts with its last second
+        let context_audio = self.context_audio.filter(|_| first);
+        let window_samples = &audio.samples[window.start_sample..window.end_sample];
+        let mut samples = match context_audio {
+            Some(context_audio) => [context_audio, window_samples].concat(),
+            None => window_samples.to_vec(),
+        };
+        let context_len = context_audio.map_or(0, <[f32]>::len);
+        let position_of = |samples: usize| {
+            self.prefix_len() + boundary::positions(context_len + samples, audio.sample_rate)
+        };
+        let filter = TokenFilter::new(
+            position_of(window.keep_from),
+            window.keep_until.map(position_of),
+        );
+        let position_samples = audio.sample_rate as usize * POSITION_MS / 1000;
+        // The padding flushes the default delay's worth of tokens; the
+        // last words of a longer delay need more silence to come out
+        let extra_positions = self.config.delay.saturating_sub(DEFAULT_DELAY);
+        samples.resize(samples.len() + extra_positions * position_samples, 0.0);
+        let chunk_audio = AudioBuffer::new(samples, audio.sample_rate);
+        let mel_tensor = self.compute_mel(&chunk_audio, &PadConfig::voxtral())?;
+
+        let prompt = match (self.prompt, context) {
+            (Some((_, encoder)), Some(context)) => {
+                encoder.encode_tail(context, self.prefix_len() - 1, self.tokenizer)
+            }
+            _ => Vec::new(),
+        };
+        let decoded = self.decode_streaming(mel_tensor, &prompt, filter, deadline, on_partial)?;
+        // A token at position `prefix_len + i` is about audio frame `i`
+        // of the chunk, context audio included
+        let window_ms = (window.start_sample * 1000 / audio.sample_rate as usize) as u64;
+        let context_ms = (context_len * 1000 / audio.sample_rate as usize) as u64;
+        let times = decoded
+            .positions
+            .iter()
+            .map(|&pos| {
+                let frame_ms = ((pos - self.prefix_len()) * POSITION_MS) as u64;
+                let start = (window_ms + frame_ms).saturating_sub(context_ms);
+                (start, start + POSITION_MS as u64)
+            })
+            .collect();
+        Ok((decoded, times))
+    }
+
     /// Run the autoregressive decode loop with per-token callback.
     ///
     /// This reimplements `Q4VoxtralModel::transcribe_streaming()` (model.rs:873-963)
     /// using the model's public decoder API, adding callback invocations.
+    /// `prompt` text tokens (at most the prefix length - 1) end the prefix; only
+    /// the text `filter` keeps is returned, and decoding stops once it has
+    /// passed the kept range; past `deadline`, decoding stops with the text so
+    /// far.
     fn decode_streaming<F: FnMut(&str)>(
         &self,
         mel: Tensor<Backend, 3>,
+        prompt: &[u32],
+        mut filter: TokenFilter,
+        deadline: Option<Instant>,
         on_partial: &mut F,
-    ) -> Result<String> {
+    ) -> Result<DecodedChunk> {
+        let prefix_len = self.prefix_len();
         let audio_embeds = self.model.encode_audio(mel);
         let [_, seq_len, d_model] = audio_embeds.dims();
 
-        if seq_len < PREFIX_LEN {
-            return Ok(String::new());
+        if seq_len < prefix_len {
+            return Ok(DecodedChunk::default());
         }
 
         let decoder = self.model.decoder();
 
-        // Build prefix: [BOS, PAD, PAD, ..., PAD] (38 tokens)
+        // Build prefix: [BOS, PAD, PAD, ..., PAD] (`prefix_len` tokens), any prompt
+        // replacing the last PADs
+        let prompt = &prompt[prompt.len().saturating_sub(prefix_len - 1)..];
         let mut prefix: Vec<i32> = vec![BOS_TOKEN];
-        prefix.extend(std::iter::repeat_n(STREAMING_PAD, PREFIX_LEN - 1));
+        prefix.extend(std::iter::repeat_n(
+            STREAMING_PAD,
+            prefix_len - 1 - prompt.len(),
+        ));
+        prefix.extend(prompt.iter().map(|&t| t as i32));
 
-        let prefix_text_embeds = decoder.embed_tokens_from_ids(&prefix, 1, PREFIX_LEN);
+        let prefix_text_embeds = decoder.embed_tokens_from_ids(&prefix, 1, prefix_len);
 
         let prefix_audio = audio_embeds
             .clone()
-            .slice([0..1, 0..PREFIX_LEN, 0..d_model]);
+            .slice([0..1, 0..prefix_len, 0..d_model]);
 
         let prefix_inputs = prefix_audio + prefix_text_embeds;
 
@@ -174,21 +440,31 @@ impl<'a> StreamingTranscriber<'a> {
         let last_logits =
             logits
                 .clone()
-                .slice([0..1, (PREFIX_LEN - 1)..PREFIX_LEN, 0..logits.dims()[2]]);
-        let first_pred = last_logits.argmax(2);
-        let first_token: i32 = first_pred.into_scalar().elem();
+                .slice([0..1, (prefix_len - 1)..prefix_len, 0..logits.dims()[2]]);
+        let (first_token, first_prob) = self.choose(last_logits, &[], "")?;
 
         let mut generated = prefix;
         generated.push(first_token);
 
-        // Track text tokens for incremental decoding
+        // Track text tokens for incremental decoding, and for each one the
+        // decoded length it reached and its probability
         let mut text_token_ids: Vec<u32> = Vec::new();
+        let mut tokens: Vec<(usize, f32)> = Vec::new();
+        let mut positions = Vec::new();
         let mut last_decoded_len: usize = 0;
-
-        // Emit first token if it's text
-        if first_token >= TEXT_TOKEN_OFFSET {
-            text_token_ids.push(first_token as u32);
+        let mut decoded_text = String::new();
+
+        // Emit first token if it's text and the filter keeps it (the delay
+        // aligns position `prefix_len + i` with audio frame `i`)
+        let first_id = first_token as u32;
+        let first_verdict = (first_token >= TEXT_TOKEN_OFFSET)
+            .then(|| filter.check(self.tokenizer, &text_token_ids, first_id, prefix_len));
+        if first_verdict == Some(Verdict::Keep) {
+            text_token_ids.push(first_id);
             if let Ok(decoded) = self.tokenizer.decode(&text_token_ids) {
+                tokens.push((decoded.len(), first_prob));
+                positions.push(prefix_len);
+                decoded_text.clone_from(&decoded);
                 let trimmed = decoded.trim().to_string();
                 if !trimmed.is_empty() {
                     last_decoded_len = trimmed.len();
@@ -198,17 +474,30 @@ impl<'a> StreamingTranscriber<'a> {
         }
 
         // Pre-slice all audio positions to avoid cloning full tensor each step
-        let audio_slices: Vec<Tensor<Backend, 3>> = (PREFIX_LEN..seq_len)
+        let audio_slices: Vec<Tensor<Backend, 3>> = (prefix_len..seq_len)
             .map(|pos| audio_embeds.clone().slice([0..1, pos..pos + 1, 0..d_model]))
             .collect();
         drop(audio_embeds);
 
         // Autoregressive decode loop — one token per iteration
-        for pos in (PREFIX_LEN + 1)..seq_len {
+        let mut timed_out = false;
+        for pos in (prefix_len + 1)..seq_len {
+            if let Some(cancel) = self.cancel {
+                cancel.check()?;
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                warn!(
+                    decoded_positions = pos - prefix_len,
+                    total_positions = seq_len - prefix_len,
+                    "Segment decode timed out"
+                );
+                timed_out = true;
+                break;
+            }
             let new_token = generated[pos - 1];
 
             let text_embed = decoder.embed_tokens_from_ids(&[new_token], 1, 1);
-            let audio_pos = audio_slices[pos - 1 - PREFIX_LEN].clone();
+            let audio_pos = audio_slices[pos - 1 - prefix_len].clone();
             let input = audio_pos + text_embed;
 
             let hidden = decoder.forward_hidden_with_cache(
@@ -218,35 +507,80 @@ impl<'a> StreamingTranscriber<'a> {
             );
             let logits = decoder.lm_head(hidden);
 
-            let pred = logits.argmax(2);
-            let next_token: i32 = pred.into_scalar().elem();
+            let (next_token, prob) = self.choose(logits, &text_token_ids, &decoded_text)?;
 
             generated.push(next_token);
 
+            if next_token < TEXT_TOKEN_OFFSET {
+                continue;
+            }
+            match filter.check(self.tokenizer, &text_token_ids, next_token as u32, pos) {
+                Verdict::Skip => continue,
+                // The rest is the next chunk's to decode
+                Verdict::Stop => break,
+                Verdict::Keep => {}
+            }
+
             // Emit text tokens incrementally
-            if next_token >= TEXT_TOKEN_OFFSET {
-                text_token_ids.push(next_token as u32);
-                if let Ok(decoded) = self.tokenizer.decode(&text_token_ids) {
-                    let trimmed = decoded.trim().to_string();
-                    if trimmed.len() > last_decoded_len {
-                        last_decoded_len = trimmed.len();
-                        on_partial(&trimmed);
-                    }
+            text_token_ids.push(next_token as u32);
+            if let Ok(decoded) = self.tokenizer.decode(&text_token_ids) {
+                tokens.push((decoded.len(), prob));
+                positions.push(pos);
+                decoded_text.clone_from(&decoded);
+                let trimmed = decoded.trim().to_string();
+                if trimmed.len() > last_decoded_len {
+                    last_decoded_len = trimmed.len();
+                    on_partial(&trimmed);
                 }
             }
         }
 
-        // Decode final text from all generated tokens
-        let final_tokens: Vec<i32> = generated.into_iter().skip(PREFIX_LEN).collect();
-        let text_tokens: Vec<u32> = final_tokens
-            .iter()
-            .filter(|&&t| t >= TEXT_TOKEN_OFFSET)
-            .map(|&t| t as u32)
-            .collect();
+        // Decode final text from the kept text tokens
+        let text = self
+            .tokenizer
+            .decode(&text_token_ids)
+            .context("Failed to decode tokens")?;
+        Ok(DecodedChunk {
+            text,
+            tokens,
+            positions,
+            timed_out,
+        })
+    }
 
-        self.tokenizer
-            .decode(&text_tokens)
-            .context("Failed to decode tokens")
+    /// Length of the decode prefix at the configured delay.
+    fn prefix_len(&self) -> usize {
+        prefix_len(self.config.delay)
+    }
+
+    /// Pick the next token from logits of shape `[1, 1, vocab]`, applying
+    /// the language bias, the repetition constraints against `history`
+    /// (the chunk's text tokens so far) and hotword boosts after `text`
+    /// (their decoded text).
+    fn choose(
+        &self,
+        logits: Tensor<Backend, 3>,
+        history: &[u32],
+        text: &str,
+    ) -> Result<(i32, f32)> {
+        let logits = match self.language {
+            Some(language) => language.apply(logits, self.tokenizer, self.device),
+            None => logits,
+        };
+        if !self.config.suppresses_repetition() && self.hotwords.is_none() {
+            return Ok(pick_token(logits));
+        }
+
+        let mut logits = logits
+            .into_data()
+            .to_vec::<f32>()
+            .map_err(|e| anyhow::anyhow!("Failed to read logits: {:?}", e))?;
+        repetition::apply_repetition_penalty(&mut logits, history, self.config.repetition_penalty);
+        repetition::ban_repeated_ngrams(&mut logits, history, self.config.no_repeat_ngram_size);
+        if let Some(hotwords) = self.hotwords {
+            hotwords.apply(&mut logits, text, self.tokenizer);
+        }
+        Ok(repetition::pick_token(&logits))
     }
 
     /// Compute mel spectrogram tensor from audio buffer.
@@ -0,0 +1,141 @@
+//! Common interface of the speech-to-text engines.
+//!
+//! - [`VoxtralEngine`](super::engine::VoxtralEngine): Voxtral Mini
+//!   realtime (Q4 GGUF, wgpu). The default, and the only engine with
+//!   hotwords, runtime model switching and a configurable delay.
+//! - [`WhisperEngine`](super::whisper::WhisperEngine): a whisper.cpp GGML
+//!   model. Needs the `whisper` feature.
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::str::FromStr;
+use voxtral_mini_realtime::audio::AudioBuffer;
+
+use super::engine::{EngineStats, TranscribeOptions};
+use super::hotwords::Hotwords;
+use super::language::LanguageHint;
+use super::types::TranscriptResult;
+
+/// A speech-to-text engine with lazy model loading.
+pub trait TranscriptionEngine: Send {
+    /// Short name for logs ("voxtral", "whisper").
+    fn name(&self) -> &'static str;
+
+    /// Whether the model is loaded.
+    fn is_loaded(&self) -> bool;
+
+    /// Load the model. Returns the load time in milliseconds (0 if it was
+    /// already loaded).
+    fn load(&mut self) -> Result<u64>;
+
+    /// Free the model.
+    fn unload(&mut self);
+
+    /// Transcribe an audio buffer. The model must be loaded first.
+    fn transcribe_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+    ) -> Result<TranscriptResult>;
+
+    /// Transcribe an audio buffer, calling `on_partial` with the text
+    /// decoded so far as it grows.
+    fn transcribe_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult>;
+
+    /// Like `transcribe_streaming_with`, for a preview of audio that is
+    /// transcribed again later (a segment still being spoken): it isn't
+    /// counted in the stats or screened for hallucinations.
+    fn preview_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult>;
+
+    /// Transcribe an audio buffer with default options.
+    fn transcribe(&self, audio: AudioBuffer) -> Result<TranscriptResult> {
+        self.transcribe_with(audio, TranscribeOptions::default())
+    }
+
+    /// Load the model if it isn't loaded. Returns the load time in
+    /// milliseconds if it had to load.
+    fn ensure_loaded(&mut self) -> Result<Option<u64>> {
+        if self.is_loaded() {
+            return Ok(None);
+        }
+        self.load().map(Some)
+    }
+
+    /// Unload the model if it has been idle for long enough. Returns
+    /// whether it was unloaded.
+    fn unload_if_idle(&mut self) -> bool {
+        false
+    }
+
+    /// Language hint transcription is biased towards, if any.
+    fn language(&self) -> Option<&LanguageHint> {
+        None
+    }
+
+    /// Replace the vocabulary boosted during decoding.
+    fn set_hotwords(&mut self, _hotwords: Option<Hotwords>) -> Result<()> {
+        bail!("The {} engine doesn't support hotwords", self.name())
+    }
+
+    /// Switch to another registered model variant. Returns the load time
+    /// in milliseconds.
+    fn switch_model(&mut self, _name: &str) -> Result<u64> {
+        bail!("The {} engine doesn't support model switching", self.name())
+    }
+
+    /// Change the decoder delay (tokens of 80 ms), trading latency for
+    /// accuracy.
+    fn set_delay(&mut self, _delay: usize) -> Result<()> {
+        bail!(
+            "The {} engine doesn't support a configurable delay",
+            self.name()
+        )
+    }
+
+    /// Transcription counters since the engine was created.
+    fn stats(&self) -> EngineStats {
+        EngineStats::default()
+    }
+}
+
+/// Which engine transcribes a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EngineKind {
+    #[default]
+    Voxtral,
+    Whisper,
+}
+
+impl FromStr for EngineKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "voxtral" => Ok(Self::Voxtral),
+            "whisper" => Ok(Self::Whisper),
+            other => bail!(
+                "Unknown engine '{}': expected 'voxtral' or 'whisper'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Voxtral => write!(f, "voxtral"),
+            Self::Whisper => write!(f, "whisper"),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Translation of final transcripts.
+//!
+//! A [`Translator`] renders a final transcript in another language. The
+//! CLI runs it on the async runtime and sends the result to clients as a
+//! separate `translation` message, so transcripts aren't held up by the
+//! round trip. [`HttpTranslator`] calls a DeepL or LibreTranslate server.
+
+use anyhow::{bail, Context, Result};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Longest wait for a translation before giving up on it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// LibreTranslate's default address when self-hosted.
+const LIBRETRANSLATE_URL: &str = "http://localhost:5000";
+
+/// A machine translation backend.
+pub trait Translator: Send + Sync {
+    /// Short name for logs ("deepl", "libretranslate").
+    fn name(&self) -> &'static str;
+
+    /// Translate `text` from `source` (ISO 639-1 code, or "auto" to
+    /// detect it) into `target`.
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<String>>;
+}
+
+/// Translation service an [`HttpTranslator`] talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranslationService {
+    DeepL,
+    #[default]
+    LibreTranslate,
+}
+
+impl FromStr for TranslationService {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "deepl" => Ok(Self::DeepL),
+            "libretranslate" | "libre" => Ok(Self::LibreTranslate),
+            other => bail!(
+                "Unknown translation service '{}': expected 'deepl' or 'libretranslate'",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TranslationService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeepL => write!(f, "deepl"),
+            Self::LibreTranslate => write!(f, "libretranslate"),
+        }
+    }
+}
+
+/// Translator calling a DeepL or LibreTranslate HTTP API.
+pub struct HttpTranslator {
+    service: TranslationService,
+    url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct DeepLResponse {
+    translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Deserialize)]
+struct DeepLTranslation {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct LibreTranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+impl HttpTranslator {
+    /// Translator for `service`. DeepL needs an API key and picks its free
+    /// or pro endpoint from it; LibreTranslate defaults to a server on
+    /// localhost and only needs a key on instances that require one.
+    pub fn new(service: TranslationService, api_key: Option<String>) -> Result<Self> {
+        let url = match service {
+            TranslationService::DeepL => {
+                let Some(ref key) = api_key else {
+                    bail!("DeepL translation needs an API key");
+                };
+                // Free-plan keys end in ":fx" and have their own endpoint
+                if key.ends_with(":fx") {
+                    "https://api-free.deepl.com"
+                } else {
+                    "https://api.deepl.com"
+                }
+            }
+            TranslationService::LibreTranslate => LIBRETRANSLATE_URL,
+        };
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self {
+            service,
+            url: url.to_string(),
+            api_key,
+            client,
+        })
+    }
+
+    /// Use the server at `url` (e.g. a LibreTranslate instance) instead of
+    /// the service's default.
+    pub fn with_url(mut self, url: String) -> Self {
+        self.url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn deepl(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let mut body = serde_json::json!({
+            "text": [text],
+            "target_lang": target.to_ascii_uppercase(),
+        });
+        if source != "auto" {
+            body["source_lang"] = source.to_ascii_uppercase().into();
+        }
+        let response = self
+            .client
+            .post(format!("{}/v2/translate", self.url))
+            .header(
+                "Authorization",
+                format!("DeepL-Auth-Key {}", self.api_key.as_deref().unwrap_or("")),
+            )
+            .json(&body)
+            .send()
+            .await
+            .context("DeepL request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("DeepL returned {}: {}", status, response.text().await?);
+        }
+        let response: DeepLResponse = response.json().await.context("Invalid DeepL response")?;
+        response
+            .translations
+            .into_iter()
+            .next()
+            .map(|t| t.text)
+            .context("DeepL returned no translation")
+    }
+
+    async fn libretranslate(&self, text: &str, source: &str, target: &str) -> Result<String> {
+        let mut body = serde_json::json!({
+            "q": text,
+            "source": source,
+            "target": target,
+            "format": "text",
+        });
+        if let Some(ref key) = self.api_key {
+            body["api_key"] = key.clone().into();
+        }
+        let response = self
+            .client
+            .post(format!("{}/translate", self.url))
+            .json(&body)
+            .send()
+            .await
+            .context("LibreTranslate request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "LibreTranslate returned {}: {}",
+                status,
+                response.text().await?
+            );
+        }
+        let response: LibreTranslateResponse = response
+            .json()
+            .await
+            .context("Invalid LibreTranslate response")?;
+        Ok(response.translated_text)
+    }
+}
+
+impl Translator for HttpTranslator {
+    fn name(&self) -> &'static str {
+        match self.service {
+            TranslationService::DeepL => "deepl",
+            TranslationService::LibreTranslate => "libretranslate",
+        }
+    }
+
+    fn translate<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a str,
+        target: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            match self.service {
+                TranslationService::DeepL => self.deepl(text, source, target).await,
+                TranslationService::LibreTranslate => {
+                    self.libretranslate(text, source, target).await
+                }
+            }
+        })
+    }
+}
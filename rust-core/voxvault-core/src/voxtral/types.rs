@@ -1,11 +1,13 @@
 use serde::Serialize;
+use std::path::PathBuf;
 
 /// Result of a transcription operation.
 #[derive(Debug, Clone, Serialize)]
 pub struct TranscriptResult {
     /// The transcribed text.
     pub text: String,
-    /// Detected language code (e.g., "pt", "en", "es").
+    /// Language code (e.g., "pt", "en", "es"): the hint's, else detected
+    /// from the text, or "auto" when it can't be told.
     pub language: String,
     /// Timestamp in milliseconds since epoch.
     pub timestamp_ms: u64,
@@ -13,4 +15,102 @@ pub struct TranscriptResult {
     pub is_final: bool,
     /// Real-Time Factor = processing_time / audio_duration. Lower is better.
     pub rtf: Option<f64>,
+    /// Start of the transcribed audio in the stream, in ms (for subtitles).
+    pub start_ms: Option<u64>,
+    /// End of the transcribed audio in the stream, in ms.
+    pub end_ms: Option<u64>,
+    /// Mean probability (0.0–1.0) the decoder gave its chosen text tokens.
+    /// None when the decode loop doesn't expose probabilities.
+    pub confidence: Option<f32>,
+    /// Confidence of each word of `text`, in order.
+    pub words: Vec<WordConfidence>,
+    /// Mean log-probability of the chosen text tokens (None when the
+    /// decode loop doesn't expose probabilities).
+    pub avg_logprob: Option<f32>,
+    /// Why the transcript looks hallucinated, when it does and is kept
+    /// flagged rather than dropped.
+    pub hallucination: Option<String>,
+    /// Whether decoding stopped at the timeout; `text` is what was decoded
+    /// until then.
+    pub timed_out: bool,
+    /// Text tokens decoded (before any hallucination screening).
+    pub tokens: usize,
+}
+
+/// A transcribed word with the decoder's confidence in it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordConfidence {
+    pub word: String,
+    /// Mean probability of the tokens the word was decoded from.
+    pub confidence: f32,
+    /// Where the word starts in the transcribed audio, in ms (None when
+    /// the engine doesn't time its tokens).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ms: Option<u64>,
+    /// Where the word ends in the transcribed audio, in ms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ms: Option<u64>,
+}
+
+impl WordConfidence {
+    /// Split `text` into words and score each by the tokens that produced
+    /// it. `tokens` holds, per decoded token, the length of `text` (in
+    /// bytes) once the token was decoded and the token's probability.
+    pub fn from_tokens(text: &str, tokens: &[(usize, f32)]) -> Vec<Self> {
+        Self::from_timed_tokens(text, tokens, &[])
+    }
+
+    /// Like [`from_tokens`](Self::from_tokens), also timing each word by
+    /// its tokens: `times` holds, per decoded token, where it starts and
+    /// ends in the audio in ms (empty if the tokens aren't timed).
+    pub fn from_timed_tokens(
+        text: &str,
+        tokens: &[(usize, f32)],
+        times: &[(u64, u64)],
+    ) -> Vec<Self> {
+        let mut words = Vec::new();
+        let mut offset = 0;
+        for word in text.split_whitespace() {
+            let start = offset + text[offset..].find(word).unwrap_or(0);
+            let end = start + word.len();
+            offset = end;
+
+            let mut token_start = 0;
+            let indices: Vec<usize> = tokens
+                .iter()
+                .enumerate()
+                .filter_map(|(index, &(token_end, _))| {
+                    let overlaps = token_start < end && token_end > start;
+                    token_start = token_end;
+                    overlaps.then_some(index)
+                })
+                .collect();
+            let (Some(&first), Some(&last)) = (indices.first(), indices.last()) else {
+                continue;
+            };
+            words.push(Self {
+                word: word.to_string(),
+                confidence: indices.iter().map(|&i| tokens[i].1).sum::<f32>()
+                    / indices.len() as f32,
+                start_ms: times.get(first).map(|&(start, _)| start),
+                end_ms: times.get(last).map(|&(_, end)| end),
+            });
+        }
+        words
+    }
+}
+
+/// Transcript of one file of a batch, with where its time went.
+#[derive(Debug)]
+pub struct FileTranscript {
+    pub path: PathBuf,
+    /// Duration of the file's audio, in seconds (None if it couldn't be
+    /// decoded).
+    pub audio_secs: Option<f64>,
+    /// Time spent decoding and resampling the file, in ms.
+    pub decode_ms: u64,
+    /// Time spent transcribing it, in ms.
+    pub transcribe_ms: u64,
+    /// The transcript, or why the file couldn't be transcribed.
+    pub result: anyhow::Result<TranscriptResult>,
 }
@@ -0,0 +1,297 @@
+//! Whisper engine, running a whisper.cpp GGML model (`ggml-*.bin`).
+//!
+//! An alternative to Voxtral for languages or machines it serves poorly.
+//! Whisper decodes a whole segment at once, so partials arrive per Whisper
+//! segment after decoding rather than token by token. Needs the `whisper`
+//! feature (builds whisper.cpp).
+
+#[cfg(not(feature = "whisper"))]
+use anyhow::bail;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use voxtral_mini_realtime::audio::AudioBuffer;
+
+use super::engine::{EngineStats, TranscribeOptions};
+use super::language::LanguageHint;
+use super::stats::RollingStats;
+use super::transcriber::TranscriptionEngine;
+use super::types::TranscriptResult;
+
+/// whisper.cpp transcription engine with lazy loading.
+pub struct WhisperEngine {
+    model_path: PathBuf,
+    language: Option<LanguageHint>,
+    #[cfg(feature = "whisper")]
+    context: Option<whisper_rs::WhisperContext>,
+    transcribed: AtomicU64,
+    rolling: RollingStats,
+}
+
+impl WhisperEngine {
+    /// Create an engine for the model at `model_path` (not loaded yet).
+    pub fn new(model_path: PathBuf) -> Self {
+        Self {
+            model_path,
+            language: None,
+            #[cfg(feature = "whisper")]
+            context: None,
+            transcribed: AtomicU64::new(0),
+            rolling: RollingStats::default(),
+        }
+    }
+
+    /// Transcribe in `language` instead of detecting it.
+    pub fn with_language(mut self, language: Option<LanguageHint>) -> Self {
+        self.language = language;
+        self
+    }
+}
+
+impl TranscriptionEngine for WhisperEngine {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    #[cfg(feature = "whisper")]
+    fn is_loaded(&self) -> bool {
+        self.context.is_some()
+    }
+
+    #[cfg(not(feature = "whisper"))]
+    fn is_loaded(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "whisper")]
+    fn load(&mut self) -> Result<u64> {
+        use anyhow::Context;
+        use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+        if self.is_loaded() {
+            return Ok(0);
+        }
+        let start = std::time::Instant::now();
+        let path = self
+            .model_path
+            .to_str()
+            .context("Whisper model path is not valid UTF-8")?;
+        tracing::info!(path, "Loading Whisper model");
+        let context = WhisperContext::new_with_params(path, WhisperContextParameters::default())
+            .with_context(|| format!("Failed to load Whisper model {}", path))?;
+        self.context = Some(context);
+
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        tracing::info!(elapsed_ms, "WhisperEngine loaded");
+        Ok(elapsed_ms)
+    }
+
+    #[cfg(not(feature = "whisper"))]
+    fn load(&mut self) -> Result<u64> {
+        bail!(
+            "Whisper engine is not available (model {}): rebuild with `--features whisper`",
+            self.model_path.display()
+        )
+    }
+
+    fn unload(&mut self) {
+        #[cfg(feature = "whisper")]
+        {
+            self.context = None;
+        }
+        tracing::info!("WhisperEngine unloaded");
+    }
+
+    fn transcribe_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+    ) -> Result<TranscriptResult> {
+        self.transcribe_streaming_with(audio, options, &mut |_| {})
+    }
+
+    #[cfg(feature = "whisper")]
+    fn transcribe_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        let audio_secs = audio.samples.len() as f64 / audio.sample_rate as f64;
+        let start = std::time::Instant::now();
+        let mut first_partial = None;
+        let result = decode::run(self, audio, options, &mut |text| {
+            first_partial.get_or_insert_with(|| start.elapsed());
+            on_partial(text)
+        })?;
+        self.transcribed.fetch_add(1, Ordering::Relaxed);
+        self.rolling.record(
+            audio_secs,
+            start.elapsed().as_secs_f64(),
+            result.tokens,
+            first_partial,
+        );
+        Ok(result)
+    }
+
+    #[cfg(not(feature = "whisper"))]
+    fn transcribe_streaming_with(
+        &self,
+        _audio: AudioBuffer,
+        _options: TranscribeOptions,
+        _on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        bail!("Whisper engine is not available: rebuild with `--features whisper`")
+    }
+
+    #[cfg(feature = "whisper")]
+    fn preview_streaming_with(
+        &self,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        decode::run(self, audio, options, on_partial)
+    }
+
+    #[cfg(not(feature = "whisper"))]
+    fn preview_streaming_with(
+        &self,
+        _audio: AudioBuffer,
+        _options: TranscribeOptions,
+        _on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        bail!("Whisper engine is not available: rebuild with `--features whisper`")
+    }
+
+    fn language(&self) -> Option<&LanguageHint> {
+        self.language.as_ref()
+    }
+
+    fn stats(&self) -> EngineStats {
+        let mut stats = EngineStats {
+            transcribed: self.transcribed.load(Ordering::Relaxed),
+            ..EngineStats::default()
+        };
+        self.rolling.fill(&mut stats);
+        stats
+    }
+}
+
+#[cfg(feature = "whisper")]
+mod decode {
+    use anyhow::{bail, Context, Result};
+    use std::time::Instant;
+    use voxtral_mini_realtime::audio::AudioBuffer;
+    use whisper_rs::{FullParams, SamplingStrategy};
+
+    use super::WhisperEngine;
+    use crate::voxtral::cancel::CancelToken;
+    use crate::voxtral::engine::TranscribeOptions;
+    use crate::voxtral::language::{reported_language, LanguageHint};
+    use crate::voxtral::types::{TranscriptResult, WordConfidence};
+
+    /// Sample rate Whisper models take.
+    const SAMPLE_RATE: u32 = 16000;
+
+    pub(super) fn run(
+        engine: &WhisperEngine,
+        audio: AudioBuffer,
+        options: TranscribeOptions,
+        on_partial: &mut dyn FnMut(&str),
+    ) -> Result<TranscriptResult> {
+        let context = engine.context.as_ref().context("Model not loaded")?;
+        if audio.sample_rate != SAMPLE_RATE {
+            bail!(
+                "Whisper needs {} Hz audio, got {} Hz",
+                SAMPLE_RATE,
+                audio.sample_rate
+            );
+        }
+        let start_time = Instant::now();
+        let audio_secs = audio.samples.len() as f64 / audio.sample_rate as f64;
+        let timestamp_ms = chrono::Utc::now().timestamp_millis() as u64;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(
+            engine.language.as_ref().map_or("auto", LanguageHint::code),
+        ));
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_special(false);
+        params.set_print_timestamps(false);
+        // Time tokens, for word timings
+        params.set_token_timestamps(true);
+        if let Some(prompt) = options.prompt {
+            params.set_initial_prompt(prompt);
+        }
+        let cancel = options.cancel.cloned();
+        let deadline = options.timeout.map(|t| start_time + t);
+        params.set_abort_callback_safe(move || {
+            cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+                || deadline.is_some_and(|d| Instant::now() >= d)
+        });
+
+        let mut state = context
+            .create_state()
+            .context("Failed to create Whisper state")?;
+        let decoded = state.full(params, &audio.samples);
+        if let Some(cancel) = options.cancel {
+            cancel.check()?;
+        }
+        let timed_out = deadline.is_some_and(|d| Instant::now() >= d);
+        if !timed_out {
+            decoded.context("Whisper transcription failed")?;
+        }
+
+        // Rebuild the text from its tokens to score and time words by them
+        let mut text = String::new();
+        let mut tokens = Vec::new();
+        let mut times = Vec::new();
+        let mut logprob_sum = 0.0;
+        for segment in 0..state.full_n_segments()? {
+            for token in 0..state.full_n_tokens(segment)? {
+                let piece = state.full_get_token_text_lossy(segment, token)?;
+                // Special tokens: [_BEG_], [_TT_150], <|endoftext|>, ...
+                if piece.starts_with("[_") || piece.starts_with("<|") {
+                    continue;
+                }
+                let data = state.full_get_token_data(segment, token)?;
+                text.push_str(&piece);
+                tokens.push((text.len(), data.p));
+                // Token times are in centiseconds
+                times.push((data.t0.max(0) as u64 * 10, data.t1.max(0) as u64 * 10));
+                logprob_sum += data.plog;
+            }
+            on_partial(text.trim());
+        }
+
+        let words = WordConfidence::from_timed_tokens(&text, &tokens, &times);
+        let (confidence, avg_logprob) = if tokens.is_empty() {
+            (None, None)
+        } else {
+            let n = tokens.len() as f32;
+            (
+                Some(tokens.iter().map(|&(_, p)| p).sum::<f32>() / n),
+                Some(logprob_sum / n),
+            )
+        };
+        let text = text.trim().to_string();
+        let rtf = (audio_secs > 0.0).then(|| start_time.elapsed().as_secs_f64() / audio_secs);
+        Ok(TranscriptResult {
+            language: reported_language(&text, engine.language.as_ref()),
+            text,
+            timestamp_ms,
+            is_final: true,
+            rtf,
+            start_ms: None,
+            end_ms: None,
+            confidence,
+            words,
+            avg_logprob,
+            hallucination: None,
+            timed_out,
+            tokens: tokens.len(),
+        })
+    }
+}
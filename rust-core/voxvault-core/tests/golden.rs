@@ -0,0 +1,81 @@
+//! Golden-output regression test of Voxtral decoding.
+//!
+//! Needs the model, so it is ignored by default. Put WAV files in
+//! `tests/golden/` with the expected transcript next to each
+//! (`meeting.wav` → `meeting.txt`), point `VOXVAULT_MODEL` and
+//! `VOXVAULT_TOKENIZER` at the model files and run
+//!
+//! ```sh
+//! cargo test --test golden -- --ignored
+//! ```
+//!
+//! `silence.wav`, a second of silence that must decode to no text, is the
+//! baseline every model passes. Speech recordings go next to it, with
+//! transcripts blessed from a real model run rather than written by hand.
+//!
+//! With `VOXVAULT_BLESS=1` the expected transcripts are written from the
+//! current output instead of compared against it. Listen to the audio and
+//! check each written file before committing it; the README's regression
+//! test section has the steps.
+
+use std::path::{Path, PathBuf};
+
+use voxvault_core::voxtral::engine::{EngineConfig, TranscribeOptions, VoxtralEngine};
+
+/// Seed the golden transcripts were produced with.
+const SEED: u64 = 0;
+
+fn env_path(name: &str) -> PathBuf {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| panic!("{} is not set", name))
+}
+
+#[test]
+#[ignore = "needs the model files (VOXVAULT_MODEL, VOXVAULT_TOKENIZER)"]
+fn seeded_decoding_matches_golden_transcripts() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut audio: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .expect("tests/golden is missing")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+        .collect();
+    audio.sort();
+    assert!(!audio.is_empty(), "No WAV files in {}", dir.display());
+
+    let config = EngineConfig {
+        seed: Some(SEED),
+        ..EngineConfig::default()
+    };
+    let mut engine = VoxtralEngine::new(env_path("VOXVAULT_MODEL"), env_path("VOXVAULT_TOKENIZER"))
+        .with_config(config);
+    engine.load().expect("Failed to load the model");
+
+    // Twice, to catch nondeterminism the golden files were blessed with
+    let first = engine.transcribe_files(&audio, TranscribeOptions::default());
+    let second = engine.transcribe_files(&audio, TranscribeOptions::default());
+    let bless = std::env::var_os("VOXVAULT_BLESS").is_some();
+    for (first, second) in first.into_iter().zip(second) {
+        let name = first.path.display().to_string();
+        let a = first.result.unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+        let b = second
+            .result
+            .unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+        assert_eq!(a.text, b.text, "{}: runs decoded different text", name);
+        assert_eq!(
+            a.tokens, b.tokens,
+            "{}: runs decoded different tokens",
+            name
+        );
+
+        let golden = first.path.with_extension("txt");
+        if bless {
+            std::fs::write(&golden, format!("{}\n", a.text)).expect("Failed to write golden file");
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap_or_else(|e| {
+            panic!("{}: {} (bless with VOXVAULT_BLESS=1)", golden.display(), e)
+        });
+        assert_eq!(a.text, expected.trim(), "{}: transcript changed", name);
+    }
+}